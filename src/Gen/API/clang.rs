@@ -1,4 +1,6 @@
 use crate::import::*;
+use miette::{LabeledSpan, Severity};
+use std::ffi::OsString;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -41,6 +43,28 @@ impl TargetOS {
         }
     }
 
+    /// The clang `-target` triple used when cross-compiling to this OS.
+    pub fn clang_triple(&self) -> Option<&'static str> {
+        match self {
+            TargetOS::Windows => Some("x86_64-pc-windows-gnu"),
+            TargetOS::Linux => Some("x86_64-unknown-linux-gnu"),
+            TargetOS::MacOS => Some("aarch64-apple-darwin"),
+            TargetOS::FreeBSD => Some("x86_64-unknown-freebsd"),
+            TargetOS::Unknown => None,
+        }
+    }
+
+    /// Type sizes for this target: the host's own when not cross-compiling,
+    /// otherwise the architecture named by `clang_triple`.
+    pub fn arch(&self) -> ArchConfig {
+        let aarch64 = if *self == TargetOS::current() {
+            cfg!(target_arch = "aarch64")
+        } else {
+            self.clang_triple().is_some_and(|triple| triple.starts_with("aarch64"))
+        };
+        if aarch64 { ArchConfig::aarch64() } else { ArchConfig::x86_64() }
+    }
+
     pub fn executable_prefix(&self) -> &'static str {
         match self {
             TargetOS::Windows => "",
@@ -69,6 +93,56 @@ impl TargetOS {
     }
 }
 
+impl OptimizationLevel {
+    pub fn clang_flags(self) -> &'static [&'static str] {
+        match self {
+            OptimizationLevel::None => &["-O0"],
+            OptimizationLevel::O1 => &["-O1"],
+            OptimizationLevel::O2 => &["-O2"],
+            OptimizationLevel::Release => &["-O2", "-DNDEBUG"],
+            OptimizationLevel::O3 => &["-O3"],
+            OptimizationLevel::Size => &["-Os"],
+            OptimizationLevel::Debug => &["-O0", "-g"],
+        }
+    }
+
+    /// `llc` has no size level, so `Size` falls back to its default `-O2`.
+    pub fn llc_flag(self) -> &'static str {
+        match self {
+            OptimizationLevel::None | OptimizationLevel::Debug => "-O0",
+            OptimizationLevel::O1 => "-O1",
+            OptimizationLevel::O2 | OptimizationLevel::Release | OptimizationLevel::Size => "-O2",
+            OptimizationLevel::O3 => "-O3",
+        }
+    }
+}
+
+/// A C compiler diagnostic located in Vix source through `#line`.
+#[derive(Debug)]
+struct CDiagnostic {
+    message: String,
+    severity: Severity,
+    span: SourceSpan,
+}
+
+impl std::fmt::Display for CDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CDiagnostic {}
+
+impl Diagnostic for CDiagnostic {
+    fn severity(&self) -> Option<Severity> {
+        Some(self.severity)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(None, self.span))))
+    }
+}
+
 pub struct Clang;
 
 impl Clang {
@@ -118,55 +192,144 @@ void* __guard_eh_cont_table = 0;
         Ok(obj_path)
     }
 
+    /// The C source of the main program, next to the executable.
+    pub const MAIN_C_SOURCE: &'static str = "release/bin/output.c";
+
+    /// Writes `c_code` to `c_path` and compiles that file to an object at `output_path`.
     pub fn compile_to_object(
         c_code: &str,
+        c_path: &Path,
         output_path: &Path,
         target_os: Option<TargetOS>,
+        optimization: OptimizationLevel,
     ) -> Result<(), String> {
         Self::ensure_build_dirs()?;
         
         let mut cmd = Command::new("clang");
         let target = target_os.unwrap_or_else(TargetOS::current);
-        let obj_path = if output_path.extension().is_none() {
-            output_path.with_extension(target.object_extension().trim_start_matches('.'))
-        } else {
-            output_path.to_path_buf()
-        };
-        
-         
-        let c_path = if output_path.starts_with("library") {
-            PathBuf::from(output_path.parent().unwrap()).join("source.c")
-        } else {
-            PathBuf::from("release/bin/output.c")
-        };
+        let obj_path = Self::object_path(output_path, target);
+        Self::write_c_source(c_code, c_path)?;
 
-        fs::write(&c_path, c_code).map_err(|e| format!("Failed to write C source: {}", e))?;
-
-        cmd.arg("-c")
-            .arg(&c_path)
-            .arg("-o")
-            .arg(&obj_path)
-            .arg("-O2")
-            .arg("-std=c17")
-            .arg("-Wall")
-            .arg("-Wextra");
-
-        if target == TargetOS::Windows {
-            cmd.arg("-D_CRT_SECURE_NO_WARNINGS");
-        }
+        cmd.args(Self::object_args(c_path, &obj_path, target, optimization));
 
         let output = cmd.output().map_err(|e| format!("Failed to execute clang: {}", e))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             let stdout = String::from_utf8_lossy(&output.stdout);
+            if stderr.contains("unknown target triple") {
+                return Err(format!(
+                    "clang cannot cross-compile for {} ({}). Install a clang build with that target enabled.\n{}",
+                    target.display_name(),
+                    target.clang_triple().unwrap_or("unknown triple"),
+                    stderr
+                ));
+            }
             eprintln!("\n{} Compilation failed! Generated C code preserved at: {}", "Debug:".yellow(), c_path.display());
+            let stderr = Self::render_c_diagnostics(&stderr);
             return Err(format!("Object compilation failed:\nSTDOUT:\n{}\nSTDERR:\n{}\n", stdout, stderr));
         }
 
         Ok(())
     }
 
+    /// Writes `c_code` to `c_path`, creating its directory. This is all a dry run does.
+    pub fn write_c_source(c_code: &str, c_path: &Path) -> Result<(), String> {
+        if let Some(parent) = c_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+        fs::write(c_path, c_code).map_err(|e| format!("Failed to write C source: {}", e))
+    }
+
+    /// Renders the C compiler's `file:line:col: level: message` lines that `#line` placed in
+    /// a `.vix` file as miette reports over that file. Other lines are passed through.
+    pub fn render_c_diagnostics(stderr: &str) -> String {
+        let diagnostic = Regex::new(r"^(.+?):(\d+):(\d+): ((?:fatal )?error|warning|note): (.*)$").unwrap();
+        let snippet = Regex::new(r"^\s*\d*\s+\|").unwrap();
+
+        let mut sources: HashMap<String, Option<String>> = HashMap::new();
+        let mut output = Vec::new();
+        let mut rendered = false;
+        for line in stderr.lines() {
+            // The compiler's own excerpt of the Vix line; the report already shows it.
+            if rendered && snippet.is_match(line) {
+                continue;
+            }
+            rendered = false;
+
+            let Some(caps) = diagnostic.captures(line) else {
+                output.push(line.to_string());
+                continue;
+            };
+            let file = &caps[1];
+            let source = sources
+                .entry(file.to_string())
+                .or_insert_with(|| (file.ends_with(".vix") || file.ends_with(".x")).then(|| fs::read_to_string(file).ok()).flatten());
+            let Some(source) = source else {
+                output.push(line.to_string());
+                continue;
+            };
+            let Some(span) = Self::line_span(source, caps[2].parse().unwrap_or(0)) else {
+                output.push(line.to_string());
+                continue;
+            };
+
+            let severity = match &caps[4] {
+                "warning" => Severity::Warning,
+                "note" => Severity::Advice,
+                _ => Severity::Error,
+            };
+            let report = Report::new(CDiagnostic {
+                message: caps[5].to_string(),
+                severity,
+                span,
+            })
+            .with_source_code(NamedSource::new(file, source.clone()));
+            output.push(format!("{:?}", report));
+            rendered = true;
+        }
+        output.join("\n")
+    }
+
+    /// The span of the non-blank text on 1-based `line` of `source`.
+    fn line_span(source: &str, line: usize) -> Option<SourceSpan> {
+        let mut offset = 0;
+        for (index, text) in source.split_inclusive('\n').enumerate() {
+            if index + 1 == line {
+                let content = text.trim_end();
+                let indent = content.len() - content.trim_start().len();
+                return Some(SourceSpan::new((offset + indent).into(), (content.len() - indent).max(1)));
+            }
+            offset += text.len();
+        }
+        None
+    }
+
+    fn object_path(output_path: &Path, target: TargetOS) -> PathBuf {
+        if output_path.extension().is_none() {
+            output_path.with_extension(target.object_extension().trim_start_matches('.'))
+        } else {
+            output_path.to_path_buf()
+        }
+    }
+
+    /// Arguments for compiling `c_path` to an object file for `target`.
+    /// `-target` is only passed when `target` differs from the host OS.
+    pub fn object_args(c_path: &Path, obj_path: &Path, target: TargetOS, optimization: OptimizationLevel) -> Vec<OsString> {
+        let mut args: Vec<OsString> = vec!["-c".into(), c_path.into(), "-o".into(), obj_path.into()];
+        if target != TargetOS::current()
+            && let Some(triple) = target.clang_triple() {
+                args.push("-target".into());
+                args.push(triple.into());
+            }
+        args.extend(optimization.clang_flags().iter().map(OsString::from));
+        args.extend(["-std=c17", "-Wall", "-Wextra"].map(OsString::from));
+        if target == TargetOS::Windows {
+            args.push("-D_CRT_SECURE_NO_WARNINGS".into());
+        }
+        args
+    }
+
     pub fn link_executable(
         object_files: &[&Path],
         output_name: &str,
@@ -198,7 +361,7 @@ void* __guard_eh_cont_table = 0;
         Self::add_platform_specific_args(&mut cmd, target);
 
         for lib in extra_libs {
-            cmd.arg(format!("-l{}", lib));
+            cmd.args(Self::library_link_args(lib, target));
         }
 
         let output = cmd.output().map_err(|e| format!("Linking failed: {}", e))?;
@@ -257,7 +420,7 @@ void* __guard_eh_cont_table = 0;
         Self::add_platform_specific_args(&mut cmd, target);
 
         for lib in extra_libs {
-            cmd.arg(format!("-l{}", lib));
+            cmd.args(Self::library_link_args(lib, target));
         }
 
         let output = cmd.output().map_err(|e| format!("Clang failed: {}", e))?;
@@ -276,6 +439,35 @@ void* __guard_eh_cont_table = 0;
         Ok(())
     }
 
+    /// The name of a library without its directory or file extension: `vendor/user32.dll` is `user32`.
+    pub fn library_name(library: &str) -> &str {
+        let file = library.trim().rsplit(['/', '\\']).next().unwrap_or(library);
+        [".dll", ".lib", ".so", ".dylib", ".a"].iter().find_map(|ext| file.strip_suffix(ext)).unwrap_or(file)
+    }
+
+    /// Linker arguments for a library named by an `extern` declaration. A directory part becomes a
+    /// `-L` search path; outside Windows a `lib` file prefix is dropped, since `-l` adds it back.
+    pub fn library_link_args(library: &str, target: TargetOS) -> Vec<String> {
+        let library = library.trim();
+        let name = Self::library_name(library);
+        if name.is_empty() {
+            return vec![];
+        }
+
+        let mut args = Vec::new();
+        if let Some(split) = library.rfind(['/', '\\']) {
+            args.push(format!("-L{}", &library[..split]));
+        }
+        let has_extension = !library.ends_with(name);
+        let name = match target {
+            TargetOS::Windows => name,
+            _ if has_extension => name.strip_prefix("lib").filter(|n| !n.is_empty()).unwrap_or(name),
+            _ => name,
+        };
+        args.push(format!("-l{}", name));
+        args
+    }
+
     pub fn add_platform_specific_args(cmd: &mut Command, target_os: TargetOS) {
         match target_os {
             TargetOS::Windows => {
@@ -359,12 +551,12 @@ void* __guard_eh_cont_table = 0;
 
     println!("   {} Parsing tokens...", "success:".bright_cyan());
     let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
-    let (program, structs, enums, externs, _, _, _, impls, _, _, import_decls) = parser.parse();
+    let (program, structs, enums, externs, _, _, _, impls, traits, _, import_decls) = parser.parse();
 
     println!("   {} Processing imports...", "success:".bright_cyan());
-    let footprint_packs = LibraryManager::process_imports_from_decls(&import_decls, target_os)?;
+    let footprint_packs = LibraryManager::process_imports_from_decls(&import_decls, target_os, false, false)?;
     
-    LibraryManager::validate_imports(&import_decls, &footprint_packs).ok();
+    LibraryManager::validate_imports(&import_decls, &footprint_packs)?;
 
     let mut library_includes = Vec::new();
     let mut library_functions = Vec::new();
@@ -407,11 +599,13 @@ void* __guard_eh_cont_table = 0;
     }
 
     println!("   {} Generating C code...", "success:".bright_cyan());
-    let arch = ArchConfig::x86_64();
+    let arch = target.arch();
     let mut codegen = Codegen::new(arch, source.to_string(), "main.vix".to_string());
     
     codegen.set_import_context(&import_decls, &library_functions);
     
+    codegen.check_trait_impls(&traits, &impls).ok();
+    
     let c_code = codegen.codegen_program_full(
         &program,
         &structs,
@@ -424,7 +618,7 @@ void* __guard_eh_cont_table = 0;
 
     println!("   {} Compiling to object file...", "success:".bright_cyan());
     let main_obj = PathBuf::from("release/bin").join(format!("main{}", target.object_extension()));
-    Clang::compile_to_object(&c_code, &main_obj, target_os)
+    Clang::compile_to_object(&c_code, Path::new(Clang::MAIN_C_SOURCE), &main_obj, target_os, OptimizationLevel::O2)
         .map_err(|e| format!("Failed to compile: {}", e))?;
 
     println!("   {} Linking executable...", "success:".bright_cyan());
@@ -436,11 +630,95 @@ void* __guard_eh_cont_table = 0;
     Clang::link_executable(
         &all_objects,
         output_name,
-        &[],
+        codegen.get_linked_libraries(),
         target_os,
     )?;
     
     println!("   {} Build complete!", "success:".green());
     Ok(())
 }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimization_levels_map_to_flags() {
+        let cases = [
+            (OptimizationLevel::None, "-O0"),
+            (OptimizationLevel::O1, "-O1"),
+            (OptimizationLevel::O2, "-O2"),
+            (OptimizationLevel::O3, "-O3"),
+            (OptimizationLevel::Size, "-Os"),
+            (OptimizationLevel::Debug, "-O0 -g"),
+            (OptimizationLevel::Release, "-O2 -DNDEBUG"),
+        ];
+        for (level, flags) in cases {
+            assert_eq!(level.clang_flags().join(" "), flags, "{:?}", level);
+        }
+        assert_eq!(OptimizationLevel::Size.llc_flag(), "-O2");
+        assert_eq!(OptimizationLevel::Debug.llc_flag(), "-O0");
+    }
+
+    #[test]
+    fn test_cross_targets_pass_clang_triple() {
+        let targets = [
+            (TargetOS::Windows, "x86_64-pc-windows-gnu"),
+            (TargetOS::Linux, "x86_64-unknown-linux-gnu"),
+            (TargetOS::MacOS, "aarch64-apple-darwin"),
+            (TargetOS::FreeBSD, "x86_64-unknown-freebsd"),
+        ];
+        for (target, triple) in targets {
+            let obj = PathBuf::from("main").with_extension(target.object_extension().trim_start_matches('.'));
+            let args = Clang::object_args(Path::new("main.c"), &obj, target, OptimizationLevel::O2);
+            let target_flag = args.iter().position(|a| a == "-target");
+
+            if target == TargetOS::current() {
+                assert_eq!(target_flag, None, "{:?}", args);
+            } else {
+                let at = target_flag.unwrap_or_else(|| panic!("no -target for {:?}: {:?}", target, args));
+                assert_eq!(args[at + 1], triple);
+            }
+            assert!(args.iter().any(|a| a == obj.as_os_str()));
+        }
+    }
+
+    #[test]
+    fn test_library_link_args_per_target() {
+        assert_eq!(Clang::library_link_args("user32.dll", TargetOS::Windows), ["-luser32"]);
+        assert_eq!(Clang::library_link_args("vendor\\lib\\sdl2.lib", TargetOS::Windows), ["-Lvendor\\lib", "-lsdl2"]);
+        assert_eq!(Clang::library_link_args("vendor/libfoo.so", TargetOS::Linux), ["-Lvendor", "-lfoo"]);
+        assert_eq!(Clang::library_link_args("libressl", TargetOS::Linux), ["-llibressl"]);
+        assert!(Clang::library_link_args("", TargetOS::Linux).is_empty());
+    }
+
+    #[test]
+    fn test_c_errors_render_against_vix_source() {
+        let Some(compiler) = ["clang", "gcc"].into_iter().find(|c| Command::new(c).arg("--version").output().is_ok()) else {
+            return;
+        };
+        let dir = std::env::temp_dir().join(format!("vix-cdiag-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let vix_path = dir.join("app.vix");
+        let c_path = dir.join("output.c");
+        fs::write(&vix_path, "func main(): int32\n    create x = 1\n    create y = missing\n    return x\nend\n").unwrap();
+        let vix = vix_path.display().to_string();
+        fs::write(&c_path, format!(
+            "#include <stdint.h>\nint32_t vix_main() {{\n#line 2 \"{0}\"\nint32_t x = 1;\n#line 3 \"{0}\"\nint32_t y = missing;\n#line 4 \"{0}\"\nreturn x;\n}}\n",
+            vix
+        )).unwrap();
+
+        let output = Command::new(compiler).arg("-c").arg(&c_path).arg("-o").arg(dir.join("output.o")).output().unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let rendered = Clang::render_c_diagnostics(&stderr);
+        let plain = Regex::new(r"\x1b\[[0-9;]*m").unwrap().replace_all(&rendered, "");
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(stderr.contains(&format!("{}:3:", vix)), "{}", stderr);
+        assert!(plain.contains(&format!("[{}:3:5]", vix)), "{}", plain);
+        assert!(plain.contains("create y = missing"), "{}", plain);
+        assert!(plain.contains("'missing' undeclared") || plain.contains("undeclared identifier 'missing'"), "{}", plain);
+        assert!(!plain.contains("3 |"), "the compiler's own excerpt should be replaced: {}", plain);
+    }
+}
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct SourceLocation {
     pub file: String,
@@ -14,12 +16,13 @@ pub struct ErrorContext {
     pub suggestions: Vec<String>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DiagnosticLevel {
     Error,
     Warning,
 }
 
+#[derive(Debug, Clone)]
 pub struct Diagnostic {
     pub level: DiagnosticLevel,
     pub code: String,
@@ -73,7 +76,7 @@ impl Diagnostic {
             
             output.push_str(&format!("   | {}{}^ {}\n", 
                 " ".repeat(primary.column.saturating_sub(1)),
-                "^".repeat(primary.length.saturating_sub(1).max(0)),
+                "^".repeat(primary.length.saturating_sub(1)),
                 self.message
             ));
         }
@@ -112,12 +115,54 @@ impl Diagnostic {
     }
 }
 
+/// Flat view of a [`Diagnostic`] as serialized for editors and other tooling.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonDiagnostic {
+    pub severity: String,
+    pub code: String,
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub length: usize,
+    pub help: Option<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl From<&Diagnostic> for JsonDiagnostic {
+    fn from(diagnostic: &Diagnostic) -> Self {
+        let location = &diagnostic.context.primary_location;
+        Self {
+            severity: match diagnostic.level {
+                DiagnosticLevel::Error => "error",
+                DiagnosticLevel::Warning => "warning",
+            }.to_string(),
+            code: diagnostic.code.clone(),
+            message: diagnostic.message.clone(),
+            file: location.file.clone(),
+            line: location.line,
+            column: location.column,
+            length: location.length,
+            help: diagnostic.context.help_message.clone(),
+            suggestions: diagnostic.context.suggestions.clone(),
+        }
+    }
+}
+
+pub fn diagnostics_to_json(diagnostics: &[Diagnostic]) -> String {
+    let diagnostics: Vec<JsonDiagnostic> = diagnostics.iter().map(JsonDiagnostic::from).collect();
+    serde_json::to_string(&diagnostics).expect("diagnostics are always serializable")
+}
+
 pub struct DiagnosticHandler {
     diagnostics: Vec<Diagnostic>,
     source_code: String,
     max_errors: usize,
     pub error_count: usize,
     pub warning_count: usize,
+    /// Pretty-print each diagnostic to stderr as it is reported. Embedders that
+    /// read [`DiagnosticHandler::diagnostics`] instead can turn this off.
+    pub echo: bool,
 }
 
 impl DiagnosticHandler {
@@ -128,23 +173,28 @@ impl DiagnosticHandler {
             max_errors: 100,
             error_count: 0,
             warning_count: 0,
+            echo: true,
         }
     }
 
     pub fn error(&mut self, code: &str, message: &str, context: ErrorContext) {
         let diagnostic = Diagnostic::error(code, message, context);
-        eprintln!("{}", diagnostic.display(&self.source_code));
+        if self.echo {
+            eprintln!("{}", diagnostic.display(&self.source_code));
+        }
         self.diagnostics.push(diagnostic);
         self.error_count += 1;
         
-        if self.error_count >= self.max_errors {
+        if self.echo && self.error_count >= self.max_errors {
             eprintln!("[Warning]: Reached {} errors, but continuing to generate code...", self.error_count);
         }
     }
 
     pub fn warning(&mut self, code: &str, message: &str, context: ErrorContext) {
         let diagnostic = Diagnostic::warning(code, message, context);
-        eprintln!("{}", diagnostic.display(&self.source_code));
+        if self.echo {
+            eprintln!("{}", diagnostic.display(&self.source_code));
+        }
         self.diagnostics.push(diagnostic);
         self.warning_count += 1;
     }
@@ -153,8 +203,12 @@ impl DiagnosticHandler {
         self.error_count > 0
     } 
 
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
     pub fn print_summary(&self) {
-        if self.error_count > 0 || self.warning_count > 0 {
+        if self.echo && (self.error_count > 0 || self.warning_count > 0) {
             eprintln!("\nCompilation finished with {} error(s) and {} warning(s).", self.error_count, self.warning_count);
         }
     }
@@ -1,6 +1,19 @@
 use crate::import::*;
 use std::collections::HashSet;
 
+const BUILTIN_INCLUDES: [&str; 6] = [
+    "#include <stdio.h>",
+    "#include <stdlib.h>",
+    "#include <stdint.h>",
+    "#include <stdbool.h>",
+    "#include <string.h>",
+    "#include <time.h>",
+];
+
+/// Stands in for the line number of a `#line` that points back into the generated C file;
+/// `finalize` replaces it once the file's layout is known.
+pub const C_LINE_MARKER: &str = "__VIX_C_LINE__";
+
 impl IR {
     pub fn new() -> Self {
         IR {
@@ -12,6 +25,8 @@ impl IR {
             functions: String::new(),
             added_typedefs: HashSet::new(),
             added_function_decls: HashSet::new(),
+            added_includes: HashSet::new(),
+            init_modules: false,
         }
     }
 
@@ -27,12 +42,11 @@ impl IR {
         let mut output = String::new();
 
 
-        output.push_str("#include <stdio.h>\n");
-        output.push_str("#include <stdlib.h>\n");
-        output.push_str("#include <stdint.h>\n");
-        output.push_str("#include <stdbool.h>\n");
-        output.push_str("#include <string.h>\n");
-        output.push_str("#include <time.h>\n\n");
+        for include in BUILTIN_INCLUDES {
+            output.push_str(include);
+            output.push('\n');
+        }
+        output.push('\n');
 
         if !self.headers.is_empty() {
             output.push_str(&self.headers);
@@ -45,7 +59,7 @@ impl IR {
         }
 
         if !self.forward_decls.is_empty() {
-            println!("External contains so many stupid things like: {}", &self.forward_decls);
+            debug_log!("External contains so many stupid things like: {}", &self.forward_decls);
             output.push_str(&self.forward_decls);
             output.push('\n');
         }
@@ -67,14 +81,40 @@ impl IR {
 
         if include_main {
             output.push_str("int main() {\n");
+            if self.init_modules {
+                output.push_str("    vix_init_modules();\n");
+            }
             output.push_str("    vix_main();\n");
             output.push_str("    return 0;\n");
             output.push_str("}\n");
         }
 
+        if output.contains(C_LINE_MARKER) {
+            // `#line N` numbers the line after it, which is line `i + 2` of the file.
+            output = output.lines().enumerate().map(|(i, line)| {
+                if line.starts_with("#line ") {
+                    line.replacen(C_LINE_MARKER, &(i + 2).to_string(), 1) + "\n"
+                } else {
+                    format!("{}\n", line)
+                }
+            }).collect();
+        }
+
         output
     }
 
+    /// Adds an `#include` line once, keeping the order in which includes were first seen.
+    pub fn add_include(&mut self, include: &str) {
+        let normalized = include.split_whitespace().collect::<Vec<_>>().join(" ");
+        if normalized.is_empty() || BUILTIN_INCLUDES.contains(&normalized.as_str()) {
+            return;
+        }
+        if self.added_includes.insert(normalized.clone()) {
+            self.headers.push_str(&normalized);
+            self.headers.push('\n');
+        }
+    }
+
     pub fn add_forward_decl(&mut self, decl: String) {
         let normalized = decl.trim().to_string();
 
@@ -160,6 +200,8 @@ impl Clone for IR {
             functions: self.functions.clone(),
             added_typedefs: self.added_typedefs.clone(),
             added_function_decls: self.added_function_decls.clone(),
+            added_includes: self.added_includes.clone(),
+            init_modules: self.init_modules,
         }
     } 
 }
\ No newline at end of file
@@ -1,6 +1,125 @@
 use crate::import::*;
+use crate::Gen::build::generate_type::escape_c_string;
 
 impl Codegen {
+    /// Reorders `f(b = 2, a = 1)` into the callee's declared parameter order.
+    pub fn codegen_call_named(&mut self, func: &str, named_args: &[(String, Expr)], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let resolved = self.resolve_function_name(func);
+        let (params, param_types): (Vec<String>, Vec<Type>) = if let Some((params, _)) = self.user_functions.get(&resolved) {
+            params.iter().cloned().unzip()
+        } else if let Some(ext) = self.extern_functions.get(&resolved) {
+            ext.params.iter().cloned().unzip()
+        } else {
+            self.diagnostics.error(
+                "UndefinedFunction",
+                &format!("Cannot call '{}' with named arguments: function is not defined", func),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Named arguments need a declared function to match parameter names against.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        };
+
+        let mut ordered: Vec<Option<Expr>> = vec![None; params.len()];
+        let mut failed = false;
+        for (arg_name, arg_expr) in named_args {
+            match params.iter().position(|p| p == arg_name) {
+                Some(idx) if ordered[idx].is_none() => ordered[idx] = Some(arg_expr.clone()),
+                Some(_) => {
+                    self.diagnostics.error(
+                        "DuplicateArgument",
+                        &format!("Argument '{}' is passed more than once to '{}'", arg_name, func),
+                        ErrorContext {
+                            primary_location: loc.clone(),
+                            secondary_locations: vec![],
+                            help_message: None,
+                            suggestions: vec![format!("Remove one of the '{}' arguments", arg_name)],
+                        }
+                    );
+                    failed = true;
+                }
+                None => {
+                    self.diagnostics.error(
+                        "UnknownArgument",
+                        &format!("Function '{}' has no parameter named '{}'", func, arg_name),
+                        ErrorContext {
+                            primary_location: loc.clone(),
+                            secondary_locations: vec![],
+                            help_message: Some(format!("Parameters of '{}': {}", func, params.join(", "))),
+                            suggestions: vec![],
+                        }
+                    );
+                    failed = true;
+                }
+            }
+        }
+
+        if let Some(defaults) = self.param_defaults.get(&resolved) {
+            for (arg, default) in ordered.iter_mut().zip(defaults) {
+                if arg.is_none() {
+                    *arg = default.clone();
+                }
+            }
+        }
+
+        let missing: Vec<&str> = params
+            .iter()
+            .zip(&ordered)
+            .filter(|(_, arg)| arg.is_none())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if !missing.is_empty() {
+            self.diagnostics.error(
+                "MissingArgument",
+                &format!("Call to '{}' is missing argument(s): {}", func, missing.join(", ")),
+                ErrorContext {
+                    primary_location: loc.clone(),
+                    secondary_locations: vec![],
+                    help_message: None,
+                    suggestions: missing.iter().map(|name| format!("Add '{} = ...'", name)).collect(),
+                }
+            );
+            failed = true;
+        }
+
+        if failed {
+            return Err(());
+        }
+
+        // Arguments run in the order they are written, each into its own temporary;
+        // the call then passes the temporaries in parameter order.
+        let mut temporaries = Vec::new();
+        for (arg_name, arg_expr) in named_args {
+            let idx = params.iter().position(|p| p == arg_name).unwrap();
+            let (var, ty) = self.codegen_expr_expecting(arg_expr, &param_types[idx], body, loc.clone())?;
+            let tmp = self.fresh_var();
+            let c_name = if matches!(ty, Type::Array { size: Some(_), .. } | Type::MultiArray { .. }) {
+                var
+            } else {
+                let decl = ty.c_declarator(&tmp, &self.arch, &mut self.type_registry);
+                body.push_str(&format!("{} = {};\n", decl, var));
+                tmp.clone()
+            };
+            ordered[idx] = Some(Expr::Var(tmp.clone()));
+            let prev = self.vars.insert(tmp.clone(), (c_name, ty));
+            temporaries.push((tmp, prev));
+        }
+
+        let positional: Vec<Expr> = ordered.into_iter().flatten().collect();
+        let result = self.codegen_call_expr(func, &positional, body, loc);
+        for (tmp, prev) in temporaries.into_iter().rev() {
+            match prev {
+                Some(prev) => { self.vars.insert(tmp, prev); }
+                None => { self.vars.remove(&tmp); }
+            }
+        }
+        named_args.iter().for_each(|(_, arg)| self.note_move(arg));
+        result
+    }
+
     pub fn codegen_tuple(&mut self, elements: &[Expr], body: &mut String) -> Result<(String, Type), ()> {
          
         let mut element_types = Vec::new();
@@ -57,7 +176,57 @@ impl Codegen {
         body: &mut String,
         _loc: SourceLocation,
     ) -> Result<(String, Type), ()> {
+        // The parser only knows the types declared in its own file; `Type.method()`
+        // on an imported type reaches here as a call on a plain name.
+        if let Expr::Var(name) = obj
+            && !self.vars.contains_key(name)
+            && !self.global_consts.contains_key(name)
+            && (self.structs.contains_key(name)
+                || self.type_registry.enum_definitions.contains_key(name)
+                || self.impl_methods.contains_key(&(name.clone(), method.to_string())))
+        {
+            return self.codegen_static_method(name, method, args, body, _loc);
+        }
+
+        if let Expr::Var(name) = obj
+            && !self.vars.contains_key(name)
+            && let Some(module) = self.resolve_module(name)
+        {
+            return self.codegen_module_call(&[module], method, args, body, _loc);
+        }
+
         let (obj_var, obj_ty) = self.codegen_expr(obj, body)?;
+
+        if matches!(method, "len" | "length") && args.is_empty() {
+            let view = match &obj_ty {
+                Type::Owned(inner) if inner.is_owned_buffer() => inner.as_ref(),
+                ty => ty,
+            };
+            let len = match view {
+                Type::Str { .. } | Type::StrSlice { .. } | Type::ConstStr | Type::Array { size: None, .. } | Type::Slice { .. } => {
+                    Some(format!("{}.len", obj_var))
+                }
+                Type::Array { size: Some(_), .. } | Type::MultiArray { .. } => {
+                    Some(format!("sizeof({0}) / sizeof({0}[0])", obj_var))
+                }
+                _ => None,
+            };
+            if let Some(len) = len {
+                let tmp = self.fresh_var();
+                body.push_str(&format!("size_t {} = {};\n", tmp, len));
+                return Ok((tmp, Type::Usize));
+            }
+        }
+
+        if let Type::HashMap { key, value } = &obj_ty {
+            let (key, value) = (key.as_ref().clone(), value.as_ref().clone());
+            return self.codegen_hashmap_method(&obj_var, &key, &value, method, args, body, _loc);
+        }
+
+        if matches!(obj_ty, Type::Option { .. } | Type::Result { .. }) && matches!(method, "unwrap" | "unwrap_or" | "expect") {
+            return self.codegen_variant_method(&obj_var, &obj_ty, method, args, body, _loc);
+        }
+
         let struct_name = match &obj_ty {
             Type::Struct { name } => Some(name.clone()),
             Type::Ref(inner) | Type::MutRef(inner) => {
@@ -76,7 +245,7 @@ impl Codegen {
             } else { None }
         } else { None };
 
-        println!("[DEBUG] codegen_method_call: method={}", method);
+        debug_log!("[DEBUG] codegen_method_call: method={}", method);
         
         let (return_type, is_instance, method_full_name) = if let Some(mi) = method_info {
             mi
@@ -139,26 +308,134 @@ impl Codegen {
         }
     }
 
+    /// The dummy value only keeps expression contexts well-formed; `Never` tells callers
+    /// that control does not continue past it.
     pub fn codegen_make_panic(&mut self, expr: &Expr, body: &mut String) -> Result<(String, Type), ()> {
-        let (msg_var, _) = self.codegen_expr(expr, body) ?;
+        let (msg_var, msg_ty) = self.codegen_expr(expr, body) ?;
         let tmp = self.fresh_var();
+        let (spec, msg) = if matches!(msg_ty, Type::Str { .. }) {
+            ("%.*s", format!("(int){0}.len, {0}.ptr", msg_var))
+        } else {
+            ("%s", msg_var)
+        };
         
-        body.push_str(&format!("fprintf(stderr, \"panic: %s\\n\", {});\n", msg_var));
+        body.push_str(&format!("fprintf(stderr, \"panic: {}\\n\", {});\n", spec, msg));
         body.push_str("exit(1);\n");
         body.push_str(&format!("int {} = 0;\n", tmp));
         
-        Ok((tmp, Type::Void))
+        Ok((tmp, Type::Never))
     }
     
+    pub fn codegen_plan(&mut self, format_str: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let mut pieces = vec![String::new()];
+        let mut chars = format_str.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => { chars.next(); pieces.last_mut().unwrap().push('{'); }
+                '}' if chars.peek() == Some(&'}') => { chars.next(); pieces.last_mut().unwrap().push('}'); }
+                '{' if chars.peek() == Some(&'}') => { chars.next(); pieces.push(String::new()); }
+                '%' => pieces.last_mut().unwrap().push_str("%%"),
+                _ => pieces.last_mut().unwrap().push(c),
+            }
+        }
+        let pieces: Vec<String> = pieces.iter().map(|piece| escape_c_string(piece)).collect();
+
+        let placeholders = pieces.len() - 1;
+        if placeholders != args.len() {
+            self.diagnostics.error(
+                "FormatArgumentMismatch",
+                &format!("Format string has {} placeholder(s) but {} argument(s) were given", placeholders, args.len()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Each '{}' in the format string takes exactly one argument; write '{{' or '}}' for a literal brace.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        }
+
+        let mut format = pieces[0].clone();
+        let mut arg_vars = Vec::new();
+        for (arg, piece) in args.iter().zip(&pieces[1..]) {
+            let (var, ty) = self.codegen_expr(arg, body)?;
+            // An owned buffer has the same C layout as the buffer it owns.
+            let ty = match ty {
+                Type::Owned(inner) if inner.is_owned_buffer() => *inner,
+                ty => ty,
+            };
+            let (spec, value) = match &ty {
+                Type::Int { bits, signed: true } if *bits > 32 => ("%lld", format!("(long long){}", var)),
+                Type::Int { bits, signed: false } if *bits > 32 => ("%llu", format!("(unsigned long long){}", var)),
+                Type::Int { signed: true, .. } | Type::Bool => ("%d", var),
+                Type::Int { signed: false, .. } => ("%u", var),
+                Type::Usize => ("%zu", var),
+                Type::Float { .. } => ("%f", var),
+                Type::Char { .. } => ("%c", var),
+                Type::Str { .. } => ("%.*s", format!("(int){0}.len, {0}.ptr", var)),
+                Type::ConstStr => ("%s", var),
+                Type::Ptr(_) | Type::RawPtr(_) | Type::Owned(_) => ("%p", format!("(void*){}", var)),
+                _ => {
+                    self.diagnostics.error(
+                        "UnsupportedFormatArgument",
+                        &format!("Cannot format a value of type {:?}", ty),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Only integers, floats, chars, strings and pointers can be formatted.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                    return Err(());
+                }
+            };
+            format.push_str(spec);
+            format.push_str(piece);
+            arg_vars.push(value);
+        }
+
+        let tmp = self.fresh_var();
+        let mut call_args = vec![format!("\"{}\"", format)];
+        call_args.extend(arg_vars);
+        body.push_str(&format!("int {} = printf({});\n", tmp, call_args.join(", ")));
+        Ok((tmp, Type::i32()))
+    }
+
+    /// A `str` as is, or a NUL-terminated `cstr` wrapped in a `Slice_char` view.
+    pub fn string_slice(&mut self, var: &str, ty: &Type, body: &mut String) -> String {
+        if matches!(ty, Type::Str { .. }) {
+            return var.to_string();
+        }
+        let slice_type = self.type_registry.generate_slices(&Type::char8(), &self.arch);
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {{ .ptr = (char*){}, .len = strlen({}) }};\n", slice_type, tmp, var, var));
+        tmp
+    }
+
+    /// Compares two `Slice_char` views by their bytes and lengths; neither needs a NUL terminator.
+    /// Equality checks the lengths first and only then the bytes.
     pub fn codegen_string_compare(&mut self, left: &str, right: &str, op: &str, body: &mut String) -> String {
         let tmp = self.fresh_var();
+
+        if matches!(op, "==" | "!=") {
+            let equal = format!("{l}.len == {r}.len && ({l}.len == 0 || memcmp({l}.ptr, {r}.ptr, {l}.len) == 0)", l = left, r = right);
+            let condition = if op == "==" { equal } else { format!("!({})", equal) };
+            body.push_str(&format!("bool {} = {};\n", tmp, condition));
+            return tmp;
+        }
+
+        let slice_type = self.type_registry.generate_slices(&Type::char8(), &self.arch);
+        self.ir.add_helper_function(
+            "vix_str_cmp",
+            format!(
+                "static int vix_str_cmp({t} a, {t} b) {{\n    size_t n = a.len < b.len ? a.len : b.len;\n    int c = n ? memcmp(a.ptr, b.ptr, n) : 0;\n    if (c != 0) return c;\n    return (a.len > b.len) - (a.len < b.len);\n}}\n",
+                t = slice_type
+            ),
+        );
         let cmp_var = self.fresh_var();
-        
-        body.push_str(&format!("int {} = strcmp({}, {});\n", cmp_var, left, right));
+        body.push_str(&format!("int {} = vix_str_cmp({}, {});\n", cmp_var, left, right));
         
         let condition = match op {
-            "==" => format!("{} == 0", cmp_var),
-            "!=" => format!("{} != 0", cmp_var),
             "<" => format!("{} < 0", cmp_var),
             "<=" => format!("{} <= 0", cmp_var),
             ">" => format!("{} > 0", cmp_var),
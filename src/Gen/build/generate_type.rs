@@ -1,24 +1,235 @@
 use crate::import::*;
 
+/// Escapes `s` for the inside of a C string literal.
+pub(crate) fn escape_c_string(s: &str) -> String {
+    let mut escaped = String::new();
+    for ch in s.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            // octal keeps a following digit from extending the escape
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\{:03o}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 impl Codegen {
     pub fn codegen_string(&mut self, s: &str, body: &mut String) -> (String, Type) {
         let ty = Type::Str { len_type: Box::new(Type::i64()) };
         let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
         
         let tmp = self.fresh_var();
-        let escaped = s.replace('\\', "\\\\")
-            .replace('"', "\\\"")
-            .replace('\n', "\\n")
-            .replace('\r', "\\r")
-            .replace('\t', "\\t");
-        
-         
         body.push_str(&format!("{} {} = {{ .ptr = \"{}\", .len = {} }};\n", 
-            c_type, tmp, escaped, s.len()));
+            c_type, tmp, escape_c_string(s), s.len()));
         
         (tmp, ty)
     }
 
+    /// Emits `[a, b, c]` as a fixed C array whose element type comes from the first element.
+    pub fn codegen_array_literal(&mut self, elements: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        if elements.is_empty() {
+            self.diagnostics.error(
+                "EmptyArrayLiteral",
+                "Cannot infer the element type of an empty array literal",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Give the variable an explicit array type.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        }
+
+        let mut vars = Vec::new();
+        let mut elem_ty = None;
+        for elem in elements {
+            let (var, ty) = self.codegen_expr(elem, body)?;
+            match &elem_ty {
+                None => elem_ty = Some(ty),
+                Some(first) if !self.types_compatible(&ty, first) => {
+                    self.diagnostics.error(
+                        "ArrayElementMismatch",
+                        &format!("Array element has type {}, expected {}", ty.name(), first.name()),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("All elements of an array literal must share one type.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                    return Err(());
+                }
+                Some(_) => {}
+            }
+            vars.push(var);
+        }
+
+        let elem_ty = elem_ty.unwrap_or(Type::Void);
+        let elem_c_type = elem_ty.to_c_type(&self.arch, &mut self.type_registry);
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {}[{}] = {{ {} }};\n", elem_c_type, tmp, vars.len(), vars.join(", ")));
+
+        Ok((tmp, Type::Array { element: Box::new(elem_ty), size: Some(vars.len()) }))
+    }
+
+    /// Builds a map from `{ [key] = value, ... }`; the first entry fixes the key and value types.
+    pub fn codegen_hashmap_literal(&mut self, entries: &[(Expr, Expr)], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        if entries.is_empty() {
+            self.diagnostics.error(
+                "EmptyMapLiteral",
+                "Cannot infer the key and value types of an empty map literal",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Map literals need at least one entry.".to_string()),
+                    suggestions: vec!["Add an initial entry such as { [\"key\"] = 0 }".to_string()],
+                }
+            );
+            return Err(());
+        }
+
+        let mut values = Vec::new();
+        for (key_expr, val_expr) in entries {
+            let key = self.codegen_expr(key_expr, body)?;
+            let value = self.codegen_expr(val_expr, body)?;
+            values.push((key, value));
+        }
+
+        let key_ty = values[0].0.1.clone();
+        let val_ty = values[0].1.1.clone();
+        for ((_, k_ty), (_, v_ty)) in &values[1..] {
+            if !self.types_compatible(k_ty, &key_ty) || !self.types_compatible(v_ty, &val_ty) {
+                self.diagnostics.error(
+                    "MapEntryMismatch",
+                    &format!("Map entry has type [{}] = {}, expected [{}] = {}", k_ty.name(), v_ty.name(), key_ty.name(), val_ty.name()),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("All keys and all values of a map literal must share one type.".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                return Err(());
+            }
+        }
+
+        if let Some(def) = self.type_registry.generate_hashmap_definition(&key_ty, &val_ty, &self.arch)
+            && !self.ir.forward_decls.contains(&def) {
+                self.ir.forward_decls.push_str(&def);
+                self.ir.forward_decls.push('\n');
+            }
+
+        let hashmap_ty = Type::HashMap { key: Box::new(key_ty), value: Box::new(val_ty) };
+        let hashmap_name = hashmap_ty.to_c_type(&self.arch, &mut self.type_registry);
+        let tmp = self.fresh_var();
+
+        body.push_str(&format!("{} {} = {{0}};\n", hashmap_name, tmp));
+        for ((key_var, _), (val_var, _)) in &values {
+            body.push_str(&format!("insert_{}(&{}, {}, {});\n", hashmap_name, tmp, key_var, val_var));
+        }
+
+        Ok((tmp, hashmap_ty))
+    }
+
+    /// Lowers `map.get(key)` and `map.insert(key, value)`; a missing key reads as the zero value.
+    #[allow(clippy::too_many_arguments)]
+    pub fn codegen_hashmap_method(
+        &mut self,
+        map_var: &str,
+        key_ty: &Type,
+        val_ty: &Type,
+        method: &str,
+        args: &[Expr],
+        body: &mut String,
+        loc: SourceLocation,
+    ) -> Result<(String, Type), ()> {
+        let hashmap_name = Type::HashMap { key: Box::new(key_ty.clone()), value: Box::new(val_ty.clone()) }
+            .to_c_type(&self.arch, &mut self.type_registry);
+        let val_c_type = val_ty.to_c_type(&self.arch, &mut self.type_registry);
+
+        match (method, args) {
+            ("get", [key]) => {
+                let (key_var, _) = self.codegen_expr(key, body)?;
+                let slot = self.fresh_var();
+                let tmp = self.fresh_var();
+                body.push_str(&format!("const {}* {} = get_{}(&{}, {});\n", val_c_type, slot, hashmap_name, map_var, key_var));
+                body.push_str(&format!("{} {} = {} ? *{} : ({}){{0}};\n", val_c_type, tmp, slot, slot, val_c_type));
+                Ok((tmp, val_ty.clone()))
+            }
+            ("insert", [key, value]) => {
+                let (key_var, _) = self.codegen_expr(key, body)?;
+                let (val_var, _) = self.codegen_expr(value, body)?;
+                body.push_str(&format!("insert_{}(&{}, {}, {});\n", hashmap_name, map_var, key_var, val_var));
+                Ok((String::new(), Type::Void))
+            }
+            _ => {
+                self.diagnostics.error(
+                    "UndefinedMethod",
+                    &format!("Map has no method '{}' taking {} argument(s)", method, args.len()),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Maps support get(key) and insert(key, value).".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                Err(())
+            }
+        }
+    }
+
+    pub fn codegen_struct_literal(&mut self, name: &str, fields: &[(String, Expr)], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let Some(info) = self.structs.get(name) else {
+            self.diagnostics.error(
+                "UnknownStruct",
+                &format!("Struct '{}' is not defined", name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Struct literals need a struct declared with 'struct Name:'.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        };
+        let known: Vec<String> = info.fields.iter().map(|(f, _, _)| f.clone()).collect();
+
+        let mut inits = Vec::new();
+        for (field, value) in fields {
+            if !known.contains(field) {
+                let closest = known.iter().min_by_key(|k| levenshtein(k, field)).filter(|k| levenshtein(k, field) <= 2);
+                self.diagnostics.error(
+                    "UnknownField",
+                    &format!("Struct '{}' has no field named '{}'", name, field),
+                    ErrorContext {
+                        primary_location: loc.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("Available fields: {}", known.join(", "))),
+                        suggestions: closest.map(|k| format!("Did you mean '{}'?", k)).into_iter().collect(),
+                    }
+                );
+                return Err(());
+            }
+
+            let (var, _) = self.codegen_expr(value, body)?;
+            inits.push(format!(".{} = {}", field, var));
+        }
+
+        if inits.is_empty() {
+            inits.push("0".to_string());
+        }
+
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = ({}){{ {} }};\n", name, tmp, name, inits.join(", ")));
+        Ok((tmp, Type::Struct { name: name.to_string() }))
+    }
+
     pub fn codegen_number(&mut self, n: i64, body: &mut String) -> (String, Type) {
         let tmp = self.fresh_var();
         if n >= i32::MIN as i64 && n <= i32::MAX as i64 {
@@ -42,10 +253,51 @@ impl Codegen {
         (tmp, Type::Bool)
     }
 
+    /// A suffixed literal like `255u8`, emitted at its declared width after a range check.
+    pub fn codegen_typed_number(&mut self, n: i64, ty: &Type, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let bits = ty.size_bits(&self.arch).min(64) as u32;
+        let signed = matches!(ty, Type::Int { signed: true, .. });
+        let (min, max) = if signed {
+            (i128::MIN >> (128 - bits), i128::MAX >> (128 - bits))
+        } else {
+            (0, (u64::MAX >> (64 - bits)) as i128)
+        };
+        if !(min..=max).contains(&(n as i128)) {
+            self.diagnostics.error(
+                "LiteralOutOfRange",
+                &format!("Literal {} does not fit in {} ({}..={})", n, ty.name(), min, max),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Use a wider suffix or a smaller value.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        }
+
+        let tmp = self.fresh_var();
+        let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = {};\n", c_type, tmp, n));
+        Ok((tmp, ty.clone()))
+    }
+
+    /// ASCII literals stay a C `char`; other codepoints need an `int32_t`.
     pub fn codegen_char(&mut self, c: i32, body: &mut String) -> (String, Type) {
         let tmp = self.fresh_var();
-        body.push_str(&format!("char {} = {};\n", tmp, c));
-        (tmp, Type::char8())
+        if (0..=0x7F).contains(&c) {
+            body.push_str(&format!("char {} = {};\n", tmp, c));
+            (tmp, Type::char8())
+        } else {
+            body.push_str(&format!("int32_t {} = {};\n", tmp, c));
+            (tmp, Type::i32())
+        }
+    }
+
+    pub fn codegen_byte_char(&mut self, b: u8, body: &mut String) -> (String, Type) {
+        let tmp = self.fresh_var();
+        body.push_str(&format!("uint8_t {} = {};\n", tmp, b));
+        (tmp, Type::u8())
     }
 
     pub fn codegen_hex_number(&mut self, n: i32, body: &mut String) -> (String, Type) {
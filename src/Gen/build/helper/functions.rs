@@ -1,7 +1,16 @@
 use crate::import::*;
 
 impl Codegen {
+    /// `malloc` that aborts instead of returning NULL; the runtime helpers allocate through it.
+    pub fn ensure_vix_malloc(&mut self) {
+        self.ir.add_helper_function(
+            "vix_malloc",
+            "static void* vix_malloc(size_t size) {\n    void* ptr = malloc(size);\n    if (!ptr && size) {\n        fprintf(stderr, \"vix: out of memory\\n\");\n        abort();\n    }\n    return ptr;\n}\n".to_string(),
+        );
+    }
+
     pub fn ensure_unified_push(&mut self) {
+        self.ensure_vix_malloc();
         if self.ir.functions.contains("vix_push") {
             return;
         }
@@ -50,6 +59,7 @@ static inline void* vix_push_impl(void* arr_ptr, const void* elem_ptr, size_t el
     }
 
     pub fn ensure_unified_extend(&mut self) {
+        self.ensure_vix_malloc();
         if self.ir.functions.contains("vix_extend") {
             return;
         }
@@ -99,31 +109,40 @@ static inline void vix_extend_impl(void* dest_ptr, const void* src_ptr, size_t e
         self.ir.functions.push_str(helper);
     }
     
+    /// Strings built by these helpers live until the program exits: they are bump-allocated
+    /// from per-thread buffers, and anything that no longer fits gets its own heap block
+    /// rather than overwriting earlier strings that may still be in use.
     pub fn ensure_zero_alloc_string_ops(&mut self) {
+        self.ensure_vix_malloc();
         if self.ir.functions.contains("vix_str_concat_view") {
             return;
         }
         
         let helper = r#"
+static inline char* vix_bump_alloc(char* buf, size_t capacity, size_t* offset, size_t size) {
+    if (!buf || size > capacity - *offset) {
+        return (char*)vix_malloc(size);
+    }
+
+    char* ptr = buf + *offset;
+    *offset += size;
+    return ptr;
+}
+
 static Slice_char vix_str_concat_view(Slice_char s1, Slice_char s2) {
     static __thread char buffer[8192];
     static __thread size_t offset = 0;
     
     size_t total = s1.len + s2.len;
+    char* ptr = vix_bump_alloc(buffer, sizeof(buffer), &offset, total);
 
-    if (offset + total >= sizeof(buffer)) {
-        offset = 0;
-    }
-
-    memcpy(buffer + offset, s1.ptr, s1.len);
-    memcpy(buffer + offset + s1.len, s2.ptr, s2.len);
+    memcpy(ptr, s1.ptr, s1.len);
+    memcpy(ptr + s1.len, s2.ptr, s2.len);
 
     Slice_char result;
-    result.ptr = buffer + offset;
+    result.ptr = ptr;
     result.len = total;
 
-    offset += total;
-
     return result;
 }
 
@@ -133,18 +152,13 @@ static inline void vix_str_append_inplace(Slice_char* dest, Slice_char src) {
     static __thread size_t extend_offset = 0;
     
     size_t total = dest->len + src.len;
-    
-    if (extend_offset + total >= sizeof(extend_buf)) {
-        extend_offset = 0;
-    }
+    char* ptr = vix_bump_alloc(extend_buf, sizeof(extend_buf), &extend_offset, total);
 
-    memcpy(extend_buf + extend_offset, dest->ptr, dest->len);
-    memcpy(extend_buf + extend_offset + dest->len, src.ptr, src.len);
+    memcpy(ptr, dest->ptr, dest->len);
+    memcpy(ptr + dest->len, src.ptr, src.len);
     
-    dest->ptr = extend_buf + extend_offset;
+    dest->ptr = ptr;
     dest->len = total;
-    
-    extend_offset += total;
 }
 
 typedef struct {
@@ -153,7 +167,7 @@ typedef struct {
     size_t capacity;
 } Arena;
 
-static Arena global_arena = {0};
+static __thread Arena global_arena = {0};
 
 static inline void vix_arena_init(size_t capacity) {
     if (!global_arena.base) {
@@ -164,13 +178,7 @@ static inline void vix_arena_init(size_t capacity) {
 }
 
 static inline char* vix_arena_alloc(size_t size) {
-    if (global_arena.offset + size > global_arena.capacity) {
-        global_arena.offset = 0;
-    }
-    
-    char* ptr = global_arena.base + global_arena.offset;
-    global_arena.offset += size;
-    return ptr;
+    return vix_bump_alloc(global_arena.base, global_arena.capacity, &global_arena.offset, size);
 }
 
 static Slice_char vix_str_concat_arena(Slice_char s1, Slice_char s2) {
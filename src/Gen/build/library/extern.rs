@@ -49,7 +49,7 @@ impl Codegen {
         let return_type = match &func.return_type {
             Type::ConstStr | Type::Str { .. } => "const char*".to_string(),
             _ => {
-                println!("No it now const char");
+                debug_log!("No it now const char");
                 func.return_type.to_c_type(&self.arch, &mut self.type_registry)
             }
         };
@@ -99,6 +99,10 @@ impl Codegen {
                 Type::Ptr(inner) if matches!(inner.as_ref(), Type::Const(t) if matches!(t.as_ref(), Type::Char { bits: 8, .. })) => {
                     "const char*".to_string()
                 }
+                Type::FunctionPointer { .. } => {
+                    params_str.push(param_type.c_declarator(param_name, &self.arch, &mut self.type_registry));
+                    continue;
+                }
                 _ => param_type.to_c_type(&self.arch, &mut self.type_registry)
             };
             
@@ -145,8 +149,11 @@ impl Codegen {
     pub fn codegen_externs(&mut self, externs: &[ExternDecl]) -> Result<(), ()> {
         for ext in externs {
             match ext {
-                ExternDecl::Single { abi, func } => {
-                    self.codegen_extern_function(abi, func, None)?;
+                ExternDecl::Single { abi, library, func } => {
+                    if let Some(library) = library {
+                        self.track_library(library);
+                    }
+                    self.codegen_extern_function(abi, func, library.as_deref())?;
                 }
                 ExternDecl::Block { abi, library, functions } => {
                     self.track_library(library);
@@ -154,41 +161,44 @@ impl Codegen {
                         self.codegen_extern_function(abi, func, Some(library))?;
                     }
                 }
-                ExternDecl::SingleWithBody { .. } => {
-                    
+                ExternDecl::SingleWithBody { func, .. } => {
+                    self.codegen_function(&extern_body_function(func), true);
                 }
             }
         }
         Ok(())
     }
-    
-    fn track_library(&mut self, library: &str) {
-        let lib_name = self.extract_library_name(library);
-        
-        if !self.linked_libraries.contains(&lib_name) {
-            self.linked_libraries.push(lib_name);
+
+    /// Generates the bodies of `extern` functions defined in Vix, once all signatures are known.
+    pub fn codegen_extern_bodies(&mut self, externs: &[ExternDecl]) {
+        for ext in externs {
+            if let ExternDecl::SingleWithBody { func, .. } = ext {
+                self.codegen_function(&extern_body_function(func), false);
+            }
         }
     }
     
-    fn extract_library_name(&self, library: &str) -> String {
-        
+    /// Records a library as written in the source; `Clang::library_link_args` turns it into linker flags.
+    fn track_library(&mut self, library: &str) {
         let library = library.trim();
-        let filename = if library.contains('/') || library.contains('\\') {
-            library.split(&['/', '\\'][..]).next_back().unwrap_or(library)
-                } else {
-            library
-        };
-        
-        filename
-            .trim_end_matches(".dll")
-            .trim_end_matches(".lib")
-            .trim_end_matches(".so")
-            .trim_end_matches(".dylib")
-            .trim_end_matches(".a")
-            .to_string()
+        if !library.is_empty() && !self.linked_libraries.iter().any(|l| l == library) {
+            self.linked_libraries.push(library.to_string());
+        }
     }
     
     pub fn get_linked_libraries(&self) -> &[String] {
         &self.linked_libraries
     }
-}
\ No newline at end of file
+}
+
+/// Public functions are emitted without `static`, which gives them external linkage.
+fn extern_body_function(func: &ExternFunctionBody) -> Function {
+    Function {
+        name: func.name.clone(),
+        params: func.params.iter().map(|(name, ty)| (name.clone(), ty.clone(), ParamModifier::Immutable)).collect(),
+        return_type: func.return_type.clone(),
+        body: func.body.clone(),
+        is_public: true,
+        ..Function::default()
+    }
+}
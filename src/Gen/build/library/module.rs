@@ -1,32 +1,39 @@
 use crate::import::*;
+use crate::Gen::build::stmt::BodyReads;
 
 impl Codegen {
     pub fn codegen_module_call(
         &mut self, 
-        module: &str, 
+        path: &[String], 
         func: &str, 
         args: &[Expr], 
         body: &mut String, 
         loc: SourceLocation
     ) -> Result<(String, Type), ()> {
-         
+        // Nested modules are registered under their `_`-joined path; only the first segment can be an alias.
+        let mut segments = path.to_vec();
+        if let Some(real) = self.module_aliases.get(&segments[0]) {
+            segments[0] = real.clone();
+        }
+        let module = segments.join("_");
+        let module = module.as_str();
         let full_func_name = format!("{}_{}", module, func);
         
-        println!("[DEBUG] codegen_module_call: module={}, func={}, full_name={}", 
+        debug_log!("[DEBUG] codegen_module_call: module={}, func={}, full_name={}", 
                  module, func, full_func_name);
         
          
         let return_type = if let Some((_params, ret_ty)) = self.user_functions.get(&full_func_name) {
-            println!("[DEBUG] Found user function: {} -> {:?}", full_func_name, ret_ty);
+            debug_log!("[DEBUG] Found user function: {} -> {:?}", full_func_name, ret_ty);
             ret_ty.clone()
         } else if let Some(ext_info) = self.extern_functions.get(&full_func_name) {
-            println!("[DEBUG] Found extern function: {} -> {:?}", full_func_name, ext_info.return_type);
+            debug_log!("[DEBUG] Found extern function: {} -> {:?}", full_func_name, ext_info.return_type);
             ext_info.return_type.clone()
         } else if let Some((_params, ret_ty, _)) = self.module_functions.get(&(module.to_string(), func.to_string())) {
-            println!("[DEBUG] Found module function: {} -> {:?}", full_func_name, ret_ty);
+            debug_log!("[DEBUG] Found module function: {} -> {:?}", full_func_name, ret_ty);
             ret_ty.clone()
         } else {
-            println!("[DEBUG] Function '{}' not found in module '{}'", func, module);
+            debug_log!("[DEBUG] Function '{}' not found in module '{}'", func, module);
             self.diagnostics.warning(
                 "UndefinedModuleFunction",
                 &format!("Function '{}' not found in module '{}'", func, module),
@@ -53,32 +60,60 @@ impl Codegen {
             arg_vars.push(var);
         }
         
+        // Functions declared in a `mod` block are emitted under their own name.
+        let callee = if self.module_functions.contains_key(&(module.to_string(), func.to_string())) {
+            func.to_string()
+        } else {
+            full_func_name
+        };
+
         let tmp = self.fresh_var();
         let c_type = return_type.to_c_type(&self.arch, &mut self.type_registry);
         let args_str = arg_vars.join(", ");
         
          
         if matches!(return_type, Type::Void) {
-            body.push_str(&format!("{}({});\n", full_func_name, args_str));
+            body.push_str(&format!("{}({});\n", callee, args_str));
              
             body.push_str(&format!("int32_t {} = 0;\n", tmp));
             Ok((tmp, Type::Void))
         } else {
-            body.push_str(&format!("{} {} = {}({});\n", c_type, tmp, full_func_name, args_str));
+            body.push_str(&format!("{} {} = {}({});\n", c_type, tmp, callee, args_str));
             Ok((tmp, return_type))
         }
     }
     
+    /// Records the aliases introduced by `use module as alias`.
+    pub fn set_module_uses(&mut self, uses: &[ModuleUse]) {
+        for module_use in uses {
+            if let Some(alias) = &module_use.alias {
+                self.module_aliases.insert(alias.clone(), module_use.module_name.clone());
+            }
+        }
+    }
+
+    /// Resolves a name used as the receiver of a call to the module it names, following aliases.
+    pub fn resolve_module(&self, name: &str) -> Option<String> {
+        let module = self.module_aliases.get(name).map(String::as_str).unwrap_or(name);
+        self.module_functions.keys().any(|(m, _)| m == module).then(|| module.to_string())
+    }
+
     pub fn codegen_module(&mut self, module: &Stmt) {
-        if let Stmt::ModuleDef { name, body, is_public: _ } = module {
+        self.codegen_module_in("", module);
+    }
+
+    /// Generates `module`, declared inside the module whose path is `prefix` (empty at the top level).
+    fn codegen_module_in(&mut self, prefix: &str, module: &Stmt) {
+        if let Stmt::ModuleDef { name, body, .. } = module.node() {
+            let name = &if prefix.is_empty() { name.clone() } else { format!("{}_{}", prefix, name) };
             let init_func_name = format!("{}_init", name);
-            self.module_init_functions.push(init_func_name.clone());
             
             let mut init_body = String::new();
             self.ir.forward_decls.push_str(&format!("void {}();\n", init_func_name));
             
             for stmt in body {
-                match stmt {
+                self.current_line = self.stmt_line(stmt);
+                match stmt.node() {
                     Stmt::Function(f) => {
                          
                         let full_func_name = format!("{}_{}", name, f.name);
@@ -118,7 +153,10 @@ impl Codegen {
                         self.codegen_enum_definition(e).map_err(|_| ()).ok(); 
                     }
                     Stmt::ModuleDef { .. } => { 
-                        self.codegen_module(stmt); 
+                        self.codegen_module_in(name, stmt); 
+                    }
+                    Stmt::TypedDeclaration { name: var_name, ty, value, is_mutable } => {
+                        self.codegen_module_var(name, var_name, ty, value, *is_mutable, &mut init_body);
                     }
                     _ => {
                         self.codegen_stmt(stmt, &mut init_body).ok();
@@ -126,8 +164,151 @@ impl Codegen {
                 }
             }
             
-            let code = format!("void {}() {{\n{}\n}}\n", init_func_name, init_body);
+            self.current_line = 0;
+
+            let mut code = format!("void {}() {{\n{}\n}}\n", init_func_name, init_body);
+            self.reset_line_directive(&mut code);
             self.ir.functions.push_str(&code);
+            // Nested modules were registered above, so they initialize before this one.
+            self.module_init_functions.push(init_func_name);
+        }
+    }
+
+    /// Declares a module-level variable as a C global, assigned when the module initializes.
+    fn codegen_module_var(&mut self, module: &str, name: &str, ty: &Type, value: &Expr, is_mutable: bool, init_body: &mut String) {
+        let loc = self.stmt_location();
+        if self.codegen_typed_declaration_impl(name, ty, value, init_body, loc, is_mutable).is_err() {
+            return;
+        }
+        let Some((local, ty)) = self.vars.remove(name) else { return };
+
+        let global = format!("{}_{}", module, name);
+        let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        self.ir.forward_decls.push_str(&format!("static {} {};\n", c_type, global));
+        init_body.push_str(&format!("{} = {};\n", global, local));
+        self.module_vars.insert((module.to_string(), name.to_string()), (global, ty, is_mutable));
+    }
+
+    pub fn codegen_module_access(&mut self, path: &[String], member: &str, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let mut segments = path.to_vec();
+        if let Some(real) = self.module_aliases.get(&segments[0]) {
+            segments[0] = real.clone();
+        }
+        if let Some((c_name, ty, _)) = self.module_vars.get(&(segments.join("_"), member.to_string())) {
+            return Ok((c_name.clone(), ty.clone()));
+        }
+
+        self.diagnostics.error(
+            "UndefinedModuleVariable",
+            &format!("Module '{}' has no variable '{}'", path.join("."), member),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("Declare it at the top level of the module with 'create {} = ...'", member)),
+                suggestions: vec![],
+            }
+        );
+        Err(())
+    }
+
+    /// Generates the top-level modules so that each comes after the modules it reads, then
+    /// emits `vix_init_modules`, which runs their inits in that order.
+    pub fn codegen_modules(&mut self, modules: &[Stmt]) {
+        let names: Vec<String> = modules.iter().filter_map(|module| match module {
+            Stmt::ModuleDef { name, .. } => Some(name.clone()),
+            _ => None,
+        }).collect();
+        if names.is_empty() {
+            return;
+        }
+
+        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+        for module in modules {
+            if let Stmt::ModuleDef { name, body, .. } = module {
+                let mut deps: Vec<String> = module_references(body).into_iter()
+                    .map(|r| self.module_aliases.get(&r).cloned().unwrap_or(r))
+                    .filter(|dep| names.contains(dep) && dep != name)
+                    .collect();
+                deps.sort_by_key(|dep| names.iter().position(|n| n == dep));
+                deps.dedup();
+                dependencies.insert(name.clone(), deps);
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut visiting = Vec::new();
+        let mut cycles = Vec::new();
+        for name in &names {
+            visit_module(name, &dependencies, &mut visiting, &mut order, &mut cycles);
+        }
+        for cycle in cycles {
+            let line = modules.iter().find_map(|module| match module {
+                Stmt::ModuleDef { name, line, .. } if *name == cycle[0] => Some(*line),
+                _ => None,
+            }).unwrap_or(0);
+            let loc = self.line_location(line);
+            self.diagnostics.error(
+                "ModuleInitCycle",
+                &format!("Modules depend on each other during initialization: {}", cycle.join(" -> ")),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("A module's top-level statements may only use modules that do not use it back.".to_string()),
+                    suggestions: vec![],
+                }
+            );
         }
+
+        for name in &order {
+            if let Some(module) = modules.iter().find(|m| matches!(m, Stmt::ModuleDef { name: n, .. } if n == name)) {
+                self.codegen_module(module);
+            }
+        }
+
+        let calls: String = self.module_init_functions.iter().map(|init| format!("    {}();\n", init)).collect();
+        self.ir.forward_decls.push_str("void vix_init_modules();\n");
+        self.ir.functions.push_str(&format!("void vix_init_modules() {{\n{}}}\n", calls));
+        self.ir.init_modules = true;
+    }
+}
+
+/// Names read by the statements a module runs when it initializes, including those of its
+/// nested modules. Function bodies only run when called, so they are not followed.
+fn module_references(body: &[Stmt]) -> HashSet<String> {
+    let mut reads = BodyReads::default();
+    for stmt in body {
+        match stmt.node() {
+            Stmt::ModuleDef { body, .. } => reads.free.extend(module_references(body)),
+            Stmt::Function(_) | Stmt::StructDef(_) | Stmt::EnumDef(_) => {}
+            _ => reads.stmt(stmt, &|_| 0),
+        }
+    }
+    reads.free
+}
+
+/// Depth-first topological visit. An edge that closes a cycle is recorded in `cycles` and
+/// skipped, so every module still gets an order.
+fn visit_module(
+    module: &str,
+    dependencies: &HashMap<String, Vec<String>>,
+    visiting: &mut Vec<String>,
+    order: &mut Vec<String>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    if order.iter().any(|m| m == module) {
+        return;
+    }
+    if let Some(start) = visiting.iter().position(|m| m == module) {
+        let mut cycle = visiting[start..].to_vec();
+        cycle.push(module.to_string());
+        cycles.push(cycle);
+        return;
+    }
+
+    visiting.push(module.to_string());
+    for dep in &dependencies[module] {
+        visit_module(dep, dependencies, visiting, order, cycles);
     }
-}
\ No newline at end of file
+    visiting.pop();
+    order.push(module.to_string());
+}
@@ -11,6 +11,9 @@ impl Codegen {
     ) -> Result<(String, Type), ()> {
         let (l_var, l_ty) = self.codegen_expr(left, body) ?;
         let (r_var, r_ty) = self.codegen_expr(right, body) ?;
+        // An untyped integer literal takes the integer type of the other operand, as in `s.len() > 2`.
+        let l_ty = if is_int_literal(left) && r_ty.is_integer() { r_ty.clone() } else { l_ty };
+        let r_ty = if is_int_literal(right) && l_ty.is_integer() { l_ty.clone() } else { r_ty };
 
          
         let left_var = l_var.clone();
@@ -96,19 +99,9 @@ impl Codegen {
         (matches!(r_ty, Type::Str { .. }) || matches!(r_ty, Type::ConstStr)) &&
         matches!(op, "==" | "!=" | "<" | "<=" | ">" | ">=") {
             
-            let l_ptr = if matches!(l_ty, Type::Str { .. }) {
-                format!("{}.ptr", l_var)
-            } else {
-                l_var.clone()
-            };
-            
-            let r_ptr = if matches!(r_ty, Type::Str { .. }) {
-                format!("{}.ptr", r_var)
-            } else {
-                r_var.clone()
-            };
-            
-            let result = self.codegen_string_compare(&l_ptr, &r_ptr, op, body);
+            let l_slice = self.string_slice(&l_var, &l_ty, body);
+            let r_slice = self.string_slice(&r_var, &r_ty, body);
+            let result = self.codegen_string_compare(&l_slice, &r_slice, op, body);
             return Ok((result, Type::Bool));
         }
 
@@ -316,6 +309,40 @@ impl Codegen {
         }
     }
 
+    /// Each branch is generated into its own buffer so only the taken branch runs;
+    /// a branch that needed temporaries becomes a statement expression.
+    pub fn codegen_if_expr(&mut self, cond: &Expr, then_expr: &Expr, else_expr: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (cond_var, _) = self.codegen_expr(cond, body)?;
+
+        let mut then_body = String::new();
+        let (then_var, then_ty) = self.codegen_expr(then_expr, &mut then_body)?;
+        let mut else_body = String::new();
+        let (else_var, else_ty) = self.codegen_expr(else_expr, &mut else_body)?;
+
+        if !self.types_compatible(&then_ty, &else_ty) {
+            self.diagnostics.error(
+                "IfBranchMismatch",
+                &format!("If expression branches have different types: {} and {}", then_ty.name(), else_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Both branches of an if expression must produce the same type.".to_string()),
+                    suggestions: vec![format!("Cast the else branch to {}", then_ty.name())],
+                }
+            );
+            return Err(());
+        }
+
+        let branch = |code: String, var: String| {
+            if code.is_empty() { var } else { format!("({{ {}{}; }})", code.replace('\n', " "), var) }
+        };
+        let result_ty = if matches!(then_ty, Type::Never) { else_ty } else { then_ty };
+        let tmp = self.fresh_var();
+        let c_type = result_ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = ({} ? {} : {});\n", c_type, tmp, cond_var, branch(then_body, then_var), branch(else_body, else_var)));
+        Ok((tmp, result_ty))
+    }
+
     pub fn codegen_typed_declaration(&mut self, name: &str, ty: &Type, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
         let (val_var, val_ty) = self.codegen_expr(value, body) ?;
         let c_name = format!("var_{}", name);
@@ -512,26 +539,17 @@ impl Codegen {
     fn binop_types_compatible_str(&self, left: &Type, right: &Type, op: &str) -> bool {
         match op {
             "+" | "-" | "*" | "/" | "%" => {
-                if matches!((left, right), (Type::Int { .. }, Type::Int { .. }) | (Type::Float { .. }, Type::Float { .. })) {
-                    true
-                } else if op == "+" && (matches!(left, Type::Str { .. } | Type::ConstStr) || matches!(right, Type::Str { .. } | Type::ConstStr)) {
+                if (left.is_integer() && right.is_integer()) || matches!((left, right), (Type::Float { .. }, Type::Float { .. })) {
                     true
-                } else {
-                    false
-                }
+                } else { op == "+" && (matches!(left, Type::Str { .. } | Type::ConstStr) || matches!(right, Type::Str { .. } | Type::ConstStr)) }
             }
 
  
-            "&" | "|" | "^" | "<<" | ">>" => {
-                matches!((left, right),
-                    (Type::Int { .. }, Type::Int { .. })
-                )
-            }
+            "&" | "|" | "^" | "<<" | ">>" => left.is_integer() && right.is_integer(),
 
  
             "==" | "!=" | "<" | "<=" | ">" | ">=" => {
-                matches!((left, right),
-                    (Type::Int { .. }, Type::Int { .. }) |
+                (left.is_integer() && right.is_integer()) || matches!((left, right),
                     (Type::Float { .. }, Type::Float { .. }) |
                     (Type::Bool, Type::Bool) |
                     (Type::Char { bits: _, signed: _ }, Type::Char { bits: _, signed: _ }) |
@@ -573,4 +591,217 @@ impl Codegen {
         Ok((val_var, val_ty))
     }
 
+    /// Generates `value` for a slot of type `expected`, which supplies the payload types
+    /// that `None`, `Ok(..)` and `Err(..)` cannot carry themselves.
+    pub fn codegen_expr_expecting(&mut self, value: &Expr, expected: &Type, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        if let Expr::Char(c) = value
+            && *expected == Type::u8() {
+                if !(0..=0x7F).contains(c) {
+                    self.diagnostics.error(
+                        "CharNotByte",
+                        &format!("'{}' is a multi-byte character and does not fit in uint8", char::from_u32(*c as u32).unwrap_or('?')),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Use an int32 for codepoints, or a byte literal like b'A' for ASCII.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                    return Err(());
+                }
+                return Ok(self.codegen_byte_char(*c as u8, body));
+            }
+
+        let (tag, payload) = match (value, expected) {
+            (Expr::Some(inner), Type::Option { inner: inner_ty }) => ("1", Some((".value", inner, inner_ty))),
+            (Expr::None, Type::Option { .. }) => ("0", None),
+            (Expr::ResultOk(inner), Type::Result { ok, .. }) => ("0", Some((".data.ok", inner, ok))),
+            (Expr::ResultErr(inner), Type::Result { err, .. }) => ("1", Some((".data.err", inner, err))),
+            _ => return self.codegen_expr(value, body),
+        };
+
+        let payload = match payload {
+            Some((field, inner, inner_ty)) => {
+                let (val_var, val_ty) = self.codegen_expr_expecting(inner, inner_ty, body, loc.clone())?;
+                if !self.types_compatible(inner_ty, &val_ty) {
+                    self.diagnostics.error(
+                        "TypeMismatch",
+                        &format!("Cannot store {} in {}", val_ty.name(), expected.name()),
+                        type_mismatch_error(&inner_ty.name(), &val_ty.name(), loc, inner.location())
+                    );
+                    return Err(());
+                }
+                Some((field, val_var))
+            }
+            None => None,
+        };
+        Ok(self.codegen_tagged(expected, tag, payload, body))
+    }
+
+    /// `Some(x)` takes its type from `x`; the other variants need a declared or return type.
+    pub fn codegen_variant_without_context(&mut self, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        if let Expr::Some(inner) = value {
+            let (val_var, val_ty) = self.codegen_expr(inner, body)?;
+            return Ok(self.codegen_tagged(&Type::option(val_ty), "1", Some((".value", val_var)), body));
+        }
+
+        let variant = match value {
+            Expr::ResultOk(_) => "Ok(..)",
+            Expr::ResultErr(_) => "Err(..)",
+            _ => "None",
+        };
+        self.diagnostics.error(
+            "UninferableVariant",
+            &format!("Cannot infer the type of `{}` here", variant),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some("Declare the variable or function with an Option[T] or Result[T, E] type.".to_string()),
+                suggestions: vec![],
+            }
+        );
+        Err(())
+    }
+
+    /// `expr?` returns the error early from a `Result` function and otherwise yields the `ok` value.
+    pub fn codegen_try(&mut self, inner: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let return_ty = match self.current_return_type.clone() {
+            Some(ty @ Type::Result { .. }) => ty,
+            other => {
+                let found = other.map(|t| t.name()).unwrap_or_else(|| "void".to_string());
+                self.diagnostics.error(
+                    "TryOutsideResult",
+                    &format!("The `?` operator can only be used in a function that returns Result, not {}", found),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Change the return type to Result[T, E] or handle the error with match.".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                return Err(());
+            }
+        };
+
+        let (val_var, val_ty) = self.codegen_expr(inner, body)?;
+        let (ok, err) = match &val_ty {
+            Type::Result { ok, err } => (ok.as_ref().clone(), err.as_ref().clone()),
+            _ => {
+                self.diagnostics.error(
+                    "TypeMismatch",
+                    &format!("The `?` operator expects a Result, found {}", val_ty.name()),
+                    type_mismatch_error("Result", &val_ty.name(), loc, inner.location())
+                );
+                return Err(());
+            }
+        };
+        if let Type::Result { err: return_err, .. } = &return_ty
+            && !self.types_compatible(return_err, &err) {
+                self.diagnostics.error(
+                    "TypeMismatch",
+                    &format!("Cannot propagate {} error from `?` in a function returning {}", err.name(), return_ty.name()),
+                    type_mismatch_error(&return_err.name(), &err.name(), loc, inner.location())
+                );
+                return Err(());
+            }
+
+        let frames = self.defer_stack.clone();
+        let mut early = String::new();
+        let (err_var, _) = self.codegen_tagged(&return_ty, "1", Some((".data.err", format!("{}.data.err", val_var))), &mut early);
+        self.emit_deferred(&frames, &mut early);
+        early.push_str(&format!("return {};\n", err_var));
+        body.push_str(&format!("if ({}.tag == 1) {{\n{}}}\n", val_var, early));
+
+        Ok((format!("{}.data.ok", val_var), ok))
+    }
+
+    /// `unwrap`, `unwrap_or` and `expect` on an `Option`/`Result`, each yielding the inner value.
+    pub fn codegen_variant_method(&mut self, obj_var: &str, obj_ty: &Type, method: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (present_tag, field, inner_ty, missing) = match obj_ty {
+            Type::Option { inner } => ("1", "value", inner.as_ref().clone(), "None"),
+            Type::Result { ok, .. } => ("0", "data.ok", ok.as_ref().clone(), "Err"),
+            _ => unreachable!(),
+        };
+
+        let expected_args = if method == "unwrap" { 0 } else { 1 };
+        if args.len() != expected_args {
+            self.diagnostics.error(
+                "ArgumentCount",
+                &format!("`{}` on {} takes {} argument(s), found {}", method, obj_ty.name(), expected_args, args.len()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: None,
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        }
+
+        let value = match method {
+            "unwrap_or" => {
+                let (default_var, default_ty) = self.codegen_expr_expecting(&args[0], &inner_ty, body, loc.clone())?;
+                if !self.types_compatible(&inner_ty, &default_ty) {
+                    self.diagnostics.error(
+                        "TypeMismatch",
+                        &format!("`unwrap_or` default must be {}, found {}", inner_ty.name(), default_ty.name()),
+                        type_mismatch_error(&inner_ty.name(), &default_ty.name(), loc, args[0].location())
+                    );
+                    return Err(());
+                }
+                format!("{0}.tag == {1} ? {0}.{2} : {3}", obj_var, present_tag, field, default_var)
+            }
+            _ => {
+                self.ensure_panic_helper();
+                body.push_str(&format!("if ({}.tag != {}) {{\n", obj_var, present_tag));
+                if method == "expect" {
+                    let (msg_var, msg_ty) = self.codegen_expr(&args[0], body)?;
+                    match msg_ty {
+                        Type::Str { .. } => body.push_str(&format!(
+                            "fprintf(stderr, \"panic: %.*s\\n\", (int){0}.len, {0}.ptr);\nexit(1);\n",
+                            msg_var
+                        )),
+                        Type::ConstStr => body.push_str(&format!("vix_panic({});\n", msg_var)),
+                        other => {
+                            self.diagnostics.error(
+                                "TypeMismatch",
+                                &format!("`expect` message must be a string, found {}", other.name()),
+                                type_mismatch_error("str", &other.name(), loc, args[0].location())
+                            );
+                            return Err(());
+                        }
+                    }
+                } else {
+                    body.push_str(&format!("vix_panic(\"called unwrap on {}\");\n", missing));
+                }
+                body.push_str("}\n");
+                format!("{}.{}", obj_var, field)
+            }
+        };
+
+        let tmp = self.fresh_var();
+        let c_type = inner_ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = {};\n", c_type, tmp, value));
+        Ok((tmp, inner_ty))
+    }
+
+    /// Builds an `Option`/`Result` value with the given tag and optional payload field.
+    fn codegen_tagged(&mut self, ty: &Type, tag: &str, payload: Option<(&str, String)>, body: &mut String) -> (String, Type) {
+        let mut init = format!(".tag = {}", tag);
+        if let Some((field, val_var)) = payload {
+            init.push_str(&format!(", {} = {}", field, val_var));
+        }
+
+        self.ensure_type_defined(ty);
+        let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = {{ {} }};\n", c_type, tmp, init));
+        (tmp, ty.clone())
+    }
+
+}
+
+/// Integer literals written without a type suffix.
+fn is_int_literal(expr: &Expr) -> bool {
+    matches!(expr, Expr::Number(_) | Expr::HexNumber(_) | Expr::BinaryNumber(_) | Expr::OctalNumber(_))
 }
\ No newline at end of file
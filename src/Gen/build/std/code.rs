@@ -3,7 +3,7 @@ use crate::import::*;
 impl Codegen {
     pub fn codegen_std_call(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
         self.ensure_runtime_functions();
-        eprintln!("[DEBUG] codegen_std_call: func_name={}", func);
+        debug_log!("[DEBUG] codegen_std_call: func_name={}", func);
         match func {
             "array" | "Array" => self.codegen_array_init(args, body),
             "vector" | "Vector" => self.codegen_vector_init(args, body),
@@ -83,12 +83,18 @@ int x_random(int max) {
         Ok((tmp, Type::Any))
     }
 
-    fn codegen_call_expr_default(&mut self, func: &str, args: &[Expr], body: &mut String, _loc: SourceLocation) -> Result<(String, Type), ()> {
+    fn codegen_call_expr_default(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        self.check_call_target(func, loc.clone())?;
         let mut arg_vars = Vec::new();
+        let mut arg_types = Vec::new();
 
-        println!("[DEBUG] codegen_call_expr_default: func={}, user_functions keys={:?}", func, self.user_functions.keys().collect::<Vec<_>>());
+        debug_log!("[DEBUG] codegen_call_expr_default: func={}, user_functions keys={:?}", func, self.user_functions.keys().collect::<Vec<_>>());
 
-        let (param_types, ret_ty) = if let Some(ext_info) = self.extern_functions.get(func) {
+        let mut callee = func.to_string();
+        let (param_types, ret_ty) = if let Some((c_name, Type::FunctionPointer { params, ret })) = self.vars.get(func) {
+            callee = c_name.clone();
+            (Some(params.clone()), *ret.clone())
+        } else if let Some(ext_info) = self.extern_functions.get(func) {
             (Some(ext_info.params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>()), ext_info.return_type.clone())
         } else if let Some((params, ret_ty)) = self.user_functions.get(func) {
             (Some(params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>()), ret_ty.clone())
@@ -97,7 +103,11 @@ int x_random(int max) {
         };
 
         for (i, arg) in args.iter().enumerate() {
+            self.check_mut_ref_arg(func, i, arg, loc.clone())?;
             let (mut var, ty) = self.codegen_expr(arg, body)?;
+            if let Some(param_ty) = param_types.as_ref().and_then(|params| params.get(i)) {
+                self.check_owned_arg(func, param_ty, &ty, loc.clone())?;
+            }
 
             if let Some(params) = &param_types
                 && let Some(param_ty) = params.get(i)
@@ -105,13 +115,77 @@ int x_random(int max) {
                         var = format!("{}.ptr", var);
                     }
 
-            arg_vars.push(var);
+            if let Some(param_ty) = param_types.as_ref().and_then(|params| params.get(i)).cloned() {
+                var = self.coerce_value(&var, &ty, &param_ty);
+            }
+
+            if param_types.is_none() && matches!(ty, Type::Str { .. }) {
+                var = format!("{}.ptr", var);
+            }
+
+            arg_vars.push(self.pass_by_modifier(func, i, var));
+            arg_types.push(ty);
+        }
+
+        args.iter().for_each(|arg| self.note_move(arg));
+
+        if param_types.is_none() {
+            self.declare_implicit_extern(func, &arg_types, loc);
         }
 
         let tmp = self.fresh_var();
         let args_str = arg_vars.join(", ");
         let c_ret_type = ret_ty.to_c_type(&self.arch, &mut self.type_registry);
-        body.push_str(&format!("{} {} = {}({});\n", c_ret_type, tmp, func, args_str));
+        body.push_str(&format!("{} {} = {}({});\n", c_ret_type, tmp, callee, args_str));
         Ok((tmp, ret_ty))
     }
+
+    /// Declares a C prototype for a call nothing defines, guessing the parameters from the
+    /// arguments (`int`, `double`, `const char*`) and warning that the signature is unchecked.
+    fn declare_implicit_extern(&mut self, func: &str, arg_types: &[Type], loc: SourceLocation) {
+        let params: Vec<(String, Type)> = arg_types.iter().enumerate()
+            .map(|(i, ty)| {
+                let param_ty = match ty {
+                    Type::Int { bits, .. } | Type::Char { bits, .. } if *bits <= 32 => Type::i32(),
+                    Type::Bool => Type::i32(),
+                    Type::Float { .. } => Type::f64(),
+                    Type::Str { .. } | Type::ConstStr => Type::ConstStr,
+                    _ => ty.clone(),
+                };
+                (format!("arg{}", i), param_ty)
+            })
+            .collect();
+
+        let c_params = if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.iter()
+                .map(|(_, ty)| match ty {
+                    Type::ConstStr => "const char*".to_string(),
+                    _ => ty.to_c_type(&self.arch, &mut self.type_registry),
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let prototype = format!("extern int32_t {}({});", func, c_params);
+        self.ir.add_forward_decl(prototype.clone());
+
+        self.diagnostics.warning(
+            "ImplicitExternDeclaration",
+            &format!("Function '{}' is not declared; assuming `{}`", func, prototype),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some("Declare the function in an `extern` block so its signature is checked".to_string()),
+                suggestions: vec![format!("extern \"C\":\n    func {}(...): int32\nend", func)],
+            }
+        );
+
+        self.extern_functions.insert(func.to_string(), ExternFunctionMap {
+            params,
+            return_type: Type::i32(),
+            abi: "C".to_string(),
+            library: None,
+        });
+    }
 }
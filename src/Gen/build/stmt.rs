@@ -20,7 +20,8 @@ impl Codegen {
             );
         }
 
-        let mut struct_code = format!("typedef struct {} {{\n", struct_def.name);
+        let layout = self.struct_layout_attributes(struct_def);
+        let mut struct_code = format!("typedef struct {}{} {{\n", layout, struct_def.name);
         let mut fields_info = Vec::new();
         
         for field in &struct_def.fields {
@@ -40,9 +41,9 @@ impl Codegen {
             }
             
             self.ensure_type_defined(&field.ty);
-            let c_type = field.ty.to_c_type(&self.arch, &mut self.type_registry);
+            let field_decl = field.ty.c_declarator(&field.name, &self.arch, &mut self.type_registry);
 
-            struct_code.push_str(&format!("    {} {};\n", c_type, field.name));
+            struct_code.push_str(&format!("    {};\n", field_decl));
             fields_info.push((field.name.clone(), field.ty.clone(), field.is_public));
         }
         
@@ -62,6 +63,40 @@ impl Codegen {
     }
 
 
+    /// `packed` and `align(N)` become GCC attributes; `repr(C)` is already how structs are laid out.
+    fn struct_layout_attributes(&mut self, struct_def: &StructDef) -> String {
+        let mut layout = Vec::new();
+        for attribute in &struct_def.attributes {
+            let align = attribute.strip_prefix("align(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .and_then(|n| n.parse::<u64>().ok());
+            match (attribute.as_str(), align) {
+                ("packed", _) => layout.push("packed".to_string()),
+                (_, Some(n)) if n.is_power_of_two() => layout.push(format!("aligned({})", n)),
+                ("repr(C)", _) => {}
+                _ => {
+                    let loc = self.line_location(struct_def.line);
+                    self.diagnostics.warning(
+                        "UnknownAttribute",
+                        &format!("Unknown attribute '{}' on struct '{}' is ignored", attribute, struct_def.name),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Structs support `packed`, `align(N)` with N a power of two, and `repr(C)`.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                }
+            }
+        }
+
+        if layout.is_empty() {
+            String::new()
+        } else {
+            format!("__attribute__(({})) ", layout.join(", "))
+        }
+    }
+
     pub fn codegen_typed_declaration_impl(&mut self, name: &str, ty: &Type, value: &Expr, body: &mut String, loc: SourceLocation, is_mutable: bool) -> Result<(), ()> {
         if matches!(ty, Type::StdStr) {
              let c_name = format!("var_{}", name);
@@ -131,9 +166,10 @@ impl Codegen {
             }
         }
         
-        let (val_var, val_ty) = self.codegen_expr(value, body)?;
+        let (val_var, val_ty) = self.codegen_expr_expecting(value, ty, body, loc.clone())?;
         
-        let effective_ty = if matches!(ty, Type::Auto) {
+        let inferred = matches!(ty, Type::Auto);
+        let effective_ty = if inferred {
             val_ty.clone()
         } else {
             ty.clone()
@@ -141,7 +177,12 @@ impl Codegen {
         let ty = &effective_ty;
 
         let c_name = format!("var_{}", name);
-        let base_c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        // A `const str` value is a C string; only declared `const str` slots are slices.
+        let base_c_type = if inferred && *ty == Type::ConstStr {
+            "const char*".to_string()
+        } else {
+            ty.to_c_type(&self.arch, &mut self.type_registry)
+        };
         
         let decl_type = if !is_mutable {
             if base_c_type.starts_with("const ") {
@@ -151,6 +192,7 @@ impl Codegen {
                     Type::Array { size: Some(_), .. } | Type::MultiArray { .. } => base_c_type.clone(),
                     Type::Str { .. } => base_c_type.clone(), 
                     Type::Struct { .. } => base_c_type.clone(),
+                    Type::MutRef(_) => format!("{} const", base_c_type),
                     _ => format!("const {}", base_c_type)
                 }
             }
@@ -185,7 +227,7 @@ impl Codegen {
                 body.push_str(&format!("memcpy({}, {}, sizeof({}));\n", c_name, val_var, c_name));
             }
             
-            Type::Array { element, size: None } => {
+            Type::Array { element, size: None } | Type::Slice { element } => {
                 if matches!(**element, Type::Void) {
                     self.diagnostics.error(
                         "VoidArrayElement",
@@ -198,9 +240,9 @@ impl Codegen {
                 
                 let _elem_c_type = element.to_c_type(&self.arch, &mut self.type_registry);
                 let slice_type = self.type_registry.generate_slices(element, &self.arch);
+                let fixed_of_element = matches!(&val_ty, Type::Array { element: val_elem, size: Some(_) } if self.types_compatible(val_elem, element));
                 
-                
-                if !self.types_compatible(&val_ty, ty) {
+                if !fixed_of_element && !self.types_compatible(&val_ty, ty) {
                     
                     match (&val_ty, element.as_ref()) {
                         (Type::Array { element: val_elem, .. }, Type::Str { .. }) 
@@ -253,7 +295,11 @@ impl Codegen {
                     }
                 }
                 
-                body.push_str(&format!("{} {} = {};\n", slice_type, c_name, val_var));
+                if let Type::Array { size: Some(len), .. } = &val_ty {
+                    body.push_str(&format!("{} {} = {{ .ptr = {}, .len = {} }};\n", slice_type, c_name, val_var, len));
+                } else {
+                    body.push_str(&format!("{} {} = {};\n", slice_type, c_name, val_var));
+                }
             }
             
             Type::MultiArray { element, dimensions } => {
@@ -304,6 +350,21 @@ impl Codegen {
             Type::Ptr(inner) if matches!(**inner, Type::Void) => {
                 body.push_str(&format!("{} {} = {};\n", decl_type, c_name, val_var));
             }
+            Type::FunctionPointer { .. } => {
+                if !self.types_compatible(ty, &val_ty) {
+                    self.diagnostics.error(
+                        "TypeMismatch",
+                        &format!("Cannot initialize variable '{}' of type {} with value of type {}",
+                                name, ty.name(), val_ty.name()),
+                        type_mismatch_error(&ty.name(), &val_ty.name(), loc.clone(), value.location())
+                    );
+                    return Err(());
+                }
+
+                let declared_name = if is_mutable { c_name.clone() } else { format!("const {}", c_name) };
+                let declarator = ty.c_declarator(&declared_name, &self.arch, &mut self.type_registry);
+                body.push_str(&format!("{} = {};\n", declarator, val_var));
+            }
             Type::StdStr => {
                  self.diagnostics.error(
                      "UnsupportedType",
@@ -320,7 +381,7 @@ impl Codegen {
 
 
             _ => {
-                if !self.types_compatible(ty, &val_ty) {
+                if !self.can_coerce(&val_ty, ty) {
                     let value_loc = value.location();
                     
                     self.diagnostics.error(
@@ -342,16 +403,23 @@ impl Codegen {
                         body.push_str(&format!("{} {} = {}.ptr;\n", decl_type, c_name, val_var));
                     }
                     _ => {
+                        let val_var = self.coerce_value(&val_var, &val_ty, ty);
                         body.push_str(&format!("{} {} = {};\n", decl_type, c_name, val_var));
                     }
                 }
             }
         }
         
+        self.note_move(value);
+        self.vars.insert(name.to_string(), (c_name, ty.clone()));
+        if is_mutable {
+            self.immutable_vars.remove(name);
+        } else {
+            self.immutable_vars.insert(name.to_string());
+        }
         if matches!(ty, Type::Owned(_)) {
-            self.owned_vars.insert(name.to_string());
+            self.begin_owned(name);
         }
-        self.vars.insert(name.to_string(), (c_name, ty.clone()));
         Ok(())
     }
 
@@ -402,6 +470,79 @@ impl Codegen {
         }
         Ok(())
     }
+
+    /// Checks that every `impl X for Trait` block provides each trait method with matching types.
+    pub fn check_trait_impls(&mut self, traits: &[TraitDef], impls: &[ImplBlock]) -> Result<(), ()> {
+        let mut ok = true;
+
+        for impl_block in impls {
+            let Some(trait_name) = &impl_block.trait_name else { continue };
+            let struct_name = &impl_block.struct_name;
+            let loc = self.line_location(impl_block.line);
+
+            let Some(trait_def) = traits.iter().find(|t| &t.name == trait_name) else {
+                self.diagnostics.error(
+                    "UndefinedTrait",
+                    &format!("Trait '{}' is not defined", trait_name),
+                    ErrorContext {
+                        primary_location: loc.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some(format!("'{}' implements a trait that does not exist.", struct_name)),
+                        suggestions: vec![format!("Define trait '{}' before implementing it", trait_name)],
+                    }
+                );
+                ok = false;
+                continue;
+            };
+
+            for required in &trait_def.methods {
+                let Some(method) = impl_block.methods.iter().find(|m| m.name == required.name) else {
+                    self.diagnostics.error(
+                        "MissingTraitMethod",
+                        &format!("'{}' does not implement '{}' required by trait '{}'", struct_name, required.name, trait_name),
+                        ErrorContext {
+                            primary_location: loc.clone(),
+                            secondary_locations: vec![],
+                            help_message: Some(format!("Every method declared in '{}' must be defined in the impl block.", trait_name)),
+                            suggestions: vec![format!("Add 'func {}(...)' to 'impl {} for {}'", required.name, struct_name, trait_name)],
+                        }
+                    );
+                    ok = false;
+                    continue;
+                };
+
+                let expected: Vec<Type> = required.params.iter().map(|(_, ty, _)| ty.resolve_self(struct_name)).collect();
+                let found: Vec<Type> = method.params.iter().map(|(_, ty, _)| ty.resolve_self(struct_name)).collect();
+                let expected_ret = required.return_type.resolve_self(struct_name);
+                let found_ret = method.return_type.resolve_self(struct_name);
+
+                if expected != found || expected_ret != found_ret {
+                    let signature = |params: &[Type], ret: &Type| format!(
+                        "({}) -> {}",
+                        params.iter().map(|t| t.name()).collect::<Vec<_>>().join(", "),
+                        ret.name()
+                    );
+                    self.diagnostics.error(
+                        "TraitMethodMismatch",
+                        &format!("Method '{}' of '{}' does not match trait '{}'", required.name, struct_name, trait_name),
+                        ErrorContext {
+                            primary_location: self.line_location(method.line),
+                            secondary_locations: vec![],
+                            help_message: Some(format!(
+                                "Expected {}, found {}",
+                                signature(&expected, &expected_ret),
+                                signature(&found, &found_ret)
+                            )),
+                            suggestions: vec![format!("Change '{}' to match the signature declared in '{}'", required.name, trait_name)],
+                        }
+                    );
+                    ok = false;
+                }
+            }
+        }
+
+        if ok { Ok(()) } else { Err(()) }
+    }
     
     pub fn codegen_constructor(
         &mut self, 
@@ -419,10 +560,9 @@ impl Codegen {
          
         let param_strs: Vec<String> = params.iter().map(|(name, ty)| {
             let c_name = format!("param_{}", name);
-            let c_type =  ty.to_c_type(&self.arch, &mut self.type_registry);
             self.vars.insert(name.clone(), (c_name.clone(), ty.clone()));
 
-            format!("{} {}", c_type, c_name)
+            ty.c_declarator(&c_name, &self.arch, &mut self.type_registry)
         }).collect();
         
         func_code.push_str(&param_strs.join(", "));
@@ -463,7 +603,7 @@ impl Codegen {
             
             let mut params_str = Vec::new();
             for (p_name, p_ty) in params {
-                params_str.push(format!("{} param_{}", p_ty.to_c_type(&self.arch, &mut self.type_registry), p_name));
+                params_str.push(p_ty.c_declarator(&format!("param_{}", p_name), &self.arch, &mut self.type_registry));
             }
             let sig = format!("{} {}({});\n", struct_name, func_name, params_str.join(", "));
             self.ir.forward_decls.push_str(&sig);
@@ -496,12 +636,13 @@ impl Codegen {
         
         let mut func_code = String::new();
         let method_name = format!("{}_{}", struct_name, method.name);
-        let params_for_registry: Vec<(String, Type)> = method.params.iter().map(|(n, t, _)| (n.clone(), t.clone())).collect();
+        let params_for_registry: Vec<(String, Type)> = method.params.iter().map(|(n, t, _)| (n.clone(), t.resolve_self(struct_name))).collect();
+        let return_type = method.return_type.resolve_self(struct_name);
         let is_instance = method.self_modifier.is_some();
 
         self.impl_methods.insert(
             (struct_name.to_string(), method.name.clone()),
-            (params_for_registry, method.return_type.clone(), is_instance)
+            (params_for_registry.clone(), return_type.clone(), is_instance)
         );
 
         if only_signatures {
@@ -510,20 +651,21 @@ impl Codegen {
             if is_instance {
                 params_str.push(format!("{}* self", struct_name));
             }
-            for (p_name, p_ty, _) in &method.params {
-                params_str.push(format!("{} {}", p_ty.to_c_type(&self.arch, &mut self.type_registry), p_name));
+            for (p_name, p_ty) in &params_for_registry {
+                params_str.push(p_ty.c_declarator(p_name, &self.arch, &mut self.type_registry));
             }
-            let c_return_type = method.return_type.to_c_type(&self.arch, &mut self.type_registry);
-            let sig = format!("{} {}({});\n", c_return_type, method_name, params_str.join(", "));
+            let c_return_type = return_type.to_c_type(&self.arch, &mut self.type_registry);
+            let sig = format!("{}{} {}({});\n", linkage(method.is_public, &method.name), c_return_type, method_name, params_str.join(", "));
             self.ir.forward_decls.push_str(&sig);
             return;
         }
 
         
-        self.current_return_type = Some(method.return_type.clone());
+        self.current_return_type = Some(return_type.clone());
+        self.type_registry.self_type = Some(struct_name.to_string());
 
         
-        match &method.return_type {
+        match &return_type {
             Type::Result { ok, err } => {
                 
                 if let Type::Tuple { fields } = ok.as_ref()
@@ -576,9 +718,9 @@ impl Codegen {
         }
 
         
-        let return_c_type = method.return_type.to_c_type(&self.arch, &mut self.type_registry);
+        let return_c_type = return_type.to_c_type(&self.arch, &mut self.type_registry);
 
-        func_code.push_str(&format!("{} {}(", return_c_type, method_name));
+        func_code.push_str(&format!("{}{} {}(", linkage(method.is_public, &method.name), return_c_type, method_name));
         
         
         if let Some(self_mod) = &method.self_modifier {
@@ -609,13 +751,12 @@ impl Codegen {
         }
         
         
-        let param_strs: Vec<String> = method.params.iter().map(|(name, ty, _)| {
-            let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        let param_strs: Vec<String> = params_for_registry.iter().map(|(name, ty)| {
             let c_name = name.clone();
 
             self.vars.insert(name.clone(), (c_name.clone(), ty.clone()));
             
-            format!("{} {}", c_type, c_name)
+            ty.c_declarator(&c_name, &self.arch, &mut self.type_registry)
         }).collect();
         
         func_code.push_str(&param_strs.join(", "));
@@ -623,31 +764,36 @@ impl Codegen {
         
         
         let mut body_code = String::new();
+        let outer_line = self.current_line;
         for (i, stmt) in method.body.iter().enumerate() {
-            if i == method.body.len() - 1 && !matches!(method.return_type, Type::Void)
-                && let Stmt::Expr(expr) = stmt
+            self.current_line = self.stmt_line(stmt);
+            if self.emit_line_directives && self.current_line > 0 {
+                body_code.push_str(&format!("#line {} \"{}\"\n", self.current_line, self.current_file.replace('\\', "/")));
+            }
+            if i == method.body.len() - 1 && !matches!(return_type, Type::Void)
+                && let Stmt::Expr(expr) = stmt.node()
                     && let Ok((res_var, _)) = self.codegen_expr(expr, &mut body_code) {
                         body_code.push_str(&format!("    return {};\n", res_var));
                         continue;
                     }
             let _ = self.codegen_stmt(stmt, &mut body_code);
         }
+        self.current_line = outer_line;
         func_code.push_str(&body_code);
         
         
-        if matches!(method.return_type, Type::Void)
+        if matches!(return_type, Type::Void)
             && !body_code.contains("return") {
                 func_code.push_str("    return;\n");
             }
         
-        func_code.push_str("}\n\n");
-        
+        func_code.push_str("}\n");
+        self.reset_line_directive(&mut func_code);
+        func_code.push('\n');
         
-        let params_for_registry: Vec<(String, Type)> = method.params.iter().map(|(n, t, _)| (n.clone(), t.clone())).collect();
-
         self.impl_methods.insert(
             (struct_name.to_string(), method.name.clone()),
-            (params_for_registry.clone(), method.return_type.clone(), is_instance)
+            (params_for_registry, return_type.clone(), is_instance)
         );
         
         
@@ -657,10 +803,39 @@ impl Codegen {
         }
         
         self.current_return_type = None;  
+        self.type_registry.self_type = None;
         
         self.ir.functions.push_str(&func_code);
     }
 
+    /// Storage class and attributes from `#[inline]`/`#[noinline]`; other attributes are
+    /// reported once, when the body is generated.
+    fn function_linkage(&mut self, func: &Function, report_unknown: bool) -> String {
+        let mut prefix = String::new();
+        let mut storage = linkage(func.is_public, &func.name);
+        for attribute in &func.attributes {
+            match attribute.as_str() {
+                "inline" => storage = "static inline ",
+                "noinline" => prefix.push_str("__attribute__((noinline)) "),
+                _ if report_unknown => {
+                    let loc = self.line_location(func.line);
+                    self.diagnostics.warning(
+                        "UnknownAttribute",
+                        &format!("Unknown attribute '{}' on function '{}' is ignored", attribute, func.name),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Functions support the `inline` and `noinline` attributes.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                }
+                _ => {}
+            }
+        }
+        prefix + storage
+    }
+
     pub fn codegen_function(&mut self, func: &Function, only_signatures: bool) {
         if !only_signatures {
             self.vars.clear();
@@ -668,6 +843,10 @@ impl Codegen {
         }
         let param_types: Vec<(String, Type)> = func.params.iter().map(|(n, t, _)| (n.clone(), t.clone())).collect();
         self.user_functions.insert(func.name.clone(), (param_types, func.return_type.clone()));
+        self.param_modifiers.insert(func.name.clone(), func.params.iter().map(|(_, _, m)| m.clone()).collect());
+        if func.param_defaults.iter().any(Option::is_some) {
+            self.param_defaults.insert(func.name.clone(), func.param_defaults.clone());
+        }
         let c_return_type = func.return_type.to_c_type(&self.arch, &mut self.type_registry);
         let c_func_name = if func.name == "main" { 
             "vix_main".to_string() 
@@ -680,35 +859,78 @@ impl Codegen {
         let mut func_code = String::new();
         let mut body_code = String::new();
         
-        for (p_name, p_ty, _) in &func.params {
-            let c_p_type = p_ty.to_c_type(&self.arch, &mut self.type_registry);
-            params_str.push(format!("{} var_{}", c_p_type, p_name));
-            
-            self.vars.insert(p_name.clone(), (format!("var_{}", p_name), p_ty.clone()));
+        for (p_name, p_ty, modifier) in &func.params {
+            if matches!(p_ty, Type::TripleDot) {
+                params_str.push("...".to_string());
+                continue;
+            }
+            let c_name = format!("var_{}", p_name);
+            match modifier {
+                ParamModifier::Reference | ParamModifier::MutableReference => {
+                    let constness = if *modifier == ParamModifier::Reference { "const " } else { "" };
+                    let c_ty = p_ty.to_c_type(&self.arch, &mut self.type_registry);
+                    params_str.push(format!("{}{}* {}", constness, c_ty, c_name));
+                    self.vars.insert(p_name.clone(), (format!("(*{})", c_name), p_ty.clone()));
+                }
+                _ => {
+                    params_str.push(p_ty.c_declarator(&c_name, &self.arch, &mut self.type_registry));
+                    self.vars.insert(p_name.clone(), (c_name, p_ty.clone()));
+                }
+            }
         }
 
+        let linkage = self.function_linkage(func, !only_signatures);
         if only_signatures {
-            let sig = format!("{} {}({});\n", c_return_type, c_func_name, params_str.join(", "));
+            let sig = format!("{}{} {}({});\n", linkage, c_return_type, c_func_name, params_str.join(", "));
             if !self.ir.forward_decls.contains(&sig) {
                 self.ir.forward_decls.push_str(&sig);
             }
             return;
         }
 
+        let outer_line = self.current_line;
+        self.lint_unreachable(&func.body);
+        self.lint_unused_variables(func);
         self.current_return_type = Some(func.return_type.clone());
-        func_code.push_str(&format!("{} {}(", c_return_type, c_func_name));
+        func_code.push_str(&format!("{}{} {}(", linkage, c_return_type, c_func_name));
         func_code.push_str(&params_str.join(", "));
         func_code.push_str(") {\n");
 
+        let outer_defers = std::mem::replace(&mut self.defer_stack, vec![Vec::new()]);
+        self.owned_vars.clear();
+        self.moved_vars.clear();
+        self.immutable_vars.clear();
+        for (p_name, p_ty, _) in &func.params {
+            if matches!(p_ty, Type::Owned(_)) {
+                self.begin_owned(p_name);
+            }
+        }
+        if matches!(func.params.last(), Some((_, Type::TripleDot, _))) {
+            self.begin_varargs(func, &mut body_code);
+        }
         for (i, stmt) in func.body.iter().enumerate() {
+            self.current_line = self.stmt_line(stmt);
+            if self.emit_line_directives && self.current_line > 0 {
+                body_code.push_str(&format!("#line {} \"{}\"\n", self.current_line, self.current_file.replace('\\', "/")));
+            }
             if i == func.body.len() - 1 && !matches!(func.return_type, Type::Void)
-                && let Stmt::Expr(expr) = stmt
-                    && let Ok((res_var, _)) = self.codegen_expr(expr, &mut body_code) {
-                        body_code.push_str(&format!("    return {};\n", res_var));
+                && let Stmt::Expr(expr) = stmt.node()
+                    && let Ok((res_var, res_ty)) = self.codegen_expr(expr, &mut body_code) {
+                        if !matches!(res_ty, Type::Never) {
+                            let frames = self.defer_stack.clone();
+                            self.emit_deferred(&frames, &mut body_code);
+                            body_code.push_str(&format!("    return {};\n", res_var));
+                        }
+                        self.defer_stack.clear();
                         continue;
                     }
             self.codegen_stmt(stmt, &mut body_code).ok();
         }
+        let deferred = std::mem::replace(&mut self.defer_stack, outer_defers);
+        if !matches!(func.body.last().map(Stmt::node), Some(Stmt::Return(_))) {
+            self.emit_deferred(&deferred, &mut body_code);
+        }
+        self.current_line = outer_line;
 
         self.current_return_type = None;
         func_code.push_str(&body_code);
@@ -718,10 +940,108 @@ impl Codegen {
                 func_code.push_str("    return;\n");
             }
 
-        func_code.push_str("}\n\n");
+        func_code.push_str("}\n");
+        self.reset_line_directive(&mut func_code);
+        func_code.push('\n');
         self.ir.functions.push_str(&func_code);
     }
     
+    /// Warns once per block about statements that follow a `return`, `break`, `continue`
+    /// or a call that never returns.
+    /// Each branch is its own block, so a returning `then` arm never marks its `else` as dead.
+    fn lint_unreachable(&mut self, stmts: &[Stmt]) {
+        if let Some((pos, terminator)) = stmts.iter().enumerate().find_map(|(i, s)| terminator_name(s).map(|t| (i, t)))
+            && pos + 1 < stmts.len() {
+                let loc = self.line_location(self.stmt_line(&stmts[pos + 1]));
+                self.diagnostics.warning(
+                    "UnreachableCode",
+                    &format!("Unreachable code after `{}`", terminator),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some(format!("Remove the statements after `{}` or move them before it.", terminator)),
+                        suggestions: vec![],
+                    }
+                );
+            }
+
+        for stmt in stmts {
+            match stmt.node() {
+                Stmt::If(_, then_body, else_body) => {
+                    self.lint_unreachable(then_body);
+                    if let Some(else_body) = else_body {
+                        self.lint_unreachable(else_body);
+                    }
+                }
+                Stmt::IfLet { then_block, else_block, .. } => {
+                    self.lint_unreachable(then_block);
+                    if let Some(else_block) = else_block {
+                        self.lint_unreachable(else_block);
+                    }
+                }
+                Stmt::Match(_, cases, default) => {
+                    for case in cases {
+                        self.lint_unreachable(&case.body);
+                    }
+                    if let Some(default) = default {
+                        self.lint_unreachable(default);
+                    }
+                }
+                Stmt::While(_, body) | Stmt::For(_, _, body) | Stmt::Loop(body) | Stmt::Scope(body) | Stmt::Unsafe(body) => {
+                    self.lint_unreachable(body);
+                }
+                Stmt::Labeled(_, inner) => self.lint_unreachable(std::slice::from_ref(inner.as_ref())),
+                _ => {}
+            }
+        }
+    }
+
+    /// Warns about declared locals that no expression in the function ever reads.
+    fn lint_unused_variables(&mut self, func: &Function) {
+        let mut reads = BodyReads::default();
+        reads.block(&func.body, &|stmt| self.stmt_line(stmt));
+
+        for (name, line, read) in reads.locals {
+            if read || name.starts_with('_') {
+                continue;
+            }
+            let loc = self.line_location(line);
+            self.diagnostics.warning(
+                "UnusedVariable",
+                &format!("Variable '{}' is never read", name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Prefix the name with an underscore to silence this warning.".to_string()),
+                    suggestions: vec![format!("Rename '{}' to '_{}'", name, name)],
+                }
+            );
+        }
+    }
+
+    /// Opens the `va_list` of a function ending in `...` and defers its `va_end`,
+    /// so every exit path closes it. The list is visible to the body as `...`.
+    fn begin_varargs(&mut self, func: &Function, body: &mut String) {
+        let Some((anchor, _, _)) = func.params.iter().rev().nth(1) else {
+            self.diagnostics.error(
+                "VariadicWithoutNamedParam",
+                &format!("Variadic function '{}' needs a named parameter before '...'", func.name),
+                ErrorContext {
+                    primary_location: self.line_location(func.line),
+                    secondary_locations: vec![],
+                    help_message: Some("C reads variadic arguments relative to the last named parameter.".to_string()),
+                    suggestions: vec![format!("Declare '{}' as: func {}(fmt: str, ...)", func.name, func.name)],
+                }
+            );
+            return;
+        };
+
+        self.ir.add_include("#include <stdarg.h>");
+        body.push_str(&format!("va_list vix_args;\nva_start(vix_args, var_{});\n", anchor));
+        self.vars.insert("...".to_string(), ("vix_args".to_string(), Type::Any));
+        self.defer_stack[0].push(Stmt::Call("va_end".to_string(), vec![Expr::Var("...".to_string())]));
+    }
+
     pub fn codegen_if(
         &mut self,
         cond: &Expr,
@@ -731,20 +1051,25 @@ impl Codegen {
     ) -> Result<(), ()> {
         let (cond_var, _cond_ty) = self.codegen_expr(cond, body) ?;
 
-        body.push_str(&format!("if ({}) {{\n", cond_var));
-
+        let mut join = self.begin_branches();
+        let mut then_code = String::new();
         for stmt in then_body {
-            let _ = self.codegen_stmt(stmt, body);
+            let _ = self.codegen_stmt(stmt, &mut then_code);
         }
+        self.leave_branch(&mut join, then_body);
 
-        body.push_str("}\n");
+        self.enter_branch(&join);
+        let else_stmts = else_body.as_deref().unwrap_or_default();
+        let mut else_code = String::new();
+        for stmt in else_stmts {
+            self.codegen_stmt(stmt, &mut else_code).ok();
+        }
+        self.leave_branch(&mut join, else_stmts);
+        let frees = self.join_branches(join);
 
-        if let Some(else_stmts) = else_body {
-            body.push_str("else {\n");
-            for stmt in else_stmts {
-                self.codegen_stmt(stmt, body).ok();
-            }
-            body.push_str("}\n");
+        body.push_str(&format!("if ({}) {{\n{}{}}}\n", cond_var, then_code, frees[0]));
+        if else_body.is_some() || !frees[1].is_empty() {
+            body.push_str(&format!("else {{\n{}{}}}\n", else_code, frees[1]));
         }
 
         Ok(())
@@ -763,6 +1088,8 @@ impl Codegen {
                         name: payload_name.clone(),
                         fields: fields.clone(),
                         is_public: enum_def.is_public,
+                        attributes: vec![],
+                        line: 0,
                      };
                      
                      
@@ -776,78 +1103,241 @@ impl Codegen {
         }
         self.type_registry.register_enum(enum_def.name.clone(), variants, enum_def.is_public);
         
+        let known_defs = self.type_registry.ordered_definitions.len();
         if let Some(def_code) = self.type_registry.generate_enum_definition(&enum_def.name, &self.config.arch) {
+            for payload_def in &self.type_registry.ordered_definitions[known_defs..] {
+                self.ir.forward_decls.push_str(payload_def);
+            }
             self.ir.forward_decls.push_str(&def_code);
         }
 
+        for variant in &enum_def.variants {
+            self.codegen_enum_constructor(&enum_def.name, variant);
+        }
+
         Ok(())
     }
 
+    /// Emits `Enum_Variant(...)`, which builds a tagged value with the payload stored in `data.Variant`.
+    fn codegen_enum_constructor(&mut self, enum_name: &str, variant: &EnumVariant) {
+        let (variant_name, params, stores): (&String, Vec<(String, Type)>, Vec<String>) = match variant {
+            EnumVariant::Simple(name) => (name, vec![], vec![]),
+            EnumVariant::Tuple(name, types) if types.len() == 1 => {
+                (name, vec![("v0".to_string(), types[0].clone())], vec![format!("data.{} = var_v0", name)])
+            }
+            EnumVariant::Tuple(name, types) => {
+                let params = types.iter().enumerate().map(|(i, ty)| (format!("v{}", i), ty.clone())).collect();
+                let stores = (0..types.len()).map(|i| format!("data.{}.field_{} = var_v{}", name, i, i)).collect();
+                (name, params, stores)
+            }
+            EnumVariant::Struct(name, fields) => {
+                let params = fields.iter().map(|f| (f.name.clone(), f.ty.clone())).collect();
+                let stores = fields.iter().map(|f| format!("data.{}.{} = var_{}", name, f.name, f.name)).collect();
+                (name, params, stores)
+            }
+        };
+
+        let func_name = format!("{}_{}", enum_name, variant_name);
+        let params_c: Vec<String> = params
+            .iter()
+            .map(|(p_name, p_ty)| p_ty.c_declarator(&format!("var_{}", p_name), &self.arch, &mut self.type_registry))
+            .collect();
+        let signature = format!(
+            "{} {}({})",
+            enum_name,
+            func_name,
+            if params_c.is_empty() { "void".to_string() } else { params_c.join(", ") }
+        );
+
+        let mut func_code = format!("{} {{\n    {} value;\n    value.tag = {}__{};\n", signature, enum_name, enum_name, variant_name);
+        for store in stores {
+            func_code.push_str(&format!("    value.{};\n", store));
+        }
+        func_code.push_str("    return value;\n}\n\n");
+
+        self.ir.forward_decls.push_str(&format!("{};\n", signature));
+        self.ir.functions.push_str(&func_code);
+        self.user_functions.insert(func_name, (params, Type::Struct { name: enum_name.to_string() }));
+    }
+
     
     pub fn codegen_scope(&mut self, stmts: &[Stmt], body: &mut String) -> Result<(), ()>{
         self.scope_depth += 1;
         let prev_owned_vars = self.owned_vars.clone();
         
         body.push_str("{\n");
+        self.defer_stack.push(Vec::new());
         for stmt in stmts {
             self.codegen_stmt(stmt, body).ok();
         }
-        
-        let current_owned = self.owned_vars.clone();
-        for var_name in current_owned {
-            if !prev_owned_vars.contains(&var_name) {
-                if let Some((c_name, _)) = self.vars.get(&var_name) {
-                    body.push_str(&format!("free({});\n", c_name));
-                }
-                self.owned_vars.remove(&var_name);
-            }
+        let deferred = self.defer_stack.pop().unwrap_or_default();
+        if !matches!(stmts.last().map(Stmt::node), Some(Stmt::Return(_))) {
+            self.emit_deferred(&[deferred], body);
         }
         
+        self.owned_vars.retain(|var_name| prev_owned_vars.contains(var_name));
+        
         body.push_str("}\n");
         self.scope_depth -= 1;
 
         Ok(())
     }
 
-    pub fn codegen_while(&mut self, cond: &Expr, loop_body: &[Stmt], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+    pub fn codegen_defer(&mut self, stmt: &Stmt, loc: SourceLocation) -> Result<(), ()> {
+        let Some(frame) = self.defer_stack.last_mut() else {
+            self.diagnostics.error(
+                "DeferOutsideScope",
+                "'defer' can only be used inside a function or scope block",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Deferred statements run when the enclosing function or 'scope:' block exits.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        };
+        frame.push(stmt.clone());
+        Ok(())
+    }
+
+    /// Lowers `assert` to a check that reports the failing source line and aborts; compiled out in release builds.
+    pub fn codegen_assert(&mut self, cond: &Expr, message: Option<&Expr>, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        if self.config.optimization_level == OptimizationLevel::Release {
+            return Ok(());
+        }
+
+        let (cond_var, cond_ty) = self.codegen_expr(cond, body)?;
+        if !cond_ty.is_scalar() {
+            self.diagnostics.error(
+                "TypeMismatch",
+                &format!("Assertion condition must be a bool or scalar, found {}", cond_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Write the condition without parentheses around it and the message: `assert x == 1, \"message\"`.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        }
+        let mut failure = String::new();
+        // The statement's own `#line` is followed by its temporaries, so `__LINE__` needs resetting.
+        let line_directive = if self.emit_line_directives && self.current_line > 0 {
+            format!("#line {} \"{}\"\n", self.current_line, self.current_file.replace('\\', "/"))
+        } else {
+            String::new()
+        };
+        match message {
+            Some(message) => {
+                let (msg_var, msg_ty) = self.codegen_expr(message, &mut failure)?;
+                let (spec, msg) = match msg_ty {
+                    Type::Str { .. } => ("%.*s", format!("(int){0}.len, {0}.ptr", msg_var)),
+                    Type::ConstStr => ("%s", msg_var),
+                    other => {
+                        self.diagnostics.error(
+                            "TypeMismatch",
+                            &format!("Assertion message must be a string, found {}", other.name()),
+                            ErrorContext {
+                                primary_location: loc,
+                                secondary_locations: vec![],
+                                help_message: Some("Write the message as a string literal, or format it with plan first.".to_string()),
+                                suggestions: vec![],
+                            }
+                        );
+                        return Err(());
+                    }
+                };
+                failure.push_str(&format!("{}fprintf(stderr, \"%s:%d: assertion failed: {}\\n\", __FILE__, __LINE__, {});\n", line_directive, spec, msg));
+            }
+            None => failure.push_str(&format!("{}fprintf(stderr, \"%s:%d: assertion failed\\n\", __FILE__, __LINE__);\n", line_directive)),
+        }
+        body.push_str(&format!("if (!({})) {{\n{}abort();\n}}\n", cond_var, failure));
+        Ok(())
+    }
+
+    /// Emits deferred statements innermost frame first, each frame in reverse order.
+    pub fn emit_deferred(&mut self, frames: &[Vec<Stmt>], body: &mut String) {
+        let saved = std::mem::take(&mut self.defer_stack);
+        for frame in frames.iter().rev() {
+            for stmt in frame.iter().rev() {
+                // The scope-exit free `begin_owned` registered, skipped once the value moved.
+                if let Stmt::Call(func, args) = stmt
+                    && func == "vix_free"
+                    && let [Expr::Var(name)] = args.as_slice()
+                    && self.owned_vars.contains(name) {
+                        self.codegen_owned_free(name, body);
+                        continue;
+                    }
+                self.codegen_stmt(stmt, body).ok();
+            }
+        }
+        self.defer_stack = saved;
+    }
+
+    /// Lowers `loop ... end` to `for (;;)`, warning when nothing in the body can leave it.
+    pub fn codegen_loop(&mut self, loop_body: &[Stmt], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        if !loop_can_exit(loop_body, true) {
+            self.diagnostics.warning(
+                "InfiniteLoop",
+                "This loop has no reachable 'break' or 'return' and never ends",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("A 'loop' only stops through 'break' or 'return'.".to_string()),
+                    suggestions: vec!["Add a 'break' once the loop's work is done".to_string()],
+                }
+            );
+        }
+
+        let break_label = self.fresh_label();
+        let continue_label = self.fresh_label();
+        self.push_loop(break_label.clone(), continue_label.clone());
+
+        body.push_str("for (;;) {\n");
+        for stmt in loop_body {
+            self.codegen_stmt(stmt, body).ok();
+        }
+        let labels = self.loop_stack.pop();
+
+        if labels.as_ref().is_some_and(|l| l.continue_used) {
+            body.push_str(&format!("{}: ;\n", continue_label));
+        }
+        body.push_str("}\n");
+        if labels.is_some_and(|l| l.break_used) {
+            body.push_str(&format!("{}: ;\n", break_label));
+        }
+        Ok(())
+    }
+
+    pub fn codegen_while(&mut self, cond: &Expr, loop_body: &[Stmt], body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
         let loop_label = self.fresh_label();
         let end_label = self.fresh_label();
+        self.push_loop(end_label.clone(), loop_label.clone());
         
         body.push_str(&format!("{}:\n", loop_label));
         
         
         let (cond_var, cond_ty) = match self.codegen_expr(cond, body) {
             Ok(result) => result,
-            Err(_) => {
-                panic!("WHILE LOOP PANIC: Failed to codegen condition!\n\
-                    Condition expr: {:?}\n\
-                    Available vars: {:?}\n\
-                    Location: {:?}", 
-                    cond, 
-                    self.vars.keys().collect::<Vec<_>>(),
-                    loc);
+            Err(()) => {
+                self.loop_stack.pop();
+                return Err(());
             }
         };
         
-        println!("[DEBUG] While condition generated: var={}, type={:?}", cond_var, cond_ty);
+        debug_log!("[DEBUG] While condition generated: var={}, type={:?}", cond_var, cond_ty);
         
         body.push_str(&format!("if (!{}) goto {};\n", cond_var, end_label));
 
         let mut loop_body_code = String::new();
-        println!("[DEBUG] Processing {} statements in while body", loop_body.len());
+        debug_log!("[DEBUG] Processing {} statements in while body", loop_body.len());
         for (idx, stmt) in loop_body.iter().enumerate() {
-            println!("[DEBUG] While body stmt {}: {:?}", idx, stmt);
-            match self.codegen_stmt(stmt, &mut loop_body_code) {
-                Ok(_) => {},
-                Err(_) => {
-                    panic!("WHILE LOOP PANIC: Failed to codegen statement {} in loop body!\n\
-                        Statement: {:?}\n\
-                        Available vars: {:?}", 
-                        idx, stmt, self.vars.keys().collect::<Vec<_>>());
-                }
-            }
+            debug_log!("[DEBUG] While body stmt {}: {:?}", idx, stmt);
+            self.codegen_stmt(stmt, &mut loop_body_code).ok();
         }
         body.push_str(&loop_body_code);
+        self.loop_stack.pop();
         
         body.push_str(&format!("goto {};\n", loop_label));
         body.push_str(&format!("{}:\n", end_label));
@@ -855,12 +1345,17 @@ impl Codegen {
         Ok(()) 
     }
 
-        pub fn codegen_for(&mut self, var: &str, iter: &Expr, loop_body: &[Stmt], body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
+        pub fn codegen_for(&mut self, var: &str, iter: &Expr, loop_body: &[Stmt], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        if let Expr::Range(start, end, inclusive) = iter {
+            return self.codegen_for_range(var, start, end, *inclusive, loop_body, body, loc);
+        }
+
         let (iter_var, iter_ty) = self.codegen_expr(iter, body)?;
-        println!("[DEBUG] codegen_for: var={}, iter_ty={:?}", var, iter_ty);
+        debug_log!("[DEBUG] codegen_for: var={}, iter_ty={:?}", var, iter_ty);
         
         let loop_label = self.fresh_label();
         let end_label = self.fresh_label();
+        let continue_label = self.fresh_label();
         let idx_var = self.fresh_var();
         
         let (size_expr, elem_access, elem_type) = match &iter_ty {
@@ -874,7 +1369,7 @@ impl Codegen {
             Type::Array { element, size: Some(size) } => {
                 (format!("{}", size), format!("{}[{}]", iter_var, idx_var), *element.clone())
             }
-            Type::Array { element, size: None } => {
+            Type::Array { element, size: None } | Type::Slice { element } => {
                 (format!("{}.len", iter_var), format!("{}.ptr[{}]", iter_var, idx_var), *element.clone())
             }
             _ => {
@@ -892,9 +1387,13 @@ impl Codegen {
         
         self.vars.insert(var.to_string(), (c_name, elem_type));
         
+        self.push_loop(end_label.clone(), continue_label.clone());
         for stmt in loop_body {
             self.codegen_stmt(stmt, body).ok();
         }
+        if self.loop_stack.pop().is_some_and(|l| l.continue_used) {
+            body.push_str(&format!("{}: ;\n", continue_label));
+        }
         
         body.push_str(&format!("{}++;\n", idx_var));
         body.push_str(&format!("goto {};\n", loop_label));
@@ -903,13 +1402,98 @@ impl Codegen {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn codegen_for_range(&mut self, var: &str, start: &Expr, end: &Expr, inclusive: bool, loop_body: &[Stmt], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let (start_var, start_ty) = self.codegen_expr(start, body)?;
+        let (end_var, end_ty) = self.codegen_expr(end, body)?;
+
+        if !matches!(start_ty, Type::Int { .. } | Type::Usize) || !matches!(end_ty, Type::Int { .. } | Type::Usize) {
+            self.diagnostics.error(
+                "InvalidRange",
+                &format!("Range bounds must be integers, found {} and {}", start_ty.name(), end_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Ranges in for loops are written as 'start..end' or 'start..=end' with integer bounds.".to_string()),
+                    suggestions: vec!["Cast the bounds to an integer type".to_string()],
+                }
+            );
+            return Err(());
+        }
+
+        // The counter takes whichever bound's type holds the other, so it can reach `end`.
+        // An unsuffixed non-negative literal adopts the other bound's type.
+        let as_int = |ty: &Type| match ty {
+            Type::Usize => Type::Int { bits: self.arch.pointer_bits, signed: false },
+            other => other.clone(),
+        };
+        let counter_ty = if matches!(start, Expr::Number(n) if *n >= 0) || self.can_coerce(&as_int(&start_ty), &as_int(&end_ty)) {
+            end_ty.clone()
+        } else if matches!(end, Expr::Number(n) if *n >= 0) || self.can_coerce(&as_int(&end_ty), &as_int(&start_ty)) {
+            start_ty.clone()
+        } else {
+            self.diagnostics.error(
+                "InvalidRange",
+                &format!("Range bounds {} and {} have no common integer type", start_ty.name(), end_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("One bound's type must be able to hold every value of the other's.".to_string()),
+                    suggestions: vec!["Cast one bound to the other's type".to_string()],
+                }
+            );
+            return Err(());
+        };
+        let start_var = self.coerce_value(&start_var, &start_ty, &counter_ty);
+        let end_var = self.coerce_value(&end_var, &end_ty, &counter_ty);
+
+        let c_name = format!("var_{}", var);
+        let c_type = counter_ty.to_c_type(&self.arch, &mut self.type_registry);
+        let cmp = if inclusive { "<=" } else { "<" };
+
+        body.push_str(&format!("for ({} {} = {}; {} {} {}; {}++) {{\n", c_type, c_name, start_var, c_name, cmp, end_var, c_name));
+
+        let shadowed = self.vars.insert(var.to_string(), (c_name, counter_ty));
+
+        let break_label = self.fresh_label();
+        let continue_label = self.fresh_label();
+        self.push_loop(break_label.clone(), continue_label.clone());
+        for stmt in loop_body {
+            self.codegen_stmt(stmt, body).ok();
+        }
+        let labels = self.loop_stack.pop();
+
+        match shadowed {
+            Some(prev) => { self.vars.insert(var.to_string(), prev); }
+            None => { self.vars.remove(var); }
+        }
+
+        if labels.as_ref().is_some_and(|l| l.continue_used) {
+            body.push_str(&format!("{}: ;\n", continue_label));
+        }
+        body.push_str("}\n");
+        if labels.is_some_and(|l| l.break_used) {
+            body.push_str(&format!("{}: ;\n", break_label));
+        }
+        Ok(())
+    }
 
-        pub fn codegen_tuple_unpack(&mut self, names: &[String], value: &Expr, body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
+        pub fn codegen_tuple_unpack(&mut self, names: &[String], value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
             let (val_var, val_ty) = self.codegen_expr(value, body)?;
             
             match val_ty {
                 Type::Tuple { fields } => {
                     if fields.len() != names.len() {
+                        self.diagnostics.error(
+                            "TupleArityMismatch",
+                            &format!("Cannot unpack a {}-element tuple into {} names", fields.len(), names.len()),
+                            ErrorContext {
+                                primary_location: loc,
+                                secondary_locations: vec![],
+                                help_message: Some(format!("The value has type {}.", Type::Tuple { fields }.name())),
+                                suggestions: vec!["Bind exactly one name per tuple element".to_string()],
+                            }
+                        );
                         return Err(());
                     }
                     
@@ -923,237 +1507,501 @@ impl Codegen {
                     }
                     Ok(())
                 }
-                _ => Err(())
+                other => {
+                    self.diagnostics.error(
+                        "NotATuple",
+                        &format!("Cannot unpack a value of type {} into {} names", other.name(), names.len()),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Only tuples can be unpacked with 'create a, b = ...'.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                    Err(())
+                }
             }
         }
 
-        pub fn codegen_if_let(
+    pub fn codegen_if_let(
         &mut self, 
         pattern: &Expr, 
         value: &Expr, 
         then_block: &[Stmt], 
         else_block: &Option<Vec<Stmt>>, 
-        body: &mut String
+        body: &mut String,
+        loc: SourceLocation
     ) -> Result<(), ()> {
-        println!("[DEBUG] codegen_if_let called!");
-        
-        
         let (val_var, val_ty) = self.codegen_expr(value, body)?;
-        println!("[DEBUG] val_var={}, val_ty={:?}", val_var, val_ty.name());
-        
-        
-        match pattern {
-            Expr::Call(pattern_name, pattern_args) => {
-                println!("[DEBUG] Pattern is Call: {}", pattern_name);
-                
-                match pattern_name.as_str() {
-                    "Ok" | "ok" => {
-                        self.codegen_if_let_ok_pattern(pattern_args, &val_var, &val_ty, then_block, else_block, body)
-                    }
-                    "Err" | "err" => {
-                        self.codegen_if_let_err_pattern(pattern_args, &val_var, &val_ty, then_block, else_block, body)
-                    }
-                    "Some" | "some" => {
-                        self.codegen_if_let_some_pattern(pattern_args, &val_var, &val_ty, then_block, else_block, body)
-                    }
-                    _ => {
-                        eprintln!("[ERROR] Unknown pattern: {}", pattern_name);
-                        Err(())
-                    }
+
+        let Some(variants) = self.match_variants(&val_ty) else {
+            self.diagnostics.error(
+                "TypeMismatch",
+                &format!("`if let` needs an Option, Result or enum value, found {}", val_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Use a plain `if` to compare other values.".to_string()),
+                    suggestions: vec![],
                 }
-            }
-            Expr::ResultOk(inner) => {
-                println!("[DEBUG] Pattern is ResultOk");
-                if let Expr::Var(binding_name) = inner.as_ref() {
-                    self.codegen_if_let_ok_pattern(&[Expr::Var(binding_name.clone())], &val_var, &val_ty, then_block, else_block, body)
-                } else {
-                    eprintln!("[ERROR] ResultOk pattern argument must be a variable");
-                    Err(())
+            );
+            return Err(());
+        };
+
+        let Some((variant_name, bindings)) = if_let_pattern(pattern, &variants) else {
+            self.diagnostics.error(
+                "InvalidPattern",
+                "`if let` pattern must be a variant with plain bindings",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Write a pattern such as `Some(x)`, `Err(e)`, `Circle(r)` or `Empty`.".to_string()),
+                    suggestions: vec![],
                 }
-            }
-            Expr::ResultErr(inner) => {
-                println!("[DEBUG] Pattern is ResultErr");
-                if let Expr::Var(binding_name) = inner.as_ref() {
-                    self.codegen_if_let_err_pattern(&[Expr::Var(binding_name.clone())], &val_var, &val_ty, then_block, else_block, body)
-                } else {
-                    eprintln!("[ERROR] ResultErr pattern argument must be a variable");
-                    Err(())
+            );
+            return Err(());
+        };
+
+        let Some((_, tag, payload)) = variants.iter().find(|(name, _, _)| *name == variant_name) else {
+            self.diagnostics.error(
+                "UnknownVariant",
+                &format!("'{}' is not a variant of the tested value", variant_name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!(
+                        "Available variants: {}",
+                        variants.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+                    )),
+                    suggestions: vec![],
                 }
-            }
-            Expr::Some(inner) => {
-                println!("[DEBUG] Pattern is Some");
-                if let Expr::Var(binding_name) = inner.as_ref() {
-                    self.codegen_if_let_some_pattern(&[Expr::Var(binding_name.clone())], &val_var, &val_ty, then_block, else_block, body)
-                } else {
-                    eprintln!("[ERROR] Some pattern argument must be a variable");
-                    Err(())
+            );
+            return Err(());
+        };
+
+        if !bindings.is_empty() && bindings.len() != payload.len() {
+            self.diagnostics.error(
+                "PatternArity",
+                &format!("Pattern '{}' binds {} value(s), but the variant carries {}", variant_name, bindings.len(), payload.len()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Bind every payload value, or write the variant name alone to ignore them.".to_string()),
+                    suggestions: vec![],
                 }
+            );
+            return Err(());
+        }
+
+        let mut join = self.begin_branches();
+        let mut then_code = String::new();
+        let mut shadowed = Vec::new();
+        for (binding, (accessor, ty)) in bindings.iter().zip(payload) {
+            if binding == "_" {
+                continue;
             }
-            _ => {
-                eprintln!("[ERROR] Pattern is not a Call, ResultOk, ResultErr, or Some expression");
-                Err(())
-            }
+            let c_name = format!("var_{}", binding);
+            let decl = ty.c_declarator(&c_name, &self.arch, &mut self.type_registry);
+            then_code.push_str(&format!("{} = {}{};\n", decl, val_var, accessor));
+            shadowed.push((binding.clone(), self.vars.insert(binding.clone(), (c_name, ty.clone()))));
         }
-    }
 
-    fn codegen_if_let_ok_pattern(
-        &mut self,
-        pattern_args: &[Expr],
-        val_var: &str,
-        val_ty: &Type,
-        then_block: &[Stmt],
-        else_block: &Option<Vec<Stmt>>,
-        body: &mut String
-    ) -> Result<(), ()> {
-        
-        if pattern_args.len() != 1 {
-            eprintln!("[ERROR] Ok pattern expects 1 argument");
-            return Err(());
+        let result = then_block.iter().try_for_each(|stmt| self.codegen_stmt(stmt, &mut then_code));
+
+        for (binding, prev) in shadowed.into_iter().rev() {
+            match prev {
+                Some(prev) => { self.vars.insert(binding, prev); }
+                None => { self.vars.remove(&binding); }
+            }
         }
-        
-        let binding_name = if let Expr::Var(name) = &pattern_args[0] {
-            name.clone()
-        } else {
-            eprintln!("[ERROR] Ok pattern argument must be a variable");
-            return Err(());
-        };
-        
-        
-        let ok_type = if let Type::Result { ok, .. } = val_ty {
-            ok.as_ref().clone()
-        } else {
-            eprintln!("[ERROR] Expected Result type, got {:?}", val_ty.name());
-            return Err(());
-        };
-        
-        let ok_c_type = ok_type.to_c_type(&self.arch, &mut self.type_registry);
-        
-        
-        body.push_str(&format!("if ({}.tag == 0) {{\n", val_var));
-        body.push_str(&format!("    {} {} = {}.data.ok;\n", ok_c_type, binding_name, val_var));
-        
-        
-        self.vars.insert(binding_name.clone(), (binding_name.clone(), ok_type));
-        
-        
-        for stmt in then_block {
-            self.codegen_stmt(stmt, body)?;
+        result?;
+        self.leave_branch(&mut join, then_block);
+
+        self.enter_branch(&join);
+        let else_stmts = else_block.as_deref().unwrap_or_default();
+        let mut else_code = String::new();
+        for stmt in else_stmts {
+            self.codegen_stmt(stmt, &mut else_code)?;
         }
-        
-        body.push('}');
-        
-        
-        if let Some(else_stmts) = else_block {
-            body.push_str(" else {\n");
-            for stmt in else_stmts {
-                self.codegen_stmt(stmt, body)?;
-            }
-            body.push('}');
+        self.leave_branch(&mut join, else_stmts);
+        let frees = self.join_branches(join);
+
+        body.push_str(&format!("if ({}.tag == {}) {{\n{}{}}}", val_var, tag, then_code, frees[0]));
+        if else_block.is_some() || !frees[1].is_empty() {
+            body.push_str(&format!(" else {{\n{}{}}}", else_code, frees[1]));
         }
-        
+
         body.push('\n');
         Ok(())
     }
+}
 
-        fn codegen_if_let_err_pattern(
-        &mut self,
-        pattern_args: &[Expr],
-        val_var: &str,
-        val_ty: &Type,
-        then_block: &[Stmt],
-        else_block: &Option<Vec<Stmt>>,
-        body: &mut String
-    ) -> Result<(), ()> {
-        
-        if pattern_args.len() != 1 {
-            return Err(());
+/// Splits an `if let` pattern into its variant name and payload bindings.
+/// A bare name only counts as a pattern when it names one of `variants`.
+#[allow(clippy::type_complexity)]
+fn if_let_pattern(pattern: &Expr, variants: &[(String, String, Vec<(String, Type)>)]) -> Option<(String, Vec<String>)> {
+    let binding = |expr: &Expr| match expr {
+        Expr::Var(name) => Some(name.clone()),
+        _ => None,
+    };
+    match pattern {
+        Expr::Some(inner) => Some(("Some".to_string(), vec![binding(inner)?])),
+        Expr::ResultOk(inner) => Some(("Ok".to_string(), vec![binding(inner)?])),
+        Expr::ResultErr(inner) => Some(("Err".to_string(), vec![binding(inner)?])),
+        Expr::None => Some(("None".to_string(), vec![])),
+        Expr::EnumPattern(name, bindings) => Some((name.clone(), bindings.clone())),
+        Expr::Call(name, args) => {
+            let name = match name.as_str() {
+                "ok" => "Ok",
+                "err" => "Err",
+                "some" => "Some",
+                other => other,
+            };
+            Some((name.to_string(), args.iter().map(binding).collect::<Option<Vec<_>>>()?))
         }
-        
-        let binding_name = if let Expr::Var(name) = &pattern_args[0] {
-            name.clone()
-        } else {
-            return Err(());
-        };
-        
-        let err_type = if let Type::Result { err, .. } = val_ty {
-            err.as_ref().clone()
-        } else {
-            return Err(());
-        };
-        
-        let err_c_type = err_type.to_c_type(&self.arch, &mut self.type_registry);
-        
-        body.push_str(&format!("if ({}.tag == 1) {{\n", val_var));
-        body.push_str(&format!("    {} {} = {}.data.err;\n", err_c_type, binding_name, val_var));
-        
-        self.vars.insert(binding_name.clone(), (binding_name.clone(), err_type));
-        
-        for stmt in then_block {
-            self.codegen_stmt(stmt, body)?;
+        Expr::Var(name) if variants.iter().any(|(variant, _, _)| variant == name) => Some((name.clone(), vec![])),
+        _ => None,
+    }
+}
+
+/// Non-public functions get internal linkage; `main` stays visible to the C entry point.
+fn linkage(is_public: bool, name: &str) -> &'static str {
+    if is_public || name == "main" { "" } else { "static " }
+}
+
+/// Whether `stmts` contain a `return`, or a `break` that leaves the enclosing loop.
+/// An unlabeled `break` inside a nested loop only exits that inner loop.
+fn loop_can_exit(stmts: &[Stmt], outermost: bool) -> bool {
+    stmts.iter().any(|stmt| match stmt.node() {
+        Stmt::Return(_) => true,
+        Stmt::Break(label) => outermost || label.is_some(),
+        Stmt::If(_, then_body, else_body) => {
+            loop_can_exit(then_body, outermost)
+                || else_body.as_ref().is_some_and(|b| loop_can_exit(b, outermost))
         }
-        
-        body.push('}');
-        
-        if let Some(else_stmts) = else_block {
-            body.push_str(" else {\n");
-            for stmt in else_stmts {
-                self.codegen_stmt(stmt, body)?;
+        Stmt::While(_, inner) | Stmt::For(_, _, inner) | Stmt::Loop(inner) => loop_can_exit(inner, false),
+        Stmt::Labeled(_, inner) => loop_can_exit(std::slice::from_ref(inner.as_ref()), outermost),
+        Stmt::Scope(inner) | Stmt::Unsafe(inner) => loop_can_exit(inner, outermost),
+        Stmt::Match(_, cases, default) => {
+            cases.iter().any(|c| loop_can_exit(&c.body, outermost))
+                || default.as_ref().is_some_and(|b| loop_can_exit(b, outermost))
+        }
+        _ => false,
+    })
+}
+
+/// Locals a body declares and the names it reads. Blocks open scopes, so a read counts for
+/// the innermost visible declaration of its name; reads that resolve to no local are kept
+/// in `free`. Plain and compound assignment to a name do not count as reading it.
+#[derive(Default)]
+pub(crate) struct BodyReads {
+    /// Declared locals as (name, line, read).
+    pub(crate) locals: Vec<(String, usize, bool)>,
+    pub(crate) free: HashSet<String>,
+    scopes: Vec<HashMap<String, usize>>,
+}
+
+impl BodyReads {
+    pub(crate) fn block(&mut self, stmts: &[Stmt], line: &dyn Fn(&Stmt) -> usize) {
+        self.bound_block(&[], stmts, line);
+    }
+
+    /// Walks `stmts` in a new scope that starts with `bindings`, such as a loop variable or
+    /// the payload names of a pattern. Those are never reported as unused.
+    fn bound_block(&mut self, bindings: &[String], stmts: &[Stmt], line: &dyn Fn(&Stmt) -> usize) {
+        self.scopes.push(HashMap::new());
+        for binding in bindings {
+            self.declare(binding, 0, true);
+        }
+        for stmt in stmts {
+            self.stmt(stmt, line);
+        }
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &str, line: usize, read: bool) {
+        self.locals.push((name.to_string(), line, read));
+        let index = self.locals.len() - 1;
+        if self.scopes.is_empty() {
+            self.scopes.push(HashMap::new());
+        }
+        self.scopes.last_mut().unwrap().insert(name.to_string(), index);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let mut names = HashSet::new();
+        expr_reads(expr, &mut names);
+        for name in names {
+            match self.scopes.iter().rev().find_map(|scope| scope.get(&name)) {
+                Some(&index) => self.locals[index].2 = true,
+                None => { self.free.insert(name); }
             }
-            body.push('}');
         }
-        
-        body.push('\n');
-        Ok(())
     }
 
-        fn codegen_if_let_some_pattern(
-        &mut self,
-        pattern_args: &[Expr],
-        val_var: &str,
-        val_ty: &Type,
-        then_block: &[Stmt],
-        else_block: &Option<Vec<Stmt>>,
-        body: &mut String
-    ) -> Result<(), ()> {
-        
-        if pattern_args.len() != 1 {
-            return Err(());
+    fn read(&mut self, name: &str) {
+        self.expr(&Expr::Var(name.to_string()));
+    }
+
+    pub(crate) fn stmt(&mut self, stmt: &Stmt, line: &dyn Fn(&Stmt) -> usize) {
+        match stmt {
+            Stmt::TypedDeclaration { name, value, .. } => {
+                self.expr(value);
+                self.declare(name, line(stmt), false);
+            }
+            Stmt::TupleUnpack { names, value } => {
+                self.expr(value);
+                for name in names {
+                    self.declare(name, line(stmt), false);
+                }
+            }
+            Stmt::ModuleAssign(path, _, value) | Stmt::ModuleCompoundAssign(path, _, _, value) => {
+                self.read(&path[0]);
+                self.expr(value);
+            }
+            Stmt::Assign(_, value)
+            | Stmt::CompoundAssign(_, _, value)
+            | Stmt::Return(Some(value))
+            | Stmt::Expr(value) => self.expr(value),
+            Stmt::IndexAssign(obj, indices, value) | Stmt::IndexCompoundAssign(obj, indices, _, value) => {
+                self.expr(obj);
+                indices.iter().for_each(|i| self.expr(i));
+                self.expr(value);
+            }
+            Stmt::MemberAssign(obj, _, value) | Stmt::DerefAssign(obj, value) => {
+                self.expr(obj);
+                self.expr(value);
+            }
+            Stmt::MemberCompoundAssign(obj, _, _, value) => {
+                self.expr(obj);
+                self.expr(value);
+            }
+            Stmt::If(cond, then_body, else_body) => {
+                self.expr(cond);
+                self.block(then_body, line);
+                if let Some(else_body) = else_body {
+                    self.block(else_body, line);
+                }
+            }
+            Stmt::IfLet { pattern, value, then_block, else_block } => {
+                self.expr(value);
+                self.bound_block(&pattern_bindings(pattern), then_block, line);
+                if let Some(else_block) = else_block {
+                    self.block(else_block, line);
+                }
+            }
+            Stmt::While(cond, body) => {
+                self.expr(cond);
+                self.block(body, line);
+            }
+            Stmt::For(var, iter, body) => {
+                self.expr(iter);
+                self.bound_block(std::slice::from_ref(var), body, line);
+            }
+            Stmt::Loop(body) | Stmt::Scope(body) | Stmt::Unsafe(body) => self.block(body, line),
+            Stmt::Match(value, cases, default) => {
+                self.expr(value);
+                for case in cases {
+                    let bindings = match &case.value {
+                        Expr::EnumPattern(_, bindings) => bindings.clone(),
+                        value => {
+                            self.expr(value);
+                            vec![]
+                        }
+                    };
+                    self.bound_block(&bindings, &case.body, line);
+                }
+                if let Some(default) = default {
+                    self.block(default, line);
+                }
+            }
+            Stmt::Call(name, args) => {
+                self.read(name);
+                args.iter().for_each(|a| self.expr(a));
+            }
+            Stmt::ModuleCall(path, _, args) => {
+                self.read(&path[0]);
+                args.iter().for_each(|a| self.expr(a));
+            }
+            Stmt::StaticMethodCall(_, _, args) => args.iter().for_each(|a| self.expr(a)),
+            Stmt::MethodCall(obj, _, args) => {
+                self.expr(obj);
+                args.iter().for_each(|a| self.expr(a));
+            }
+            Stmt::MethodCallNamed(obj, _, args) => {
+                self.expr(obj);
+                args.iter().for_each(|(_, a)| self.expr(a));
+            }
+            Stmt::StaticMethodCallNamed(_, _, args) => args.iter().for_each(|(_, a)| self.expr(a)),
+            Stmt::Labeled(_, inner) | Stmt::Defer(inner) => self.stmt(inner, line),
+            Stmt::Located(stmt_line, inner) => self.stmt(inner, &|_| *stmt_line),
+            Stmt::Assert(cond, message) => {
+                self.expr(cond);
+                if let Some(message) = message {
+                    self.expr(message);
+                }
+            }
+            Stmt::TypeAlias { target, .. } => self.expr(target),
+            Stmt::Return(None)
+            | Stmt::Break(_)
+            | Stmt::Continue(_)
+            | Stmt::StructDef(_)
+            | Stmt::EnumDef(_)
+            | Stmt::ImplBlock(_)
+            | Stmt::TraitDef(_)
+            | Stmt::ExternDecl(_)
+            | Stmt::ModuleImport(_)
+            | Stmt::ModuleUse(_)
+            | Stmt::ModuleDef { .. }
+            | Stmt::Function(_)
+            | Stmt::ModuleExports { .. } => {}
         }
-        
-        let binding_name = if let Expr::Var(name) = &pattern_args[0] {
-            name.clone()
-        } else {
-            return Err(());
-        };
-        
-        let inner_type = if let Type::Option { inner } = val_ty {
-            inner.as_ref().clone()
-        } else {
-            return Err(());
-        };
-        
-        let inner_c_type = inner_type.to_c_type(&self.arch, &mut self.type_registry);
-        
-        body.push_str(&format!("if ({}.tag == 1) {{\n", val_var));
-        body.push_str(&format!("    {} {} = {}.value;\n", inner_c_type, binding_name, val_var));
-        
-        self.vars.insert(binding_name.clone(), (binding_name.clone(), inner_type));
-        
-        for stmt in then_block {
-            self.codegen_stmt(stmt, body)?;
+    }
+}
+
+/// Names a variant pattern such as `Some(x)` or `Circle(r)` binds for its block.
+fn pattern_bindings(pattern: &Expr) -> Vec<String> {
+    let names = |args: &[Expr]| args.iter().filter_map(|arg| match arg {
+        Expr::Var(name) => Some(name.clone()),
+        _ => None,
+    }).collect();
+    match pattern {
+        Expr::Some(inner) | Expr::ResultOk(inner) | Expr::ResultErr(inner) => names(std::slice::from_ref(inner.as_ref())),
+        Expr::Call(_, args) => names(args),
+        Expr::EnumPattern(_, bindings) => bindings.clone(),
+        _ => vec![],
+    }
+}
+
+/// The keyword or call after which control cannot reach the next statement.
+pub(crate) fn terminator_name(stmt: &Stmt) -> Option<&'static str> {
+    match stmt.node() {
+        Stmt::Return(_) => Some("return"),
+        Stmt::Break(_) => Some("break"),
+        Stmt::Continue(_) => Some("continue"),
+        Stmt::Call(name, _) | Stmt::Expr(Expr::Call(name, _)) if name == "panic" => Some("panic"),
+        Stmt::Call(name, _) | Stmt::Expr(Expr::Call(name, _)) if name == "exit" => Some("exit"),
+        _ => None,
+    }
+}
+
+/// Adds every name `expr` could read to `reads`, erring towards counting a name as read.
+fn expr_reads(expr: &Expr, reads: &mut HashSet<String>) {
+    match expr {
+        Expr::Var(name) | Expr::FuncAddr(name) => {
+            reads.insert(name.clone());
         }
-        
-        body.push('}');
-        
-        if let Some(else_stmts) = else_block {
-            body.push_str(" else {\n");
-            for stmt in else_stmts {
-                self.codegen_stmt(stmt, body)?;
+        Expr::ModuleAccess(path, _) => {
+            reads.insert(path[0].clone());
+        }
+        Expr::Call(name, args) => {
+            reads.insert(name.clone());
+            args.iter().for_each(|a| expr_reads(a, reads));
+        }
+        Expr::ModuleCall(path, _, args) => {
+            reads.insert(path[0].clone());
+            args.iter().for_each(|a| expr_reads(a, reads));
+        }
+        Expr::CallNamed(name, args) | Expr::ModuleCallNamed(name, _, args) => {
+            reads.insert(name.clone());
+            args.iter().for_each(|(_, a)| expr_reads(a, reads));
+        }
+        Expr::StaticMethodCall(_, _, args) | Expr::Plan(_, args) | Expr::Tuple(args) | Expr::Array(args) | Expr::OneOf(args) => {
+            args.iter().for_each(|a| expr_reads(a, reads));
+        }
+        Expr::StaticMethodCallNamed(_, _, args) | Expr::StructInit(_, args) | Expr::StructLiteral(_, args) => {
+            args.iter().for_each(|(_, a)| expr_reads(a, reads));
+        }
+        Expr::ResultOk(inner)
+        | Expr::ResultErr(inner)
+        | Expr::Not(inner)
+        | Expr::Wait(inner)
+        | Expr::Unwrap(inner)
+        | Expr::Try(inner)
+        | Expr::Chars(inner)
+        | Expr::IsNotEmpty(inner)
+        | Expr::Collect(inner)
+        | Expr::IsEmpty(inner)
+        | Expr::Some(inner)
+        | Expr::UnOp(_, inner)
+        | Expr::AddressOf(inner)
+        | Expr::Deref(inner)
+        | Expr::TupleAccess(inner, _)
+        | Expr::MemberAccess(inner, _)
+        | Expr::TypeOf(inner)
+        | Expr::Panic(inner)
+        | Expr::Lines(inner)
+        | Expr::FixedArray(inner, _)
+        | Expr::Slots(inner) => expr_reads(inner, reads),
+        Expr::ArrayGet { obj: a, reference: b }
+        | Expr::Filter { obj: a, reference: b }
+        | Expr::UnwrapOr(a, b)
+        | Expr::Have { obj: a, item: b }
+        | Expr::Contain { obj: a, item: b }
+        | Expr::IndexOf { obj: a, item: b }
+        | Expr::BinOp(_, a, b)
+        | Expr::Pipe(a, b)
+        | Expr::Split(a, b)
+        | Expr::Join(a, b)
+        | Expr::Map { obj: a, func: b, .. }
+        | Expr::Range(a, b, _) => {
+            expr_reads(a, reads);
+            expr_reads(b, reads);
+        }
+        Expr::Slice(a, b, c) | Expr::IfExpr(a, b, c) => {
+            expr_reads(a, reads);
+            expr_reads(b, reads);
+            expr_reads(c, reads);
+        }
+        Expr::ContainAll { obj, items } => {
+            expr_reads(obj, reads);
+            items.iter().for_each(|i| expr_reads(i, reads));
+        }
+        Expr::Index(obj, indices) => {
+            expr_reads(obj, reads);
+            indices.iter().for_each(|i| expr_reads(i, reads));
+        }
+        Expr::MethodCall(obj, _, args) | Expr::ArrayMethod { obj, args, .. } | Expr::OptionMethod { obj, args, .. } => {
+            expr_reads(obj, reads);
+            args.iter().for_each(|a| expr_reads(a, reads));
+        }
+        Expr::MethodCallNamed(obj, _, args) => {
+            expr_reads(obj, reads);
+            args.iter().for_each(|(_, a)| expr_reads(a, reads));
+        }
+        Expr::Cast(inner, target) => {
+            expr_reads(inner, reads);
+            match target {
+                CastTarget::LibraryCall(_, args) | CastTarget::LibraryModuleCall(_, _, args) => {
+                    args.iter().for_each(|a| expr_reads(a, reads));
+                }
+                CastTarget::Type(_) | CastTarget::LibraryCallTyped(_, _) => {}
             }
-            body.push('}');
         }
-        
-        body.push('\n');
-        Ok(())
+        Expr::HashMap(entries) => {
+            for (key, value) in entries {
+                expr_reads(key, reads);
+                expr_reads(value, reads);
+            }
+        }
+        Expr::Number(_)
+        | Expr::TypedNumber(_, _)
+        | Expr::Float(_)
+        | Expr::String(_)
+        | Expr::Bool(_)
+        | Expr::HexNumber(_)
+        | Expr::BinaryNumber(_)
+        | Expr::OctalNumber(_)
+        | Expr::Char(_)
+        | Expr::ByteChar(_)
+        | Expr::None
+        | Expr::EnumPattern(_, _)
+        | Expr::ReferenceTo(_)
+        | Expr::SizeOf(_)
+        | Expr::AlignOf(_)
+        | Expr::VaNext(_)
+        | Expr::OffsetOf { .. }
+        | Expr::Type(_)
+        | Expr::NullPtr => {}
     }
-}
\ No newline at end of file
+}
@@ -1,5 +1,12 @@
 use crate::import::*;
 
+/// Calls the code generator handles itself, plus the free functions of the standard library.
+const BUILTIN_CALLS: &[&str] = &[
+    "panic", "exit", "size_of", "sizeof", "as_bytes", "as_ptr", "as_mut_ptr",
+    "array", "Array", "vector", "Vector", "buffer", "Buffer", "hashMap", "range", "random",
+    "print", "vix_malloc", "vix_realloc", "vix_free",
+];
+
 impl Codegen {
         pub fn codegen_assign(
         &mut self,
@@ -32,15 +39,20 @@ impl Codegen {
         };
         
         let (val_var, val_ty) = self.codegen_expr(value, body)?;
+        self.note_move(value);
+        if self.owned_vars.contains(name) {
+            self.codegen_owned_free(name, body);
+            self.moved_vars.remove(name);
+        }
         
          
         match (&var_ty, &val_ty) {
-            (Type::ConstStr { .. }, Type::Str { .. }) => {
+            (Type::ConstStr, Type::Str { .. }) => {
                  
                 body.push_str(&format!("{} = {}.ptr;\n", c_name, val_var));
             }
             _ => {
-                 
+                let val_var = self.coerce_value(&val_var, &val_ty, &var_ty);
                 body.push_str(&format!("{} = {};\n", c_name, val_var));
             }
         }
@@ -76,6 +88,20 @@ impl Codegen {
             return Err(());
         }
 
+        if op == "+=" && let Type::Slice { element } = &var_ty {
+            self.diagnostics.error(
+                "SliceNotGrowable",
+                &format!("Cannot push to '{}': {} is a borrowed view", name, var_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Slices do not own their elements, so they cannot grow.".to_string()),
+                    suggestions: vec![format!("Declare '{}' as an owned {}[] array", name, element.name())],
+                }
+            );
+            return Err(());
+        }
+
         if op == "+=" {
             match (&var_ty, &val_ty) {
                  
@@ -155,14 +181,29 @@ impl Codegen {
         Ok(())
     }
 
+    /// `args` extended with the declared defaults of the parameters it leaves out,
+    /// or `None` when nothing needs filling (or a missing parameter has no default).
+    pub(crate) fn fill_default_args(&self, func: &str, args: &[Expr]) -> Option<Vec<Expr>> {
+        let defaults = self.param_defaults.get(func)?;
+        if args.len() >= defaults.len() {
+            return None;
+        }
+        let trailing = defaults[args.len()..].iter().cloned().collect::<Option<Vec<_>>>()?;
+        Some(args.iter().cloned().chain(trailing).collect())
+    }
+
     pub fn codegen_call_expr(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
-        eprintln!("[DEBUG] codegen_call_expr: original func_name={}", func);
+        debug_log!("[DEBUG] codegen_call_expr: original func_name={}", func);
         
         
         let resolved_func = self.resolve_function_name(func);
         
         if resolved_func != func {
-            eprintln!("[DEBUG] codegen_call_expr: resolved {} -> {}", func, resolved_func);
+            debug_log!("[DEBUG] codegen_call_expr: resolved {} -> {}", func, resolved_func);
+        }
+
+        if let Some(filled) = self.fill_default_args(&resolved_func, args) {
+            return self.codegen_call_expr(func, &filled, body, loc);
         }
 
         
@@ -200,7 +241,7 @@ impl Codegen {
         }
 
         
-        if self.linked_libraries.contains(&func.to_string()) {
+        if self.linked_libraries.iter().any(|lib| Clang::library_name(lib) == func) {
             self.diagnostics.error(
                 "InvalidLibraryCall",
                 &format!("Cannot call library '{}' directly", func),
@@ -231,7 +272,7 @@ impl Codegen {
                 let slice_name = slice_ty.to_c_type(&self.arch, &mut self.type_registry);
                 body.push_str(&format!("{} {} = {{ .ptr = (uint8_t*){}.ptr, .len = {}.len }};\n", 
                     slice_name, tmp, obj_var, obj_var));
-                return Ok((tmp, slice_ty));
+                Ok((tmp, slice_ty))
             }
             "as_ptr" => {
                 if args.len() != 1 { return Err(()); }
@@ -244,7 +285,7 @@ impl Codegen {
                 };
                 body.push_str(&format!("const {}* {} = {}.ptr;\n", 
                     inner_type.to_c_type(&self.arch, &mut self.type_registry), tmp, obj_var));
-                return Ok((tmp, Type::Ptr(Box::new(Type::Const(Box::new(inner_type))))));
+                Ok((tmp, Type::Ptr(Box::new(Type::Const(Box::new(inner_type))))))
             }
             "as_mut_ptr" => {
                 if args.len() != 1 { return Err(()); }
@@ -257,14 +298,26 @@ impl Codegen {
                 };
                 body.push_str(&format!("{}* {} = {}.ptr;\n", 
                     inner_type.to_c_type(&self.arch, &mut self.type_registry), tmp, obj_var));
-                return Ok((tmp, Type::MutRef(Box::new(inner_type))));
+                Ok((tmp, Type::MutRef(Box::new(inner_type))))
+            }
+            "panic" if !self.user_functions.contains_key(&resolved_func) => {
+                if args.len() != 1 { return Err(()); }
+                self.codegen_make_panic(&args[0], body)
+            }
+            "exit" if !self.user_functions.contains_key(&resolved_func) => {
+                if args.len() != 1 { return Err(()); }
+                let (code_var, _) = self.codegen_expr(&args[0], body)?;
+                let tmp = self.fresh_var();
+                body.push_str(&format!("exit({});\n", code_var));
+                body.push_str(&format!("int {} = 0;\n", tmp));
+                Ok((tmp, Type::Never))
             }
             "size_of" | "sizeof" => {
                 if args.len() != 1 { return Err(()); }
                 let (var, _ty) = self.codegen_expr(&args[0], body)?;
                 let tmp = self.fresh_var();
                 body.push_str(&format!("size_t {} = sizeof({});\n", tmp, var));
-                return Ok((tmp, Type::Int { bits: 64, signed: false }));
+                Ok((tmp, Type::Usize))
             }
             _ => {
                 
@@ -313,10 +366,30 @@ impl Codegen {
         }
     }
 
-    pub fn codegen_member_access(&mut self, obj: &Expr, field: &str, body: &mut String) -> Result<(String, Type), ()> {
-        let (obj_var, obj_ty) = self.codegen_expr(obj, body)?;
-        
-        let struct_name = match &obj_ty {
+    /// A chain such as `a.b.c` is read into a single temporary rather than one per hop.
+    pub fn codegen_member_access(&mut self, obj: &Expr, field: &str, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (obj_var, obj_ty) = self.member_lvalue(obj, body, loc.clone())?;
+        let (op, field_ty) = self.member_field(&obj_ty, field, loc)?;
+
+        let tmp = self.fresh_var();
+        let c_type = field_ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = {}{}{};\n", c_type, tmp, obj_var, op, field));
+        Ok((tmp, field_ty))
+    }
+
+    /// Builds the C lvalue for `a.b.c`, choosing `.` or `->` per segment from that segment's type.
+    pub fn member_lvalue(&mut self, obj: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let Expr::MemberAccess(inner, field) = obj else {
+            return self.codegen_expr(obj, body);
+        };
+        let (inner_lvalue, inner_ty) = self.member_lvalue(inner, body, loc.clone())?;
+        let (op, field_ty) = self.member_field(&inner_ty, field, loc)?;
+        Ok((format!("{}{}{}", inner_lvalue, op, field), field_ty))
+    }
+
+    /// Resolves `field` on a struct or reference to a struct, returning the access operator and field type.
+    fn member_field(&mut self, obj_ty: &Type, field: &str, loc: SourceLocation) -> Result<(&'static str, Type), ()> {
+        let struct_name = match obj_ty {
             Type::Struct { name } => name.clone(),
             Type::Ref(inner) | Type::MutRef(inner) => {
                 if let Type::Struct { name } = &**inner {
@@ -329,21 +402,108 @@ impl Codegen {
         };
 
          
-        let field_ty = if let Some(struct_info) = self.structs.get(&struct_name) {
-            struct_info.fields.iter()
-                .find(|f| f.0 == field)
-                .map(|f| f.1.clone())
-                .unwrap_or(Type::i32())
-        } else {
-            Type::i32()
+        let Some(struct_info) = self.structs.get(&struct_name) else {
+            self.diagnostics.error(
+                "UndefinedStruct",
+                &format!("Struct '{}' is not defined", struct_name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("Cannot access field '{}' on undefined struct '{}'.", field, struct_name)),
+                    suggestions: vec![format!("Define struct '{}' before using it", struct_name)],
+                }
+            );
+            return Err(());
+        };
+
+        let Some(field_ty) = struct_info.fields.iter().find(|f| f.0 == field).map(|f| f.1.clone()) else {
+            let known: Vec<&str> = struct_info.fields.iter().map(|f| f.0.as_str()).collect();
+            let closest = known.iter().min_by_key(|k| levenshtein(k, field)).filter(|k| levenshtein(k, field) <= 2);
+            let context = ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("Available fields: {}", known.join(", "))),
+                suggestions: closest.map(|k| format!("Did you mean '{}'?", k)).into_iter().collect(),
+            };
+            self.diagnostics.error(
+                "UnknownField",
+                &format!("Struct '{}' has no field named '{}'", struct_name, field),
+                context
+            );
+            return Err(());
         };
 
-        let tmp = self.fresh_var();
         let op = if matches!(obj_ty, Type::Ref(_) | Type::MutRef(_)) { "->" } else { "." };
-        
-        let c_type = field_ty.to_c_type(&self.arch, &mut self.type_registry);
-        body.push_str(&format!("{} {} = {}{}{};\n", c_type, tmp, obj_var, op, field));
-        Ok((tmp, field_ty))
+        Ok((op, field_ty))
+    }
+
+    /// Reads the next argument of the enclosing variadic function with `va_arg`.
+    /// Types narrower than `int` or `double` are read promoted and cast back.
+    pub fn codegen_va_next(&mut self, ty: &Type, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let Some((va_list, _)) = self.vars.get("...").cloned() else {
+            self.diagnostics.error(
+                "VaNextOutsideVariadic",
+                "'va_next' can only be used inside a function declared with '...'",
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("Add a trailing '...' parameter to read variadic arguments.".to_string()),
+                    suggestions: vec![],
+                }
+            );
+            return Err(());
+        };
+
+        let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        let promoted = match ty {
+            Type::Bool | Type::Int { bits: 8 | 16, .. } | Type::Char { bits: 8 | 16, .. } => "int".to_string(),
+            Type::Float { bits: 32 } => "double".to_string(),
+            _ => c_type.clone(),
+        };
+        let tmp = self.fresh_var();
+        body.push_str(&format!("{} {} = ({})va_arg({}, {});\n", c_type, tmp, c_type, va_list, promoted));
+        Ok((tmp, ty.clone()))
+    }
+
+    /// Lowers `offsetof(Struct, field)` to the C macro, after checking both names exist.
+    pub fn codegen_offsetof(&mut self, struct_name: &str, field: &str, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let Some(struct_info) = self.structs.get(struct_name) else {
+            self.diagnostics.error(
+                "UndefinedStruct",
+                &format!("Struct '{}' is not defined", struct_name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("offsetof needs a known struct, found '{}'.", struct_name)),
+                    suggestions: vec![format!("Define struct '{}' before using it", struct_name)],
+                }
+            );
+            return Err(());
+        };
+
+        if !struct_info.fields.iter().any(|f| f.0 == field) {
+            let known: Vec<&str> = struct_info.fields.iter().map(|f| f.0.as_str()).collect();
+            let closest = known.iter().min_by_key(|k| levenshtein(k, field)).filter(|k| levenshtein(k, field) <= 2);
+            let context = ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("Available fields: {}", known.join(", "))),
+                suggestions: closest.map(|k| format!("Did you mean '{}'?", k)).into_iter().collect(),
+            };
+            self.diagnostics.error(
+                "UnknownField",
+                &format!("Struct '{}' has no field named '{}'", struct_name, field),
+                context
+            );
+            return Err(());
+        }
+
+        self.ir.add_include("#include <stddef.h>");
+
+        let c_type = Type::Struct { name: struct_name.to_string() }.to_c_type(&self.arch, &mut self.type_registry);
+        let tmp = self.fresh_var();
+        body.push_str(&format!("size_t {} = offsetof({}, {});\n", tmp, c_type, field));
+        Ok((tmp, Type::Usize))
     }
     pub fn codegen_cast_target(&mut self, expr: &Expr, target: &CastTarget, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
         if let CastTarget::Type(ty) = target {
@@ -353,31 +513,183 @@ impl Codegen {
         }
     }
 
+    /// Reads `arr[i][j]`, checking constant indices into fixed arrays and, with
+    /// `config.bounds_checks`, slice indices at runtime.
+    pub fn codegen_index(&mut self, arr: &Expr, indices: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (arr_var, arr_ty) = self.codegen_expr(arr, body)?;
+
+        let mut access = arr_var;
+        let mut elem_ty = arr_ty;
+
+        for idx in indices {
+            let (idx_var, _idx_ty) = self.codegen_expr(idx, body)?;
+
+            let (layer, next_ty, fixed_len) = match elem_ty {
+                Type::Array { element, size: None } | Type::Slice { element } => (".ptr", *element, None),
+                Type::Str { .. } => (".ptr", Type::char8(), None),
+                Type::Array { element, size: Some(size) } => ("", *element, Some(size)),
+                Type::MultiArray { element, dimensions } if dimensions.len() > 1 => {
+                    let len = dimensions[0];
+                    ("", Type::MultiArray { element, dimensions: dimensions[1..].to_vec() }, Some(len))
+                }
+                Type::MultiArray { element, dimensions } => ("", *element, dimensions.first().copied()),
+                Type::Ptr(inner) | Type::RawPtr(inner) => ("", *inner, None),
+                other => {
+                    self.diagnostics.error(
+                        "NotIndexable",
+                        &format!("Cannot index into a value of type {}", other.name()),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some("Only arrays, slices, strings and pointers can be indexed.".to_string()),
+                            suggestions: vec![],
+                        }
+                    );
+                    return Err(());
+                }
+            };
+
+            if let (Some(len), Expr::Number(n)) = (fixed_len, idx)
+                && (*n < 0 || *n as usize >= len) {
+                    self.diagnostics.error(
+                        "IndexOutOfBounds",
+                        &format!("Index {} is out of bounds for an array of length {}", n, len),
+                        ErrorContext {
+                            primary_location: loc,
+                            secondary_locations: vec![],
+                            help_message: Some(format!("Valid indices are 0 to {}.", len.saturating_sub(1))),
+                            suggestions: vec![],
+                        }
+                    );
+                    return Err(());
+                }
+
+            if self.config.bounds_checks && layer == ".ptr" {
+                self.ensure_panic_helper();
+                body.push_str(&format!("if ((size_t){} >= {}.len) {{ vix_panic(\"index out of bounds\"); }}\n", idx_var, access));
+            }
+
+            access = format!("{}{}[{}]", access, layer, idx_var);
+            elem_ty = next_ty;
+        }
+
+        let tmp = self.fresh_var();
+        let c_type = elem_ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = {};\n", c_type, tmp, access));
+        Ok((tmp, elem_ty))
+    }
+
+    /// `obj[start..end]` as a `{ ptr, len }` view into `obj`; fixed arrays decay to a pointer.
+    /// With `config.bounds_checks`, the range is checked against the length at runtime.
+    pub fn codegen_slice(&mut self, obj: &Expr, start: &Expr, end: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (obj_var, obj_ty) = self.codegen_expr(obj, body)?;
+        let (start_var, _) = self.codegen_expr(start, body)?;
+        let (end_var, _) = self.codegen_expr(end, body)?;
+
+        let (ptr, len, slice_ty) = match &obj_ty {
+            Type::Array { element, size: None } | Type::Slice { element } => {
+                (format!("{}.ptr", obj_var), format!("{}.len", obj_var), Type::Array { element: element.clone(), size: None })
+            }
+            Type::Str { .. } => (format!("{}.ptr", obj_var), format!("{}.len", obj_var), obj_ty.clone()),
+            Type::Array { element, size: Some(size) } => {
+                (obj_var.clone(), size.to_string(), Type::Array { element: element.clone(), size: None })
+            }
+            Type::MultiArray { element, dimensions } if dimensions.len() == 1 => {
+                (obj_var.clone(), dimensions[0].to_string(), Type::Array { element: element.clone(), size: None })
+            }
+            other => {
+                self.diagnostics.error(
+                    "NotSliceable",
+                    &format!("Cannot slice a value of type {}", other.name()),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Only arrays, slices and strings can be sliced.".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                return Err(());
+            }
+        };
+
+        if self.config.bounds_checks {
+            self.ensure_panic_helper();
+            body.push_str(&format!(
+                "if ((size_t){0} > (size_t){1} || (size_t){1} > {2}) {{ vix_panic(\"slice out of bounds\"); }}\n",
+                start_var, end_var, len
+            ));
+        }
+
+        self.ensure_type_defined(&slice_ty);
+        let tmp = self.fresh_var();
+        let c_type = slice_ty.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = {{ .ptr = {} + {}, .len = {} - {} }};\n", c_type, tmp, ptr, start_var, end_var, start_var));
+        Ok((tmp, slice_ty))
+    }
+
+    pub(crate) fn ensure_panic_helper(&mut self) {
+        self.ir.add_helper_function(
+            "vix_panic",
+            "static void vix_panic(const char* msg) {\n    fprintf(stderr, \"panic: %s\\n\", msg);\n    exit(1);\n}\n".to_string(),
+        );
+    }
+
     pub fn codegen_index_assign(&mut self, arr: &Expr, indices: &[Expr], value: &Expr, body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
         let (arr_var, arr_ty) = self.codegen_expr(arr, body)?;
         let (val_var, _val_ty) = self.codegen_expr(value, body)?;
-        
-         
-        let is_slice = matches!(arr_ty, Type::Array { size: None, .. } | Type::Str { .. });
-        
-        let mut index_str = if is_slice {
-            format!("{}.ptr", arr_var)
-        } else {
-            arr_var.clone()
-        };
+        let (index_str, _) = self.index_lvalue(arr_var, arr_ty, indices, body)?;
+
+        body.push_str(&format!("{} = {};\n", index_str, val_var));
+
+        Ok(())
+    }
+
+    /// Applies `op` in place, so each index expression is evaluated exactly once.
+    pub fn codegen_index_compound_assign(&mut self, arr: &Expr, indices: &[Expr], op: &str, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let (arr_var, arr_ty) = self.codegen_expr(arr, body)?;
+        let (index_str, elem_ty) = self.index_lvalue(arr_var, arr_ty, indices, body)?;
+        let (val_var, val_ty) = self.codegen_expr(value, body)?;
+
+        if matches!(elem_ty, Type::Void) || matches!(val_ty, Type::Void) {
+            self.diagnostics.error(
+                "VoidOperation",
+                "Cannot perform compound assignment on void type",
+                void_operation_error(op, loc)
+            );
+            return Err(());
+        }
+
+        body.push_str(&format!("{} {} {};\n", index_str, op, val_var));
+        Ok(())
+    }
+
+    /// Builds the C lvalue for `arr[i][j]...`, storing each index in a temp first.
+    pub(crate) fn index_lvalue(&mut self, arr_var: String, arr_ty: Type, indices: &[Expr], body: &mut String) -> Result<(String, Type), ()> {
+        let mut index_str = arr_var;
+        let mut elem_ty = arr_ty;
         
         for idx in indices {
             let (idx_var, _idx_ty) = self.codegen_expr(idx, body)?;
-            index_str = format!("{}[{}]", index_str, idx_var);
+             
+            let (layer, next_ty) = match elem_ty {
+                Type::Array { element, size: None } | Type::Slice { element } => (".ptr", *element),
+                Type::Str { .. } => (".ptr", Type::char8()),
+                Type::Array { element, size: Some(_) } => ("", *element),
+                Type::MultiArray { element, dimensions } if dimensions.len() > 1 => {
+                    ("", Type::MultiArray { element, dimensions: dimensions[1..].to_vec() })
+                }
+                Type::MultiArray { element, .. } => ("", *element),
+                other => ("", other),
+            };
+            index_str = format!("{}{}[{}]", index_str, layer, idx_var);
+            elem_ty = next_ty;
         }
-        
-        body.push_str(&format!("{} = {};\n", index_str, val_var));
 
-        Ok(())
+        Ok((index_str, elem_ty))
     }
 
-    pub fn codegen_member_assign(&mut self, obj: &Expr, field: &str, value: &Expr, body: &mut String, _loc: SourceLocation) -> Result<(), ()> {
-        let (obj_var, obj_ty) = self.codegen_expr(obj, body)?;
+    pub fn codegen_member_assign(&mut self, obj: &Expr, field: &str, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let (obj_var, obj_ty) = self.member_lvalue(obj, body, loc)?;
         let (val_var, _val_ty) = self.codegen_expr(value, body)?;
 
         let op = if matches!(obj_ty, Type::Ref(_) | Type::MutRef(_)) { "->" } else { "." };
@@ -387,64 +699,137 @@ impl Codegen {
     }
 
 
+    /// Rejects a call to an unknown name that is a near miss of a known function.
+    /// Other unknown names are left for the C compiler to resolve as library symbols;
+    /// a name that merely extends a known one, like `printf` for `print`, counts as other.
+    pub fn check_call_target(&mut self, func: &str, loc: SourceLocation) -> Result<(), ()> {
+        let known = |name: &str| {
+            self.vars.contains_key(name)
+                || self.user_functions.contains_key(name)
+                || self.extern_functions.contains_key(name)
+                || self.structs.contains_key(name)
+                || BUILTIN_CALLS.contains(&name)
+        };
+        if known(func) || known(&self.resolve_function_name(func)) {
+            return Ok(());
+        }
+
+        let closest = self.user_functions.keys()
+            .chain(self.extern_functions.keys())
+            .chain(self.structs.keys())
+            .map(String::as_str)
+            .chain(BUILTIN_CALLS.iter().copied())
+            .map(|name| (levenshtein(name, func), name))
+            .filter(|(distance, name)| *distance <= (func.len().min(name.len()) / 3).max(1))
+            .filter(|(_, name)| !(func.contains(name) || name.contains(func)))
+            .min()
+            .map(|(_, name)| name.to_string());
+        let Some(closest) = closest else {
+            return Ok(());
+        };
+
+        self.diagnostics.error(
+            "UndefinedFunction",
+            &format!("Function '{}' is not defined", func),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("did you mean `{}`?", closest)),
+                suggestions: vec![format!("Replace '{}' with '{}'", func, closest)],
+            }
+        );
+        Err(())
+    }
+
     pub fn codegen_call_stmt(&mut self, func: &str, args: &[Expr], body: &mut String, loc: SourceLocation) -> Result<(), ()> {
-    eprintln!("[DEBUG] codegen_call_stmt: original func_name={}", func);
+        debug_log!("[DEBUG] codegen_call_stmt: original func_name={}", func);
     
+        if let Some(filled) = self.fill_default_args(&self.resolve_function_name(func), args) {
+            return self.codegen_call_stmt(func, &filled, body, loc);
+        }
+
+        if func == "panic" && !self.user_functions.contains_key(func) {
+            return self.codegen_call_expr(func, args, body, loc).map(|_| ());
+        }
+
+        // Freeing an owned value by hand consumes it, so its scope-exit free is skipped.
+        if func == "vix_free"
+            && let [Expr::Var(name)] = args
+            && self.owned_vars.contains(name) {
+                self.codegen_var(name, loc)?;
+                self.codegen_owned_free(name, body);
+                self.note_move(&args[0]);
+                return Ok(());
+            }
+        if func == "vix_free" && !self.user_functions.contains_key(func) {
+            self.ensure_vix_free();
+        }
+
+        self.check_call_target(func, loc.clone())?;
      
-    let resolved_func = self.resolve_function_name(func);
+        let mut resolved_func = self.resolve_function_name(func);
     
-    if resolved_func != func {
-        eprintln!("[DEBUG] codegen_call_stmt: resolved {} -> {}", func, resolved_func);
-    }
+        if resolved_func != func {
+            debug_log!("[DEBUG] codegen_call_stmt: resolved {} -> {}", func, resolved_func);
+        }
     
-    let mut arg_vars = Vec::new();
+        let mut arg_vars = Vec::new();
     
      
-    let param_types = if let Some(ext_info) = self.extern_functions.get(&resolved_func) {
-        Some(ext_info.params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>())
-    } else if let Some((params, _)) = self.user_functions.get(&resolved_func) {
-        Some(params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>())
-    } else {
-        None
-    };
-
-    for (i, arg) in args.iter().enumerate() {
-        let (mut var, ty) = self.codegen_expr(arg, body)?;
+        let param_types = if let Some((c_name, Type::FunctionPointer { params, .. })) = self.vars.get(func) {
+            resolved_func = c_name.clone();
+            Some(params.clone())
+        } else if let Some(ext_info) = self.extern_functions.get(&resolved_func) {
+            Some(ext_info.params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>())
+        } else if let Some((params, _)) = self.user_functions.get(&resolved_func) {
+            Some(params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>())
+        } else {
+            None
+        };
+
+        for (i, arg) in args.iter().enumerate() {
+            self.check_mut_ref_arg(&resolved_func, i, arg, loc.clone())?;
+            let (mut var, ty) = self.codegen_expr(arg, body)?;
         
          
-        if let Some(params) = &param_types {
-            if let Some(param_ty) = params.get(i) {
-                let needs_ptr = match param_ty {
-                    Type::ConstStr => true,
-                    Type::Ptr(inner) => {
-                        matches!(inner.as_ref(), Type::Const(t) if matches!(t.as_ref(), Type::Char { .. }))
-                    }
-                    _ => false
-                };
+            if let Some(params) = &param_types {
+                if let Some(param_ty) = params.get(i) {
+                    self.check_owned_arg(func, param_ty, &ty, loc.clone())?;
+                    let needs_ptr = match param_ty {
+                        Type::ConstStr => true,
+                        Type::Ptr(inner) => {
+                            matches!(inner.as_ref(), Type::Const(t) if matches!(t.as_ref(), Type::Char { .. }))
+                        }
+                        _ => false
+                    };
                 
-                if needs_ptr && matches!(ty, Type::Str { .. }) {
-                    var = format!("{}.ptr", var);
+                    if needs_ptr && matches!(ty, Type::Str { .. }) {
+                        var = format!("{}.ptr", var);
+                    }
                 }
-            }
-        } else {
+            } else {
              
-            if matches!(ty, Type::Str { .. }) {
-                var = format!("{}.ptr", var);
+                if matches!(ty, Type::Str { .. }) {
+                    var = format!("{}.ptr", var);
+                }
             }
-        }
         
-        arg_vars.push(var);
-    }
+            arg_vars.push(self.pass_by_modifier(&resolved_func, i, var));
+        }
+        args.iter().for_each(|arg| self.note_move(arg));
 
-    let args_str = arg_vars.join(", ");
+        let args_str = arg_vars.join(", ");
     
      
-    body.push_str(&format!("{}({});\n", resolved_func, args_str));
+        body.push_str(&format!("{}({});\n", resolved_func, args_str));
 
-    Ok(())
-}
+        Ok(())
+    }
 
     pub fn codegen_program(&mut self, functions: &[Function]) -> Result<(), ()> {
+        for func in functions {
+            self.codegen_function(func, true)
+        }
         for func in functions {
             self.codegen_function(func, false)
         }
@@ -470,7 +855,7 @@ impl Codegen {
         loc: SourceLocation
     ) -> Result<(String, Type), ()> {
         if method == "new" {
-            println!("[DEBUG] Generating constructor call for {}", type_name);
+            debug_log!("[DEBUG] Generating constructor call for {}", type_name);
 
             let constructor_name = format!("{}_new", type_name);
 
@@ -541,14 +926,79 @@ impl Codegen {
             );
         }
         
+        if let (Type::Struct { name: from }, Type::Struct { name: to }) = (&source_ty, target_ty) {
+            if from == to {
+                return Ok((var, source_ty));
+            }
+
+            let from_size = self.struct_layout(from).map(|(size, _)| size);
+            let to_size = self.struct_layout(to).map(|(size, _)| size);
+            if from_size.is_none() || from_size != to_size {
+                self.diagnostics.error(
+                    "InvalidCast",
+                    &format!("Cannot cast struct '{}' to struct '{}'", from, to),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some(format!(
+                            "Struct casts reinterpret memory and need both structs to have the same size ({} vs {} bytes).",
+                            from_size.map_or("unknown".to_string(), |s| s.to_string()),
+                            to_size.map_or("unknown".to_string(), |s| s.to_string()),
+                        )),
+                        suggestions: vec![format!("Build a new '{}' from the fields of '{}' instead", to, from)],
+                    }
+                );
+                return Err(());
+            }
+
+            let c_type = target_ty.to_c_type(&self.arch, &mut self.type_registry);
+            let tmp = self.fresh_var();
+            body.push_str(&format!("{} {};\n", c_type, tmp));
+            body.push_str(&format!("memcpy(&{}, &{}, sizeof({}));\n", tmp, var, c_type));
+            return Ok((tmp, target_ty.clone()));
+        }
+
         let c_type = target_ty.to_c_type(&self.arch, &mut self.type_registry);
         let tmp = self.fresh_var();
 
         body.push_str(&format!("{} {} = ({}){};\n", c_type, tmp, c_type, var));
         Ok((tmp, target_ty.clone()))
     }
+
+    /// Size and alignment in bytes of a struct, following C layout rules.
+    pub fn struct_layout(&self, name: &str) -> Option<(usize, usize)> {
+        let info = self.structs.get(name)?;
+        let mut size: usize = 0;
+        let mut align = 1;
+
+        for (_, field_ty, _) in &info.fields {
+            let (field_size, field_align) = match field_ty {
+                Type::Struct { name } => self.struct_layout(name)?,
+                Type::Array { element, size: Some(count) } => {
+                    let (elem_size, elem_align) = match element.as_ref() {
+                        Type::Struct { name } => self.struct_layout(name)?,
+                        other => {
+                            let bytes = other.size_bits(&self.arch).div_ceil(8);
+                            (bytes, bytes.clamp(1, self.arch.pointer_bits / 8))
+                        }
+                    };
+                    (elem_size * count, elem_align)
+                }
+                other => {
+                    let bytes = other.size_bits(&self.arch).div_ceil(8);
+                    (bytes, bytes.clamp(1, self.arch.pointer_bits / 8))
+                }
+            };
+
+            size = size.next_multiple_of(field_align) + field_size;
+            align = align.max(field_align);
+        }
+
+        Some((size.next_multiple_of(align), align))
+    }
     pub fn types_compatible(&self, ty1: &Type, ty2: &Type) -> bool {
         match (ty1, ty2) {
+            (Type::Never, _) | (_, Type::Never) => true,
             (Type::Int { bits: b1, signed: s1 }, Type::Int { bits: b2, signed: s2 }) => b1 == b2 && s1 == s2,
             (Type::Float { bits: b1 }, Type::Float { bits: b2 }) => b1 == b2,
             (Type::Bool, Type::Bool) => true,
@@ -564,10 +1014,18 @@ impl Codegen {
             (Type::Str { .. }, Type::StdStr) => true,
             (Type::StdStr, Type::Str { .. }) => true,
             (Type::Ptr(inner1), Type::Ptr(inner2)) => self.types_compatible(inner1, inner2),
+            (Type::Ref(inner1), Type::Ref(inner2))
+            | (Type::MutRef(inner1), Type::MutRef(inner2))
+            | (Type::Owned(inner1), Type::Owned(inner2)) => {
+                self.types_compatible(inner1, inner2)
+            },
             (Type::Struct { name: n1 }, Type::Struct { name: n2 }) => n1 == n2,
             (Type::Array { element: e1, size: s1 }, Type::Array { element: e2, size: s2 }) => {
                 self.types_compatible(e1, e2) && (s1 == s2)
             },
+            (Type::Slice { element: e1 }, Type::Slice { element: e2 } | Type::Array { element: e2, size: None }) => {
+                self.types_compatible(e1, e2)
+            },
             (Type::Tuple { fields: f1 }, Type::Tuple { fields: f2 }) => {
                 if f1.len() != f2.len() { return false; }
                 f1.iter().zip(f2.iter()).all(|(t1, t2)| self.types_compatible(t1, t2))
@@ -582,10 +1040,40 @@ impl Codegen {
             (Type::HashMap { key: k1, value: v1 }, Type::HashMap { key: k2, value: v2 }) => {
                 self.types_compatible(k1, k2) && self.types_compatible(v1, v2)
             },
+            (Type::FunctionPointer { params: p1, ret: r1 }, Type::FunctionPointer { params: p2, ret: r2 }) => {
+                if p1.len() != p2.len() { return false; }
+                p1.iter().zip(p2.iter()).all(|(t1, t2)| self.types_compatible(t1, t2))
+                    && self.types_compatible(r1, r2)
+            },
             _ => false, 
         }
     }
 
+    /// Whether a value of type `from` can be stored in `to` without losing information:
+    /// anything `types_compatible` accepts, plus integer widening and unsigned to wider signed.
+    pub fn can_coerce(&self, from: &Type, to: &Type) -> bool {
+        match (from, to) {
+            (Type::Int { bits: from_bits, signed: from_signed }, Type::Int { bits: to_bits, signed: to_signed }) => {
+                (from_signed == to_signed && from_bits <= to_bits)
+                    || (!from_signed && *to_signed && from_bits < to_bits)
+            }
+            _ => self.types_compatible(to, from),
+        }
+    }
+
+    /// Casts `value` to `to` when it only fits through an integer widening,
+    /// and keeps it within `to` when that width has no C type of its own.
+    pub fn coerce_value(&mut self, value: &str, from: &Type, to: &Type) -> String {
+        let value = if matches!((from, to), (Type::Int { .. }, Type::Int { .. }))
+            && !self.types_compatible(to, from)
+            && self.can_coerce(from, to) {
+            format!("({}){}", to.to_c_type(&self.arch, &mut self.type_registry), value)
+        } else {
+            value.to_string()
+        };
+        to.wrap_int_width(&value)
+    }
+
     pub fn codegen_member_compound_assign(
         &mut self, 
         obj: &Expr, 
@@ -595,8 +1083,8 @@ impl Codegen {
         body: &mut String, 
         loc: SourceLocation
     ) -> Result<(), ()> {
-        let (obj_var, obj_ty) = self.codegen_expr(obj, body).map_err(|_| ())?;
-        let (val_var, val_ty) = self.codegen_expr(value, body).map_err(|_| ())?;
+        let (obj_var, obj_ty) = self.member_lvalue(obj, body, loc.clone())?;
+        let (val_var, _val_ty) = self.codegen_expr(value, body).map_err(|_| ())?;
 
         if matches!(obj_ty, Type::Void) {
             self.diagnostics.error(
@@ -623,13 +1111,12 @@ impl Codegen {
             "."
         };
 
-        if op == "+=" {
-            if let Type::Struct { name: struct_name } = struct_ty {
-                if let Some(struct_info) = self.structs.get(struct_name) {
-                    if let Some((_, field_ty, _)) = struct_info.fields.iter()
-                        .find(|(fname, _, _)| fname == field) 
-                    {
-                        if matches!(field_ty, Type::StdStr) {
+        if op == "+="
+            && let Type::Struct { name: struct_name } = struct_ty
+                && let Some(struct_info) = self.structs.get(struct_name)
+                    && let Some((_, field_ty, _)) = struct_info.fields.iter()
+                        .find(|(fname, _, _)| fname == field)
+                        && matches!(field_ty, Type::StdStr) {
                              self.diagnostics.error(
                                  "UnsupportedFeature",
                                  "String member compound assignment is not supported in No-OS mode.",
@@ -643,11 +1130,6 @@ impl Codegen {
                              return Err(());
                         }
 
-                    }
-                }
-            }
-        }
-
         body.push_str(&format!("{}{}{} {} {};\n", obj_var, access_op, field, op, val_var));
         Ok(())
     }
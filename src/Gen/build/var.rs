@@ -1,18 +1,112 @@
 use crate::import::*;
+use crate::Gen::build::generate_type::escape_c_string;
+use crate::Gen::build::stmt::terminator_name;
+
+/// Move state before a set of mutually exclusive branches, and how each branch left it.
+/// A branch that ends in a terminator never reaches the join and records `None`.
+pub(crate) struct BranchJoin {
+    owned: HashSet<String>,
+    moved: HashSet<String>,
+    ends: Vec<Option<HashSet<String>>>,
+}
 
 impl Codegen {
+    /// Temporaries use the `__vix_` prefix, which the lexer rejects in Vix identifiers,
+    /// so they can never collide with user variables or functions.
     pub fn fresh_var(&mut self) -> String {
-        let varaable = format!("t{}", self.var_count);
+        let varaable = format!("__vix_t{}", self.var_count);
         self.var_count += 1;
 
         varaable
     }
     
+    /// Takes the address of an argument bound to a `reference` or `mutable reference` parameter.
+    pub fn pass_by_modifier(&self, func: &str, index: usize, var: String) -> String {
+        match self.param_modifiers.get(func).and_then(|mods| mods.get(index)) {
+            Some(ParamModifier::Reference | ParamModifier::MutableReference) => format!("&{}", var),
+            _ => var,
+        }
+    }
+
+    /// A `mutable reference` parameter writes through to its argument, which must therefore
+    /// be a place the caller declared `mut`; a temporary would silently absorb the write.
+    pub fn check_mut_ref_arg(&mut self, func: &str, index: usize, arg: &Expr, loc: SourceLocation) -> Result<(), ()> {
+        if !matches!(self.param_modifiers.get(func).and_then(|mods| mods.get(index)), Some(ParamModifier::MutableReference)) {
+            return Ok(());
+        }
+        let (code, message, help) = match arg {
+            Expr::Var(_) | Expr::MemberAccess(..) | Expr::Index(..) if !self.lvalue_is_shared(arg) => return Ok(()),
+            Expr::Var(name) | Expr::MemberAccess(_, name) => (
+                "AssignThroughSharedRef",
+                format!("Cannot pass immutable '{}' to a mutable reference parameter of '{}'", name, func),
+                format!("'{}' writes through this parameter; declare the variable with `mut`.", func),
+            ),
+            Expr::Index(..) => (
+                "AssignThroughSharedRef",
+                format!("Cannot pass an element of an immutable array to a mutable reference parameter of '{}'", func),
+                format!("'{}' writes through this parameter; declare the array with `mut`.", func),
+            ),
+            _ => (
+                "MutRefToTemporary",
+                format!("Argument {} of '{}' is a mutable reference and needs a variable, not a temporary value", index + 1, func),
+                format!("'{}' writes through this parameter; the write would be lost on a temporary.", func),
+            ),
+        };
+        self.diagnostics.error(
+            code,
+            &message,
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(help),
+                suggestions: vec!["Store the value in a `mut` variable and pass that".to_string()],
+            }
+        );
+        Err(())
+    }
+
+    /// A `~T` parameter frees its argument when the callee returns, so it only takes a value
+    /// that is itself owned; a literal or borrowed `T` would be freed out from under its owner.
+    pub fn check_owned_arg(&mut self, func: &str, param_ty: &Type, arg_ty: &Type, loc: SourceLocation) -> Result<(), ()> {
+        if !matches!(param_ty, Type::Owned(_)) || self.types_compatible(param_ty, arg_ty) {
+            return Ok(());
+        }
+        self.diagnostics.error(
+            "TypeMismatch",
+            &format!("'{}' takes an owned '{}', but the argument is '{}'", func, param_ty.name(), arg_ty.name()),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("The callee frees its '{}' argument, so only an owned value can be moved into it.", param_ty.name())),
+                suggestions: vec![format!("Declare the parameter as '{}' to borrow the value instead", arg_ty.name())],
+            }
+        );
+        Err(())
+    }
+
     pub fn codegen_var(&mut self, name: &str, loc: SourceLocation) -> Result<(String, Type), ()> {
-        println!("[DEBUG] codegen_var: looking up '{}', vars keys={:?}", name, self.vars.keys().collect::<Vec<_>>());
+        debug_log!("[DEBUG] codegen_var: looking up '{}', vars keys={:?}", name, self.vars.keys().collect::<Vec<_>>());
+        if self.moved_vars.contains(name) {
+            self.diagnostics.error(
+                "UseAfterMove",
+                &format!("Use of moved value '{}'", name),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some(format!("'{}' is owned, and its value was moved out by an earlier assignment, call or return.", name)),
+                    suggestions: vec![format!("Assign a new value to '{}' before using it again", name)],
+                }
+            );
+            return Err(());
+        }
+
         if let Some((c_name, ty)) = self.vars.get(name) {
             return Ok((c_name.clone(), ty.clone()));
         }
+
+        if let Some((c_name, ty)) = self.global_consts.get(name) {
+            return Ok((c_name.clone(), ty.clone()));
+        }
         
         for ((_, var_name), (c_name, ty, _)) in &self.module_vars {
             if var_name == name {
@@ -20,7 +114,7 @@ impl Codegen {
             }
         }
         
-        println!("[DEBUG] codegen_var: '{}' NOT FOUND", name);
+        debug_log!("[DEBUG] codegen_var: '{}' NOT FOUND", name);
         
         
         if self.structs.contains_key(name) {
@@ -64,43 +158,583 @@ impl Codegen {
         Err(())
     }
 
+    pub fn codegen_func_addr(&mut self, name: &str, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let signature = if let Some((params, ret_ty)) = self.user_functions.get(name) {
+            Some((params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>(), ret_ty.clone()))
+        } else {
+            self.extern_functions.get(name)
+                .map(|ext| (ext.params.iter().map(|(_, ty)| ty.clone()).collect::<Vec<_>>(), ext.return_type.clone()))
+        };
+
+        if let Some((params, ret)) = signature {
+            let c_name = if name == "main" { "vix_main".to_string() } else { name.to_string() };
+            return Ok((c_name, Type::FunctionPointer { params, ret: Box::new(ret) }));
+        }
+
+        if self.vars.contains_key(name) {
+            return self.codegen_address_of(&Expr::Var(name.to_string()), body, loc);
+        }
+
+        self.diagnostics.error(
+            "UndefinedFunction",
+            &format!("Cannot take the address of undefined function '{}'", name),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some(format!("Function '{}' must be declared before its address is taken.", name)),
+                suggestions: vec!["Check for typos in the function name".to_string()],
+            }
+        );
+        Err(())
+    }
+
+    /// Makes `name` the owner of its value, freed once when the enclosing scope exits.
+    pub(crate) fn begin_owned(&mut self, name: &str) {
+        self.owned_vars.insert(name.to_string());
+        self.moved_vars.remove(name);
+        self.ensure_vix_free();
+        if let Some(frame) = self.defer_stack.last_mut() {
+            frame.push(Stmt::Call("vix_free".to_string(), vec![Expr::Var(name.to_string())]));
+        }
+    }
+
+    pub(crate) fn ensure_vix_free(&mut self) {
+        self.ir.add_helper_function("vix_free", "static void vix_free(void* ptr) {\n    free(ptr);\n}\n".to_string());
+    }
+
+    /// An owned variable used as a whole value hands its ownership to the receiver.
+    /// A loop body may only move values it declared, since the next iteration would move again.
+    pub(crate) fn note_move(&mut self, expr: &Expr) {
+        if let Expr::Var(name) = expr
+            && self.owned_vars.contains(name) {
+                if self.loop_stack.last().is_some_and(|l| l.outer_owned.contains(name)) {
+                    self.diagnostics.error(
+                        "MoveInLoop",
+                        &format!("Owned value '{}' is moved inside a loop", name),
+                        ErrorContext {
+                            primary_location: self.stmt_location(),
+                            secondary_locations: vec![],
+                            help_message: Some(format!("'{}' is declared outside the loop, so a second iteration would use it after the move.", name)),
+                            suggestions: vec![format!("Move '{}' after the loop, or declare it inside the loop body", name)],
+                        }
+                    );
+                }
+                self.moved_vars.insert(name.clone());
+            }
+    }
+
+    pub(crate) fn begin_branches(&self) -> BranchJoin {
+        BranchJoin {
+            owned: self.owned_vars.clone(),
+            moved: self.moved_vars.clone(),
+            ends: Vec::new(),
+        }
+    }
+
+    pub(crate) fn enter_branch(&mut self, join: &BranchJoin) {
+        self.moved_vars = join.moved.clone();
+    }
+
+    pub(crate) fn leave_branch(&mut self, join: &mut BranchJoin, stmts: &[Stmt]) {
+        let diverges = stmts.last().is_some_and(|stmt| terminator_name(stmt).is_some());
+        join.ends.push((!diverges).then(|| self.moved_vars.clone()));
+    }
+
+    /// Joins the branches. A value moved on some paths is freed at the end of the others,
+    /// so it counts as moved on every path afterwards. Returns that code per branch.
+    pub(crate) fn join_branches(&mut self, join: BranchJoin) -> Vec<String> {
+        let mut moved = join.moved.clone();
+        moved.extend(join.ends.iter().flatten().flatten().cloned());
+
+        let mut frees = Vec::new();
+        for end in &join.ends {
+            let mut code = String::new();
+            if let Some(end) = end {
+                self.moved_vars = end.clone();
+                let mut pending: Vec<&String> = moved.difference(end).filter(|name| join.owned.contains(*name)).collect();
+                pending.sort();
+                for name in pending {
+                    self.codegen_owned_free(name, &mut code);
+                }
+            }
+            frees.push(code);
+        }
+        self.moved_vars = moved;
+        frees
+    }
+
+    /// The scope-exit free of an owned variable, skipped once its value has moved.
+    pub(crate) fn codegen_owned_free(&mut self, name: &str, body: &mut String) {
+        if self.moved_vars.contains(name) {
+            return;
+        }
+        if let Some((c_name, Type::Owned(inner))) = self.vars.get(name) {
+            let target = if inner.is_owned_buffer() { format!("{}.ptr", c_name) } else { c_name.clone() };
+            body.push_str(&format!("vix_free({});\n", target));
+        }
+    }
+
+    /// `&operand` for a variable, field or element; a bare name that is no variable is a function.
+    pub fn codegen_address_of(&mut self, operand: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (lvalue, ty) = match operand {
+            Expr::Var(name) if !self.vars.contains_key(name) => return self.codegen_func_addr(name, body, loc),
+            Expr::Var(_) | Expr::MemberAccess(..) => self.member_lvalue(operand, body, loc.clone())?,
+            Expr::Index(arr, indices) => {
+                let (arr_var, arr_ty) = self.member_lvalue(arr, body, loc.clone())?;
+                self.index_lvalue(arr_var, arr_ty, indices, body)?
+            }
+            _ => {
+                self.diagnostics.error(
+                    "AddressOfRvalue",
+                    "Cannot take the address of a temporary value",
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Only variables, struct fields and array elements have an address.".to_string()),
+                        suggestions: vec!["Store the value in a variable and take its address instead".to_string()],
+                    }
+                );
+                return Err(());
+            }
+        };
+
+        if matches!(ty, Type::Void) {
+            self.diagnostics.error(
+                "VoidAddressOf",
+                "Cannot take address of void expression",
+                void_operation_error("address-of (&)", loc)
+            );
+            return Err(());
+        }
+
+        let tmp = self.fresh_var();
+        let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+        if self.lvalue_is_shared(operand) {
+            body.push_str(&format!("const {}* {} = &{};\n", c_type, tmp, lvalue));
+            Ok((tmp, Type::Ref(Box::new(ty))))
+        } else {
+            body.push_str(&format!("{}* {} = &{};\n", c_type, tmp, lvalue));
+            Ok((tmp, Type::MutRef(Box::new(ty))))
+        }
+    }
+
+    /// Whether an lvalue lives in an immutable local or behind a shared reference,
+    /// so it may be read through its address but not written.
+    fn lvalue_is_shared(&self, lvalue: &Expr) -> bool {
+        match lvalue {
+            Expr::Var(name) => {
+                self.immutable_vars.contains(name)
+                    || matches!(self.vars.get(name), Some((_, Type::Ref(_))))
+            }
+            Expr::MemberAccess(obj, _) | Expr::Index(obj, _) => self.lvalue_is_shared(obj),
+            _ => false,
+        }
+    }
 
-    pub fn codegen_break(&self, body: &mut String) -> Result<(), ()> {
-        body.push_str("break;\n");
+    /// Evaluates `ptr` and returns it with its own type and the type it points to,
+    /// rejecting non-pointers.
+    fn deref_target(&mut self, ptr: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type, Type), ()> {
+        let (ptr_var, ptr_ty) = self.codegen_expr(ptr, body)?;
+        let pointee = match &ptr_ty {
+            Type::Ptr(inner) | Type::RawPtr(inner) | Type::Owned(inner) | Type::Ref(inner) | Type::MutRef(inner) => *inner.clone(),
+            _ => {
+                self.diagnostics.error(
+                    "DerefNonPointer",
+                    &format!("Cannot dereference a value of type {}", ptr_ty.name()),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Only pointers and references can be dereferenced with `*`.".to_string()),
+                        suggestions: vec!["Remove the `*`, or take an address with `&` first".to_string()],
+                    }
+                );
+                return Err(());
+            }
+        };
+
+        if matches!(pointee, Type::Void) {
+            self.diagnostics.error(
+                "VoidDeref",
+                "Cannot dereference a void pointer",
+                void_operation_error("dereference (*)", loc)
+            );
+            return Err(());
+        }
+        Ok((ptr_var, ptr_ty, pointee))
+    }
+
+    pub fn codegen_deref(&mut self, ptr: &Expr, body: &mut String, loc: SourceLocation) -> Result<(String, Type), ()> {
+        let (ptr_var, _, pointee) = self.deref_target(ptr, body, loc)?;
+        let tmp = self.fresh_var();
+        let c_type = pointee.to_c_type(&self.arch, &mut self.type_registry);
+        body.push_str(&format!("{} {} = *{};\n", c_type, tmp, ptr_var));
+        Ok((tmp, pointee))
+    }
+
+    pub fn codegen_deref_assign(&mut self, ptr: &Expr, value: &Expr, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let (ptr_var, ptr_ty, pointee) = self.deref_target(ptr, body, loc.clone())?;
+        if matches!(ptr_ty, Type::Ref(_)) {
+            self.diagnostics.error(
+                "AssignThroughSharedRef",
+                &format!("Cannot assign through a shared reference of type {}", ptr_ty.name()),
+                ErrorContext {
+                    primary_location: loc,
+                    secondary_locations: vec![],
+                    help_message: Some("A `&` reference only reads its target; writing needs `&mut`.".to_string()),
+                    suggestions: vec!["Declare the target with `mut` and pass it as `&mut`".to_string()],
+                }
+            );
+            return Err(());
+        }
+        let (val_var, val_ty) = self.codegen_expr_expecting(value, &pointee, body, loc.clone())?;
+
+        if !self.can_coerce(&val_ty, &pointee) {
+            self.diagnostics.error(
+                "TypeMismatch",
+                &format!("Cannot store a value of type {} through a pointer to {}", val_ty.name(), pointee.name()),
+                type_mismatch_error(&pointee.name(), &val_ty.name(), loc, value.location())
+            );
+            return Err(());
+        }
+
+        let val_var = self.coerce_value(&val_var, &val_ty, &pointee);
+        body.push_str(&format!("*{} = {};\n", ptr_var, val_var));
+        Ok(())
+    }
+
+    /// Integer constants become `#define`s so other constants can use them in
+    /// their initializers; everything else is a `static const`.
+    pub fn codegen_global_consts(&mut self, constants: &[GlobalConst]) {
+        for constant in constants {
+            let ty = if matches!(constant.ty, Type::Void) {
+                Parser::infer_type(&constant.value)
+            } else {
+                constant.ty.clone()
+            };
+            let c_name = format!("const_{}", constant.name);
+
+            let Some(init) = self.const_initializer(&constant.value) else {
+                let loc = self.line_location(constant.line);
+                self.diagnostics.error(
+                    "InvalidConstant",
+                    &format!("The value of constant '{}' is not known at compile time", constant.name),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Constants may only use literals, other constants and operators.".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                continue;
+            };
+
+            self.ensure_type_defined(&ty);
+            let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+            let decl = if matches!(ty, Type::Int { .. } | Type::Usize) {
+                format!("#define {} (({}){})\n", c_name, c_type, init)
+            } else {
+                format!("static const {} {} = {};\n", c_type, c_name, init)
+            };
+            self.ir.forward_decls.push_str(&decl);
+            self.global_consts.insert(constant.name.clone(), (c_name, ty));
+        }
+    }
+
+    fn const_initializer(&self, expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Number(n) | Expr::TypedNumber(n, _) => Some(n.to_string()),
+            Expr::HexNumber(n) | Expr::BinaryNumber(n) | Expr::OctalNumber(n) => Some(n.to_string()),
+            Expr::Float(f) => Some(format!("{:?}", f)),
+            Expr::Bool(b) => Some(b.to_string()),
+            Expr::Char(c) => Some(c.to_string()),
+            Expr::ByteChar(b) => Some(b.to_string()),
+            Expr::String(s) => Some(format!("{{ .ptr = \"{}\", .len = {} }}", escape_c_string(s), s.len())),
+            Expr::Var(name) => self.global_consts.get(name).map(|(c_name, _)| c_name.clone()),
+            Expr::Call(name, args) if args.is_empty() => self.global_consts.get(name).map(|(c_name, _)| c_name.clone()),
+            Expr::UnOp(op, inner) if matches!(op.as_str(), "-" | "!" | "~") => {
+                Some(format!("({}{})", op, self.const_initializer(inner)?))
+            }
+            Expr::BinOp(op, left, right) => {
+                Some(format!("({} {} {})", self.const_initializer(left)?, op, self.const_initializer(right)?))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn codegen_break(&mut self, label: &Option<String>, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        match self.loop_target(label, "break", loc)? {
+            Some(idx) => {
+                self.emit_loop_exit_defers(idx, body);
+                let target = &mut self.loop_stack[idx];
+                target.break_used = true;
+                body.push_str(&format!("goto {};\n", target.break_label));
+            }
+            None => body.push_str("break;\n"),
+        }
         Ok(())
     }
 
-    pub fn codegen_continue(&self, body: &mut String) -> Result<(), ()> {
-        body.push_str("continue;\n");
+    pub fn codegen_continue(&mut self, label: &Option<String>, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        match self.loop_target(label, "continue", loc)? {
+            Some(idx) => {
+                self.emit_loop_exit_defers(idx, body);
+                let target = &mut self.loop_stack[idx];
+                target.continue_used = true;
+                body.push_str(&format!("goto {};\n", target.continue_label));
+            }
+            None => body.push_str("continue;\n"),
+        }
         Ok(())
     }
 
-    pub fn codegen_match(&mut self, expr: &Expr, cases: &[MatchCase], default: &Option<Vec<Stmt>>, body: &mut String) -> Result<(), ()> {
-        let (match_var, _match_ty) = self.codegen_expr(expr, body)?;
+    /// Runs the defers of the `scope:` blocks a jump to loop `idx` leaves.
+    fn emit_loop_exit_defers(&mut self, idx: usize, body: &mut String) {
+        let depth = self.loop_stack[idx].defer_depth.min(self.defer_stack.len());
+        let frames = self.defer_stack[depth..].to_vec();
+        self.emit_deferred(&frames, body);
+    }
+
+    fn loop_target(&mut self, label: &Option<String>, keyword: &str, loc: SourceLocation) -> Result<Option<usize>, ()> {
+        let Some(name) = label else {
+            return Ok(self.loop_stack.len().checked_sub(1));
+        };
+
+        if let Some(idx) = self.loop_stack.iter().rposition(|l| l.name.as_deref() == Some(name.as_str())) {
+            return Ok(Some(idx));
+        }
+
+        let known: Vec<String> = self.loop_stack.iter().filter_map(|l| l.name.clone()).collect();
+        self.diagnostics.error(
+            "UnknownLoopLabel",
+            &format!("'{} {}' does not refer to an enclosing loop", keyword, name),
+            ErrorContext {
+                primary_location: loc,
+                secondary_locations: vec![],
+                help_message: Some("Labels are declared as 'name: while ...' or 'name: for ...'.".to_string()),
+                suggestions: known.iter().map(|l| format!("Did you mean '{} {}'?", keyword, l)).collect(),
+            }
+        );
+        Err(())
+    }
+
+    pub fn push_loop(&mut self, break_label: String, continue_label: String) {
+        let name = self.pending_loop_label.take();
+        self.loop_stack.push(LoopLabels {
+            name,
+            break_label,
+            continue_label,
+            break_used: false,
+            continue_used: false,
+            outer_owned: self.owned_vars.clone(),
+            defer_depth: self.defer_stack.len(),
+        });
+    }
+
+    pub fn codegen_match(&mut self, expr: &Expr, cases: &[MatchCase], default: &Option<Vec<Stmt>>, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let (match_var, match_ty) = self.codegen_expr(expr, body)?;
+
+        if let Some(variants) = self.match_variants(&match_ty) {
+            let patterns: Option<Vec<(String, Vec<String>)>> = cases
+                .iter()
+                .map(|case| match &case.value {
+                    Expr::EnumPattern(name, bindings) => Some((name.clone(), bindings.clone())),
+                    Expr::Var(name) if variants.iter().any(|v| &v.0 == name) => Some((name.clone(), vec![])),
+                    _ => None,
+                })
+                .collect();
+
+            if let Some(patterns) = patterns {
+                return self.codegen_match_switch(&match_var, &variants, &patterns, cases, default, body, loc);
+            }
+        }
 
         let end_label = self.fresh_label();
 
+        let mut join = self.begin_branches();
+        let mut arms = Vec::new();
         for case in cases {
-            let (case_val, _) = self.codegen_expr(&case.value, body)?;
+            self.enter_branch(&join);
+            let mut arm = String::new();
+            let (case_val, _) = self.codegen_expr(&case.value, &mut arm)?;
             
             let _case_label = self.fresh_label();
-            body.push_str(&format!("if ({} == {}) {{\n", match_var, case_val));
+            arm.push_str(&format!("if ({} == {}) {{\n", match_var, case_val));
 
             for stmt in &case.body {
-                self.codegen_stmt(stmt, body).ok();
+                self.codegen_stmt(stmt, &mut arm).ok();
             }
+            self.leave_branch(&mut join, &case.body);
+            arms.push(arm);
+        }
+
+        self.enter_branch(&join);
+        let default_body = default.as_deref().unwrap_or_default();
+        let mut default_code = String::new();
+        for stmt in default_body {
+            self.codegen_stmt(stmt, &mut default_code).ok();
+        }
+        self.leave_branch(&mut join, default_body);
 
-            body.push_str(&format!("goto {};\n}}\n", end_label));
+        let frees = self.join_branches(join);
+        for (arm, free) in arms.iter().zip(&frees) {
+            body.push_str(&format!("{}{}goto {};\n}}\n", arm, free, end_label));
+        }
+        body.push_str(&default_code);
+        body.push_str(&frees[arms.len()]);
+        body.push_str(&format!("{}: ;\n", end_label));
+
+        Ok(())
+    }
+
+    /// Variants of a tagged type as (name, C tag value, payload accessors with their types).
+    #[allow(clippy::type_complexity)]
+    pub fn match_variants(&self, ty: &Type) -> Option<Vec<(String, String, Vec<(String, Type)>)>> {
+        match ty {
+            Type::Option { inner } => Some(vec![
+                ("Some".to_string(), "1".to_string(), vec![(".value".to_string(), *inner.clone())]),
+                ("None".to_string(), "0".to_string(), vec![]),
+            ]),
+            Type::Result { ok, err } => Some(vec![
+                ("Ok".to_string(), "0".to_string(), vec![(".data.ok".to_string(), *ok.clone())]),
+                ("Err".to_string(), "1".to_string(), vec![(".data.err".to_string(), *err.clone())]),
+            ]),
+            Type::Struct { name } => {
+                let enum_def = self.type_registry.enum_definitions.get(name)?;
+                Some(enum_def.variants.iter().map(|(variant, payload)| {
+                    let accessors = match payload {
+                        None => vec![],
+                        Some(Type::Tuple { fields }) if fields.len() == 1 => {
+                            vec![(format!(".data.{}", variant), fields[0].clone())]
+                        }
+                        Some(Type::Tuple { fields }) => fields
+                            .iter()
+                            .enumerate()
+                            .map(|(i, f)| (format!(".data.{}.field_{}", variant, i), f.clone()))
+                            .collect(),
+                        Some(Type::Struct { name: payload_name }) => self
+                            .structs
+                            .get(payload_name)
+                            .map(|info| info.fields.iter().map(|(f, t, _)| (format!(".data.{}.{}", variant, f), t.clone())).collect())
+                            .unwrap_or_default(),
+                        Some(other) => vec![(format!(".data.{}", variant), other.clone())],
+                    };
+                    (variant.clone(), format!("{}__{}", name, variant), accessors)
+                }).collect())
+            }
+            _ => None,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn codegen_match_switch(
+        &mut self,
+        match_var: &str,
+        variants: &[(String, String, Vec<(String, Type)>)],
+        patterns: &[(String, Vec<String>)],
+        cases: &[MatchCase],
+        default: &Option<Vec<Stmt>>,
+        body: &mut String,
+        loc: SourceLocation,
+    ) -> Result<(), ()> {
+        if default.is_none() {
+            let missing: Vec<&str> = variants
+                .iter()
+                .filter(|(name, _, _)| !patterns.iter().any(|(covered, _)| covered == name))
+                .map(|(name, _, _)| name.as_str())
+                .collect();
+
+            if !missing.is_empty() {
+                self.diagnostics.error(
+                    "NonExhaustiveMatch",
+                    &format!("Match does not cover variant(s): {}", missing.join(", ")),
+                    ErrorContext {
+                        primary_location: loc.clone(),
+                        secondary_locations: vec![],
+                        help_message: Some("Every variant must be handled, or a 'default:' arm added.".to_string()),
+                        suggestions: missing.iter().map(|name| format!("Add 'case {}:'", name)).collect(),
+                    }
+                );
+                return Err(());
+            }
+        }
+
+        let mut join = self.begin_branches();
+        let mut arms = Vec::new();
+        for ((variant_name, bindings), case) in patterns.iter().zip(cases) {
+            self.enter_branch(&join);
+            let Some((_, tag, payload)) = variants.iter().find(|(name, _, _)| name == variant_name) else {
+                self.diagnostics.error(
+                    "UnknownVariant",
+                    &format!("'{}' is not a variant of the matched value", variant_name),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some(format!(
+                            "Available variants: {}",
+                            variants.iter().map(|(name, _, _)| name.as_str()).collect::<Vec<_>>().join(", ")
+                        )),
+                        suggestions: vec![],
+                    }
+                );
+                return Err(());
+            };
+
+            if !bindings.is_empty() && bindings.len() != payload.len() {
+                self.diagnostics.error(
+                    "PatternArity",
+                    &format!("Pattern '{}' binds {} value(s), but the variant carries {}", variant_name, bindings.len(), payload.len()),
+                    ErrorContext {
+                        primary_location: loc,
+                        secondary_locations: vec![],
+                        help_message: Some("Bind every payload value, or write the variant name alone to ignore them.".to_string()),
+                        suggestions: vec![],
+                    }
+                );
+                return Err(());
+            }
+
+            let mut arm = format!("case {}: {{\n", tag);
+            let mut shadowed = Vec::new();
+            for (binding, (accessor, ty)) in bindings.iter().zip(payload) {
+                if binding == "_" {
+                    continue;
+                }
+                let c_name = format!("var_{}", binding);
+                let decl = ty.c_declarator(&c_name, &self.arch, &mut self.type_registry);
+                arm.push_str(&format!("{} = {}{};\n", decl, match_var, accessor));
+                shadowed.push((binding.clone(), self.vars.insert(binding.clone(), (c_name, ty.clone()))));
+            }
+
+            for stmt in &case.body {
+                self.codegen_stmt(stmt, &mut arm).ok();
+            }
+            self.leave_branch(&mut join, &case.body);
+
+            for (binding, prev) in shadowed.into_iter().rev() {
+                match prev {
+                    Some(prev) => { self.vars.insert(binding, prev); }
+                    None => { self.vars.remove(&binding); }
+                }
+            }
+            arms.push(arm);
         }
 
         if let Some(default_body) = default {
+            self.enter_branch(&join);
+            let mut arm = "default: {\n".to_string();
             for stmt in default_body {
-                self.codegen_stmt(stmt, body).ok();
+                self.codegen_stmt(stmt, &mut arm).ok();
             }
+            self.leave_branch(&mut join, default_body);
+            arms.push(arm);
         }
 
-        body.push_str(&format!("{}:\n", end_label));
-
+        let frees = self.join_branches(join);
+        body.push_str(&format!("switch ({}.tag) {{\n", match_var));
+        for (arm, free) in arms.iter().zip(&frees) {
+            body.push_str(&format!("{}{}break;\n}}\n", arm, free));
+        }
+        body.push_str("}\n");
         Ok(())
     }
 
@@ -135,9 +769,30 @@ impl Codegen {
 
 
  
-    pub fn codegen_return(&mut self, expr: &Option<Expr>, body: &mut String) -> Result<(), ()> {
+    pub fn codegen_return(&mut self, expr: &Option<Expr>, body: &mut String, loc: SourceLocation) -> Result<(), ()> {
+        let frames = self.defer_stack.clone();
         if let Some(e) = expr {
-            let (var, ty) = self.codegen_expr(e, body)?;
+            let (mut var, mut ty) = match self.current_return_type.clone() {
+                Some(return_ty) => self.codegen_expr_expecting(e, &return_ty, body, loc)?,
+                None => self.codegen_expr(e, body)?,
+            };
+            // Returning leaves every enclosing loop, so this move happens once.
+            if let Expr::Var(name) = e
+                && self.owned_vars.contains(name) {
+                    self.moved_vars.insert(name.clone());
+                }
+            if let (Some(Type::Struct { name }), Type::Ref(inner) | Type::MutRef(inner)) = (&self.current_return_type, &ty)
+                && matches!(inner.as_ref(), Type::Struct { name: inner_name } if inner_name == name) {
+                    var = format!("*{}", var);
+                    ty = inner.as_ref().clone();
+                }
+            if !matches!(ty, Type::Void) && frames.iter().any(|f| !f.is_empty()) {
+                let tmp = self.fresh_var();
+                let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
+                body.push_str(&format!("{} {} = {};\n", c_type, tmp, var));
+                var = tmp;
+            }
+            self.emit_deferred(&frames, body);
             
             if !matches!(ty, Type::Void) {
                 body.push_str(&format!("return {};\n", var));
@@ -145,6 +800,7 @@ impl Codegen {
                 body.push_str("return;\n");
             }
         } else {
+            self.emit_deferred(&frames, body);
             body.push_str("return;\n");
         }
         Ok(())
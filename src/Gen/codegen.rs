@@ -1,4 +1,5 @@
 use crate::import::*;
+use crate::Gen::API::irgen::C_LINE_MARKER;
 
 pub trait ErrorCheck<T> {
     fn check_error(self) -> T;
@@ -68,6 +69,8 @@ impl Codegen {
                 arch: arch.clone(),
                 optimization_level: OptimizationLevel::default(),
                 debug_info: false,
+                bounds_checks: false,
+                dry_run: false,
             },
             type_registry: TypeRegistry::new(),
             impl_methods: HashMap::new(),
@@ -77,17 +80,22 @@ impl Codegen {
             label_count: 0,
             vars: HashMap::new(),
             owned_vars: HashSet::new(),
+            moved_vars: HashSet::new(),
+            immutable_vars: HashSet::new(),
             extern_functions: HashMap::new(),
             extern_block: HashMap::new(),
             structs: HashMap::new(),
             module_vars: HashMap::new(),
             module_functions: HashMap::new(),
+            module_aliases: HashMap::new(),
             compilation_mode: CompilationMode::default(),
             exported_functions: Vec::new(),
             externs_bodies: HashSet::new(),
             unsafe_depth: 0,
             scope_depth: 0,
             user_functions: HashMap::new(),
+            param_modifiers: HashMap::new(),
+            param_defaults: HashMap::new(),
             linked_libraries: Vec::new(),
             ir: IR::new(),
             arch,
@@ -98,6 +106,22 @@ impl Codegen {
             module_init_functions: Vec::new(),
             current_return_type: None,
             import_function_map: HashMap::new(),
+            emit_line_directives: true,
+            loop_stack: Vec::new(),
+            pending_loop_label: None,
+            defer_stack: Vec::new(),
+            global_consts: HashMap::new(),
+            progress_log: None,
+            current_line: 0,
+            c_file: Clang::MAIN_C_SOURCE.to_string(),
+        }
+    }
+
+    /// Prints a progress line, or buffers it in `progress_log` when one is set.
+    pub fn progress(&mut self, line: String) {
+        match &mut self.progress_log {
+            Some(log) => log.push(line),
+            None => println!("{}", line),
         }
     }
 
@@ -140,6 +164,12 @@ impl Codegen {
         Type::Ptr(inner) | Type::RawPtr(inner) | Type::Owned(inner) | Type::Ref(inner) | Type::MutRef(inner) | Type::Const(inner) => {
             self.ensure_type_defined(inner);
         }
+        Type::FunctionPointer { params, ret } => {
+            for param in params {
+                self.ensure_type_defined(param);
+            }
+            self.ensure_type_defined(ret);
+        }
         _ => {}
     }
 
@@ -183,6 +213,35 @@ impl Codegen {
         }
     }
 
+    /// Location of a whole source line, for nodes that only record their line.
+    pub fn line_location(&self, line: usize) -> SourceLocation {
+        SourceLocation {
+            file: self.current_file.clone(),
+            line,
+            column: 1,
+            length: 1,
+        }
+    }
+
+    /// Location of the statement being generated.
+    pub fn stmt_location(&self) -> SourceLocation {
+        self.line_location(self.current_line)
+    }
+
+    /// Follows a generated C function whose body carries `#line`s with one that points back
+    /// into the C file, so the runtime helpers and `main` after it are not blamed on the source.
+    pub fn reset_line_directive(&self, code: &mut String) {
+        if self.emit_line_directives && code.contains("#line ") {
+            code.push_str(&format!("#line {} \"{}\"\n", C_LINE_MARKER, self.c_file.replace('\\', "/")));
+        }
+    }
+
+    /// Line the parser recorded for `stmt`. Statements built during codegen, such as the
+    /// frees of owned values, have none and take the line of the statement being generated.
+    pub fn stmt_line(&self, stmt: &Stmt) -> usize {
+        stmt.line().unwrap_or(self.current_line)
+    }
+
     pub fn fresh_label(&mut self) -> String {
         let label = format!("label_{}", self.label_count);
         self.label_count += 1;
@@ -190,7 +249,15 @@ impl Codegen {
     }
 
     pub fn codegen_stmt(&mut self, stmt: &Stmt, body: &mut String) -> Result<(), ()> {
-        let loc = self.default_location();
+        let line = self.stmt_line(stmt);
+        let outer_line = std::mem::replace(&mut self.current_line, line);
+        let result = self.codegen_stmt_kind(stmt.node(), body);
+        self.current_line = outer_line;
+        result
+    }
+
+    fn codegen_stmt_kind(&mut self, stmt: &Stmt, body: &mut String) -> Result<(), ()> {
+        let loc = self.stmt_location();
         
         match stmt {
             Stmt::TypedDeclaration { name, ty, value, is_mutable, .. } => {
@@ -198,18 +265,30 @@ impl Codegen {
             }
             Stmt::Assign(name, value) => self.codegen_assign(name, value, body, loc),
             Stmt::TupleUnpack { names, value } => self.codegen_tuple_unpack(names, value, body, loc).map_err(|_| ()),
-            Stmt::Match(expr, cases, default) => {self.codegen_match(expr, cases, default, body);  Ok(()) }
+            Stmt::Match(expr, cases, default) => self.codegen_match(expr, cases, default, body, loc),
             Stmt::CompoundAssign(name, op, value) => self.codegen_compound_assign(name, op, value, body, loc),
             Stmt::IndexAssign(arr, indices, value) => self.codegen_index_assign(arr, indices, value, body, loc),
+            Stmt::IndexCompoundAssign(arr, indices, op, value) => self.codegen_index_compound_assign(arr, indices, op, value, body, loc),
             Stmt::MemberAssign(obj, field, value) => self.codegen_member_assign(obj, field, value, body, loc),
+            Stmt::DerefAssign(ptr, value) => self.codegen_deref_assign(ptr, value, body, loc),
             Stmt::If(cond, then_body, else_body) => self.codegen_if(cond, then_body, else_body, body),
-            Stmt::IfLet { pattern, value, then_block, else_block } => self.codegen_if_let(pattern, value, then_block, else_block, body).map_err(|_| ()),
+            Stmt::IfLet { pattern, value, then_block, else_block } => self.codegen_if_let(pattern, value, then_block, else_block, body, loc).map_err(|_| ()),
             Stmt::While(cond, loop_body) => self.codegen_while(cond, loop_body, body, loc),
+            Stmt::Loop(loop_body) => self.codegen_loop(loop_body, body, loc),
             Stmt::For(var, iter, loop_body) => self.codegen_for(var, iter, loop_body, body, loc),
-            Stmt::Return(expr) => self.codegen_return(expr, body).map_err(|_| ()),
+            Stmt::Return(expr) => self.codegen_return(expr, body, loc).map_err(|_| ()),
             Stmt::Call(func, args) => self.codegen_call_stmt(func, args, body, loc),
-            Stmt::Break => self.codegen_break(body).map_err(|_| ()),
-            Stmt::Continue => self.codegen_continue(body).map_err(|_| ()),
+            Stmt::ModuleCall(path, func, args) => self.codegen_module_call(path, func, args, body, loc).map(|_| ()),
+            Stmt::Break(label) => self.codegen_break(label, body, loc),
+            Stmt::Continue(label) => self.codegen_continue(label, body, loc),
+            Stmt::Defer(deferred) => self.codegen_defer(deferred, loc),
+            Stmt::Assert(cond, message) => self.codegen_assert(cond, message.as_ref(), body, loc),
+            Stmt::Labeled(label, inner) => {
+                self.pending_loop_label = Some(label.clone());
+                let result = self.codegen_stmt(inner, body);
+                self.pending_loop_label = None;
+                result
+            }
             Stmt::Scope(stmts) => self.codegen_scope(stmts, body),
             Stmt::StructDef(s) => self.codegen_struct_definition(s),
             Stmt::EnumDef(e) => self.codegen_enum_definition(e),
@@ -261,19 +340,27 @@ impl Codegen {
     }
 
     pub fn codegen_expr(&mut self, expr: &Expr, body: &mut String) -> Result<(String, Type), ()> {
-        let loc = self.default_location();
+        let loc = self.stmt_location();
         match expr {
             Expr::Number(n) => Ok(self.codegen_number(*n, body)),
+            Expr::TypedNumber(n, ty) => self.codegen_typed_number(*n, ty, body, loc),
             Expr::Float(f) => Ok(self.codegen_float(*f, body)),
             Expr::Bool(b) => Ok(self.codegen_bool(*b, body)),
             Expr::Char(c) => Ok(self.codegen_char(*c, body)),
+            Expr::ByteChar(b) => Ok(self.codegen_byte_char(*b, body)),
             Expr::HexNumber(n) => Ok(self.codegen_hex_number(*n, body)),
             Expr::BinaryNumber(n) => Ok(self.codegen_binary_number(*n, body)),
             Expr::OctalNumber(n) => Ok(self.codegen_octal_number(*n, body)),
             Expr::String(s) => Ok(self.codegen_string(s, body)),
             Expr::Var(name) => self.codegen_var(name, loc),
+            Expr::Call(name, args) if args.is_empty() && self.global_consts.contains_key(name) => self.codegen_var(name, loc),
             Expr::BinOp(op, left, right) => self.codegen_binop(op, left, right, body, loc),
             Expr::UnOp(op, operand) => self.codegen_unop(op, operand, body, loc),
+            Expr::IfExpr(cond, then_expr, else_expr) => self.codegen_if_expr(cond, then_expr, else_expr, body, loc),
+            Expr::FuncAddr(name) => self.codegen_func_addr(name, body, loc),
+            Expr::AddressOf(operand) => self.codegen_address_of(operand, body, loc),
+            Expr::Deref(ptr) => self.codegen_deref(ptr, body, loc),
+            Expr::StructLiteral(name, fields) => self.codegen_struct_literal(name, fields, body, loc),
             Expr::Call(name, args) if self.structs.contains_key(name) => {
                 let constructor_name = format!("{}_new", name);
                 
@@ -290,20 +377,17 @@ impl Codegen {
                 Ok((tmp, Type::Struct { name: name.clone() }))
             }
             Expr::Call(func, args) => {
-                eprintln!("[DEBUG] codegen.rs Expr::Call: {}", func);
+                debug_log!("[DEBUG] codegen.rs Expr::Call: {}", func);
                 self.codegen_call_expr(func, args, body, loc)
             },
-            Expr::MemberAccess(obj, field) => self.codegen_member_access(obj, field, body),
+            Expr::MemberAccess(obj, field) => self.codegen_member_access(obj, field, body, loc),
             Expr::Not(expr) => self.codegen_not(expr, body).map_err(|_| ()),
             Expr::Tuple(elements) => self.codegen_tuple(elements, body),
             Expr::MethodCall(obj, method, args) => self.codegen_method_call(obj, method, args, body, loc),
             Expr::Cast(expr, target) => self.codegen_cast_target(expr, target, body, loc),
             Expr::StaticMethodCall(type_name, method, args) => {self.codegen_static_method(type_name, method, args, body, loc)}
-            Expr::ModuleCall(module, func, args) => {
-                self.codegen_module_call(module, func, args, body, loc).ok();
-
-                Err(())
-            }
+            Expr::ModuleCall(module, func, args) => self.codegen_module_call(module, func, args, body, loc),
+            Expr::ModuleAccess(path, member) => self.codegen_module_access(path, member, loc),
 
             Expr::CallNamed(name, named_args) => {
                 if self.structs.contains_key(name) {
@@ -321,100 +405,40 @@ impl Codegen {
                     body.push_str(&format!("{} {} = {}({});\n", name, tmp, constructor_name, args_str));
                     return Ok((tmp, Type::Struct { name: name.clone() }));
                 }
-                
-                
-                let mut arg_vars = Vec::new();
-                for (_arg_name, arg_expr) in named_args {
-                    let (var, _ty) = self.codegen_expr(arg_expr, body)?;
-                    arg_vars.push(var);
-                }
-                
-                let tmp = self.fresh_var();
-                let args_str = arg_vars.join(", ");
-                body.push_str(&format!("int32_t {} = {}({});\n", tmp, name, args_str));
-                Ok((tmp, Type::i32()))
-            }
 
-            Expr::Slice(arr, start, end) => {
-                let (arr_var, arr_ty) = self.codegen_expr(arr, body)?;
-                let (start_var, _) = self.codegen_expr(start, body)?;
-                let (end_var, _) = self.codegen_expr(end, body)?;
-                
-                let elem_ty = match &arr_ty {
-                    Type::Array { element, .. } => *element.clone(),
-                    _ => Type::Void,
-                };
-                
-                let tmp = self.fresh_var();
-                let slice_type = self.type_registry.generate_slices(&elem_ty, &self.arch);
-                
-                body.push_str(&format!("{} {} = {{ .ptr = &{}.ptr[{}], .len = {} - {} }};\n", 
-                    slice_type, tmp, arr_var, start_var, end_var, start_var));
-                
-                Ok((tmp, Type::Array { element: Box::new(elem_ty), size: None }))
+                self.codegen_call_named(name, named_args, body, loc)
             }
 
-            Expr::HashMap(entries) => {
-                if entries.is_empty() {
-                    return Err(());
-                }
-                
-                 
-                let (first_key, first_val) = &entries[0];
-                let (_, key_ty) = self.codegen_expr(first_key, body)?;
-                let (_, val_ty) = self.codegen_expr(first_val, body)?;
-                
-                 
-                let hashmap_ty = Type::HashMap { 
-                    key: Box::new(key_ty.clone()), 
-                    value: Box::new(val_ty.clone()) 
-                };
-                
-                if let Some(def) = self.type_registry.generate_hashmap_definition(&key_ty, &val_ty, &self.arch)
-                    && !self.ir.forward_decls.contains(&def) {
-                        self.ir.forward_decls.push_str(&def);
-                        self.ir.forward_decls.push('\n');
-                    }
-                
-                let hashmap_name = format!("HashMap_{}_{}", 
-                    key_ty.name().replace(" ", "_"), 
-                    val_ty.name().replace(" ", "_"));
-                
-                let tmp = self.fresh_var();
-                
-                 
-                body.push_str(&format!("{} {} = {{0}};\n", hashmap_name, tmp));
-                body.push_str(&format!("{}.size = 0;\n", tmp));
-                body.push_str(&format!("for (int i = 0; i < 256; i++) {{ {}.entries[i].occupied = false; }}\n", tmp));
-                
-                 
-                for (key_expr, val_expr) in entries {
-                    let (key_var, _) = self.codegen_expr(key_expr, body)?;
-                    let (val_var, _) = self.codegen_expr(val_expr, body)?;
-                    
-                    body.push_str(&format!("insert_{}(&{}, {}, {});\n", 
-                        hashmap_name, tmp, key_var, val_var));
-                }
-                
-                Ok((tmp, hashmap_ty))
-            }
+            Expr::Slice(arr, start, end) => self.codegen_slice(arr, start, end, body, loc),
+
+            Expr::HashMap(entries) => self.codegen_hashmap_literal(entries, body, loc),
+            Expr::Array(elements) => self.codegen_array_literal(elements, body, loc),
+            Expr::Index(arr, indices) => self.codegen_index(arr, indices, body, loc),
 
             Expr::SizeOf(ty) => {
                 let tmp = self.fresh_var();
                 let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
                 body.push_str(&format!("size_t {} = sizeof({});\n", tmp, c_type));
-                Ok((tmp, Type::i64()))
+                Ok((tmp, Type::Usize))
             }
             Expr::AlignOf(ty) => {
                 let tmp = self.fresh_var();
                 let c_type = ty.to_c_type(&self.arch, &mut self.type_registry);
                 body.push_str(&format!("size_t {} = _Alignof({});\n", tmp, c_type));
-                Ok((tmp, Type::i64()))
+                Ok((tmp, Type::Usize))
             }
+            Expr::OffsetOf { struct_type, field } => self.codegen_offsetof(struct_type, field, body, loc),
+            Expr::VaNext(ty) => self.codegen_va_next(ty, body, loc),
             Expr::TypeOf(expr) => {
-                let (var, ty) = self.codegen_expr(expr, body)?;
-                Ok((var, ty))
+                // Only the type is needed; the operand's code is dropped so it never runs.
+                let (_, ty) = self.codegen_expr(expr, &mut String::new())?;
+                let tmp = self.fresh_var();
+                body.push_str(&format!("const char* {} = \"{}\";\n", tmp, ty.name()));
+                Ok((tmp, Type::ConstStr))
             }
+            Expr::Plan(format_str, args) => self.codegen_plan(format_str, args, body, loc),
+            Expr::Some(_) | Expr::None | Expr::ResultOk(_) | Expr::ResultErr(_) => self.codegen_variant_without_context(expr, body, loc),
+            Expr::Try(inner) => self.codegen_try(inner, body, loc),
             Expr::Pipe(left, right) => {
                 let _ = self.codegen_expr(left, body)?;
                 match right.as_ref() {
@@ -453,44 +477,45 @@ pub fn codegen_library(
     structs: &[StructDef],
     enums: &[EnumDef],
     impls: &[ImplBlock],
-    externs: &[ExternDecl],
+    _externs: &[ExternDecl],
     library_includes: &[String],
 ) -> Result<String, String> {
-    println!("   {} Starting library codegen...", "success:".bright_cyan());
+    self.progress(format!("   {} Starting library codegen...", "success:".bright_cyan()));
     
      
     for include in library_includes {
-        self.ir.headers.push_str(include);
-        self.ir.headers.push('\n');
+        self.ir.add_include(include);
     }
     
-    println!("   {} Generating struct definitions...", "success:".bright_black());
+    self.progress(format!("   {} Generating struct definitions...", "success:".bright_black()));
     
      
     for struct_def in structs {
         if self.codegen_struct_definition(struct_def).is_err() {
-            eprintln!("   {} Failed to generate struct: {}", "Error:".red(), struct_def.name);
+            self.progress(format!("   {} Failed to generate struct: {}", "Error:".red(), struct_def.name));
         }
     }
 
-    println!("   {} Generating enum definitions...", "success:".bright_black());
+    self.progress(format!("   {} Generating enum definitions...", "success:".bright_black()));
     for enum_def in enums {
         if self.codegen_enum_definition(enum_def).is_err() {
-            eprintln!("   {} Failed to generate enum: {}", "Error:".red(), enum_def.name);
+            self.progress(format!("   {} Failed to generate enum: {}", "Error:".red(), enum_def.name));
         }
     }
 
-    println!("   {} Processing {} modules...", "success:".bright_black(), program.modules.len());
+    self.codegen_global_consts(&program.constants);
+
+    self.progress(format!("   {} Processing {} modules...", "success:".bright_black(), program.modules.len()));
      
     for module in &program.modules {
         if let Stmt::ModuleDef { name, .. } = module {
-            println!("      {} Processing module: {}", "success:".bright_black(), name);
+            self.progress(format!("      {} Processing module: {}", "success:".bright_black(), name));
         }
 
         self.codegen_module(module);
     }
 
-    println!("   {} Ensuring type definitions...", "success:".bright_black());
+    self.progress(format!("   {} Ensuring type definitions...", "success:".bright_black()));
      
     for func in &program.functions {
         self.ensure_type_defined(&func.return_type);
@@ -508,7 +533,7 @@ pub fn codegen_library(
         }
     }
 
-    println!("   {} Generating {} function declarations...", "success:".bright_black(), program.functions.len());
+    self.progress(format!("   {} Generating {} function declarations...", "success:".bright_black(), program.functions.len()));
      
     for func in &program.functions {
         self.codegen_function(func, true);
@@ -518,37 +543,38 @@ pub fn codegen_library(
     }
 
 
-    println!("   {} Generating {} function implementations...", "success:".bright_black(), program.functions.len());
+    self.progress(format!("   {} Generating {} function implementations...", "success:".bright_black(), program.functions.len()));
      
     for func in &program.functions {
-        println!("      {} Generating: {}", "success:".bright_black(), func.name);
+        self.progress(format!("      {} Generating: {}", "success:".bright_black(), func.name));
         self.codegen_function(func, false);
     }
 
     for impl_block in impls {
         if self.codegen_impl_block(impl_block, false).is_err() {
-            eprintln!("   {} Failed to generate impl block", "Error:".red());
+            self.progress(format!("   {} Failed to generate impl block", "Error:".red()));
         }
     }
     
     if self.diagnostics.warning_count > 0 {
-        println!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count);
+        self.progress(format!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count));
     }
     
-    println!("Hello, 'i'm confused!");
-    println!("   {} Finalizing library code...", "success:".bright_black());
+    debug_log!("Hello, 'i'm confused!");
+    self.progress(format!("   {} Finalizing library code...", "success:".bright_black()));
     
     for def in &self.type_registry.ordered_definitions {
-        println!("Setting everything fortworddd!");
+        debug_log!("Setting everything fortworddd!");
         self.ir.add_type_definition(def.clone());
     }
-    println!("   {} Library codegen complete!", "success:".green());
+    self.progress(format!("   {} Library codegen complete!", "success:".green()));
     
      
      
     Ok(self.ir.clone().finalize_library())
 }
 
+    #[allow(clippy::too_many_arguments)]
      pub fn codegen_program_full(
         &mut self, 
         program: &Program,
@@ -561,15 +587,14 @@ pub fn codegen_library(
     ) -> Result<String, String> {
          
         for include in library_includes {
-            self.ir.headers.push_str(include);
-            self.ir.headers.push('\n');
+            self.ir.add_include(include);
         }
         
-        println!("   {} Processing {} library function signatures...", "success:".bright_cyan(), library_functions.len());
+        self.progress(format!("   {} Processing {} library function signatures...", "success:".bright_cyan(), library_functions.len()));
         
          
         for func_sig in library_functions {
-            println!("      {} Registering library function: {}", "success:".bright_black(), func_sig.name);
+            self.progress(format!("      {} Registering library function: {}", "success:".bright_black(), func_sig.name));
             
             let param_str = if func_sig.parameters.is_empty() {
                 "void".to_string()
@@ -605,12 +630,12 @@ pub fn codegen_library(
         
         self.type_registry.generate_slices(&Type::char8(), &self.arch);
         
-        println!("   {} Generating struct definitions...", "processing:".bright_black());
+        self.progress(format!("   {} Generating struct definitions...", "processing:".bright_black()));
          
 
         self.codegen_externs(externs).ok();
         for def in &self.type_registry.ordered_definitions {
-            println!("Setting everything fortworddd!");
+            debug_log!("Setting everything fortworddd!");
             self.ir.add_type_definition(def.clone());
         }
         
@@ -622,12 +647,12 @@ pub fn codegen_library(
             self.codegen_enum_definition(enum_def).ok();
         }
 
-        println!("   {} Processing modules...", "processing:".bright_black());
-        for module in &program.modules {
-            self.codegen_module(module);
-        }
+        self.codegen_global_consts(&program.constants);
+
+        self.progress(format!("   {} Processing modules...", "processing:".bright_black()));
+        self.codegen_modules(&program.modules);
 
-        println!("   {} Pre-processing type definitions...", "processing:".bright_black());
+        self.progress(format!("   {} Pre-processing type definitions...", "processing:".bright_black()));
         for func in &program.functions {
             self.ensure_type_defined(&func.return_type);
             for (_, param_ty, _) in &func.params {
@@ -653,30 +678,28 @@ pub fn codegen_library(
 
         self.codegen_externs(externs).ok();
 
-        println!("   {} Generating function code...", "processing:".bright_black());
+        self.progress(format!("   {} Generating function code...", "processing:".bright_black()));
         for func in &program.functions {
             self.codegen_function(func, false);
         }
+        self.codegen_extern_bodies(externs);
 
-        println!("   {} Generating impl block code...", "processing:".bright_black());
+        self.progress(format!("   {} Generating impl block code...", "processing:".bright_black()));
         for impl_block in impls {
             if self.codegen_impl_block(impl_block, false).is_err() {
             }
         }
         
         if self.diagnostics.has_errors() {
-            println!();
+            self.progress(String::new());
             self.diagnostics.print_summary();
-            println!("Code generation failed due to errors");
+            self.progress("Code generation failed due to errors".to_string());
         }
         
         if self.diagnostics.warning_count > 0 {
-            println!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count);
+            self.progress(format!("   {} {} warning(s) generated", "Warning:".yellow(), self.diagnostics.warning_count));
         }
 
-        for init_func in &self.module_init_functions {
-            self.ir.functions.push_str(&format!("    {}();\n", init_func));
-        }
 
         Ok(self.ir.clone().finalize())
     }
@@ -712,7 +735,7 @@ pub fn codegen_library(
         import_decls: &[ImportDecl],
         library_functions: &[FunctionSignature],
     ) {
-        println!("   {} Building import mappings...", "success:".bright_cyan());
+        self.progress(format!("   {} Building import mappings...", "success:".bright_cyan()));
         
         for decl in import_decls {
             match decl {
@@ -736,17 +759,17 @@ pub fn codegen_library(
                         .map(|sig| sig.name.clone())
                         .unwrap_or_else(|| {
                              
-                            println!("      {} Warning: Function '{}' from '{}' not found in libraries", 
-                                "⚠".yellow(), name, from);
+                            self.progress(format!("      {} Warning: Function '{}' from '{}' not found in libraries", 
+                                "⚠".yellow(), name, from));
                             name.clone()
                         });
                     
                     if actual_name != *name {
-                        println!("      {} Mapping: {} success: {}", 
+                        self.progress(format!("      {} Mapping: {} success: {}", 
                             "success:".green(), 
                             name, 
                             actual_name
-                        );
+                        ));
                     }
                     
                     self.import_function_map.insert(name.clone(), actual_name);
@@ -754,7 +777,7 @@ pub fn codegen_library(
                 
                 ImportDecl::WildcardImport { from } => {
                      
-                    println!("      {} Processing wildcard import from '{}'", "success:".bright_black(), from);
+                    self.progress(format!("      {} Processing wildcard import from '{}'", "success:".bright_black(), from));
                     
                     for sig in library_functions {
                          
@@ -767,7 +790,7 @@ pub fn codegen_library(
                                 short_name.to_string(), 
                                 sig.name.clone()
                             );
-                            println!("         {} {} success: {}", "success:".bright_black(), short_name, sig.name);
+                            self.progress(format!("         {} {} success: {}", "success:".bright_black(), short_name, sig.name));
                         }
                         
                          
@@ -781,18 +804,23 @@ pub fn codegen_library(
                 ImportDecl::LibraryImport { name } => {
                      
                      
-                    println!("      {} Library import: {}", "success:".bright_black(), name);
+                    self.progress(format!("      {} Library import: {}", "success:".bright_black(), name));
                 }
             }
         }
         
-        println!("   {} Import mappings complete ({} mappings)", 
+        self.progress(format!("   {} Import mappings complete ({} mappings)", 
             "success:".green(), 
             self.import_function_map.len()
-        );
+        ));
     }
 
      
+    /// Every error and warning reported so far, whether or not it was echoed.
+    pub fn collect_diagnostics(&self) -> Vec<crate::Gen::API::error::Diagnostic> {
+        self.diagnostics.diagnostics().to_vec()
+    }
+
     pub fn resolve_function_name(&self, name: &str) -> String {
         if let Some(actual_name) = self.import_function_map.get(name) {
             actual_name.clone()
@@ -802,3 +830,2408 @@ pub fn codegen_library(
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generate(source: &str) -> (Codegen, String) {
+        generate_with_optimization(source, OptimizationLevel::default())
+    }
+
+    fn generate_with_optimization(source: &str, optimization: OptimizationLevel) -> (Codegen, String) {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
+
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        codegen.config.optimization_level = optimization;
+        let c_code = codegen
+            .codegen_program_full(&program, &structs, &enums, &impls, &externs, &[], &[])
+            .expect("codegen should produce output");
+        (codegen, c_code)
+    }
+
+    /// The parser's messages for `source`, which `generate` does not look at.
+    fn parse_errors(source: &str) -> Vec<String> {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        parser.parse_program();
+        parser.diags.iter().map(|d| d.message.clone()).collect()
+    }
+
+    /// Compiles `c_code` with the first C compiler found and runs it, returning its stdout,
+    /// or `None` when no compiler is installed.
+    fn run_c(c_code: &str) -> Option<String> {
+        static RUNS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let compiler = ["clang", "gcc"].into_iter().find(|c| Command::new(c).arg("--version").output().is_ok())?;
+        let run = RUNS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("vix-run-{}-{}", std::process::id(), run));
+        fs::create_dir_all(&dir).unwrap();
+        let (c_path, exe) = (dir.join("output.c"), dir.join("output"));
+        fs::write(&c_path, c_code).unwrap();
+
+        let build = Command::new(compiler).arg("-w").arg(&c_path).arg("-o").arg(&exe).output().unwrap();
+        assert!(build.status.success(), "{}\n{}", String::from_utf8_lossy(&build.stderr), c_code);
+        let output = Command::new(&exe).output().unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert!(output.status.success(), "{}\n{}", String::from_utf8_lossy(&output.stderr), c_code);
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    #[test]
+    fn test_function_pointer_declarators() {
+        let source = r#"
+struct Handler:
+    callback = func(int32, int32): int32
+end
+
+func add(a: int32, b: int32): int32
+    return a + b
+end
+
+func apply(f: func(int32, int32): int32, x: int32): int32
+    return f(x, x)
+end
+
+func main()
+    op: func(int32, int32): int32 = &add
+    apply(op, 2)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("int32_t (*callback)(int32_t, int32_t);"), "{}", c_code);
+        assert!(c_code.contains("int32_t apply(int32_t (*var_f)(int32_t, int32_t), int32_t var_x)"), "{}", c_code);
+        assert!(c_code.contains("int32_t (*const var_op)(int32_t, int32_t) = add;"), "{}", c_code);
+        assert!(c_code.contains("= var_f(var_x, var_x);"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_range_for_lowers_to_c_for() {
+        let source = r#"
+func main()
+    mut total: int32 = 0
+    for i in 0..10 do
+        total += i
+    end
+    for j in 1..=3 do
+        total += j
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("for (int32_t var_i = __vix_t1; var_i < __vix_t2; var_i++) {\nvar_total += var_i;\n}"), "{}", c_code);
+        assert!(c_code.contains("var_j <= "), "{}", c_code);
+    }
+
+    #[test]
+    fn test_range_counter_takes_the_wider_bound_type() {
+        let source = r#"
+func count(small: int32, big: int64, n: usize)
+    for i in small..big do
+        print(i)
+    end
+    for k in 0u8..n do
+        print(k)
+    end
+    for m in 0..n do
+        print(m)
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("for (int64_t var_i = (int64_t)var_small; var_i < var_big; var_i++) {"), "{}", c_code);
+        assert!(c_code.contains("for (size_t var_k = "), "{}", c_code);
+        assert!(c_code.contains("for (size_t var_m = "), "{}", c_code);
+
+        let source = "func main()\n    if true\n        for x in 1.5..3 do\n        end\n    end\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "InvalidRange").expect("float bound should be rejected");
+        assert_eq!(error.context.primary_location.line, 3);
+    }
+
+    #[test]
+    fn test_continue_in_range_for_keeps_increment() {
+        let source = r#"
+func main()
+    mut odd: int32 = 0
+    for i in 0..10 do
+        if i % 2 == 0 then
+            continue
+        end
+        odd += i
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("for (int32_t var_i = __vix_t1; var_i < __vix_t2; var_i++) {"), "{}", c_code);
+        assert!(c_code.contains("if (__vix_t6) {\ngoto label_1;\n}\nvar_odd += var_i;\nlabel_1: ;\n}"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_loop_lowers_to_c_for() {
+        let source = r#"
+func main()
+    mut n: int32 = 0
+    loop:
+        n += 1
+        if n > 5 then
+            break
+        end
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("for (;;) {\nint32_t __vix_t1 = 1;\nvar_n += __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("if (__vix_t3) {\ngoto label_0;\n}\n}\nlabel_0: ;"), "{}", c_code);
+        assert!(!codegen.diagnostics.diagnostics().iter().any(|d| d.code == "InfiniteLoop"));
+    }
+
+    #[test]
+    fn test_loop_without_break_warns() {
+        let source = r#"
+func main()
+    mut n: int32 = 0
+    loop:
+        n += 1
+        while n > 5 do
+            break
+        end
+    end
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(codegen.diagnostics.diagnostics().iter().any(|d| d.code == "InfiniteLoop"));
+    }
+
+    #[test]
+    fn test_line_directives_for_function_body() {
+        let source = "func main()\n    mut a: int32 = 1\n\n    a += 2\n    a += 3\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("#line 2 \"test.vix\"\n"), "{}", c_code);
+        assert!(c_code.contains("#line 4 \"test.vix\"\nint32_t __vix_t1 = 2;\nvar_a += __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("#line 5 \"test.vix\"\n"), "{}", c_code);
+
+        let resets: Vec<usize> = c_code.lines().enumerate()
+            .filter(|(_, line)| line.starts_with("#line ") && line.ends_with("\"release/bin/output.c\""))
+            .map(|(i, line)| {
+                assert_eq!(line, format!("#line {} \"release/bin/output.c\"", i + 2), "{}", c_code);
+                i
+            })
+            .collect();
+        assert_eq!(resets.len(), 1, "{}", c_code);
+        let after_main = &c_code[c_code.find("vix_main() {").expect(&c_code)..];
+        assert!(after_main.contains("}\n#line "), "{}", c_code);
+        assert!(!c_code.contains(C_LINE_MARKER), "{}", c_code);
+    }
+
+    #[test]
+    fn test_impl_methods_carry_statement_lines() {
+        let source = r#"
+struct Counter:
+    n = int32
+end
+
+impl Counter:
+    func bump(&mut self)
+        self.n += 1
+        if self.n > 3 then
+            missing = 2
+        end
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(c_code.contains("#line 8 \"test.vix\"\n"), "{}", c_code);
+        assert!(c_code.contains("#line 9 \"test.vix\"\n"), "{}", c_code);
+        let error = codegen.diagnostics.diagnostics().iter()
+            .find(|d| d.code == "UndefinedVariable")
+            .expect("assignment to an undeclared name should be reported");
+        assert_eq!(error.context.primary_location.line, 10);
+    }
+
+    #[test]
+    fn test_struct_to_struct_cast() {
+        let source = r#"
+struct Pair:
+    a = int32
+    b = int32
+end
+
+struct Span:
+    start = uint32
+    len = uint32
+end
+
+struct Wide:
+    a = int64
+    b = int64
+end
+
+func main()
+end
+"#;
+        let (mut codegen, _) = generate(source);
+        codegen.vars.insert("p".to_string(), ("var_p".to_string(), Type::Struct { name: "Pair".to_string() }));
+        let loc = codegen.default_location();
+
+        let mut body = String::new();
+        let (tmp, ty) = codegen
+            .codegen_cast(&Expr::Var("p".to_string()), &Type::Struct { name: "Span".to_string() }, &mut body, loc.clone())
+            .expect("same-size struct cast should succeed");
+        assert_eq!(ty, Type::Struct { name: "Span".to_string() });
+        assert!(body.contains(&format!("Span {};\nmemcpy(&{}, &var_p, sizeof(Span));", tmp, tmp)), "{}", body);
+        assert!(!body.contains("= (Span)"));
+
+        let mut body = String::new();
+        let result = codegen.codegen_cast(&Expr::Var("p".to_string()), &Type::Struct { name: "Wide".to_string() }, &mut body, loc);
+        assert!(result.is_err());
+        assert!(codegen.diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_labeled_break_exits_outer_loop() {
+        let source = r#"
+func main()
+    mut hits: int32 = 0
+    outer: for i in 0..3 do
+        for j in 0..3 do
+            if j == 2 then
+                break outer
+            end
+            hits += 1
+        end
+    end
+    hits += 100
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+
+        let outer_start = c_code.find("for (int32_t var_i").expect("outer loop");
+        let goto_pos = c_code.find("goto label_").expect("labeled break should lower to goto");
+        let label = c_code[goto_pos + 5..].split(';').next().unwrap().to_string();
+        let target_pos = c_code.find(&format!("{}: ;", label)).expect("break target emitted");
+        let after_outer = c_code.rfind("var_hits += ").unwrap();
+
+        assert!(outer_start < goto_pos && goto_pos < target_pos && target_pos < after_outer, "{}", c_code);
+        assert!(c_code[outer_start..target_pos].ends_with("}\n}\n"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_unknown_loop_label_is_reported_at_the_break() {
+        let source = "func main()\n    outer: while true do\n        if true then\n            break outr\n        end\n    end\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownLoopLabel").expect("unknown label should be reported");
+        assert_eq!(error.context.primary_location.line, 4);
+        assert_eq!(error.context.suggestions, vec!["Did you mean 'break outer'?".to_string()]);
+    }
+
+    #[test]
+    fn test_defer_runs_at_every_exit() {
+        let source = r#"
+func release(n: int32)
+end
+
+func work(flag: bool): int32
+    defer release(1)
+    defer release(2)
+    if flag then
+        return 7
+    end
+    scope:
+        defer release(3)
+        release(4)
+    end
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+
+        // early return runs both function-level defers, newest first
+        assert!(c_code.contains("int32_t __vix_t2 = 2;\nrelease(__vix_t2);\nint32_t __vix_t3 = 1;\nrelease(__vix_t3);\nreturn __vix_t1;\n}"), "{}", c_code);
+        // scope block runs its own defer at its natural end
+        assert!(c_code.contains("{\nint32_t __vix_t4 = 4;\nrelease(__vix_t4);\nint32_t __vix_t5 = 3;\nrelease(__vix_t5);\n}"), "{}", c_code);
+        // final return runs the function-level defers again, and nothing after it
+        assert!(c_code.contains("release(__vix_t8);\nint32_t __vix_t9 = 1;\nrelease(__vix_t9);\nreturn __vix_t7;\n}"), "{}", c_code);
+        assert_eq!(c_code.matches("release(__vix_t").count(), 6, "{}", c_code);
+    }
+
+    #[test]
+    fn test_break_and_continue_run_the_defers_of_scopes_they_leave() {
+        let source = r#"
+func main(): int32
+    for i in 0..4 do
+        scope:
+            defer plan("d{}\n", i)
+            if i == 1 then
+                continue
+            end
+            if i == 2 then
+                break
+            end
+            plan("body{}\n", i)
+        end
+    end
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "body0\nd0\nd1\nd2\n");
+        }
+    }
+
+    #[test]
+    fn test_defer_outside_a_function_is_reported_at_the_defer() {
+        let source = "mod settings {\n    create level = 3\n    defer print(level)\n}\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "DeferOutsideScope").expect("module-level defer should be rejected");
+        assert_eq!(error.context.primary_location.line, 3);
+    }
+
+    #[test]
+    fn test_enum_payload_constructors_and_match() {
+        let source = r#"
+enum Shape:
+    Circle(float32)
+    Rect(float32, float32)
+    Named(id: int32)
+    Empty
+end
+
+func kind(s: Shape): int32
+    match s:
+        case Circle:
+            return 1
+        case Rect:
+            return 2
+        default:
+            return 0
+    end
+    return 0
+end
+
+func main()
+    c: Shape = Shape_Circle(2.0)
+    r: Shape = Shape_Rect(1.0, 3.0)
+    n: Shape = Shape_Named(7)
+    e: Shape = Shape_Empty()
+    k: int32 = kind(c)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+
+        assert!(c_code.contains("    union {\n        float Circle;\n        Tuple_float32_float32 Rect;\n        Shape_Named_Payload Named;\n    } data;"), "{}", c_code);
+        assert!(c_code.contains("Shape Shape_Circle(float var_v0) {\n    Shape value;\n    value.tag = Shape__Circle;\n    value.data.Circle = var_v0;"), "{}", c_code);
+        assert!(c_code.contains("value.data.Rect.field_1 = var_v1;"), "{}", c_code);
+        assert!(c_code.contains("value.data.Named.id = var_id;"), "{}", c_code);
+        assert!(c_code.contains("Shape Shape_Empty(void);"), "{}", c_code);
+        assert!(c_code.contains("Shape var_c = __vix_t2;"), "{}", c_code);
+        assert!(c_code.contains("switch (var_s.tag) {\ncase Shape__Circle: {"), "{}", c_code);
+        assert!(c_code.contains("case Shape__Rect: {"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_match_binds_payloads() {
+        let source = r#"
+enum Shape:
+    Circle(float32)
+    Rect(float32, float32)
+    Empty
+end
+
+func unwrap_or_zero(opt: Option[int32]): int32
+    match opt:
+        case Some(x):
+            return x
+        case None:
+            return 0
+    end
+    return 0
+end
+
+func width(s: Shape): float32
+    match s:
+        case Rect(w, h):
+            return w
+        case Circle(r):
+            return r
+        default:
+            return 0.0
+    end
+    return 0.0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+
+        assert!(c_code.contains("switch (var_opt.tag) {\ncase 1: {\nint32_t var_x = var_opt.value;\nreturn var_x;\nbreak;\n}\ncase 0: {"), "{}", c_code);
+        assert!(c_code.contains("case Shape__Rect: {\nfloat var_w = var_s.data.Rect.field_0;\nfloat var_h = var_s.data.Rect.field_1;"), "{}", c_code);
+        assert!(c_code.contains("case Shape__Circle: {\nfloat var_r = var_s.data.Circle;"), "{}", c_code);
+        assert!(c_code.contains("default: {"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_enum_match_errors_report_the_match_line() {
+        let source = r#"
+enum Shape:
+    Circle(float32)
+    Rect(float32, float32)
+end
+
+func area(s: Shape): float32
+    match s:
+        case Square(x):
+            return x
+        default:
+            return 0.0
+    end
+    return 0.0
+end
+
+func width(s: Shape): float32
+    match s:
+        case Rect(w):
+            return w
+        default:
+            return 0.0
+    end
+    return 0.0
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| matches!(d.code.as_str(), "UnknownVariant" | "PatternArity"))
+            .map(|d| (d.code.as_str(), d.context.primary_location.line))
+            .collect();
+        assert_eq!(errors, vec![("UnknownVariant", 8), ("PatternArity", 18)]);
+    }
+
+    #[test]
+    fn test_exhaustive_match_has_no_diagnostic() {
+        let source = r#"
+enum Light:
+    Red
+    Yellow
+    Green
+end
+
+func next(l: Light): int32
+    match l:
+        case Red:
+            return 1
+        case Yellow:
+            return 2
+        case Green:
+            return 0
+    end
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_non_exhaustive_match_lists_missing_variants() {
+        let source = r#"
+enum Light:
+    Red
+    Yellow
+    Green
+end
+
+func next(l: Light): int32
+    match l:
+        case Yellow:
+            return 2
+    end
+    return 0
+end
+
+func with_default(l: Light): int32
+    match l:
+        case Red:
+            return 1
+        default:
+            return 0
+    end
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen
+            .diagnostics
+            .diagnostics()
+            .iter()
+            .filter(|d| d.code == "NonExhaustiveMatch")
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("Red, Green"), "{}", errors[0].message);
+        assert_eq!(errors[0].context.primary_location.line, 9);
+    }
+
+    #[test]
+    fn test_struct_literal_compound() {
+        let source = r#"
+struct Point:
+    x = int32
+    y = int32
+end
+
+func main()
+    p: Point = Point { x = 1, y = 2 }
+    origin: Point = Point {}
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("Point __vix_t2 = (Point){ .x = __vix_t0, .y = __vix_t1 };\nPoint var_p = __vix_t2;"), "{}", c_code);
+        assert!(c_code.contains("(Point){ 0 }"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_struct_literal_unknown_field() {
+        let source = r#"
+struct Point:
+    x = int32
+    y = int32
+end
+
+func main()
+    p: Point = Point { x = 1, z = 2 }
+end
+"#;
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownField").expect("unknown field reported");
+        assert!(error.message.contains("'z'"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_member_access_unknown_field_suggests_closest() {
+        let source = r#"
+struct Point:
+    width = int32
+    height = int32
+end
+
+func area(p: Point): int32
+    return p.widht
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownField").expect("unknown field reported");
+        assert!(error.message.contains("'widht'"), "{}", error.message);
+        assert_eq!(error.context.suggestions, ["Did you mean 'width'?"]);
+        assert_eq!(error.context.help_message.as_deref(), Some("Available fields: width, height"));
+        assert_eq!(error.context.primary_location.line, 8);
+        assert!(!c_code.contains(".widht"), "{}", c_code);
+
+        let source = "struct Point:\n    x = int32\nend\n\nfunc main()\n    mut p: Point = Point { x = 1 }\n    if p.x < 3 then\n        print(p.y)\n    end\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownField").expect("unknown field reported");
+        assert_eq!(error.context.primary_location.line, 8);
+    }
+
+    #[test]
+    fn test_global_consts_resolve_in_functions() {
+        let source = r#"
+const MAX: int32 = 100
+const LIMIT: int32 = MAX * 2
+const RATIO: float32 = 0.5
+
+func next(): int32
+    return MAX + 1
+end
+
+func scale(): float32
+    return RATIO
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("#define const_MAX ((int32_t)100)\n"), "{}", c_code);
+        assert!(c_code.contains("#define const_LIMIT ((int32_t)(const_MAX * 2))\n"), "{}", c_code);
+        assert!(c_code.contains("static const float const_RATIO = 0.5;\n"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t1 = const_MAX + __vix_t0;\nreturn __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("return const_RATIO;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_string_consts_escape_and_bad_consts_report_their_line() {
+        let source = r#"
+const GREET: str = "hi\rthere\x01"
+
+func main(): int32
+    plan("{}\n", GREET)
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("const_GREET = { .ptr = \"hi\\rthere\\001\", .len = 9 };"), "{}", c_code);
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "hi\rthere\x01\n");
+        }
+
+        let (codegen, _) = generate("func f(): int32\n    return 1\nend\n\nconst BAD: int32 = f()\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "InvalidConstant").expect("call in a constant reported");
+        assert_eq!(error.context.primary_location.line, 5);
+    }
+
+    #[test]
+    fn test_named_arguments_reordered() {
+        let source = r#"
+func rect(x: int32, y: int32, w: int32): int32
+    return x + y + w
+end
+
+func main()
+    a: int32 = rect(w = 30, x = 10, y = 20)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        // arguments are evaluated in call-site order, then passed in declaration order
+        assert!(c_code.contains(
+            "int32_t __vix_t0 = 30;\nint32_t __vix_t1 = __vix_t0;\nint32_t __vix_t2 = 10;\nint32_t __vix_t3 = __vix_t2;\n\
+             int32_t __vix_t4 = 20;\nint32_t __vix_t5 = __vix_t4;\nint32_t __vix_t6 = rect(__vix_t3, __vix_t5, __vix_t1);"
+        ), "{}", c_code);
+    }
+
+    #[test]
+    fn test_plan_placeholders_follow_argument_types() {
+        let source = r#"
+func show(count: int32, big: int64, ratio: float64, name: str)
+    plan("{} {} {} {}: 100%", count, big, ratio, name)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("printf(\"%d %lld %f %.*s: 100%%\""), "{}", c_code);
+        assert!(c_code.contains(", (int)var_name.len, var_name.ptr);"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_plan_escapes_control_characters() {
+        let source = r#"
+func main(): int32
+    plan("a\rb\x01c \"{}\"\t\\\n", 7)
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("printf(\"a\\rb\\001c \\\"%d\\\"\\t\\\\\\n\", "), "{}", c_code);
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "a\rb\x01c \"7\"\t\\\n");
+        }
+    }
+
+    #[test]
+    fn test_plan_placeholder_count_mismatch() {
+        let source = r#"
+func main()
+    plan("{} and {}", 1)
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(codegen.diagnostics.diagnostics().iter().any(|d| d.message.contains("2 placeholder(s) but 1 argument(s)")));
+    }
+
+    #[test]
+    fn test_named_arguments_unknown_and_missing() {
+        let source = r#"
+func rect(x: int32, y: int32, w: int32): int32
+    return x + y + w
+end
+
+func main()
+    a: int32 = rect(x = 1, h = 2)
+end
+"#;
+        let (codegen, _) = generate(source);
+        let codes: Vec<&str> = codegen.diagnostics.diagnostics().iter().map(|d| d.code.as_str()).collect();
+        assert!(codes.contains(&"UnknownArgument"), "{:?}", codes);
+        let missing = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "MissingArgument").unwrap();
+        assert!(missing.message.ends_with("y, w"), "{}", missing.message);
+    }
+
+    #[test]
+    fn test_impl_method_returns_self() {
+        let source = r#"
+struct Point:
+    x = int32
+    y = int32
+end
+
+impl Point:
+    func with_x(&mut self, v: int32): Self
+        self.x = v
+        return self
+    end
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("Point Point_with_x(Point* self, int32_t v);"), "{}", c_code);
+        assert!(c_code.contains("Point Point_with_x(Point* self, int32_t v) {"), "{}", c_code);
+        assert!(c_code.contains("return *self;"), "{}", c_code);
+        let (_, ret_ty, _) = &codegen.impl_methods[&("Point".to_string(), "with_x".to_string())];
+        assert_eq!(ret_ty, &Type::Struct { name: "Point".to_string() });
+    }
+
+    #[test]
+    fn test_if_expression_lowers_to_ternary() {
+        let source = r#"
+func pick(flag: bool, a: int32, b: int32): int32
+    x: int32 = if flag then a else b
+    return if x > 0 then x else 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("int32_t __vix_t0 = (var_flag ? var_a : var_b);\nconst int32_t var_x = __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("(__vix_t2 ? var_x : ({ int32_t __vix_t3 = 0; __vix_t3; }));\nreturn __vix_t4;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_if_expression_branch_mismatch() {
+        let source = r#"
+func pick(flag: bool): int32
+    x: int32 = if flag then 1 else "no"
+    return x
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(codegen.diagnostics.diagnostics().iter().any(|d| d.code == "IfBranchMismatch"));
+    }
+
+    #[test]
+    fn test_multi_array_index_assign() {
+        let (mut codegen, _) = generate("func main()\nend\n");
+        let grid = Type::MultiArray { element: Box::new(Type::i32()), dimensions: vec![3, 3] };
+        let rows = Type::Array { element: Box::new(Type::Array { element: Box::new(Type::i32()), size: None }), size: None };
+        codegen.vars.insert("grid".to_string(), ("var_grid".to_string(), grid));
+        codegen.vars.insert("rows".to_string(), ("var_rows".to_string(), rows));
+        let loc = codegen.default_location();
+        let indices = [Expr::Number(1), Expr::Number(2)];
+
+        let mut body = String::new();
+        codegen.codegen_index_assign(&Expr::Var("grid".to_string()), &indices, &Expr::Number(7), &mut body, loc.clone()).unwrap();
+        assert!(body.ends_with("var_grid[__vix_t1][__vix_t2] = __vix_t0;\n"), "{}", body);
+
+        let mut body = String::new();
+        codegen.codegen_index_assign(&Expr::Var("rows".to_string()), &indices, &Expr::Number(7), &mut body, loc).unwrap();
+        assert!(body.ends_with("var_rows.ptr[__vix_t4].ptr[__vix_t5] = __vix_t3;\n"), "{}", body);
+    }
+
+    #[test]
+    fn test_len_on_slices_and_strings() {
+        let source = r#"
+func count(items: [int32]): usize
+    return items.len()
+end
+
+func greeting(): usize
+    return "hello".length()
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("size_t __vix_t0 = var_items.len;\nreturn __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("= { .ptr = \"hello\", .len = 5 };\nsize_t __vix_t1 = __vix_t0.len;"), "{}", c_code);
+        assert!(!c_code.contains("len(") && !c_code.contains("length("), "{}", c_code);
+    }
+
+    #[test]
+    fn test_len_on_fixed_array() {
+        let (mut codegen, _) = generate("func main()\nend\n");
+        let fixed = Type::Array { element: Box::new(Type::i32()), size: Some(4) };
+        codegen.vars.insert("nums".to_string(), ("var_nums".to_string(), fixed));
+        let loc = codegen.default_location();
+
+        let mut body = String::new();
+        let (tmp, ty) = codegen.codegen_method_call(&Expr::Var("nums".to_string()), "len", &[], &mut body, loc).unwrap();
+        assert_eq!(ty, Type::Usize);
+        assert_eq!(body, format!("size_t {} = sizeof(var_nums) / sizeof(var_nums[0]);\n", tmp));
+    }
+
+    #[test]
+    fn test_len_compares_and_adds_with_literals() {
+        let source = r#"
+func long(s: str): bool
+    return s.len() > 2
+end
+
+func padded(s: str): usize
+    return 1 + s.len() * 2
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("bool __vix_t2 = __vix_t0 > __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("size_t __vix_t3 = __vix_t1 * __vix_t2;\nsize_t __vix_t4 = __vix_t0 + __vix_t3;"), "{}", c_code);
+    }
+
+    fn check_traits(source: &str) -> (Vec<TraitDef>, Codegen) {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let (_, _, _, _, _, _, _, impls, traits, _, _) = parser.parse();
+
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        let _ = codegen.check_trait_impls(&traits, &impls);
+        (traits, codegen)
+    }
+
+    #[test]
+    fn test_trait_impl_conforms() {
+        let source = r#"
+trait Shape:
+    func area(self): int32
+    func scaled(self, factor: int32): Self
+end
+
+struct Square:
+    side = int32
+end
+
+impl Square for Shape:
+    func area(self): int32
+        return self.side * self.side
+    end
+
+    func scaled(self, by: int32): Square
+        return Square { side: self.side * by }
+    end
+end
+"#;
+        let (traits, codegen) = check_traits(source);
+        assert_eq!(traits.len(), 1);
+        assert_eq!(traits[0].methods.len(), 2);
+        assert_eq!(traits[0].methods[1].params[0].1, Type::i32());
+        assert_eq!(traits[0].methods[1].return_type, Type::SelfType);
+        assert!(!codegen.diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_trait_impl_missing_and_mismatched_methods() {
+        let source = r#"
+trait Shape:
+    func area(self): int32
+    func perimeter(self): int32
+end
+
+struct Square:
+    side = int32
+end
+
+impl Square for Shape:
+    func area(self): bool
+        return true
+    end
+end
+"#;
+        let (_, codegen) = check_traits(source);
+        let codes: Vec<&str> = codegen.diagnostics.diagnostics().iter().map(|d| d.code.as_str()).collect();
+        assert!(codes.contains(&"MissingTraitMethod"), "{:?}", codes);
+        assert!(codes.contains(&"TraitMethodMismatch"), "{:?}", codes);
+        let missing = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "MissingTraitMethod").unwrap();
+        assert!(missing.message.contains("perimeter"));
+        assert_eq!(missing.context.primary_location.line, 11);
+        let mismatch = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TraitMethodMismatch").unwrap();
+        assert_eq!(mismatch.context.primary_location.line, 12);
+    }
+
+    #[test]
+    fn test_forward_prototypes_allow_later_definitions() {
+        let source = r#"
+func main()
+    x: int32 = helper(2)
+end
+
+func helper(n: int32): int32
+    return n * 2
+end
+
+public func exported(): int32
+    return 1
+end
+"#;
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let (program, ..) = parser.parse();
+
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        codegen.codegen_program(&program.functions).unwrap();
+        assert!(!codegen.diagnostics.has_errors());
+
+        let decls = &codegen.ir.forward_decls;
+        assert!(decls.contains("static int32_t helper(int32_t var_n);"), "{}", decls);
+        assert!(decls.starts_with("int32_t vix_main();"), "{}", decls);
+        assert!(decls.contains("\nint32_t exported();"), "{}", decls);
+
+        let c_code = codegen.ir.clone().finalize();
+        let prototype = c_code.find("static int32_t helper(int32_t var_n);").unwrap();
+        let call = c_code.find("helper(").unwrap();
+        assert!(prototype <= call, "{}", c_code);
+        assert!(c_code.contains("static int32_t helper(int32_t var_n) {"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_private_functions_are_static() {
+        let source = r#"
+extern "C":
+    func puts(s: const str): int32
+end
+
+func helper(): int32
+    return 1
+end
+
+public func api(): int32
+    return helper()
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("static int32_t helper() {"), "{}", c_code);
+        assert!(c_code.contains("\nint32_t api() {"), "{}", c_code);
+        assert!(!c_code.contains("static int32_t api"), "{}", c_code);
+        assert!(c_code.contains("puts(") && !c_code.contains("static int32_t puts"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_typeof_yields_type_name() {
+        let (mut codegen, _) = generate("func main()\nend\n");
+        let mut body = String::new();
+        let (tmp, ty) = codegen.codegen_expr(&Expr::TypeOf(Box::new(Expr::Float(2.5))), &mut body).unwrap();
+
+        assert_eq!(ty, Type::ConstStr);
+        assert_eq!(body.lines().count(), 1, "{}", body);
+        assert!(body.starts_with(&format!("const char* {} = \"", tmp)), "{}", body);
+        assert!(body.contains("float") || body.contains("double"), "{}", body);
+
+        let source = "func f(): int32\n    return 1\nend\n\nfunc main()\n    create name = typeof(f())\n    print(name)\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("= \"int32\";\nconst char* var_name = "), "{}", c_code);
+        assert!(!c_code.contains("= f();"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_offsetof_and_alignof() {
+        let source = r#"
+struct Pair:
+    a = int8
+    b = int64
+end
+
+func b_offset(): usize
+    return offsetof(Pair, b)
+end
+
+func pair_align(): usize
+    return alignof(Pair)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("#include <stddef.h>"), "{}", c_code);
+        assert!(c_code.contains("size_t __vix_t0 = offsetof(Pair, b);"), "{}", c_code);
+        assert!(c_code.contains("size_t __vix_t0 = _Alignof(Pair);"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_size_of_types() {
+        let source = r#"
+struct Pair:
+    a = int8
+    b = int64
+end
+
+func wide(): usize
+    return size_of(int64)
+end
+
+func pair_size(): usize
+    return size_of(Pair)
+end
+
+func value_size(x: int32): usize
+    return sizeof(x)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("size_t __vix_t0 = sizeof(int64_t);"), "{}", c_code);
+        assert!(c_code.contains("size_t __vix_t0 = sizeof(Pair);"), "{}", c_code);
+        assert!(c_code.contains("size_t __vix_t0 = sizeof(var_x);"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_offsetof_unknown_field() {
+        let source = r#"
+struct Pair:
+    a = int8
+    b = int64
+end
+
+func bad(): usize
+    return offsetof(Pair, c)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownField").expect("unknown field reported");
+        assert!(error.message.contains("'c'"));
+        assert!(!c_code.contains("offsetof(Pair, c)"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_hashmap_literal_insert_and_get() {
+        let source = r#"
+func lookup(): int32
+    create m = { ["a"] = 1, ["b"] = 2 }
+    return m.get("a")
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("} HashMap_char8_int32;"), "{}", c_code);
+        assert!(c_code.contains("HashMap_char8_int32 __vix_t4 = {0};\ninsert_HashMap_char8_int32(&__vix_t4, __vix_t0, __vix_t1);\ninsert_HashMap_char8_int32(&__vix_t4, __vix_t2, __vix_t3);"), "{}", c_code);
+        assert!(c_code.contains("const int32_t* __vix_t6 = get_HashMap_char8_int32(&var_m, __vix_t5);\nint32_t __vix_t7 = __vix_t6 ? *__vix_t6 : (int32_t){0};\nreturn __vix_t7;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_reference_params_pass_pointers() {
+        let source = r#"
+func bump(mutable reference n: int32)
+    n += 1
+end
+
+func peek(reference n: int32): int32
+    return n
+end
+
+func main()
+    mut count: int32 = 0
+    bump(count)
+    bump(count)
+    seen: int32 = peek(count)
+    plan("{} {}\n", count, seen)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("static void bump(int32_t* var_n) {\n#line 3 \"test.vix\"\nint32_t __vix_t0 = 1;\n(*var_n) += __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("static int32_t peek(const int32_t* var_n);"), "{}", c_code);
+        assert!(c_code.contains("bump(&var_count);"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t1 = peek(&var_count);"), "{}", c_code);
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "2 2\n");
+        }
+
+        let source = r#"
+func bump(mutable reference n: int32)
+    n += 1
+end
+
+func main()
+    fixed: int32 = 1
+    bump(fixed)
+    bump(5)
+    create view = fixed
+    bump(view)
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.level == DiagnosticLevel::Error)
+            .map(|d| (d.code.as_str(), d.context.primary_location.line))
+            .collect();
+        assert_eq!(errors, vec![("AssignThroughSharedRef", 8), ("MutRefToTemporary", 9), ("AssignThroughSharedRef", 11)]);
+    }
+
+    #[test]
+    fn test_create_infers_declared_type() {
+        let source = r#"
+struct Point:
+    x = int32
+    y = int32
+end
+
+impl Point(x: int32, y: int32):
+end
+
+func main()
+    create count = 42
+    create name = "vix"
+    create origin = Point(1, 2)
+    create nums = [1, 2, 3]
+    view: [int32] = [4, 5]
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("const int32_t var_count = __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("Slice_char var_name = __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("Point var_origin = __vix_t4;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t8[3] = { __vix_t5, __vix_t6, __vix_t7 };\nint32_t var_nums[3];"), "{}", c_code);
+        assert!(c_code.contains("Slice_int32 var_view = { .ptr = __vix_t11, .len = 2 };"), "{}", c_code);
+        assert!(!c_code.contains("Auto"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_bounds_checked_slice_index() {
+        let (mut codegen, _) = generate("func main()\nend\n");
+        codegen.config.bounds_checks = true;
+        let slice = Type::Array { element: Box::new(Type::i32()), size: None };
+        codegen.vars.insert("items".to_string(), ("var_items".to_string(), slice));
+
+        let mut body = String::new();
+        let index = Expr::Index(Box::new(Expr::Var("items".to_string())), vec![Expr::Var("i".to_string())]);
+        codegen.vars.insert("i".to_string(), ("var_i".to_string(), Type::i32()));
+        let (tmp, ty) = codegen.codegen_expr(&index, &mut body).unwrap();
+
+        assert_eq!(ty, Type::i32());
+        assert_eq!(body, format!(
+            "if ((size_t)var_i >= var_items.len) {{ vix_panic(\"index out of bounds\"); }}\nint32_t {} = var_items.ptr[var_i];\n",
+            tmp
+        ));
+        assert!(codegen.ir.helper_functions.contains("static void vix_panic(const char* msg)"));
+    }
+
+    #[test]
+    fn test_constant_index_past_fixed_array_is_rejected() {
+        let (mut codegen, _) = generate("func main()\nend\n");
+        let fixed = Type::Array { element: Box::new(Type::i32()), size: Some(4) };
+        codegen.vars.insert("nums".to_string(), ("var_nums".to_string(), fixed));
+
+        let mut body = String::new();
+        let ok = Expr::Index(Box::new(Expr::Var("nums".to_string())), vec![Expr::Number(3)]);
+        assert!(codegen.codegen_expr(&ok, &mut body).is_ok());
+        assert!(!body.contains("vix_panic"));
+
+        let past_end = Expr::Index(Box::new(Expr::Var("nums".to_string())), vec![Expr::Number(4)]);
+        assert!(codegen.codegen_expr(&past_end, &mut body).is_err());
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "IndexOutOfBounds").expect("out of bounds reported");
+        assert!(error.message.contains("length 4"));
+    }
+
+    #[test]
+    fn test_push_on_owned_array_is_allowed() {
+        let source = r#"
+func first(view: &[int32]): int32
+    return view[0]
+end
+
+func main()
+    mut xs: int32[] = [1, 2]
+    xs += 3
+    print(first(xs))
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics().iter().map(|d| &d.message).collect::<Vec<_>>());
+        assert!(c_code.contains("int32_t first(Slice_int32 var_view)"), "{}", c_code);
+        assert!(c_code.contains("vix_push(var_xs, "), "{}", c_code);
+    }
+
+    #[test]
+    fn test_push_on_slice_is_rejected() {
+        let source = r#"
+func grow(view: &[int32])
+    view += 3
+end
+"#;
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "SliceNotGrowable").expect("slice push reported");
+        assert!(error.message.contains("&[int32]"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_misspelled_call_suggests_known_function() {
+        let source = r#"
+func main()
+    puts("ok")
+    printf("%d", 1)
+    print("fine")
+    prnt("hi")
+    create n = randm(6)
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen.diagnostics.diagnostics().iter().filter(|d| d.code == "UndefinedFunction").collect();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].message.contains("'prnt'"), "{}", errors[0].message);
+        assert!(errors[0].context.help_message.as_deref().unwrap().contains("`print`"));
+        assert!(errors[1].message.contains("'randm'"), "{}", errors[1].message);
+        assert!(errors[1].context.help_message.as_deref().unwrap().contains("`random`"));
+    }
+
+    #[test]
+    fn test_variadic_function_forwards_to_vprintf() {
+        let source = r#"
+func trace(fmt: str, ...)
+    vprintf(fmt, ...)
+end
+
+func first(count: int32, ...): int32
+    return va_next(int32)
+end
+
+func main()
+    trace("%d\n", first(1, 7))
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics().iter().map(|d| &d.message).collect::<Vec<_>>());
+        assert!(c_code.contains("#include <stdarg.h>"), "{}", c_code);
+        assert!(c_code.contains("static void trace(Slice_char var_fmt, ...) {"), "{}", c_code);
+        assert!(c_code.contains("va_start(vix_args, var_fmt);"), "{}", c_code);
+        assert!(c_code.contains("vprintf(var_fmt.ptr, vix_args);"), "{}", c_code);
+        assert!(c_code.contains("va_arg(vix_args, int32_t)"), "{}", c_code);
+        assert_eq!(c_code.matches("va_end(vix_args);").count(), 2, "{}", c_code);
+    }
+
+    #[test]
+    fn test_variadic_without_named_param_is_reported_at_the_function() {
+        let source = "func main()\nend\n\nfunc bare(...)\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "VariadicWithoutNamedParam").expect("bare '...' should be rejected");
+        assert_eq!(error.context.primary_location.line, 4);
+    }
+
+    #[test]
+    fn test_tuple_unpack_declares_each_name() {
+        let source = r#"
+func pair(): (int32, bool)
+    return (3, true)
+end
+
+func main()
+    create a, b = pair()
+    create x, y, z = (1, 2.5, a)
+    create total = x + z
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics().iter().map(|d| &d.message).collect::<Vec<_>>());
+        assert!(c_code.contains("int32_t var_a = __vix_t0.field_0;"), "{}", c_code);
+        assert!(c_code.contains("bool var_b = __vix_t0.field_1;"), "{}", c_code);
+        assert!(c_code.contains("float var_y = __vix_t3.field_1;"), "{}", c_code);
+        assert!(c_code.contains("int32_t var_z = __vix_t3.field_2;"), "{}", c_code);
+        assert!(c_code.contains("var_total"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_tuple_unpack_arity_mismatch() {
+        let (codegen, _) = generate("func main()\n    create a, b = (1, 2, 3)\nend\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TupleArityMismatch").expect("arity mismatch reported");
+        assert!(error.message.contains("3-element tuple into 2 names"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_usize_follows_selected_arch() {
+        let mut registry = TypeRegistry::new();
+        for arch in [ArchConfig::x86_64(), ArchConfig::aarch64()] {
+            assert_eq!(Type::Usize.to_c_type(&arch, &mut registry), "size_t", "{}", arch.target);
+            assert_eq!(Type::Usize.size_bits(&arch), 64, "{}", arch.target);
+            assert_eq!(Type::Ptr(Box::new(Type::i32())).size_bits(&arch), 64, "{}", arch.target);
+        }
+
+        let x86 = ArchConfig::x86();
+        assert_eq!(Type::Usize.size_bits(&x86), 32);
+        assert_eq!(Type::u64().to_c_type(&x86, &mut registry), "uint64_t");
+        assert_eq!(ArchConfig::aarch64().target, "aarch64");
+    }
+
+    #[test]
+    fn test_as_cast_inside_arithmetic() {
+        let source = r#"
+func widen(a: int32, b: int64): int64
+    return a as int64 + b * 2
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("int64_t __vix_t0 = (int64_t)var_a;\nint32_t __vix_t1 = 2;\nint64_t __vix_t2 = var_b * __vix_t1;\nint64_t __vix_t3 = __vix_t0 + __vix_t2;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_library_includes_emitted_once() {
+        let source = "func main()\nend\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
+
+        let includes = vec![
+            "#include <math.h>".to_string(),
+            "#include \"net.h\"".to_string(),
+            "#include  <math.h>".to_string(),
+            "#include <stdio.h>".to_string(),
+        ];
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        let c_code = codegen
+            .codegen_program_full(&program, &structs, &enums, &impls, &externs, &includes, &[])
+            .unwrap();
+
+        assert_eq!(c_code.matches("#include <math.h>").count(), 1, "{}", c_code);
+        assert_eq!(c_code.matches("#include <stdio.h>").count(), 1, "{}", c_code);
+        let math = c_code.find("#include <math.h>").unwrap();
+        let net = c_code.find("#include \"net.h\"").unwrap();
+        assert!(math < net, "{}", c_code);
+    }
+
+    #[test]
+    fn test_function_pointer_compatibility() {
+        let codegen = Codegen::new(ArchConfig::x86_64(), String::new(), "test.vix".to_string());
+        let binary = Type::FunctionPointer { params: vec![Type::i32(), Type::i32()], ret: Box::new(Type::i32()) };
+        let same = Type::FunctionPointer { params: vec![Type::i32(), Type::i32()], ret: Box::new(Type::i32()) };
+        let unary = Type::FunctionPointer { params: vec![Type::i32()], ret: Box::new(Type::i32()) };
+        let returns_bool = Type::FunctionPointer { params: vec![Type::i32(), Type::i32()], ret: Box::new(Type::Bool) };
+
+        assert!(codegen.types_compatible(&binary, &same));
+        assert!(!codegen.types_compatible(&binary, &unary));
+        assert!(!codegen.types_compatible(&binary, &returns_bool));
+    }
+
+    #[test]
+    fn test_assert_emits_runtime_check() {
+        let source = r#"
+func main(): int32
+    x: int32 = 3
+    assert x > 2, "x too small"
+    assert x < 10
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("assertion failed: %.*s\\n\", __FILE__, __LINE__, (int)__vix_t3.len, __vix_t3.ptr);"), "{}", c_code);
+        assert!(c_code.contains("x too small"), "{}", c_code);
+        assert_eq!(c_code.matches("abort();").count(), 2, "{}", c_code);
+        assert!(c_code.contains("#line 5 \"test.vix\"\nfprintf(stderr, \"%s:%d: assertion failed\\n\""), "{}", c_code);
+
+        let (codegen, _) = generate("func main(): int32\n    x: int32 = 3\n    assert x > 2, x\n    return 0\nend\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TypeMismatch").expect("int message should be rejected");
+        assert_eq!(error.context.primary_location.line, 3);
+
+        let (codegen, c_code) = generate("func main(): int32\n    assert(1 == 1, \"ok\")\n    return 0\nend\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TypeMismatch").expect("tuple condition should be rejected");
+        assert_eq!(error.context.primary_location.line, 2);
+        assert!(!c_code.contains("abort();"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_assert_elided_in_release() {
+        let source = r#"
+func main(): int32
+    x: int32 = 3
+    assert x > 2, "x too small"
+    return 0
+end
+"#;
+        let (_, c_code) = generate_with_optimization(source, OptimizationLevel::Release);
+        assert!(!c_code.contains("assertion failed"), "{}", c_code);
+        assert!(!c_code.contains("x too small"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_index_compound_assign_evaluates_lvalue_once() {
+        let source = r#"
+func bump(arr: [int32], i: int32)
+    arr[i] += 5
+end
+
+func scale(matrix: [[int32]], i: int32, j: int32)
+    matrix[i + 1][j] *= 2
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("var_arr.ptr[var_i] += __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t1 = var_i + __vix_t0;\nint32_t __vix_t2 = 2;\nvar_matrix.ptr[__vix_t1].ptr[var_j] *= __vix_t2;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_member_compound_assign_through_mut_ref_field() {
+        let source = r#"
+struct Counter:
+    count = int32
+end
+
+struct Holder:
+    target = &mut Counter
+end
+
+func bump(h: Holder)
+    h.target.count += 1
+end
+
+func bump_ref(h: &mut Holder)
+    h.target.count -= 1
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("var_h.target->count += __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("var_h->target->count -= __vix_t0;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_member_chain_reads_into_one_temp() {
+        let source = r#"
+struct Inner:
+    value = int32
+end
+
+struct Middle:
+    inner = Inner
+end
+
+struct Outer:
+    middle = &Middle
+end
+
+func read(o: Outer): int32
+    return o.middle.inner.value
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("int32_t __vix_t0 = var_o.middle->inner.value;\nreturn __vix_t0;"), "{}", c_code);
+        assert!(!c_code.contains(" __vix_t1 = "), "{}", c_code);
+    }
+
+    #[test]
+    fn test_inline_attributes_become_c_qualifiers() {
+        let source = r#"
+#[inline]
+func square(x: int32): int32
+    return x * x
+end
+
+@noinline
+func cube(x: int32): int32
+    return x * x * x
+end
+
+#[hot]
+func main()
+    print(square(2) + cube(2))
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(c_code.contains("static inline int32_t square(int32_t var_x) {"), "{}", c_code);
+        assert!(c_code.contains("__attribute__((noinline)) static int32_t cube(int32_t var_x) {"), "{}", c_code);
+        let warnings: Vec<&str> = codegen.diagnostics.diagnostics().iter().map(|d| d.code.as_str()).collect();
+        assert_eq!(warnings, ["UnknownAttribute"]);
+        assert_eq!(codegen.diagnostics.diagnostics()[0].context.primary_location.line, 13);
+        assert!(!codegen.diagnostics.has_errors());
+    }
+
+    #[test]
+    fn test_struct_layout_attributes() {
+        let source = r#"
+#[packed]
+struct Header:
+    tag = uint8
+    size = uint32
+end
+
+#[repr(C)]
+#[align(16)]
+struct Vec4:
+    x = float32
+end
+
+@packed
+struct Wire:
+    byte = uint8
+end
+
+func main()
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(codegen.diagnostics.diagnostics().is_empty());
+        assert!(c_code.contains("typedef struct __attribute__((packed)) Header {"), "{}", c_code);
+        assert!(c_code.contains("typedef struct __attribute__((aligned(16))) Vec4 {"), "{}", c_code);
+        assert!(c_code.contains("typedef struct __attribute__((packed)) Wire {"), "{}", c_code);
+
+        let (codegen, _) = generate("func main()\nend\n\n#[align(3)]\nstruct Odd:\n    x = int32\nend\n");
+        let warning = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UnknownAttribute").expect("align(3) should be reported");
+        assert_eq!(warning.context.primary_location.line, 5);
+    }
+
+    #[test]
+    fn test_result_and_option_constructors() {
+        let source = r#"
+func parse(flag: bool): Result[int32, int32]
+    if flag then
+        return Ok(5)
+    end
+    return Err(1)
+end
+
+func first(flag: bool): Option[int32]
+    none: Option[int32] = None
+    if flag then
+        return Some(3)
+    end
+    return none
+end
+
+func main(): int32
+    match parse(true):
+        case Ok(v):
+            return v
+        case Err(e):
+            return e
+    end
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("Result_int32_int32 __vix_t1 = { .tag = 0, .data.ok = __vix_t0 };\nreturn __vix_t1;"), "{}", c_code);
+        assert!(c_code.contains("Result_int32_int32 __vix_t3 = { .tag = 1, .data.err = __vix_t2 };"), "{}", c_code);
+        assert!(c_code.contains("Option_int32 __vix_t0 = { .tag = 0 };"), "{}", c_code);
+        assert!(c_code.contains("Option_int32 __vix_t2 = { .tag = 1, .value = __vix_t1 };"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_none_without_declared_type_is_rejected() {
+        let (codegen, _) = generate("func main()\n    create x = None\nend\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "UninferableVariant").expect("untyped None should be rejected");
+        assert_eq!(error.context.primary_location.line, 2);
+    }
+
+    #[test]
+    fn test_variant_payload_mismatch_is_reported_at_the_statement() {
+        let source = "func pick(flag: bool): Option[int32]\n    if flag then\n        return Some(true)\n    end\n    return None\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TypeMismatch").expect("bool payload should be rejected");
+        assert_eq!(error.context.primary_location.line, 3);
+    }
+
+    #[test]
+    fn test_try_propagates_err_through_chained_calls() {
+        let source = r#"
+func half(n: int32): Result[int32, int32]
+    if n % 2 == 1 then
+        return Err(n)
+    end
+    return Ok(n / 2)
+end
+
+func quarter(n: int32): Result[int32, int32]
+    create a = half(n)?
+    create b = half(a)?
+    return Ok(b)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("if (__vix_t0.tag == 1) {\nResult_int32_int32 __vix_t1 = { .tag = 1, .data.err = __vix_t0.data.err };\nreturn __vix_t1;\n}"), "{}", c_code);
+        assert!(c_code.contains("__vix_t0.data.ok"), "{}", c_code);
+        assert!(c_code.contains("Result_int32_int32 __vix_t3 = { .tag = 1, .data.err = __vix_t2.data.err };"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_try_outside_result_function_is_rejected() {
+        let source = "func half(n: int32): Result[int32, int32]\n    return Ok(n)\nend\n\nfunc count(n: int32): int32\n    create a = half(n)?\n    return a\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TryOutsideResult").expect("? outside a Result function should be rejected");
+        assert_eq!(error.context.primary_location.line, 6);
+
+        let source = "func half(n: int32): Result[int32, bool]\n    return Ok(n)\nend\n\nfunc count(n: int32): Result[int32, int32]\n    if n > 0 then\n        create a = half(n)?\n        return Ok(a)\n    end\n    return Ok(0)\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "TypeMismatch").expect("mismatched error type should be rejected");
+        assert_eq!(error.context.primary_location.line, 7);
+    }
+
+    #[test]
+    fn test_option_and_result_unwrap_methods() {
+        let source = r#"
+func parse(flag: bool): Result[int32, int32]
+    if flag then
+        return Ok(5)
+    end
+    return Err(1)
+end
+
+func main(): int32
+    some: Option[int32] = Some(3)
+    none: Option[int32] = None
+    create a = some.unwrap()
+    create b = none.unwrap_or(10)
+    create c = parse(true).expect("parse failed")
+    create d = parse(false).unwrap_or(20)
+    create e = parse(false).unwrap()
+    return a + b + c + d + e
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("if (var_some.tag != 1) {\nvix_panic(\"called unwrap on None\");\n}\nint32_t __vix_t3 = var_some.value;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t5 = var_none.tag == 1 ? var_none.value : __vix_t4;"), "{}", c_code);
+        assert!(c_code.contains("if (__vix_t8.tag != 0) {\nSlice_char __vix_t9 = { .ptr = \"parse failed\", .len = 12 };\nfprintf(stderr, \"panic: %.*s\\n\", (int)__vix_t9.len, __vix_t9.ptr);\nexit(1);\n}\nint32_t __vix_t10 = __vix_t8.data.ok;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t15 = __vix_t13.tag == 0 ? __vix_t13.data.ok : __vix_t14;"), "{}", c_code);
+        assert!(c_code.contains("vix_panic(\"called unwrap on Err\");"), "{}", c_code);
+
+        let source = "func main(): int32\n    some: Option[int32] = Some(3)\n    if true then\n        create a = some.unwrap(1)\n    end\n    create b = some.unwrap_or(true)\n    create c = some.expect(7)\n    return 0\nend\n";
+        let (codegen, _) = generate(source);
+        let lines = |code: &str| codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == code)
+            .map(|d| d.context.primary_location.line)
+            .collect::<Vec<_>>();
+        assert_eq!(lines("ArgumentCount"), [4]);
+        assert_eq!(lines("TypeMismatch"), [6, 7]);
+    }
+
+    #[test]
+    fn test_code_after_return_is_unreachable() {
+        let source = r#"
+func main(): int32
+    while true do
+        break
+        print("never")
+    end
+    return 0
+    print("dead")
+end
+"#;
+        let (codegen, _) = generate(source);
+        let unreachable: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "UnreachableCode")
+            .map(|d| (d.message.as_str(), d.context.primary_location.line))
+            .collect();
+        assert_eq!(unreachable, [("Unreachable code after `return`", 8), ("Unreachable code after `break`", 5)]);
+    }
+
+    #[test]
+    fn test_returning_if_else_arms_are_not_unreachable() {
+        let source = r#"
+func sign(n: int32): int32
+    if n < 0 then
+        return -1
+    else
+        return 1
+    end
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(!codegen.diagnostics.diagnostics().iter().any(|d| d.code == "UnreachableCode"));
+    }
+
+    #[test]
+    fn test_unused_variable_is_reported() {
+        let source = r#"
+func main(): int32
+    create unused = 1
+    create _ignored = 2
+    create total = 3
+    total += 1
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        let unused: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "UnusedVariable")
+            .map(|d| d.message.clone())
+            .collect();
+        assert_eq!(unused, vec!["Variable 'unused' is never read", "Variable 'total' is never read"]);
+    }
+
+    #[test]
+    fn test_unused_variable_follows_scopes_and_tuple_bindings() {
+        let source = r#"
+func pair(): (int32, int32)
+    return (1, 2)
+end
+
+func main(opt: Option[int32]): int32
+    create shadowed = 1
+    if true then
+        create shadowed = 2
+        print(shadowed)
+        create left, right = pair()
+        print(left)
+    end
+    if Some(shadowed) = opt then
+        print(shadowed)
+    end
+    create counter = 0
+    for counter in 0..3 do
+        print(counter)
+    end
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        let unused: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "UnusedVariable")
+            .map(|d| (d.message.as_str(), d.context.primary_location.line))
+            .collect();
+        assert_eq!(unused, [
+            ("Variable 'shadowed' is never read", 7),
+            ("Variable 'right' is never read", 11),
+            ("Variable 'counter' is never read", 17),
+        ]);
+    }
+
+    #[test]
+    fn test_read_variable_is_not_reported() {
+        let source = r#"
+func main(): int32
+    create base = 1
+    if base > 0 then
+        create step = 2
+        return base + step
+    end
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        assert!(!codegen.diagnostics.diagnostics().iter().any(|d| d.code == "UnusedVariable"));
+    }
+
+    #[test]
+    fn test_byte_and_codepoint_chars_are_typed() {
+        let source = "func main(): int32\n    create a = b'A'\n    create e = '\u{e9}'\n    create c = 'c'\n    byte: uint8 = 'z'\n    return e\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("uint8_t __vix_t0 = 65;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t1 = 233;"), "{}", c_code);
+        assert!(c_code.contains("char __vix_t2 = 99;"), "{}", c_code);
+        assert!(c_code.contains("uint8_t __vix_t3 = 122;"), "{}", c_code);
+
+        let (codegen, _) = generate("func main()\n    create ok = 1\n    byte: uint8 = '\u{e9}'\nend\n");
+        let lines: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "CharNotByte")
+            .map(|d| d.context.primary_location.line)
+            .collect();
+        assert_eq!(lines, [3]);
+    }
+
+    #[test]
+    fn test_suffixed_literals_use_their_width() {
+        let source = "func main(): int32\n    create small = 255u8\n    create big = 0xFF_FFi64\n    create low = -128i8\n    return 0\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("uint8_t __vix_t0 = 255;"), "{}", c_code);
+        assert!(c_code.contains("int64_t __vix_t1 = 65535;"), "{}", c_code);
+        assert!(c_code.contains("int8_t __vix_t2 = -128;"), "{}", c_code);
+
+        let (codegen, _) = generate("func main()\n    create ok = 1u8\n    create x = 300u8\nend\n");
+        let overflow = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "LiteralOutOfRange").unwrap();
+        assert_eq!(overflow.message, "Literal 300 does not fit in uint8 (0..=255)");
+        assert_eq!(overflow.context.primary_location.line, 3);
+    }
+
+    #[test]
+    fn test_slice_string_and_arrays() {
+        let (codegen, c_code) = generate("func main()\n    create s = \"hello\"\n    create t = s[1..3]\n    print(t)\nend\n");
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("Slice_char __vix_t3 = { .ptr = var_s.ptr + __vix_t1, .len = __vix_t2 - __vix_t1 };"), "{}", c_code);
+
+        let (mut codegen, _) = generate("func main()\nend\n");
+        codegen.config.bounds_checks = true;
+        let fixed = Type::Array { element: Box::new(Type::i32()), size: Some(4) };
+        codegen.vars.insert("nums".to_string(), ("var_nums".to_string(), fixed));
+        let dynamic = Type::Array { element: Box::new(Type::i32()), size: None };
+        codegen.vars.insert("items".to_string(), ("var_items".to_string(), dynamic.clone()));
+
+        let slice = |name: &str| Expr::Slice(Box::new(Expr::Var(name.to_string())), Box::new(Expr::Var("i".to_string())), Box::new(Expr::Var("j".to_string())));
+        codegen.vars.insert("i".to_string(), ("var_i".to_string(), Type::i32()));
+        codegen.vars.insert("j".to_string(), ("var_j".to_string(), Type::i32()));
+
+        let mut body = String::new();
+        let (tmp, ty) = codegen.codegen_expr(&slice("nums"), &mut body).unwrap();
+        assert_eq!(ty, dynamic);
+        assert_eq!(body, format!(
+            "if ((size_t)var_i > (size_t)var_j || (size_t)var_j > 4) {{ vix_panic(\"slice out of bounds\"); }}\nSlice_int32 {} = {{ .ptr = var_nums + var_i, .len = var_j - var_i }};\n",
+            tmp
+        ));
+
+        let mut body = String::new();
+        codegen.codegen_expr(&slice("items"), &mut body).unwrap();
+        assert!(body.contains("|| (size_t)var_j > var_items.len)"), "{}", body);
+        assert!(body.contains(".ptr = var_items.ptr + var_i"), "{}", body);
+    }
+
+    #[test]
+    fn test_slices_of_array_literals_and_fixed_arrays_compile() {
+        let source = r#"
+func main(): int32
+    create arr = [1, 2, 3, 4, 5]
+    create mid = arr[1..4]
+    fixed: int32[5] = [10, 20, 30, 40, 50]
+    create tail = fixed[2..5]
+    create s = "hello"
+    create word = s[1..3]
+    plan("{} {} {} {} {}\n", mid.len(), mid[0], tail.len(), tail[2], word)
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "3 2 3 50 el\n");
+        }
+    }
+
+    #[test]
+    fn test_uppercase_local_is_a_value() {
+        let source = "func main(): int32\n    create MAX = 10\n    return MAX + 1\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(!c_code.contains("MAX()"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t2 = var_MAX + __vix_t1;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_if_let_binds_payload() {
+        let source = r#"
+enum Shape:
+    Circle(float32)
+    Empty
+end
+
+func consume(v: int32): int32
+    return v
+end
+
+func show(opt: Option[int32]): int32
+    if Some(x) = opt then
+        return consume(x)
+    end
+    return 0
+end
+
+func radius(s: Shape): float32
+    if Circle(r) = s then
+        return r
+    else
+        return 0.0
+    end
+end
+"#;
+        assert_eq!(parse_errors(source), Vec::<String>::new());
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("if (var_opt.tag == 1) {\nint32_t var_x = var_opt.value;\nint32_t __vix_t0 = consume(var_x);"), "{}", c_code);
+        assert!(c_code.contains("if (var_s.tag == Shape__Circle) {\nfloat var_r = var_s.data.Circle;"), "{}", c_code);
+        assert!(c_code.contains("return var_r;\n} else {\nfloat __vix_t"), "{}", c_code);
+        assert!(!codegen.vars.contains_key("x"));
+
+        let (codegen, _) = generate("func main(opt: Option[int32])\n    if Some(1) = opt then\n    end\nend\n");
+        let invalid = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "InvalidPattern").unwrap();
+        assert_eq!(invalid.message, "`if let` pattern must be a variant with plain bindings");
+        assert_eq!(invalid.context.primary_location.line, 2);
+
+        let source = r#"
+enum Shape:
+    Circle(float32)
+    Empty
+end
+
+func plain(n: int32)
+    if Some(x) = n then
+    end
+end
+
+func unknown(s: Shape)
+    create ok = 1
+    if Square(x) = s then
+    end
+end
+
+func arity(s: Shape)
+    if Circle(a, b) = s then
+    end
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code != "UnusedVariable")
+            .map(|d| (d.code.as_str(), d.context.primary_location.line))
+            .collect();
+        assert_eq!(errors, [("TypeMismatch", 8), ("UnknownVariant", 14), ("PatternArity", 19)]);
+    }
+
+    #[test]
+    fn test_default_parameters_fill_omitted_arguments() {
+        let source = r#"
+func connect(host: str, port: int32 = 8080): int32
+    return port
+end
+
+func main(): int32
+    connect("h")
+    return connect("h", 22)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("int32_t __vix_t1 = 8080;\nconnect(__vix_t0, __vix_t1);"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t5 = 22;\nint32_t __vix_t6 = connect(__vix_t4, __vix_t5);"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_diagnostics_are_collected_without_echo() {
+        let source = "func main(): int32\n    return missing\nend\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = Parser::new(tokens, source.to_string(), lexer.spans.clone()).parse();
+
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        codegen.diagnostics.echo = false;
+        codegen.codegen_program_full(&program, &structs, &enums, &impls, &externs, &[], &[]).ok();
+
+        let diagnostics = codegen.collect_diagnostics();
+        let undefined = diagnostics.iter().find(|d| d.code == "UndefinedVariable").unwrap();
+        assert_eq!(undefined.level, DiagnosticLevel::Error);
+    }
+
+    #[test]
+    fn test_extern_function_with_body_has_external_linkage() {
+        let source = "extern \"C\" func add(a: int32, b: int32): int32\n    return a + b\nend\n\nfunc main(): int32\n    return add(1, 2)\nend\n";
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(c_code.contains("\nint32_t add(int32_t var_a, int32_t var_b);"), "{}", c_code);
+        assert_eq!(c_code.matches("\nint32_t add(int32_t var_a, int32_t var_b) {").count(), 1, "{}", c_code);
+        assert!(!c_code.contains("static int32_t add("), "{}", c_code);
+        assert!(c_code.contains(" = add("), "{}", c_code);
+    }
+
+    #[test]
+    fn test_extern_block_library_becomes_link_argument() {
+        let source = "extern \"C\" from \"user32.dll\":\n    func MessageBeep(kind: uint32): int32\nend\n\nfunc main(): int32\n    return MessageBeep(0)\nend\n";
+        let (codegen, _) = generate(source);
+        assert_eq!(codegen.get_linked_libraries(), ["user32.dll"]);
+        assert_eq!(Clang::library_link_args(&codegen.get_linked_libraries()[0], TargetOS::Windows), ["-luser32"]);
+    }
+
+    #[test]
+    fn test_unknown_call_declares_implicit_extern() {
+        let source = r#"
+func main(): int32
+    create a = mystery(1, 2.5, "label")
+    create b = mystery(2, 3.5, "again")
+    return 0
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors());
+        let warnings: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "ImplicitExternDeclaration")
+            .collect();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].message.contains("'mystery'"));
+        assert!(c_code.contains("extern int32_t mystery(int32_t, double, const char*);"), "{}", c_code);
+        assert_eq!(c_code.matches("extern int32_t mystery(").count(), 1, "{}", c_code);
+    }
+
+    #[test]
+    fn test_integer_widening_is_coerced() {
+        let source = r#"
+func take(v: int64): int64
+    return v
+end
+
+func widen(small: int32, tiny: uint8): int64
+    wide: int64 = small
+    signed: int16 = tiny
+    mut total: int64 = 0
+    total = small
+    return take(small)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("const int64_t var_wide = (int64_t)var_small;"), "{}", c_code);
+        assert!(c_code.contains("const int16_t var_signed = (int16_t)var_tiny;"), "{}", c_code);
+        assert!(c_code.contains("var_total = (int64_t)var_small;"), "{}", c_code);
+        assert!(c_code.contains("take((int64_t)var_small)"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_integer_narrowing_is_rejected() {
+        for (from, to) in [("int64", "int32"), ("uint32", "int32"), ("int8", "uint16")] {
+            let source = format!("func narrow(v: {}): int32\n    n: {} = v\n    return 0\nend\n", from, to);
+            let (codegen, _) = generate(&source);
+            assert!(
+                codegen.diagnostics.diagnostics().iter().any(|d| d.code == "TypeMismatch"),
+                "{} -> {} should be rejected", from, to
+            );
+        }
+
+        let (codegen, _) = generate("func same(v: int32): int32\n    n: int32 = v\n    return n\nend\n");
+        assert!(!codegen.diagnostics.has_errors());
+        assert!(!codegen.types_compatible(&Type::i64(), &Type::i32()));
+        assert!(codegen.can_coerce(&Type::i32(), &Type::i64()));
+    }
+
+    #[test]
+    fn test_panicking_branch_type_checks() {
+        let source = r#"
+func checked(n: int32): int32
+    if n < 0 then
+        panic("negative")
+        print("never")
+    end
+    return n
+end
+
+func pick(n: int32): int32
+    x: int32 = if n > 0 then n else panic("non-positive")
+    return x
+end
+
+func fail(): int32
+    exit(2)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        let unreachable: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "UnreachableCode")
+            .map(|d| d.message.clone())
+            .collect();
+        assert_eq!(unreachable, vec!["Unreachable code after `panic`"]);
+        assert!(c_code.contains("int32_t __vix_t4 = (__vix_t1 ? var_n : ({ Slice_char __vix_t2 = { .ptr = \"non-positive\", .len = 12 }; fprintf(stderr, \"panic: %.*s\\n\", (int)__vix_t2.len, __vix_t2.ptr); exit(1);"), "{}", c_code);
+        assert!(c_code.contains("Slice_char __vix_t2 = { .ptr = \"negative\", .len = 8 };\nfprintf(stderr, \"panic: %.*s\\n\", (int)__vix_t2.len, __vix_t2.ptr);\nexit(1);"), "{}", c_code);
+        assert!(c_code.contains("exit(__vix_t0);\n}"), "{}", c_code);
+        assert!(!codegen.types_compatible(&Type::Void, &Type::i32()));
+        assert!(codegen.types_compatible(&Type::Never, &Type::i32()));
+        assert!(codegen.types_compatible(&Type::Struct { name: "Point".into() }, &Type::Never));
+    }
+
+    #[test]
+    fn test_user_variable_named_like_a_temporary() {
+        let source = r#"
+func t1(): int32
+    return 1
+end
+
+func main(): int32
+    create t0 = 5
+    create total = t0 + t1()
+    return total
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("const int32_t var_t0 = __vix_t0;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t1 = t1();"), "{}", c_code);
+        assert!(!c_code.contains(" t0 "), "{}", c_code);
+    }
+
+    #[test]
+    fn test_arbitrary_width_integers() {
+        let source = r#"
+func pack(a: int(24), b: uint(12)): int32
+    x: int(24) = a
+    y: uint(12) = b
+    wide: int32 = x
+    return wide + y
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("int32_t pack(int32_t var_a, uint16_t var_b)"), "{}", c_code);
+        assert!(c_code.contains("const int32_t var_x = ((int32_t)((uint32_t)var_a << 8) >> 8);"), "{}", c_code);
+        assert!(c_code.contains("const uint16_t var_y = (var_b & 0xFFFu);"), "{}", c_code);
+        assert!(c_code.contains("const int32_t var_wide = (int32_t)var_x;"), "{}", c_code);
+        assert_eq!(Type::int(24, true).size_bits(&ArchConfig::x86_64()), 32);
+    }
+
+    #[test]
+    fn test_address_of_lvalues_and_functions() {
+        let source = r#"
+struct Point:
+    x = int32
+    y = int32
+end
+
+func add(a: int32, b: int32): int32
+    return a + b
+end
+
+func main(): int32
+    create n = 1
+    create p = Point(1, 2)
+    create nums = [1, 2, 3]
+    create pn = &n
+    create px = &p.x
+    create pe = &nums[1]
+    create f = &add
+    return f(1, 2)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("\nconst int32_t* __vix_t8 = &var_n;\nconst int32_t* var_pn = __vix_t8;"), "{}", c_code);
+        assert!(c_code.contains("\nconst int32_t* __vix_t9 = &var_p.x;"), "{}", c_code);
+        assert!(c_code.contains("\nconst int32_t* __vix_t11 = &var_nums[__vix_t10];"), "{}", c_code);
+        assert!(c_code.contains("int32_t (*const var_f)(int32_t, int32_t) = add;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_address_of_temporary_is_rejected() {
+        let (codegen, _) = generate("func one(): int32\n    return 1\nend\n\nfunc main(): int32\n    create p = &one()\n    return 0\nend\n");
+        assert!(codegen.diagnostics.diagnostics().iter().any(|d| d.code == "AddressOfRvalue"));
+    }
+
+    #[test]
+    fn test_read_and_write_through_deref() {
+        let source = r#"
+func bump(p: &mut int32)
+    *p = *p + 1
+end
+
+func main(): int32
+    mut n: int32 = 41
+    bump(&n)
+    create q = &n
+    create v = *q
+    return v
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("int32_t __vix_t0 = *var_p;"), "{}", c_code);
+        assert!(c_code.contains("*var_p = __vix_t2;"), "{}", c_code);
+        assert!(c_code.contains("int32_t __vix_t3 = *var_q;\nconst int32_t var_v = __vix_t3;"), "{}", c_code);
+        assert!(c_code.contains("\nint32_t* __vix_t1 = &var_n;\nbump(__vix_t1);"), "{}", c_code);
+        assert!(c_code.contains("int32_t* const var_q = __vix_t2;"), "{}", c_code);
+
+        let source = "func main(): int32\n    create n = 1\n    create p = &n\n    *p = 2\n    return n\nend\n";
+        let (codegen, _) = generate(source);
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "AssignThroughSharedRef").expect("write through & reported");
+        assert_eq!(error.context.primary_location.line, 4);
+    }
+
+    #[test]
+    fn test_deref_of_non_pointer_is_rejected() {
+        let (codegen, _) = generate("func main(): int32\n    create n = 1\n    return *n\nend\n");
+        let error = codegen.diagnostics.diagnostics().iter().find(|d| d.code == "DerefNonPointer").expect("deref of int reported");
+        assert!(error.message.contains("int32"), "{}", error.message);
+    }
+
+    #[test]
+    fn test_owned_values_are_freed_once_by_their_last_owner() {
+        let source = r#"
+func consume(s: ~str)
+end
+
+func take(s: ~str): int32
+    create t = s
+    consume(t)
+    return 0
+end
+
+func keep(items: ~int32[]): int32
+    return 1
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("static void consume(Slice_char var_s) {\nvix_free(var_s.ptr);"), "{}", c_code);
+        assert_eq!(c_code.matches("vix_free(var_s.ptr);").count(), 1, "{}", c_code);
+        assert!(!c_code.contains("vix_free(var_t"), "{}", c_code);
+        assert!(c_code.contains("vix_free(var_items.ptr);\nreturn"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_use_after_move_is_reported() {
+        let source = r#"
+func consume(s: ~str)
+end
+
+func twice(s: ~str)
+    consume(s)
+    consume(s)
+end
+"#;
+        let (codegen, _) = generate(source);
+        let errors: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "UseAfterMove")
+            .map(|d| d.message.clone())
+            .collect();
+        assert_eq!(errors, vec!["Use of moved value 's'"]);
+    }
+
+    #[test]
+    fn test_owned_params_only_take_owned_values() {
+        let source = r#"
+extern "C":
+    func malloc(n: usize): ^void
+end
+
+func consume(p: ~int32)
+    *p = 7
+    plan("got {}\n", *p)
+end
+
+func measure(s: ~str): usize
+    plan("{} has {} bytes\n", s, s.len())
+    return s.length()
+end
+
+func bad(): usize
+    create k = "x"
+    measure("literal")
+    return measure(k)
+end
+
+func main(): int32
+    create p = malloc(4) as ~int32
+    consume(p)
+    return 0
+end
+"#;
+        let (codegen, _) = generate(source);
+        let lines: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "TypeMismatch")
+            .map(|d| d.context.primary_location.line)
+            .collect();
+        assert_eq!(lines, vec![18, 19]);
+
+        let source = source.replace("    measure(\"literal\")\n    return measure(k)", "    return 0");
+        let (codegen, c_code) = generate(&source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        if let Some(stdout) = run_c(&c_code) {
+            assert_eq!(stdout, "got 7\n");
+        }
+    }
+
+    #[test]
+    fn test_move_of_outer_owned_in_loop_is_reported() {
+        let source = r#"
+func consume(s: ~str)
+end
+
+func spin(s: ~str)
+    while true do
+        consume(s)
+    end
+end
+"#;
+        let (codegen, _) = generate(source);
+        let lines: Vec<_> = codegen.diagnostics.diagnostics().iter()
+            .filter(|d| d.code == "MoveInLoop")
+            .map(|d| d.context.primary_location.line)
+            .collect();
+        assert_eq!(lines, vec![7]);
+    }
+
+    #[test]
+    fn test_branch_moves_free_on_the_other_paths() {
+        let source = r#"
+func consume(p: ~int32)
+end
+
+func pick(p: ~int32, flag: bool)
+    if flag then
+        consume(p)
+    end
+end
+
+func choose(q: ~int32, n: int32)
+    match n:
+        case 1:
+            consume(q)
+        default:
+            printf("other\n")
+    end
+end
+
+func drop(r: ~int32, p: ptr)
+    vix_free(r)
+    vix_free(p)
+end
+"#;
+        let (codegen, c_code) = generate(source);
+        assert!(!codegen.diagnostics.has_errors(), "{:?}", codegen.diagnostics.diagnostics());
+        assert!(c_code.contains("consume(var_p);\n}\nelse {\nvix_free(var_p);\n}"), "{}", c_code);
+        assert!(c_code.contains("printf(__vix_t1.ptr);\nvix_free(var_q);\nlabel_0: ;"), "{}", c_code);
+        assert!(c_code.contains("vix_free(var_r);\n"), "{}", c_code);
+        assert!(c_code.contains("vix_free(var_p);\n    return;"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_string_compare_checks_length_before_bytes() {
+        let source = r#"
+func check(): bool
+    a: str = "abc"
+    b: str = "abcd"
+    same: bool = a == b
+    less: bool = a < b
+    return same or less
+end
+"#;
+        let (_, c_code) = generate(source);
+        assert!(!c_code.contains("strcmp("), "{}", c_code);
+        assert!(c_code.contains("var_a.len == var_b.len && (var_a.len == 0 || memcmp(var_a.ptr, var_b.ptr, var_a.len) == 0)"), "{}", c_code);
+        assert!(c_code.contains("vix_str_cmp(var_a, var_b)"), "{}", c_code);
+        assert_eq!(c_code.matches("static int vix_str_cmp(").count(), 1, "{}", c_code);
+    }
+
+    #[test]
+    fn test_string_larger_than_scratch_buffer_is_heap_allocated() {
+        let chunk = "0123456789".repeat(900);
+        let source = format!(r#"
+func build(): str
+    mut big: str = "{chunk}"
+    big += "{chunk}"
+    return big + "!"
+end
+"#);
+        let (codegen, c_code) = generate(&source);
+        assert!(codegen.diagnostics.diagnostics().is_empty(), "{:?}", codegen.diagnostics.diagnostics());
+        for reset in ["offset = 0;", "extend_offset = 0;", "global_arena.offset = 0;"] {
+            assert!(!c_code.contains(&format!("{{\n        {}\n    }}", reset)), "{}", c_code);
+        }
+        assert!(c_code.contains("if (!buf || size > capacity - *offset) {\n        return (char*)vix_malloc(size);"), "{}", c_code);
+        assert_eq!(c_code.matches("static void* vix_malloc(size_t size) {").count(), 1, "{}", c_code);
+        assert!(c_code.contains("vix_str_append_inplace(&var_big, "), "{}", c_code);
+        assert!(c_code.contains("vix_str_concat_arena(var_big, "), "{}", c_code);
+    }
+
+    #[test]
+    fn test_progress_is_buffered_when_a_log_is_set() {
+        let source = "func main(): int32\n    return 0\nend\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
+
+        let mut codegen = Codegen::new(ArchConfig::x86_64(), source.to_string(), "test.vix".to_string());
+        codegen.progress_log = Some(Vec::new());
+        codegen.codegen_program_full(&program, &structs, &enums, &impls, &externs, &[], &[]).unwrap();
+
+        let log = codegen.progress_log.unwrap();
+        assert!(log.iter().any(|line| line.contains("Generating function code")), "{:?}", log);
+    }
+}
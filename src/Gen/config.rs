@@ -29,6 +29,10 @@ impl ArchConfig {
     }
     
     pub fn arm64() -> Self {
+        Self::aarch64()
+    }
+
+    pub fn aarch64() -> Self {
         Self {
             pointer_bits: 64,
             pointer_align: 8,
@@ -1,5 +1,8 @@
+// Codegen reports failures through its diagnostics, so its `Err` carries nothing.
+#[allow(clippy::result_unit_err)]
 pub mod codegen;
 pub mod config;
+#[allow(clippy::result_unit_err)]
 pub mod build;
 pub mod API;
 pub mod r#type;
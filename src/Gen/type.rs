@@ -6,6 +6,7 @@ pub struct TypeRegistry {
     pub ordered_definitions: Vec<String>,
     pub struct_definitions: HashMap<String, StructDefinition>,
     pub enum_definitions: HashMap<String, EnumDefinition>,
+    pub self_type: Option<String>,
 }
 
 pub struct StructDefinition {
@@ -32,6 +33,7 @@ impl TypeRegistry {
             ordered_definitions: Vec::new(),
             struct_definitions: HashMap::new(),
             enum_definitions: HashMap::new(),
+            self_type: None,
         }
     }
 
@@ -105,7 +107,7 @@ static inline void insert_{HM}({HM} *map, {KT} key, {VT} value) {{
     map->size++;
 }}
 
-static inline {VT}* get_{HM}({HM} *map, {KT} key) {{
+static inline const {VT}* get_{HM}(const {HM} *map, {KT} key) {{
     unsigned int index = hash_{HM}(key);
     unsigned int original = index;
     
@@ -243,7 +245,7 @@ pub fn generate_enum_definition(&mut self, name: &str, arch: &ArchConfig) -> Opt
         );
         
         self.generated_types.insert(type_id.clone(), def.clone());
-        println!("pushhing: {}", def.clone());
+        debug_log!("pushhing: {}", def.clone());
         self.ordered_definitions.push(def);
         type_id
     }
@@ -445,10 +447,30 @@ impl Type {
         matches!(self, Type::Void)
     }
 
+    /// Slices that carry their own `.ptr`, so `~T` of them is the slice itself rather than a pointer to it.
+    pub fn is_owned_buffer(&self) -> bool {
+        matches!(self, Type::Str { .. } | Type::Array { size: None, .. } | Type::Slice { .. })
+    }
+
     pub fn is_ptr(&self) -> bool {
         matches!(self, Type::Ptr(_) | Type::RawPtr(_) | Type::Owned(_) | Type::Ref(_) | Type::MutRef(_))
     }
 
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Int { .. } | Type::Usize)
+    }
+
+    /// Types C can test for truth directly, as in `if (x)`.
+    pub fn is_scalar(&self) -> bool {
+        match self {
+            Type::Bool | Type::Int { .. } | Type::Float { .. } | Type::Char { .. } | Type::Usize
+            | Type::ConstStr | Type::FnPtr { .. } | Type::FunctionPointer { .. } => true,
+            Type::Owned(inner) => !inner.is_owned_buffer(),
+            Type::Const(inner) => inner.is_scalar(),
+            other => other.is_ptr(),
+        }
+    }
+
     pub fn i8() -> Self { Self::Int { bits: 8, signed: true } }
     pub fn i16() -> Self { Self::Int { bits: 16, signed: true } }
     pub fn i32() -> Self { Self::Int { bits: 32, signed: true } }
@@ -499,10 +521,53 @@ impl Type {
         Type::Const(Box::new(self))
     }
 
+    pub fn resolve_self(&self, struct_name: &str) -> Type {
+        let resolve = |inner: &Type| Box::new(inner.resolve_self(struct_name));
+        match self {
+            Type::SelfType => Type::Struct { name: struct_name.to_string() },
+            Type::Ptr(inner) => Type::Ptr(resolve(inner)),
+            Type::RawPtr(inner) => Type::RawPtr(resolve(inner)),
+            Type::Owned(inner) => Type::Owned(resolve(inner)),
+            Type::Ref(inner) => Type::Ref(resolve(inner)),
+            Type::MutRef(inner) => Type::MutRef(resolve(inner)),
+            Type::Const(inner) => Type::Const(resolve(inner)),
+            Type::Option { inner } => Type::Option { inner: resolve(inner) },
+            Type::Result { ok, err } => Type::Result { ok: resolve(ok), err: resolve(err) },
+            Type::Array { element, size } => Type::Array { element: resolve(element), size: *size },
+            Type::Slice { element } => Type::Slice { element: resolve(element) },
+            Type::Tuple { fields } => Type::Tuple { fields: fields.iter().map(|f| f.resolve_self(struct_name)).collect() },
+            other => other.clone(),
+        }
+    }
+
+    /// The standard C width an integer of `bits` is stored in, e.g. 24 bits in 32.
+    pub fn int_storage_bits(bits: usize) -> usize {
+        if bits > 64 { bits } else { bits.next_power_of_two().max(8) }
+    }
+
+    /// Keeps `value` within a width C has no type for (an `int(24)` held in an `int32_t`)
+    /// by sign-extending or masking it; standard widths are returned unchanged.
+    pub fn wrap_int_width(&self, value: &str) -> String {
+        let Type::Int { bits, signed } = self else {
+            return value.to_string();
+        };
+        let storage = Type::int_storage_bits(*bits);
+        if storage == *bits {
+            return value.to_string();
+        }
+
+        let shift = storage - bits;
+        if *signed {
+            format!("((int{}_t)((uint{}_t){} << {}) >> {})", storage, storage, value, shift, shift)
+        } else {
+            format!("({} & 0x{:X}u)", value, (1u64 << bits) - 1)
+        }
+    }
+
     pub fn to_c_type(&self, arch: &ArchConfig, registry: &mut TypeRegistry) -> String {
         match self {
              
-            Type::Int { bits: 64, signed: false } => "size_t".to_string(),
+            Type::Int { bits: 64, signed: false } if arch.pointer_bits == 64 => "size_t".to_string(),
             
             Type::Int { bits, signed } => match (bits, signed) {
                 (8, true) => "int8_t".to_string(),
@@ -514,6 +579,8 @@ impl Type {
                 (16, false) => "uint16_t".to_string(),
                 (32, false) => "uint32_t".to_string(),
                 (128, false) => "unsigned __int128".to_string(),
+                (b, true) if *b <= 64 => format!("int{}_t", Type::int_storage_bits(*b)),
+                (b, false) if *b <= 64 => format!("uint{}_t", Type::int_storage_bits(*b)),
                 (b, true) => format!("int{}_t", b),
                 (b, false) => format!("uint{}_t", b),
             },
@@ -534,16 +601,16 @@ impl Type {
             Type::HashMap { key, value } => format!("HashMap_{}_{}", 
                 TypeRegistry::sanitize_type_name(&key.name()), 
                 TypeRegistry::sanitize_type_name(&value.name())),
-            Type::SelfType => "Self".to_string(),
+            Type::SelfType => registry.self_type.clone().unwrap_or_else(|| "Self".to_string()),
             Type::Bool => "bool".to_string(),
-            Type::Void => "void".to_string(),
+            Type::Void | Type::Never => "void".to_string(),
             Type::Ptr(inner) => format!("{}*", inner.to_c_type(arch, registry)),
             Type::RawPtr(inner) => format!("{}*", inner.to_c_type(arch, registry)),
             Type::ConstStr => registry.generate_slices(&Type::char8(), arch),
             Type::Str { .. } => registry.generate_slices(&Type::char8(), arch),
             Type::StrSlice { char_type, .. } => registry.generate_slices(char_type, arch),
             Type::Struct { name } => name.clone(),
-            Type::Array { element, .. } => registry.generate_slices(element, arch),
+            Type::Array { element, .. } | Type::Slice { element } => registry.generate_slices(element, arch),
             Type::Intersection { types } => types.first()
                 .map(|t| t.to_c_type(arch, registry))
                 .unwrap_or_else(|| "void".to_string()),
@@ -552,6 +619,7 @@ impl Type {
             Type::Variadic => "...".to_string(),
             Type::Any => "void*".to_string(),
             Type::Trait => "void*".to_string(),
+            Type::Owned(inner) if inner.is_owned_buffer() => inner.to_c_type(arch, registry),
             Type::Owned(inner) | Type::Ref(inner) | Type::MutRef(inner) => {
                 format!("{}*", inner.to_c_type(arch, registry))
             }
@@ -559,6 +627,11 @@ impl Type {
                 let param_types: Vec<String> = params.iter().map(|p| p.to_c_type(arch, registry)).collect();
                 format!("{} (*)({})", return_type.to_c_type(arch, registry), param_types.join(", "))
             },
+            Type::FunctionPointer { params, ret } => {
+                let param_types: Vec<String> = params.iter().map(|p| p.to_c_type(arch, registry)).collect();
+                let params_str = if param_types.is_empty() { "void".to_string() } else { param_types.join(", ") };
+                format!("{} (*)({})", ret.to_c_type(arch, registry), params_str)
+            },
             
             Type::Tuple { fields } => {
                 let names: Vec<String> = fields.iter().map(|f| TypeRegistry::sanitize_type_name(&f.name())).collect();
@@ -579,6 +652,17 @@ impl Type {
         }
     }
 
+    pub fn c_declarator(&self, name: &str, arch: &ArchConfig, registry: &mut TypeRegistry) -> String {
+        match self {
+            Type::FunctionPointer { params, ret } => {
+                let param_types: Vec<String> = params.iter().map(|p| p.to_c_type(arch, registry)).collect();
+                let params_str = if param_types.is_empty() { "void".to_string() } else { param_types.join(", ") };
+                format!("{} (*{})({})", ret.to_c_type(arch, registry), name, params_str)
+            }
+            _ => format!("{} {}", self.to_c_type(arch, registry), name),
+        }
+    }
+
     pub fn size_bits(&self, arch: &ArchConfig) -> usize {
         match self {
             Type::ConstStr => arch.pointer_bits,
@@ -589,14 +673,15 @@ impl Type {
                 let entry_size = key.size_bits(arch) + value.size_bits(arch) + 8;
                 256 * entry_size + arch.pointer_bits
             }
-            Type::Int { bits, .. } | Type::Float { bits } | Type::Char { bits, .. } => *bits,
+            Type::Int { bits, .. } => Type::int_storage_bits(*bits),
+            Type::Float { bits } | Type::Char { bits, .. } => *bits,
             Type::Bool => 8,
-            Type::Void | Type::Variadic => 0,
-            Type::Ptr(_) | Type::RawPtr(_) | Type::FnPtr { .. } => arch.pointer_bits,
+            Type::Void | Type::Never | Type::Variadic => 0,
+            Type::Ptr(_) | Type::RawPtr(_) | Type::FnPtr { .. } | Type::FunctionPointer { .. } => arch.pointer_bits,
             Type::Str { len_type } | Type::StrSlice { length_type: len_type, .. } => {arch.pointer_bits + len_type.size_bits(arch)}
             Type::Struct { .. } => arch.pointer_bits,
             Type::Array { element, size: Some(size) } => element.size_bits(arch) * size,
-            Type::Array { .. } => arch.pointer_bits + arch.pointer_bits,
+            Type::Array { .. } | Type::Slice { .. } => arch.pointer_bits + arch.pointer_bits,
             Type::Tuple { fields } => fields.iter().map(|f| f.size_bits(arch)).sum(),
             Type::Option { inner } => 8 + inner.size_bits(arch),
             Type::SelfType => 10,
@@ -637,6 +722,7 @@ impl Type {
             Type::Char { bits: 32, .. } => "char32".to_string(),
             Type::Char { bits, .. } => format!("char{}", bits),
             Type::Void => "void".to_string(),
+            Type::Never => "never".to_string(),
             Type::Ptr(inner) => format!("*{}", inner.name()),
             Type::RawPtr(inner) => format!("^{}", inner.name()),
             Type::Str { .. } => "char8".to_string(),
@@ -644,6 +730,7 @@ impl Type {
             Type::Struct { name } => name.clone(),
             Type::Array { element, size: Some(s) } => format!("{}[{}]", element.name(), s),
             Type::Array { element, size: None } => format!("{}[]", element.name()),
+            Type::Slice { element } => format!("&[{}]", element.name()),
             Type::SelfType => "Self".to_string(),
             Type::Option { inner } => format!("Option<{}>", inner.name()),
             Type::Result { ok, err } => format!("Result<{}, {}>", ok.name(), err.name()),
@@ -673,6 +760,10 @@ impl Type {
                 let param_names: Vec<String> = params.iter().map(|p| p.name()).collect();
                 format!("fn({}) -> {}", param_names.join(", "), return_type.name())
             }
+            Type::FunctionPointer { params, ret } => {
+                let param_names: Vec<String> = params.iter().map(|p| p.name()).collect();
+                format!("func({}): {}", param_names.join(", "), ret.name())
+            }
         }
     }
 
@@ -25,6 +25,7 @@ impl std::fmt::Display for LibraryError {
             LibraryError::FileReadError(path, msg) => write!(f, "File read error at {:?}: {}", path, msg),
             LibraryError::JsonParseError(path, msg) => write!(f, "JSON parse error at {:?}: {}", path, msg),
             LibraryError::MissingLibraryFiles(lib, files) => write!(f, "Missing files in {}: {:?}", lib, files),
+            LibraryError::MissingSymbol(symbol, lib) => write!(f, "Symbol '{}' not found in library '{}'", symbol, lib),
         }
     }
 }
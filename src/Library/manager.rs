@@ -12,6 +12,8 @@ pub struct FootprintPack {
     pub function_signatures: Vec<FunctionSignature>,
     pub includes: Vec<String>,
     pub functions: Vec<String>,
+    #[serde(default)]
+    pub constants: Vec<String>,
     pub source_library: String,
 }
 
@@ -43,7 +45,8 @@ pub struct IncludeSection {
 pub struct PackageInformation {
     pub name: String,
     pub version: String,
-    pub pulicher: String,
+    #[serde(alias = "pulicher")]
+    pub publisher: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -67,6 +70,57 @@ pub struct LibraryMetadata {
     pub verified_syntax: Vec<PathBuf>,
     pub verified_errors: Vec<PathBuf>,
     pub includes: Vec<String>,
+    /// Filled on first use by [`LibraryMetadata::parsed`].
+    pub parsed: OnceLock<Result<ParsedLibrary, LibraryError>>,
+}
+
+/// A library's Vix sources, lexed and parsed as one unit.
+pub struct ParsedLibrary {
+    pub source: String,
+    pub tokens: Vec<Token>,
+    pub program: Program,
+    pub structs: Vec<StructDef>,
+    pub enums: Vec<EnumDef>,
+    pub externs: Vec<ExternDecl>,
+    pub impls: Vec<ImplBlock>,
+}
+
+impl ParsedLibrary {
+    fn from_source(source: String) -> Result<Self, LibraryError> {
+        let mut lexer = Lexer::new(&source);
+        let tokens = lexer.tokenize();
+
+        if !lexer.errors.is_empty() {
+            let messages: Vec<&str> = lexer.errors.iter().map(|error| error.message.as_str()).collect();
+            return Err(LibraryError::ParseError(format!("Lexer failed: {}", messages.join("; "))));
+        }
+
+        let parser = Parser::new(tokens.clone(), source.clone(), lexer.spans.clone());
+        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
+        Ok(ParsedLibrary { source, tokens, program, structs, enums, externs, impls })
+    }
+}
+
+impl LibraryMetadata {
+    /// The library's `.x`/`.vix` scripts, read and parsed on first call. Symbol
+    /// extraction, signature extraction and codegen all share this one parse.
+    pub fn parsed(&self) -> Result<&ParsedLibrary, LibraryError> {
+        self.parsed
+            .get_or_init(|| {
+                let mut source = String::new();
+                for script_path in &self.verified_scripts {
+                    if matches!(script_path.extension().and_then(|s| s.to_str()), Some("x" | "vix")) {
+                        let script = fs::read_to_string(script_path)
+                            .map_err(|e| LibraryError::FileReadError(script_path.clone(), e.to_string()))?;
+                        source.push_str(&script);
+                        source.push_str("\n\n");
+                    }
+                }
+                ParsedLibrary::from_source(source)
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
 }
 
 pub struct PackageInfo {
@@ -82,7 +136,7 @@ pub struct DependencyInfo {
     pub path: PathBuf,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LibraryError {
     MissingField(&'static str),
     MissingSection(&'static str),
@@ -94,16 +148,46 @@ pub enum LibraryError {
     FileReadError(PathBuf, String),
     JsonParseError(PathBuf, String),
     MissingLibraryFiles(String, Vec<String>),
+    MissingSymbol(String, String),
 }
 
 impl LibraryManager {
     pub fn get_vix_path() -> Result<PathBuf, LibraryError> {
-        Ok(PathBuf::from("C:/Users/Client/Vix"))
+        let vix_home = std::env::var_os("VIX_HOME").filter(|v| !v.is_empty()).map(PathBuf::from);
+        let platform_home = if cfg!(windows) {
+            std::env::var_os("APPDATA").filter(|v| !v.is_empty()).map(|appdata| PathBuf::from(appdata).join("Vix"))
+        } else {
+            std::env::home_dir().map(|home| home.join(".vix"))
+        };
+        Self::find_vix_path(vix_home, platform_home)
+    }
+
+    /// The first of `vix_home` (from `VIX_HOME`) and the platform default that is a directory.
+    fn find_vix_path(vix_home: Option<PathBuf>, platform_home: Option<PathBuf>) -> Result<PathBuf, LibraryError> {
+        let candidates: Vec<PathBuf> = vix_home.into_iter().chain(platform_home).collect();
+
+        if let Some(path) = candidates.iter().find(|p| p.is_dir()) {
+            return Ok(path.clone());
+        }
+
+        let tried = candidates.iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>();
+
+        Err(LibraryError::PathNotFound(if tried.is_empty() {
+            "Vix home could not be resolved; set VIX_HOME to your Vix installation directory".to_string()
+        } else {
+            format!(
+                "Vix home not found (tried: {}); set VIX_HOME to your Vix installation directory",
+                tried.join(", ")
+            )
+        }))
     }
 
 
       pub fn generate_all_library_code(
         library_metadata: &[LibraryMetadata],
+        arch: &ArchConfig,
     ) -> Result<Vec<(String, String)>, LibraryError> {
         let mut library_codes = Vec::new();
 
@@ -111,7 +195,7 @@ impl LibraryManager {
 
          
         println!("   {} Generating core library code", "success:".bright_cyan());
-        let (core_name, core_code) = Self::generate_core_library_code()?;
+        let (core_name, core_code) = Self::generate_core_library_code(arch)?;
         library_codes.push((core_name, core_code));
 
          
@@ -119,7 +203,7 @@ impl LibraryManager {
             println!("   {} Generating library code: {}", "success:".bright_cyan(), lib_meta.name);
             
             let lib_name = format!("{}-{}", lib_meta.name, lib_meta.version);
-            let c_code = Self::generate_library_code(lib_meta)?;
+            let c_code = Self::generate_library_code(lib_meta, arch)?;
             library_codes.push((lib_name, c_code));
 
             println!("   {} C code generated for: {}", "success:".green(), lib_meta.name);
@@ -130,7 +214,7 @@ impl LibraryManager {
     }
 
      
-    fn generate_core_library_code() -> Result<(String, String), LibraryError> {
+    fn generate_core_library_code(arch: &ArchConfig) -> Result<(String, String), LibraryError> {
         let vix_path = Self::get_vix_path()?;
         let core_path = vix_path.join("Library/core");
 
@@ -139,7 +223,7 @@ impl LibraryManager {
         }
 
         let mut all_scripts = Vec::new();
-        Self::collect_vix_files(&core_path, &mut all_scripts)?;
+        Self::collect_vix_files(&core_path, &core_path, &mut all_scripts)?;
 
         let mut all_source = String::new();
         for script in &all_scripts {
@@ -159,8 +243,7 @@ impl LibraryManager {
         let parser = Parser::new(tokens, all_source.clone(), lexer.spans.clone());
         let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
 
-        let arch = ArchConfig::x86_64();
-        let mut codegen = Codegen::new(arch, all_source, "core".to_string());
+        let mut codegen = Codegen::new(arch.clone(), all_source, "core".to_string());
 
         let c_code = codegen.codegen_library(
             &program, 
@@ -175,19 +258,14 @@ impl LibraryManager {
     }
 
      
-    fn generate_library_code(lib_metadata: &LibraryMetadata) -> Result<String, LibraryError> {
-        let mut all_source = String::new();
+    fn generate_library_code(lib_metadata: &LibraryMetadata, arch: &ArchConfig) -> Result<String, LibraryError> {
+        let mut has_vix = false;
         
         for script_path in &lib_metadata.verified_scripts {
             let ext = script_path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
             match ext {
-                "x" | "vix" => {
-                    let source = fs::read_to_string(script_path)
-                        .map_err(|e| LibraryError::FileReadError(script_path.clone(), e.to_string()))?;
-                    all_source.push_str(&source);
-                    all_source.push_str("\n\n");
-                }
+                "x" | "vix" => has_vix = true,
                 "c" | "cpp" => {
                      
                     return fs::read_to_string(script_path)
@@ -197,30 +275,19 @@ impl LibraryManager {
             }
         }
 
-        if all_source.is_empty() {
+        if !has_vix {
             return Ok(String::new());
         }
 
-         
-        let mut lexer = Lexer::new(&all_source);
-        let tokens = lexer.tokenize();
-
-        if !lexer.errors.is_empty() {
-            return Err(LibraryError::ParseError("Lexer failed".to_string()));
-        }
-
-        let parser = Parser::new(tokens, all_source.clone(), lexer.spans.clone());
-        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
-        
-        let arch = ArchConfig::x86_64();
-        let mut codegen = Codegen::new(arch, all_source, "library".to_string());
+        let parsed = lib_metadata.parsed()?;
+        let mut codegen = Codegen::new(arch.clone(), parsed.source.clone(), "library".to_string());
         
         let c_code = codegen.codegen_library(
-            &program, 
-            &structs, 
-            &enums, 
-            &impls, 
-            &externs, 
+            &parsed.program, 
+            &parsed.structs, 
+            &parsed.enums, 
+            &parsed.impls, 
+            &parsed.externs, 
             &lib_metadata.includes
         ).map_err(|e| LibraryError::ParseError(format!("Codegen failed: {}", e)))?;
 
@@ -237,10 +304,10 @@ impl LibraryManager {
         let vix_path = Self::get_vix_path()?;
         let core_path = vix_path.join("Library/core");
         let mut core_scripts = Vec::new();
-        Self::collect_vix_files(&core_path, &mut core_scripts)?;
+        Self::collect_vix_files(&core_path, &core_path, &mut core_scripts)?;
         
-        let (functions, classes) = Self::extract_core_symbols(&core_scripts)?;
-        let function_signatures = Self::extract_core_function_signatures(&core_scripts)?;
+        let (functions, classes, constants) = Self::extract_core_symbols(&core_scripts)?;
+        let function_signatures = Self::extract_core_function_signatures(&core_scripts, &TargetOS::current().arch())?;
 
         footprint_packs.push(FootprintPack {
             name: "core".to_string(),
@@ -249,14 +316,15 @@ impl LibraryManager {
             classes,
             function_signatures,
             functions,
+            constants,
             includes: vec![],
             source_library: "core".to_string(),  
         });
 
          
         for lib_meta in library_metadata {
-            let (functions, classes) = Self::extract_library_symbols(lib_meta)?;
-            let function_signatures = Self::extract_function_signatures(lib_meta)?;
+            let (functions, classes, constants) = Self::extract_library_symbols(lib_meta)?;
+            let function_signatures = Self::extract_function_signatures(lib_meta, &TargetOS::current().arch())?;
 
             footprint_packs.push(FootprintPack {
                 name: lib_meta.name.clone(),
@@ -265,6 +333,7 @@ impl LibraryManager {
                 classes,
                 function_signatures,
                 functions,
+                constants,
                 includes: lib_meta.includes.clone(),
                 source_library: format!("{}-{}", lib_meta.name, lib_meta.version),
             });
@@ -295,22 +364,7 @@ impl LibraryManager {
         for lib_name in required_libs {
             println!("   {} Processing library: {}", "success:".bright_cyan(), lib_name);
 
-            let lib_dirs: Vec<_> = fs::read_dir(&library_path)
-                .map_err(|e| LibraryError::FileReadError(library_path.clone(), e.to_string()))?
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let dir_name = e.file_name();
-                    let dir_str = dir_name.to_str().unwrap_or("");
-                    dir_str.starts_with(&lib_name) || dir_str == lib_name
-                })
-                .collect();
-
-            if lib_dirs.is_empty() {
-                eprintln!("   {} Library not found: {}", "Error:".red(), lib_name);
-                return Err(LibraryError::PathNotFound(lib_name));
-            }
-
-            let lib_dir = lib_dirs[0].path();
+            let lib_dir = Self::find_library_dir(&library_path, &lib_name)?;
             let package_json_path = lib_dir.join("package.json");
 
             if !package_json_path.exists() {
@@ -336,13 +390,14 @@ impl LibraryManager {
             let metadata = LibraryMetadata {
                 name: package_json.information.name.clone(),
                 version: package_json.information.version.clone(),
-                publisher: package_json.information.pulicher.clone(),
+                publisher: package_json.information.publisher.clone(),
                 path: lib_dir,
                 package_json: package_json.clone(),
                 verified_scripts,
                 verified_syntax: Vec::new(),
                 verified_errors: Vec::new(),
                 includes: package_json.include.clang.clone(),
+                parsed: OnceLock::new(),
             };
 
             library_metadata.push(metadata);
@@ -362,37 +417,80 @@ impl LibraryManager {
 
          
         println!("   {} Compiling core library", "success:".bright_cyan());
-        let core_pack = Self::load_core_library(target_os)?;
+        let core_pack = Self::load_core_library(target_os, false, false)?;
         footprint_packs.push(core_pack);
 
          
-        for lib_meta in library_metadata {
-            println!("   {} Compiling library: {}", "success:".bright_cyan(), lib_meta.name);
-            
-            let lib_manager = LibraryManager;
-            let binary_path = lib_manager.compile_library(lib_meta, target_os)?;
+        let binary_dir = Self::get_library_bin_dir()?;
+        let code_dir = Self::get_library_code_dir()?;
+        footprint_packs.extend(Self::build_footprint_packs(library_metadata, target_os, &binary_dir, &code_dir, false)?);
 
-            let (functions, classes) = Self::extract_library_symbols(lib_meta)?;
-            let function_signatures = Self::extract_function_signatures(lib_meta)?;
+        Self::save_footprint_libraries(&footprint_packs)?;
+        println!("   {} All libraries compiled successfully\n", "success:".green());
 
-            footprint_packs.push(FootprintPack {
-                name: lib_meta.name.clone(),
-                version: lib_meta.version.clone(),
-                publisher: lib_meta.publisher.clone(),
-                classes,
-                function_signatures,
-                functions,
-                includes: lib_meta.includes.clone(),
-                source_library: binary_path.to_string_lossy().to_string(),
-            });
+        Ok(footprint_packs)
+    }
 
-            println!("   {} Binary created: {}", "success:".green(), binary_path.display());
+    fn build_footprint_packs(
+        library_metadata: &[LibraryMetadata],
+        target_os: Option<TargetOS>,
+        binary_dir: &Path,
+        code_dir: &Path,
+        dry_run: bool,
+    ) -> Result<Vec<FootprintPack>, LibraryError> {
+        library_metadata
+            .par_iter()
+            .map(|lib_meta| {
+                let mut log = vec![format!("   {} Compiling library: {}", "success:".bright_cyan(), lib_meta.name)];
+                let pack = Self::build_footprint_pack(lib_meta, target_os, binary_dir, code_dir, dry_run, &mut log);
+                Self::flush_log(&log);
+                pack
+            })
+            .collect()
+    }
+
+    /// Compiles one library and extracts its symbols. Runs on the rayon pool, so
+    /// progress goes to `log` and is printed by the caller in one piece.
+    /// A dry run writes the library's C but does not run clang.
+    fn build_footprint_pack(
+        lib_meta: &LibraryMetadata,
+        target_os: Option<TargetOS>,
+        binary_dir: &Path,
+        code_dir: &Path,
+        dry_run: bool,
+        log: &mut Vec<String>,
+    ) -> Result<FootprintPack, LibraryError> {
+        let binary_path = Self::compile_library_into(lib_meta, target_os, binary_dir, code_dir, dry_run, log)?;
+        if !dry_run {
+            log.push(format!("   {} Binary created: {}", "success:".green(), binary_path.display()));
         }
 
-        Self::save_footprint_libraries(&footprint_packs)?;
-        println!("   {} All libraries compiled successfully\n", "success:".green());
+        let (functions, classes, constants) = Self::extract_library_symbols(lib_meta)?;
+        let arch = target_os.unwrap_or_else(TargetOS::current).arch();
+        let function_signatures = Self::collect_function_signatures(lib_meta, &arch, log)?;
 
-        Ok(footprint_packs)
+        log.push(format!("   {} Extracted {} functions, {} classes",
+            "success:".bright_black(), functions.len(), classes.len()));
+
+        Ok(FootprintPack {
+            name: lib_meta.name.clone(),
+            version: lib_meta.version.clone(),
+            publisher: lib_meta.publisher.clone(),
+            classes,
+            function_signatures,
+            functions,
+            constants,
+            includes: lib_meta.includes.clone(),
+            source_library: binary_path.to_string_lossy().to_string(),
+        })
+    }
+
+    fn flush_log(log: &[String]) {
+        use std::io::Write;
+        let mut out = std::io::stdout().lock();
+        for line in log {
+            let _ = writeln!(out, "{}", line);
+        }
     }
 
      
@@ -407,13 +505,13 @@ impl LibraryManager {
             let vix_path = Self::get_vix_path()?;
             let core_path = vix_path.join("Library/core");
             let mut core_scripts = Vec::new();
-            Self::collect_vix_files(&core_path, &mut core_scripts)?;
-            all_signatures.extend(Self::extract_core_function_signatures(&core_scripts)?);
+            Self::collect_vix_files(&core_path, &core_path, &mut core_scripts)?;
+            all_signatures.extend(Self::extract_core_function_signatures(&core_scripts, &TargetOS::current().arch())?);
         }
 
          
         for lib_meta in library_metadata {
-            all_signatures.extend(Self::extract_function_signatures(lib_meta)?);
+            all_signatures.extend(Self::extract_function_signatures(lib_meta, &TargetOS::current().arch())?);
         }
 
         Ok(all_signatures)
@@ -421,7 +519,7 @@ impl LibraryManager {
 
 
 
-     pub fn load_core_library(target_os: Option<TargetOS>) -> Result<FootprintPack, LibraryError> {
+     pub fn load_core_library(target_os: Option<TargetOS>, dry_run: bool, quiet: bool) -> Result<FootprintPack, LibraryError> {
         let vix_path = Self::get_vix_path()?;
         let core_path = vix_path.join("Library/core");
 
@@ -429,19 +527,26 @@ impl LibraryManager {
             return Err(LibraryError::PathNotFound("core library not found".to_string()));
         }
 
-        println!("   {} Loading core library from: {}", "success:".bright_cyan(), core_path.display());
+        let mut log = vec![format!("   {} Loading core library from: {}", "success:".bright_cyan(), core_path.display())];
 
         let mut all_scripts = Vec::new();
-        Self::collect_vix_files(&core_path, &mut all_scripts)?;
-
-        println!("   {} Found {} core library files", "success:".green(), all_scripts.len());
+        Self::collect_vix_files(&core_path, &core_path, &mut all_scripts)?;
 
-        let binary_path = Self::compile_core_library(&all_scripts, target_os)?;
-        let (functions, classes) = Self::extract_core_symbols(&all_scripts)?;
-        let function_signatures = Self::extract_core_function_signatures(&all_scripts)?;
+        log.push(format!("   {} Found {} core library files", "success:".green(), all_scripts.len()));
 
-        println!("   {} Core library loaded: {} functions, {} classes", 
-            "success:".green(), functions.len(), classes.len());
+        let binary_path = Self::compile_core_library(&all_scripts, target_os, dry_run, &mut log);
+        if !quiet {
+            Self::flush_log(&log);
+        }
+        let binary_path = binary_path?;
+        let (functions, classes, constants) = Self::extract_core_symbols(&all_scripts)?;
+        let arch = target_os.unwrap_or_else(TargetOS::current).arch();
+        let function_signatures = Self::extract_core_function_signatures(&all_scripts, &arch)?;
+
+        if !quiet {
+            println!("   {} Core library loaded: {} functions, {} classes", 
+                "success:".green(), functions.len(), classes.len());
+        }
 
         Ok(FootprintPack {
             name: "core".to_string(),
@@ -450,13 +555,33 @@ impl LibraryManager {
             classes,
             function_signatures,
             functions,
+            constants,
             includes: vec![],
             source_library: binary_path.to_string_lossy().to_string(),
         })
     }
 
-     
-    fn collect_vix_files(dir: &Path, scripts: &mut Vec<PathBuf>) -> Result<(), LibraryError> {
+    /// Collects the `.vix`/`.x` sources under `dir`, skipping hidden directories
+    /// and any path listed in the `.vixignore` file at `root`.
+    fn collect_vix_files(root: &Path, dir: &Path, scripts: &mut Vec<PathBuf>) -> Result<(), LibraryError> {
+        let ignore = Self::load_vixignore(root);
+        Self::walk_vix_files(root, dir, &ignore, scripts)
+    }
+
+    /// Reads `.vixignore`: one name or root-relative path per line, `#` starts a comment.
+    fn load_vixignore(root: &Path) -> Vec<String> {
+        fs::read_to_string(root.join(".vixignore"))
+            .map(|content| {
+                content
+                    .lines()
+                    .map(|line| line.split('#').next().unwrap_or("").trim().trim_matches('/').to_string())
+                    .filter(|line| !line.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn walk_vix_files(root: &Path, dir: &Path, ignore: &[String], scripts: &mut Vec<PathBuf>) -> Result<(), LibraryError> {
         if !dir.is_dir() {
             return Ok(());
         }
@@ -467,21 +592,27 @@ impl LibraryManager {
         for entry in entries {
             let entry = entry.map_err(|e| LibraryError::FileReadError(dir.to_path_buf(), e.to_string()))?;
             let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let relative = path.strip_prefix(root).unwrap_or(&path).to_string_lossy().replace('\\', "/");
+            if ignore.iter().any(|pattern| *pattern == name || *pattern == relative) {
+                continue;
+            }
 
             if path.is_dir() {
-                Self::collect_vix_files(&path, scripts)?;
-            } else if let Some(ext) = path.extension() {
-                if ext == "vix" || ext == "x" {
+                if !name.starts_with('.') {
+                    Self::walk_vix_files(root, &path, ignore, scripts)?;
+                }
+            } else if let Some(ext) = path.extension()
+                && (ext == "vix" || ext == "x") {
                     scripts.push(path);
                 }
-            }
         }
 
         Ok(())
     }
     
 
-    fn extract_core_function_signatures(scripts: &[PathBuf]) -> Result<Vec<FunctionSignature>, LibraryError> {
+    fn extract_core_function_signatures(scripts: &[PathBuf], arch: &ArchConfig) -> Result<Vec<FunctionSignature>, LibraryError> {
         let mut signatures = Vec::new();
 
         for script_path in scripts {
@@ -497,10 +628,10 @@ impl LibraryManager {
             for func in &program.functions {
                 if func.is_public {
                     let mut registry = TypeRegistry::new();
-                    let return_type = func.return_type.to_c_type(&ArchConfig::x86_64(), &mut registry);
+                    let return_type = func.return_type.to_c_type(arch, &mut registry);
                     
                     let parameters: Vec<(String, String)> = func.params.iter()
-                        .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(&ArchConfig::x86_64(), &mut registry)))
+                        .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(arch, &mut registry)))
                         .collect();
 
                     signatures.push(FunctionSignature {
@@ -515,22 +646,22 @@ impl LibraryManager {
             
             
             for module in &program.modules {
-                if let Stmt::ModuleDef { name: module_name, body, is_public } = module {
+                if let Stmt::ModuleDef { name: module_name, body, is_public, .. } = module {
                     if !is_public {
                         continue;
                     }
                     
                     for stmt in body {
-                        if let Stmt::Function(func) = stmt {
-                            if func.is_public {
+                        if let Stmt::Function(func) = stmt.node()
+                            && func.is_public {
                                 
                                 let prefixed_name = format!("{}_{}", module_name, func.name);
                                 
                                 let mut registry = TypeRegistry::new();
-                                let return_type = func.return_type.to_c_type(&ArchConfig::x86_64(), &mut registry);
+                                let return_type = func.return_type.to_c_type(arch, &mut registry);
                                 
                                 let parameters: Vec<(String, String)> = func.params.iter()
-                                    .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(&ArchConfig::x86_64(), &mut registry)))
+                                    .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(arch, &mut registry)))
                                     .collect();
 
                                 
@@ -541,7 +672,6 @@ impl LibraryManager {
                                     abi: "c".to_string(),
                                 });
                             }
-                        }
                     }
                 }
             }
@@ -551,9 +681,12 @@ impl LibraryManager {
     }
 
     
-    fn extract_core_symbols(scripts: &[PathBuf]) -> Result<(Vec<String>, Vec<String>), LibraryError> {
+    /// Returns the public functions, classes and constants declared by the core scripts.
+    #[allow(clippy::type_complexity)]
+    fn extract_core_symbols(scripts: &[PathBuf]) -> Result<(Vec<String>, Vec<String>, Vec<String>), LibraryError> {
         let mut functions = Vec::new();
-        let mut classes = Vec::new();
+        let classes = Vec::new();
+        let mut constants = Vec::new();
 
         for script_path in scripts {
             let source = fs::read_to_string(script_path)
@@ -571,27 +704,30 @@ impl LibraryManager {
                 }
             }
 
+            constants.extend(program.constants.iter()
+                .filter(|constant| constant.is_public)
+                .map(|constant| constant.name.clone()));
+
             
             for module in &program.modules {
-                if let Stmt::ModuleDef { name: module_name, body, is_public } = module {
+                if let Stmt::ModuleDef { name: module_name, body, is_public, .. } = module {
                     if !is_public {
                         continue;
                     }
 
                     for stmt in body {
-                        if let Stmt::Function(func) = stmt {
-                            if func.is_public {
+                        if let Stmt::Function(func) = stmt.node()
+                            && func.is_public {
                                 
                                 let prefixed_name = format!("{}_{}", module_name, func.name);
                                 functions.push(prefixed_name);
                             }
-                        }
                     }
                 }
             }
         }
 
-        Ok((functions, classes))
+        Ok((functions, classes, constants))
     }
         
     pub fn load_footprint_libraries() -> Result<Vec<FootprintPack>, LibraryError> {
@@ -623,6 +759,7 @@ impl LibraryManager {
         output_path: &PathBuf,
         lang: &str,
         _target_os: Option<TargetOS>,
+        optimization: OptimizationLevel,
     ) -> Result<PathBuf, LibraryError> {
         let mut cmd = Command::new("clang");
         if lang == "cpp" {
@@ -634,7 +771,7 @@ impl LibraryManager {
            .arg(source_path)
            .arg("-o")
            .arg(output_path)
-           .arg("-O3")
+           .args(optimization.clang_flags())
            .arg("-std=c17");
          
 
@@ -642,7 +779,7 @@ impl LibraryManager {
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(LibraryError::ParseError(format!("Compilation failed: {}", stderr)));
+            return Err(LibraryError::ParseError(format!("Compilation failed: {}", Clang::render_c_diagnostics(&stderr))));
         }
 
         Ok(output_path.clone())
@@ -652,12 +789,14 @@ impl LibraryManager {
         source_path: &PathBuf,
         output_path: &PathBuf,
         _target_os: Option<TargetOS>,
+        optimization: OptimizationLevel,
     ) -> Result<PathBuf, LibraryError> {
         let obj_path = output_path.with_extension("o");
 
          
         let status = Command::new("llc")
             .arg(source_path)
+            .arg(optimization.llc_flag())
             .arg("-filetype=obj")
             .arg("-o")
             .arg(&obj_path)
@@ -698,18 +837,7 @@ impl LibraryManager {
         let library_path = vix_path.join("library");
 
         for lib_name in required_libs {
-            let lib_dirs: Vec<_> = fs::read_dir(&library_path)
-                .map_err(|e| LibraryError::FileReadError(library_path.clone(), e.to_string()))?
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_name().to_str().unwrap_or("").starts_with(&lib_name))
-                .collect();
-
-            if lib_dirs.is_empty() {
-                eprintln!("   {} Library not found: {}", "Error:".red(), lib_name);
-                return Err(LibraryError::PathNotFound(lib_name));
-            }
-
-            let lib_dir = lib_dirs[0].path();
+            let lib_dir = Self::find_library_dir(&library_path, &lib_name)?;
             let package_json_path = lib_dir.join("package.json");
 
             if !package_json_path.exists() {
@@ -730,28 +858,31 @@ impl LibraryManager {
             let lib_metadata = LibraryMetadata {
                 name: package_json.information.name.clone(),
                 version: package_json.information.version.clone(),
-                publisher: package_json.information.pulicher.clone(),
+                publisher: package_json.information.publisher.clone(),
                 path: lib_dir,
                 package_json: package_json.clone(),
                 verified_scripts,
                 verified_syntax: Vec::new(),
                 verified_errors: Vec::new(),
                 includes: package_json.include.clang.clone(),
+                parsed: OnceLock::new(),
             };
 
             let binary_path = Self.compile_library(&lib_metadata, target_os)?;
 
              
-            let (functions, classes) = Self::extract_library_symbols(&lib_metadata)?;
-            let function_signatures = Self::extract_function_signatures(&lib_metadata)?;
+            let (functions, classes, constants) = Self::extract_library_symbols(&lib_metadata)?;
+            let arch = target_os.unwrap_or_else(TargetOS::current).arch();
+            let function_signatures = Self::extract_function_signatures(&lib_metadata, &arch)?;
 
             footprint_packs.push(FootprintPack {
                 name: package_json.information.name,
                 version: package_json.information.version,
-                publisher: package_json.information.pulicher,
+                publisher: package_json.information.publisher,
                 classes,
                 function_signatures,  
                 functions,
+                constants,
                 includes: package_json.include.clang.clone(),
                 source_library: binary_path.to_string_lossy().to_string(),
             });
@@ -783,9 +914,37 @@ impl LibraryManager {
     }
 
 
+    /// Library names are matched case-insensitively so an import resolves the same
+    /// directory on every filesystem; an exact name wins over a versioned prefix.
+    fn find_library_dir(library_path: &Path, lib_name: &str) -> Result<PathBuf, LibraryError> {
+        let wanted = lib_name.to_lowercase();
+        let mut candidates: Vec<PathBuf> = fs::read_dir(library_path)
+            .map_err(|e| LibraryError::FileReadError(library_path.to_path_buf(), e.to_string()))?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_str().unwrap_or("").to_lowercase().starts_with(&wanted))
+            .map(|e| e.path())
+            .collect();
+        candidates.sort();
+
+        let exact = candidates.iter().position(|p| {
+            p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.eq_ignore_ascii_case(lib_name))
+        });
+
+        match exact.or((!candidates.is_empty()).then_some(0)) {
+            Some(idx) => Ok(candidates.swap_remove(idx)),
+            None => {
+                eprintln!("   {} Library not found: {}", "Error:".red(), lib_name);
+                Err(LibraryError::PathNotFound(lib_name.to_string()))
+            }
+        }
+    }
+
+
     pub fn process_imports_from_decls(
         import_decls: &[ImportDecl],
         target_os: Option<TargetOS>,
+        dry_run: bool,
+        quiet: bool,
     ) -> Result<Vec<FootprintPack>, LibraryError> {
         let imports = Self::extract_imports_from_decls(import_decls);
         let mut required_libs = HashSet::new();
@@ -796,174 +955,163 @@ impl LibraryManager {
         
         for (lib_name, _symbol) in imports {
             if lib_name != "core" {
-                required_libs.insert(lib_name.to_lowercase());  
+                required_libs.insert(lib_name);
             }
         }
 
-        println!("   {} Auto-loading core library", "success:".bright_cyan());
-        let core_pack = Self::load_core_library(target_os)?;
+        if !quiet {
+            println!("   {} Auto-loading core library", "success:".bright_cyan());
+        }
+        let core_pack = Self::load_core_library(target_os, dry_run, quiet)?;
         footprint_packs.push(core_pack);
 
-        for lib_name in required_libs {
-            println!("   {} Processing library: {}", "success:".bright_cyan(), lib_name);
-
-            let lib_dirs: Vec<_> = fs::read_dir(&library_path)
-                .map_err(|e| LibraryError::FileReadError(library_path.clone(), e.to_string()))?
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let dir_name = e.file_name();
-                    let dir_str = dir_name.to_str().unwrap_or("").to_lowercase();
-                    dir_str.starts_with(&lib_name) || dir_str == lib_name
-                })
-                .collect();
-
-            if lib_dirs.is_empty() {
-                eprintln!("   {} Library not found: {}", "Error:".red(), lib_name);
-                return Err(LibraryError::PathNotFound(lib_name));
-            }
-
-            let lib_dir = lib_dirs[0].path();
-            let package_json_path = lib_dir.join("package.json");
-
-            if !package_json_path.exists() {
-                eprintln!("   {} Missing package.json for: {}", "Error:".red(), lib_name);
-                return Err(LibraryError::MissingPackageJson(lib_dir));
-            }
-
-            let content = fs::read_to_string(&package_json_path)
-                .map_err(|e| LibraryError::FileReadError(package_json_path.clone(), e.to_string()))?;
-            let package_json: PackageJson = serde_json::from_str(&content)
-                .map_err(|e| LibraryError::JsonParseError(package_json_path.clone(), e.to_string()))?;
-
-            println!(
-                "   {} Found library: {} v{}",
-                "success:".green(),
-                package_json.information.name,
-                package_json.information.version
-            );
-
-            
-            let mut verified_scripts = Vec::new();
-            let src_dir = lib_dir.join("src");
-            
-            if src_dir.exists() && src_dir.is_dir() {
-                println!("   {} Scanning directory: {}", "success:".bright_black(), src_dir.display());
-                Self::collect_vix_files(&src_dir, &mut verified_scripts)?;
-                println!("   {} Found {} source files", "success:".green(), verified_scripts.len());
-                
-                for script in &verified_scripts {
-                    println!("      {} {}", "->".bright_black(), script.display());
+        let binary_dir = Self::get_library_bin_dir()?;
+        let code_dir = Self::get_library_code_dir()?;
+        let library_packs: Result<Vec<FootprintPack>, LibraryError> = required_libs
+            .par_iter()
+            .map(|lib_name| {
+                let mut log = vec![format!("   {} Processing library: {}", "success:".bright_cyan(), lib_name)];
+                let pack = Self::resolve_library_metadata(&library_path, lib_name, &mut log)
+                    .and_then(|lib_metadata| Self::build_footprint_pack(&lib_metadata, target_os, &binary_dir, &code_dir, dry_run, &mut log));
+                if !quiet {
+                    Self::flush_log(&log);
                 }
-            }
+                pack
+            })
+            .collect();
+        footprint_packs.extend(library_packs?);
 
-            if verified_scripts.is_empty() {
-                eprintln!("   {} No source files found in {}", "Warning:".yellow(), src_dir.display());
-            }
-
-            
-            let mut verified_syntax = Vec::new();
-            let syntax_dir = lib_dir.join("syntax");
-            if syntax_dir.exists() && syntax_dir.is_dir() {
-                Self::collect_syntax_files(&syntax_dir, &mut verified_syntax).ok();
-            }
+        Self::save_footprint_libraries(&footprint_packs)?;
+        if !quiet {
+            println!("\n   {} All libraries processed successfully", "success:".green());
+        }
 
-            let mut verified_errors = Vec::new();
-            if syntax_dir.exists() && syntax_dir.is_dir() {
-                Self::collect_error_files(&syntax_dir, &mut verified_errors).ok();
-            }
+        Ok(footprint_packs)
+    }
 
-            let lib_metadata = LibraryMetadata {
-                name: package_json.information.name.clone(),
-                version: package_json.information.version.clone(),
-                publisher: package_json.information.pulicher.clone(),
-                path: lib_dir,
-                package_json: package_json.clone(),
-                verified_scripts,
-                verified_syntax,
-                verified_errors,
-                includes: package_json.include.clang.clone(),
-            };
+    fn resolve_library_metadata(
+        library_path: &Path,
+        lib_name: &str,
+        log: &mut Vec<String>,
+    ) -> Result<LibraryMetadata, LibraryError> {
+        let lib_dir = Self::find_library_dir(library_path, lib_name)?;
+        let package_json_path = lib_dir.join("package.json");
+
+        if !package_json_path.exists() {
+            log.push(format!("   {} Missing package.json for: {}", "Error:".red(), lib_name));
+            return Err(LibraryError::MissingPackageJson(lib_dir));
+        }
 
-            let binary_path = Self.compile_library(&lib_metadata, target_os)?;
+        let content = fs::read_to_string(&package_json_path)
+            .map_err(|e| LibraryError::FileReadError(package_json_path.clone(), e.to_string()))?;
+        let package_json: PackageJson = serde_json::from_str(&content)
+            .map_err(|e| LibraryError::JsonParseError(package_json_path.clone(), e.to_string()))?;
 
-            println!("   {} Binary created: {}", "success:".green(), binary_path.display());
+        log.push(format!(
+            "   {} Found library: {} v{}",
+            "success:".green(),
+            package_json.information.name,
+            package_json.information.version
+        ));
 
+        
+        let mut verified_scripts = Vec::new();
+        let src_dir = lib_dir.join("src");
+        
+        if src_dir.exists() && src_dir.is_dir() {
+            log.push(format!("   {} Scanning directory: {}", "success:".bright_black(), src_dir.display()));
+            Self::collect_vix_files(&lib_dir, &src_dir, &mut verified_scripts)?;
+            log.push(format!("   {} Found {} source files", "success:".green(), verified_scripts.len()));
             
-            let (functions, classes) = Self::extract_library_symbols(&lib_metadata)?;
-            let function_signatures = Self::extract_function_signatures(&lib_metadata)?;
+            for script in &verified_scripts {
+                log.push(format!("      {} {}", "->".bright_black(), script.display()));
+            }
+        }
 
-            println!("   {} Extracted {} functions, {} classes", 
-                "success:".bright_black(), functions.len(), classes.len());
+        if verified_scripts.is_empty() {
+            log.push(format!("   {} No source files found in {}", "Warning:".yellow(), src_dir.display()));
+        }
 
-            footprint_packs.push(FootprintPack {
-                name: package_json.information.name.to_lowercase(),  
-                version: package_json.information.version,
-                publisher: package_json.information.pulicher,
-                classes,
-                function_signatures,
-                functions,
-                includes: package_json.include.clang.clone(),
-                source_library: binary_path.to_string_lossy().to_string(),
-            });
+        
+        let mut verified_syntax = Vec::new();
+        let syntax_dir = lib_dir.join("syntax");
+        if syntax_dir.exists() && syntax_dir.is_dir() {
+            Self::collect_syntax_files(&syntax_dir, &mut verified_syntax).ok();
         }
 
-        Self::save_footprint_libraries(&footprint_packs)?;
-        println!("\n   {} All libraries processed successfully", "success:".green());
+        let mut verified_errors = Vec::new();
+        if syntax_dir.exists() && syntax_dir.is_dir() {
+            Self::collect_error_files(&syntax_dir, &mut verified_errors).ok();
+        }
 
-        Ok(footprint_packs)
+        Ok(LibraryMetadata {
+            name: package_json.information.name.clone(),
+            version: package_json.information.version.clone(),
+            publisher: package_json.information.publisher.clone(),
+            path: lib_dir,
+            package_json: package_json.clone(),
+            verified_scripts,
+            verified_syntax,
+            verified_errors,
+            includes: package_json.include.clang.clone(),
+            parsed: OnceLock::new(),
+        })
     }
 
-    fn extract_library_symbols(lib_metadata: &LibraryMetadata) -> Result<(Vec<String>, Vec<String>), LibraryError> {
+    #[allow(clippy::type_complexity)]
+    fn extract_library_symbols(lib_metadata: &LibraryMetadata) -> Result<(Vec<String>, Vec<String>, Vec<String>), LibraryError> {
         let mut functions = Vec::new();
         let mut classes = Vec::new();
+        let mut constants = Vec::new();
 
-        for script_path in &lib_metadata.verified_scripts {
-            let source = fs::read_to_string(script_path)
-                .map_err(|e| LibraryError::FileReadError(script_path.clone(), e.to_string()))?;
-
-            let mut lexer = Lexer::new(&source);
-            let tokens = lexer.tokenize();
+        let tokens = &lib_metadata.parsed()?.tokens;
 
-            let mut i = 0;
-            while i < tokens.len() {
-                match &tokens[i] {
-                    Token::Pub => {
-                        i += 1;
-                        if i < tokens.len() {
-                            match &tokens[i] {
-                                Token::Func => {
-                                    i += 1;
-                                    if i < tokens.len()
-                                        && let Token::Identifier(name) = &tokens[i] {
-                                            functions.push(name.clone());
-                                        }
-                                }
-                                Token::Struct => {
-                                    i += 1;
-                                    if i < tokens.len()
-                                        && let Token::Identifier(name) = &tokens[i] {
-                                            classes.push(name.clone());
-                                        }
-                                }
-                                _ => {}
+        let mut i = 0;
+        while i < tokens.len() {
+            match &tokens[i] {
+                Token::Pub => {
+                    i += 1;
+                    if i < tokens.len() {
+                        match &tokens[i] {
+                            Token::Func => {
+                                i += 1;
+                                if i < tokens.len()
+                                    && let Token::Identifier(name) = &tokens[i] {
+                                        functions.push(name.clone());
+                                    }
+                            }
+                            Token::Struct => {
+                                i += 1;
+                                if i < tokens.len()
+                                    && let Token::Identifier(name) = &tokens[i] {
+                                        classes.push(name.clone());
+                                    }
+                            }
+                            Token::Const => {
+                                i += 1;
+                                if i < tokens.len()
+                                    && let Token::Identifier(name) = &tokens[i] {
+                                        constants.push(name.clone());
+                                    }
                             }
+                            _ => {}
                         }
                     }
-                    Token::Impl => {
-                        i += 1;
-                        if i < tokens.len()
-                            && let Token::Identifier(name) = &tokens[i]
-                                && !classes.contains(name) {
-                                    classes.push(name.clone());
-                                }
-                    }
-                    _ => {}
                 }
-                i += 1;
+                Token::Impl => {
+                    i += 1;
+                    if i < tokens.len()
+                        && let Token::Identifier(name) = &tokens[i]
+                            && !classes.contains(name) {
+                                classes.push(name.clone());
+                            }
+                }
+                _ => {}
             }
+            i += 1;
         }
 
-        Ok((functions, classes))
+        Ok((functions, classes, constants))
     }
 
     
@@ -1006,23 +1154,30 @@ impl LibraryManager {
         for decl in import_decls {
             match decl {
                 ImportDecl::FileImport { name, from } => {
-                    let lib = footprint_packs.iter().find(|pack| pack.name == *from).ok_or_else(|| LibraryError::PathNotFound(from.clone()))?;
+                    let lib = footprint_packs.iter().find(|pack| pack.name.eq_ignore_ascii_case(from)).ok_or_else(|| LibraryError::PathNotFound(from.clone()))?;
                     let symbol_exists = lib.functions.contains(name) || lib.classes.contains(name);
 
                     if !symbol_exists {
-                        eprintln!("   {} Symbol '{}' not found in library '{}'", "Warning:".yellow(), name, from);
+                        eprintln!("   {} Symbol '{}' not found in library '{}'", "Error:".red(), name, from);
                         eprintln!("      Available functions: {:?}", lib.functions);
                         eprintln!("      Available classes: {:?}", lib.classes);
+                        let closest = lib.functions.iter().chain(&lib.classes)
+                            .min_by_key(|symbol| levenshtein(symbol, name))
+                            .filter(|symbol| levenshtein(symbol, name) <= 2);
+                        if let Some(symbol) = closest {
+                            eprintln!("      {} did you mean '{}'?", "help:".cyan(), symbol);
+                        }
+                        return Err(LibraryError::MissingSymbol(name.clone(), from.clone()));
                     }
                 }
                 ImportDecl::LibraryImport { name } => {
-                    let exists = footprint_packs.iter().any(|pack| pack.name == *name);
+                    let exists = footprint_packs.iter().any(|pack| pack.name.eq_ignore_ascii_case(name));
                     if !exists {
                         return Err(LibraryError::PathNotFound(name.clone()));
                     }
                 }
                 ImportDecl::WildcardImport { from } => {
-                    let exists = footprint_packs.iter().any(|pack| pack.name == *from);
+                    let exists = footprint_packs.iter().any(|pack| pack.name.eq_ignore_ascii_case(from));
                     if !exists {
                         return Err(LibraryError::PathNotFound(from.clone()));
                     }
@@ -1033,73 +1188,72 @@ impl LibraryManager {
         Ok(())
     }
 
-        pub fn extract_function_signatures(lib_metadata: &LibraryMetadata) -> Result<Vec<FunctionSignature>, LibraryError> {
-        let mut signatures = Vec::new();
-
-        for script_path in &lib_metadata.verified_scripts {
-            let source = fs::read_to_string(script_path)
-                .map_err(|e| LibraryError::FileReadError(script_path.clone(), e.to_string()))?;
+    pub fn extract_function_signatures(lib_metadata: &LibraryMetadata, arch: &ArchConfig) -> Result<Vec<FunctionSignature>, LibraryError> {
+        let mut log = Vec::new();
+        let signatures = Self::collect_function_signatures(lib_metadata, arch, &mut log);
+        Self::flush_log(&log);
+        signatures
+    }
 
-            let mut lexer = Lexer::new(&source);
-            let tokens = lexer.tokenize();
-            let parser = Parser::new(tokens, source.clone(), lexer.spans.clone());
-            let (program, _, _, _, _, _, _, _, _, _, _) = parser.parse();
+    fn collect_function_signatures(lib_metadata: &LibraryMetadata, arch: &ArchConfig, log: &mut Vec<String>) -> Result<Vec<FunctionSignature>, LibraryError> {
+        let mut signatures = Vec::new();
 
-            println!("      {} Scanning {} functions in library", "success:".bright_black(), program.functions.len());
-            
-             
-            for func in &program.functions {
-                if func.is_public {
-                    let mut registry = TypeRegistry::new();
-                    let return_type = func.return_type.to_c_type(&ArchConfig::x86_64(), &mut registry);
-                    
-                    let parameters: Vec<(String, String)> = func.params.iter()
-                        .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(&ArchConfig::x86_64(), &mut registry)))
-                        .collect();
+        let program = &lib_metadata.parsed()?.program;
 
-                    signatures.push(FunctionSignature {
-                        name: func.name.clone(),
-                        return_type: return_type.clone(),
-                        parameters,
-                        abi: "c".to_string(),
-                    });
-                    
-                    println!("         {} Public function: {} -> {}", "success:".green(), func.name, return_type);
-                }
+        log.push(format!("      {} Scanning {} functions in library", "success:".bright_black(), program.functions.len()));
+        
+         
+        for func in &program.functions {
+            if func.is_public {
+                let mut registry = TypeRegistry::new();
+                let return_type = func.return_type.to_c_type(arch, &mut registry);
+                
+                let parameters: Vec<(String, String)> = func.params.iter()
+                    .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(arch, &mut registry)))
+                    .collect();
+
+                signatures.push(FunctionSignature {
+                    name: func.name.clone(),
+                    return_type: return_type.clone(),
+                    parameters,
+                    abi: "c".to_string(),
+                });
+                
+                log.push(format!("         {} Public function: {} -> {}", "success:".green(), func.name, return_type));
             }
-            
-             
-            for module in &program.modules {
-                if let Stmt::ModuleDef { name: module_name, body, is_public } = module {
-                    if !is_public {
-                        continue;
-                    }
-                    
-                    println!("      {} Scanning public module: {}", "success:".bright_black(), module_name);
-                    
-                    for stmt in body {
-                        if let Stmt::Function(func) = stmt
-                            && func.is_public {
-                                 
-                                let prefixed_name = format!("{}_{}", module_name, func.name);
-                                
-                                let mut registry = TypeRegistry::new();
-                                let return_type = func.return_type.to_c_type(&ArchConfig::x86_64(), &mut registry);
-                                
-                                let parameters: Vec<(String, String)> = func.params.iter()
-                                    .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(&ArchConfig::x86_64(), &mut registry)))
-                                    .collect();
-
-                                signatures.push(FunctionSignature {
-                                    name: prefixed_name.clone(),
-                                    return_type: return_type.clone(),
-                                    parameters,
-                                    abi: "c".to_string(),
-                                });
-                                
-                                println!("         {} Module function: {} -> {}", "success:".green(), prefixed_name, return_type);
-                            }
-                    }
+        }
+        
+         
+        for module in &program.modules {
+            if let Stmt::ModuleDef { name: module_name, body, is_public, .. } = module {
+                if !is_public {
+                    continue;
+                }
+                
+                log.push(format!("      {} Scanning public module: {}", "success:".bright_black(), module_name));
+                
+                for stmt in body {
+                    if let Stmt::Function(func) = stmt.node()
+                        && func.is_public {
+                             
+                            let prefixed_name = format!("{}_{}", module_name, func.name);
+                            
+                            let mut registry = TypeRegistry::new();
+                            let return_type = func.return_type.to_c_type(arch, &mut registry);
+                            
+                            let parameters: Vec<(String, String)> = func.params.iter()
+                                .map(|(name, ty, _modifier)| (name.clone(), ty.to_c_type(arch, &mut registry)))
+                                .collect();
+
+                            signatures.push(FunctionSignature {
+                                name: prefixed_name.clone(),
+                                return_type: return_type.clone(),
+                                parameters,
+                                abi: "c".to_string(),
+                            });
+                            
+                            log.push(format!("         {} Module function: {} -> {}", "success:".green(), prefixed_name, return_type));
+                        }
                 }
             }
         }
@@ -1122,10 +1276,27 @@ impl LibraryManager {
         Ok(code_dir)
     }
 
+    /// A missing binary, or any input newer than it, forces a rebuild.
+    fn needs_recompile(binary_path: &Path, inputs: &[PathBuf]) -> bool {
+        let binary_modified = match fs::metadata(binary_path).and_then(|m| m.modified()) {
+            Ok(modified) => modified,
+            Err(_) => return true,
+        };
+
+        inputs.iter().any(|input| {
+            fs::metadata(input)
+                .and_then(|m| m.modified())
+                .map(|input_modified| input_modified > binary_modified)
+                .unwrap_or(true)
+        })
+    }
+
      
     fn compile_core_library(
         scripts: &[PathBuf],
         target_os: Option<TargetOS>,
+        dry_run: bool,
+        log: &mut Vec<String>,
     ) -> Result<PathBuf, LibraryError> {
         let binary_dir = Self::get_library_bin_dir()?;
         let code_dir = Self::get_library_code_dir()?;
@@ -1134,28 +1305,12 @@ impl LibraryManager {
         let binary_path = binary_dir.join(format!("core{}", target.object_extension()));
         let c_code_path = code_dir.join("core.c");
 
-        if binary_path.exists() {
-            let binary_modified = fs::metadata(&binary_path)
-                .and_then(|m| m.modified())
-                .ok();
-
-            let should_recompile = scripts.iter().any(|script| {
-                fs::metadata(script)
-                    .and_then(|m| m.modified())
-                    .ok()
-                    .map(|script_modified| {
-                        binary_modified.map_or(true, |bin_mod| script_modified > bin_mod)
-                    })
-                    .unwrap_or(true)
-            });
-
-            if !should_recompile {
-                println!("   {} Core library binary is up to date", "success:".green());
-                return Ok(binary_path);
-            }
+        if !Self::needs_recompile(&binary_path, scripts) {
+            log.push(format!("   {} Core library binary is up to date", "success:".green()));
+            return Ok(binary_path);
         }
 
-        println!("   {} Compiling core library...", "success:".bright_cyan());
+        log.push(format!("   {} Compiling core library...", "success:".bright_cyan()));
 
         let mut all_source = String::new();
         for script in scripts {
@@ -1169,18 +1324,17 @@ impl LibraryManager {
         let tokens = lexer.tokenize();
 
         if !lexer.errors.is_empty() {
-            eprintln!("   {} Lexer errors in core library:", "Error:".red());
-            for error in &lexer.errors {
-                eprintln!("      {}", error.message);
-            }
-            return Err(LibraryError::ParseError("Core library lexer failed".to_string()));
+            let messages: Vec<&str> = lexer.errors.iter().map(|error| error.message.as_str()).collect();
+            return Err(LibraryError::ParseError(format!("Core library lexer failed: {}", messages.join("; "))));
         }
 
         let parser = Parser::new(tokens, all_source.clone(), lexer.spans.clone());
         let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
 
-        let arch = ArchConfig::x86_64();
+        let arch = target.arch();
         let mut codegen = Codegen::new(arch, all_source, "core".to_string());
+        codegen.progress_log = Some(Vec::new());
+        codegen.c_file = c_code_path.display().to_string();
         
         let c_code = codegen.codegen_library(
             &program, 
@@ -1189,21 +1343,22 @@ impl LibraryManager {
             &impls, 
             &externs, 
             &[]
-        ).map_err(|e| LibraryError::ParseError(format!("Core codegen failed: {}", e)))?;
-
-         
-        use std::io::Write;
-        if let Ok(mut file) = fs::File::create(&c_code_path) {
-            let _ = file.write_all(c_code.as_bytes());
-            println!("   {} Core C code saved: {}", "success:".green(), c_code_path.display());
+        );
+        log.extend(codegen.progress_log.take().unwrap_or_default());
+        let c_code = c_code.map_err(|e| LibraryError::ParseError(format!("Core codegen failed: {}", e)))?;
+
+        if dry_run {
+            Clang::write_c_source(&c_code, &c_code_path).map_err(LibraryError::ParseError)?;
+            log.push(format!("   {} Dry run, core C code written to: {}", "success:".green(), c_code_path.display()));
+            return Ok(binary_path);
         }
 
-         
-        Clang::compile_to_object(&c_code, &binary_path, target_os)
+        Clang::compile_to_object(&c_code, &c_code_path, &binary_path, target_os, OptimizationLevel::O2)
             .map_err(LibraryError::ParseError)?;
+        log.push(format!("   {} Core C code saved: {}", "success:".green(), c_code_path.display()));
 
-        println!("   {} Core library compiled successfully", "success:".green());
-        println!("   {} Binary: {}", "success:".green(), binary_path.display());
+        log.push(format!("   {} Core library compiled successfully", "success:".green()));
+        log.push(format!("   {} Binary: {}", "success:".green(), binary_path.display()));
         
         Ok(binary_path)
     }
@@ -1216,6 +1371,20 @@ impl LibraryManager {
 ) -> Result<PathBuf, LibraryError> {
     let binary_dir = Self::get_library_bin_dir()?;
     let code_dir = Self::get_library_code_dir()?;
+    let mut log = Vec::new();
+    let binary_path = Self::compile_library_into(lib_metadata, target_os, &binary_dir, &code_dir, false, &mut log);
+    Self::flush_log(&log);
+    binary_path
+}
+
+fn compile_library_into(
+    lib_metadata: &LibraryMetadata,
+    target_os: Option<TargetOS>,
+    binary_dir: &Path,
+    code_dir: &Path,
+    dry_run: bool,
+    log: &mut Vec<String>,
+) -> Result<PathBuf, LibraryError> {
     let target = target_os.unwrap_or_else(TargetOS::current);
     
     let output_name = format!("{}-{}", lib_metadata.name, lib_metadata.version);
@@ -1224,93 +1393,92 @@ impl LibraryManager {
     let binary_path = binary_dir.join(format!("{}{}", output_name, target.object_extension()));
     let c_code_path = code_dir.join(format!("{}.c", output_name));
 
-    if binary_path.exists() {
-        println!("   {} Library binary already exists: {}", "success:".green(), binary_path.display());
+    let mut inputs = lib_metadata.verified_scripts.clone();
+    inputs.push(lib_metadata.path.join("package.json"));
+
+    if !Self::needs_recompile(&binary_path, &inputs) {
+        log.push(format!("   {} Library binary is up to date: {}", "success:".green(), binary_path.display()));
         return Ok(binary_path);
     }
 
-    let mut all_source = String::new();
+    let mut has_vix = false;
     for script_path in &lib_metadata.verified_scripts {
         let ext = script_path.extension().and_then(|s| s.to_str()).unwrap_or("");
 
         match ext {
-            "x" | "vix" => {
-                let source = fs::read_to_string(script_path)
-                    .map_err(|e| LibraryError::FileReadError(script_path.clone(), e.to_string()))?;
-                all_source.push_str(&source);
-                all_source.push_str("\n\n");
+            "x" | "vix" => has_vix = true,
+            "c" | "cpp" | "ll" if dry_run => {
+                log.push(format!("   {} Dry run, not compiling {}", "success:".bright_black(), script_path.display()));
+                return Ok(binary_path);
             }
             "c" | "cpp" => {
-                return Self::compile_c_cpp_library(script_path, &binary_path, ext, target_os);
+                return Self::compile_c_cpp_library(script_path, &binary_path, ext, target_os, OptimizationLevel::O3);
             }
             "ll" => {
-                return Self::compile_llvm_library(script_path, &binary_path, target_os);
+                return Self::compile_llvm_library(script_path, &binary_path, target_os, OptimizationLevel::O2);
             }
             _ => {}
         }
     }
 
-    if !all_source.is_empty() {
+    if has_vix {
         Self::compile_vix_library_to_dirs(
-            &all_source, 
+            lib_metadata.parsed()?, 
             &binary_path,
             &c_code_path,
             target_os, 
             &lib_metadata.includes,
-            &lib_metadata.name
+            &lib_metadata.name,
+            dry_run,
+            log
         )?;
     }
 
-    println!("   {} Library compiled successfully", "success:".green());
+    if !dry_run {
+        log.push(format!("   {} Library compiled successfully", "success:".green()));
+    }
     Ok(binary_path)
 }
 
      
+    #[allow(clippy::too_many_arguments)]
     fn compile_vix_library_to_dirs(
-        source: &str,
+        parsed: &ParsedLibrary,
         binary_path: &Path,
         c_code_path: &Path,
         target_os: Option<TargetOS>,
         library_includes: &[String],
         lib_name: &str,
+        dry_run: bool,
+        log: &mut Vec<String>,
     ) -> Result<(), LibraryError> {
-        let mut lexer = Lexer::new(source);
-        let tokens = lexer.tokenize();
-
-        if !lexer.errors.is_empty() {
-            eprintln!("   {} Lexer errors:", "Error:".red());
-            for error in &lexer.errors {
-                eprintln!("      {}", error.message);
-            }
-            return Err(LibraryError::ParseError("Lexer failed".to_string()));
-        }
-
-        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
-        let (program, structs, enums, externs, _, _, _, impls, _, _, _) = parser.parse();
-        let arch = ArchConfig::x86_64();
-        let mut codegen = Codegen::new(arch, source.to_string(), "library".to_string());
+        let arch = target_os.unwrap_or_else(TargetOS::current).arch();
+        let mut codegen = Codegen::new(arch, parsed.source.clone(), "library".to_string());
+        codegen.progress_log = Some(Vec::new());
+        codegen.c_file = c_code_path.display().to_string();
         
         let c_code = codegen.codegen_library(
-            &program,
-            &structs,
-            &enums,
-            &impls,
-            &externs,
+            &parsed.program,
+            &parsed.structs,
+            &parsed.enums,
+            &parsed.impls,
+            &parsed.externs,
             library_includes
-        ).map_err(|e| LibraryError::ParseError(format!("Codegen failed: {}", e)))?;
+        );
+        log.extend(codegen.progress_log.take().unwrap_or_default());
+        let c_code = c_code.map_err(|e| LibraryError::ParseError(format!("Codegen failed: {}", e)))?;
 
-         
-        use std::io::Write;
-        if let Ok(mut file) = fs::File::create(c_code_path) {
-            let _ = file.write_all(c_code.as_bytes());
-            println!("   {} {} C code saved: {}", "success:".green(), lib_name, c_code_path.display());
+        if dry_run {
+            Clang::write_c_source(&c_code, c_code_path).map_err(LibraryError::ParseError)?;
+            log.push(format!("   {} Dry run, {} C code written to: {}", "success:".green(), lib_name, c_code_path.display()));
+            return Ok(());
         }
 
-         
-        Clang::compile_to_object(&c_code, binary_path, target_os)
+        Clang::compile_to_object(&c_code, c_code_path, binary_path, target_os, OptimizationLevel::O2)
             .map_err(LibraryError::ParseError)?;
+        log.push(format!("   {} {} C code saved: {}", "success:".green(), lib_name, c_code_path.display()));
 
-        println!("   {} {} binary saved: {}", "success:".green(), lib_name, binary_path.display());
+        log.push(format!("   {} {} binary saved: {}", "success:".green(), lib_name, binary_path.display()));
 
         Ok(())
     }
@@ -1329,14 +1497,20 @@ impl LibraryManager {
         let code_dir = Self::get_library_code_dir()?;
         let c_code_path = code_dir.join(format!("{}.c", lib_name));
         
-        Self::compile_vix_library_to_dirs(
-            source,
+        let parsed = ParsedLibrary::from_source(source.to_string())?;
+        let mut log = Vec::new();
+        let result = Self::compile_vix_library_to_dirs(
+            &parsed,
             output_path,
             &c_code_path,
             target_os,
             library_includes,
-            lib_name
-        )
+            lib_name,
+            false,
+            &mut log
+        );
+        Self::flush_log(&log);
+        result
     }
 
      
@@ -1391,4 +1565,269 @@ pub fn collect_error_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), L
     Ok(())
 }
 
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vix-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn pack(name: &str) -> FootprintPack {
+        FootprintPack {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            publisher: "vix".to_string(),
+            classes: vec![],
+            function_signatures: vec![],
+            includes: vec![],
+            functions: vec!["greet".to_string()],
+            constants: vec![],
+            source_library: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_vix_path_prefers_vix_home_then_platform_default() {
+        let root = scratch_dir("vixpath");
+        let vix_home = root.join("custom");
+        let platform_home = root.join(".vix");
+        fs::create_dir_all(&vix_home).unwrap();
+        fs::create_dir_all(&platform_home).unwrap();
+
+        let found = LibraryManager::find_vix_path(Some(vix_home.clone()), Some(platform_home.clone())).unwrap();
+        assert_eq!(found, vix_home);
+
+        let missing = root.join("missing");
+        let found = LibraryManager::find_vix_path(Some(missing.clone()), Some(platform_home.clone())).unwrap();
+        assert_eq!(found, platform_home);
+        let found = LibraryManager::find_vix_path(None, Some(platform_home.clone())).unwrap();
+        assert_eq!(found, platform_home);
+
+        match LibraryManager::find_vix_path(Some(missing.clone()), Some(root.join("gone"))) {
+            Err(LibraryError::PathNotFound(message)) => {
+                assert!(message.contains(&missing.display().to_string()), "{}", message);
+                assert!(message.contains("VIX_HOME"), "{}", message);
+            }
+            other => panic!("expected PathNotFound, got {:?}", other),
+        }
+        assert!(matches!(LibraryManager::find_vix_path(None, None), Err(LibraryError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_library_names_match_case_insensitively() {
+        let library_path = scratch_dir("libcase");
+        fs::create_dir_all(library_path.join("MyLib")).unwrap();
+        fs::create_dir_all(library_path.join("MyLibExtra")).unwrap();
+
+        let found = LibraryManager::find_library_dir(&library_path, "MyLib").unwrap();
+        assert_eq!(found.file_name().unwrap(), "MyLib");
+        let found = LibraryManager::find_library_dir(&library_path, "mylib").unwrap();
+        assert_eq!(found.file_name().unwrap(), "MyLib");
+        assert!(LibraryManager::find_library_dir(&library_path, "Missing").is_err());
+
+        let imports = vec![
+            ImportDecl::LibraryImport { name: "MyLib".to_string() },
+            ImportDecl::FileImport { name: "greet".to_string(), from: "mylib".to_string() },
+        ];
+        assert!(LibraryManager::validate_imports(&imports, &[pack("MyLib")]).is_ok());
+
+        let typo = vec![ImportDecl::FileImport { name: "gret".to_string(), from: "MyLib".to_string() }];
+        assert!(matches!(
+            LibraryManager::validate_imports(&typo, &[pack("MyLib")]),
+            Err(LibraryError::MissingSymbol(symbol, library)) if symbol == "gret" && library == "MyLib"
+        ));
+
+        fs::remove_dir_all(&library_path).unwrap();
+    }
+
+    #[test]
+    fn test_touched_source_triggers_recompile() {
+        let dir = scratch_dir("mtime");
+        let script = dir.join("main.vix");
+        let package_json = dir.join("package.json");
+        let binary = dir.join("lib.o");
+        fs::write(&script, "func main()\nend\n").unwrap();
+        fs::write(&package_json, "{}").unwrap();
+
+        let inputs = vec![script.clone(), package_json.clone()];
+        assert!(LibraryManager::needs_recompile(&binary, &inputs));
+
+        fs::write(&binary, "").unwrap();
+        let built = fs::metadata(&binary).unwrap().modified().unwrap();
+        let before = built - std::time::Duration::from_secs(60);
+        for input in &inputs {
+            fs::File::options().write(true).open(input).unwrap().set_modified(before).unwrap();
+        }
+        assert!(!LibraryManager::needs_recompile(&binary, &inputs));
+
+        let after = built + std::time::Duration::from_secs(60);
+        fs::File::options().write(true).open(&script).unwrap().set_modified(after).unwrap();
+        assert!(LibraryManager::needs_recompile(&binary, &inputs));
+
+        fs::File::options().write(true).open(&script).unwrap().set_modified(before).unwrap();
+        fs::File::options().write(true).open(&package_json).unwrap().set_modified(after).unwrap();
+        assert!(LibraryManager::needs_recompile(&binary, &inputs));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_publisher_accepts_legacy_spelling() {
+        for key in ["publisher", "pulicher"] {
+            let json = format!(r#"{{"name": "io", "version": "1.0.0", "{}": "vix"}}"#, key);
+            let info: PackageInformation = serde_json::from_str(&json).unwrap();
+            assert_eq!(info.publisher, "vix");
+
+            let emitted = serde_json::to_string(&info).unwrap();
+            assert!(emitted.contains(r#""publisher":"vix""#), "{}", emitted);
+            assert!(!emitted.contains("pulicher"), "{}", emitted);
+        }
+    }
+
+    fn library(root: &Path, name: &str, source: &str) -> LibraryMetadata {
+        let lib_dir = root.join(name);
+        fs::create_dir_all(lib_dir.join("src")).unwrap();
+        let script = lib_dir.join("src").join("main.vix");
+        fs::write(&script, source).unwrap();
+
+        let json = format!(
+            r#"{{"Information": {{"name": "{}", "version": "1.0.0", "publisher": "vix"}},
+                "src": {{"scripts": ["main.vix"]}}, "syntax": {{"syntax": [], "error": []}}}}"#,
+            name
+        );
+        fs::write(lib_dir.join("package.json"), &json).unwrap();
+
+        LibraryMetadata {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            publisher: "vix".to_string(),
+            path: lib_dir,
+            package_json: serde_json::from_str(&json).unwrap(),
+            verified_scripts: vec![script],
+            verified_syntax: vec![],
+            verified_errors: vec![],
+            includes: vec![],
+            parsed: OnceLock::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_footprint_packs_in_parallel() {
+        let root = scratch_dir("parallel");
+        let (binary_dir, code_dir) = (root.join("bin"), root.join("code"));
+        fs::create_dir_all(&binary_dir).unwrap();
+        fs::create_dir_all(&code_dir).unwrap();
+
+        let libraries: Vec<LibraryMetadata> = ["alpha", "beta", "gamma"]
+            .iter()
+            .map(|name| library(&root, name, &format!("public func {}_run(): int32\n    return 1\nend\n", name)))
+            .collect();
+
+        // cached binaries newer than their sources, so no C compiler is needed
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        for lib in &libraries {
+            fs::write(binary_dir.join(format!("{}-1.0.0{}", lib.name, TargetOS::current().object_extension())), "").unwrap();
+        }
+
+        let packs = LibraryManager::build_footprint_packs(&libraries, None, &binary_dir, &code_dir, false).unwrap();
+        let names: Vec<&str> = packs.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["alpha", "beta", "gamma"]);
+        for pack in &packs {
+            assert_eq!(pack.functions, [format!("{}_run", pack.name)]);
+        }
+
+        let mut broken = libraries;
+        broken[1].verified_scripts = vec![root.join("beta").join("src").join("missing.vix")];
+        broken[1].parsed = OnceLock::new();
+        assert!(LibraryManager::build_footprint_packs(&broken, None, &binary_dir, &code_dir, false).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_library_c_source_and_progress_stay_per_library() {
+        let root = scratch_dir("percode");
+        let (binary_dir, code_dir) = (root.join("bin"), root.join("code"));
+        fs::create_dir_all(&binary_dir).unwrap();
+        fs::create_dir_all(&code_dir).unwrap();
+        let lib = library(&root, "solo", "public func solo_run(): int32\n    return 1\nend\n");
+
+        let mut log = Vec::new();
+        LibraryManager::build_footprint_pack(&lib, None, &binary_dir, &code_dir, true, &mut log).unwrap();
+
+        let c_code = fs::read_to_string(code_dir.join("solo-1.0.0.c")).unwrap();
+        assert!(c_code.contains("solo_run("), "{}", c_code);
+        assert!(log.iter().any(|line| line.contains("Starting library codegen")), "{:?}", log);
+        assert!(fs::read_dir(&binary_dir).unwrap().next().is_none(), "a dry run must not produce objects");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_pub_const_exported_into_footprint_pack() {
+        let root = scratch_dir("pubconst");
+        let (binary_dir, code_dir) = (root.join("bin"), root.join("code"));
+        fs::create_dir_all(&binary_dir).unwrap();
+        fs::create_dir_all(&code_dir).unwrap();
+
+        let source = "public const MAX_SIZE: int32 = 64\nconst HIDDEN = 1\n\npublic func size(): int32\n    return MAX_SIZE\nend\n";
+        let libraries = vec![library(&root, "limits", source)];
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(binary_dir.join(format!("limits-1.0.0{}", TargetOS::current().object_extension())), "").unwrap();
+
+        let packs = LibraryManager::build_footprint_packs(&libraries, None, &binary_dir, &code_dir, false).unwrap();
+        assert_eq!(packs[0].constants, ["MAX_SIZE"]);
+        assert_eq!(packs[0].functions, ["size"]);
+
+        let script = root.join("limits").join("src").join("main.vix");
+        let (_, _, constants) = LibraryManager::extract_core_symbols(&[script]).unwrap();
+        assert_eq!(constants, ["MAX_SIZE"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_collect_vix_files_skips_hidden_and_ignored_dirs() {
+        let root = scratch_dir("vixignore");
+        for dir in ["src/util", ".git", "src/backup"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("src/main.vix"), "").unwrap();
+        fs::write(root.join("src/util/math.x"), "").unwrap();
+        fs::write(root.join(".git/foo.vix"), "").unwrap();
+        fs::write(root.join("src/backup/old.vix"), "").unwrap();
+        fs::write(root.join(".vixignore"), "# editor leftovers\nsrc/backup/\n").unwrap();
+
+        let mut scripts = Vec::new();
+        LibraryManager::collect_vix_files(&root, &root, &mut scripts).unwrap();
+        scripts.sort();
+        assert_eq!(scripts, vec![root.join("src/main.vix"), root.join("src/util/math.x")]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_library_sources_are_parsed_once() {
+        let root = scratch_dir("parseonce");
+        let lib = library(&root, "cached", "public func ping(): int32\n    return 1\nend\n");
+
+        let (functions, _, _) = LibraryManager::extract_library_symbols(&lib).unwrap();
+        assert_eq!(functions, ["ping"]);
+
+        // later stages must reuse the parse instead of reading the script again
+        fs::remove_file(&lib.verified_scripts[0]).unwrap();
+        let signatures = LibraryManager::extract_function_signatures(&lib, &TargetOS::current().arch()).unwrap();
+        assert_eq!(signatures[0].name, "ping");
+        let c_code = LibraryManager::generate_library_code(&lib, &TargetOS::current().arch()).unwrap();
+        assert!(c_code.contains("ping("), "{}", c_code);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
+
@@ -40,13 +40,14 @@ impl LibraryManager {
             metadata_list.push(LibraryMetadata {
                 name: package_json.information.name.clone(),
                 version: package_json.information.version.clone(),
-                publisher: package_json.information.pulicher.clone(),
+                publisher: package_json.information.publisher.clone(),
                 path: dep.path.clone(),
                 package_json: package_json.clone(),
                 verified_scripts,
                 verified_syntax,
                 verified_errors,
                 includes: package_json.include.clang.clone(),
+                parsed: OnceLock::new(),
             });
         }
         
@@ -69,12 +70,11 @@ impl LibraryManager {
             if path.is_dir() {
                  
                 Self::collect_source_files(&path, files)?;
-            } else if let Some(ext) = path.extension() {
+            } else if let Some(ext) = path.extension()
                  
-                if ext == "vix" || ext == "x" || ext == "c" || ext == "cpp" || ext == "ll" {
+                && (ext == "vix" || ext == "x" || ext == "c" || ext == "cpp" || ext == "ll") {
                     files.push(path);
                 }
-            }
         }
 
         Ok(())
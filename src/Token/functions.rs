@@ -36,6 +36,7 @@ impl Parser {
             }
 
             Expr::Number(_) => Type::i32(),
+            Expr::TypedNumber(_, ty) => ty.clone(),
             Expr::HexNumber(_) => Type::i32(),
             Expr::BinaryNumber(_) => Type::i32(),
             Expr::OctalNumber(_) => Type::i32(),
@@ -43,6 +44,7 @@ impl Parser {
             Expr::String(_) => Type::Str { len_type: Box::new(Type::i32()) },
             Expr::Bool(_) => Type::Bool,
             Expr::Char(_) => Type::char32(),
+            Expr::ByteChar(_) => Type::u8(),
             Expr::Some(inner) => {
                 Type::Option { 
                     inner: Box::new(Parser::infer_type(inner)) 
@@ -147,7 +149,9 @@ impl Parser {
             }
 
             Expr::IndexOf { .. } => Type::i32(),
-            Expr::SizeOf(_) | Expr::AlignOf(_) => Type::u64(),
+            Expr::SizeOf(_) => Type::u64(),
+            Expr::AlignOf(_) | Expr::OffsetOf { .. } => Type::Usize,
+            Expr::VaNext(ty) => ty.clone(),
             Expr::TypeOf(_) => Type::Str { len_type: Box::new(Type::i32()) },
             Expr::Panic(_) => Type::Void,
             Expr::ReferenceTo(_) => Type::u32(),
@@ -169,6 +173,52 @@ impl Parser {
     }
 
 
+    pub fn eval_const_expr(&self, expr: &Expr) -> Result<i64, String> {
+        match expr {
+            Expr::Number(n) => Ok(*n),
+            Expr::HexNumber(n) | Expr::BinaryNumber(n) | Expr::OctalNumber(n) => Ok(*n as i64),
+            // uppercase names such as `SIZE` come out of the parser as zero-argument calls
+            Expr::Var(name) | Expr::Call(name, _) => match (expr, self.const_values.get(name)) {
+                (Expr::Call(_, args), _) if !args.is_empty() => Err("Function calls are not allowed in a constant expression".to_string()),
+                (_, Some(value)) => Ok(*value),
+                (_, None) => Err(format!("'{}' is not a compile-time constant", name)),
+            },
+            Expr::UnOp(op, inner) if op == "-" => {
+                let value = self.eval_const_expr(inner)?;
+                value.checked_neg().ok_or_else(|| "Constant expression overflows".to_string())
+            }
+            Expr::BinOp(op, left, right) => {
+                let l = self.eval_const_expr(left)?;
+                let r = self.eval_const_expr(right)?;
+                let folded = match op.as_str() {
+                    "+" => l.checked_add(r),
+                    "-" => l.checked_sub(r),
+                    "*" => l.checked_mul(r),
+                    "/" => l.checked_div(r),
+                    "%" => l.checked_rem(r),
+                    "<<" => u32::try_from(r).ok().and_then(|r| l.checked_shl(r)),
+                    ">>" => u32::try_from(r).ok().and_then(|r| l.checked_shr(r)),
+                    "&" => Some(l & r),
+                    "|" => Some(l | r),
+                    "^" => Some(l ^ r),
+                    _ => return Err(format!("Operator '{}' is not allowed in a constant expression", op)),
+                };
+                folded.ok_or_else(|| format!("Constant expression '{} {} {}' overflows or divides by zero", l, op, r))
+            }
+            _ => Err("Expected a constant integer expression".to_string()),
+        }
+    }
+
+    /// Whether `expr` means the same at every call site it is substituted into:
+    /// a literal or a constant integer expression, never a name from the callee.
+    pub fn is_const_default(&self, expr: &Expr) -> bool {
+        match expr {
+            Expr::Float(_) | Expr::String(_) | Expr::Bool(_) | Expr::Char(_) | Expr::ByteChar(_) | Expr::None | Expr::TypedNumber(..) => true,
+            Expr::UnOp(op, inner) if op == "-" => matches!(inner.as_ref(), Expr::Float(_)) || self.eval_const_expr(expr).is_ok(),
+            _ => self.eval_const_expr(expr).is_ok(),
+        }
+    }
+
     pub fn parse_expr_list(&mut self) -> Vec<Expr> {
         let mut exprs = Vec::new();
         while self.current() != Token::RightParen && self.current() != Token::Semicolon && self.current() != Token::EOF {
@@ -18,6 +18,66 @@ impl Parser {
         self.spans.get(self.pos).cloned().unwrap_or(SourceSpan::from(0..0))
     }
 
+    /// Whether the current token is the first one on its line.
+    pub fn starts_line(&self) -> bool {
+        let line_at = |pos: usize| self.spans.get(pos).map(|span| self.get_location(span.offset()).line);
+        self.pos > 0 && line_at(self.pos) != line_at(self.pos - 1)
+    }
+
+    /// Whether the current `*` opens a line of the form `*p = value` (or `*p += value`).
+    /// Any other line-leading `*` continues the expression on the line before.
+    pub fn starts_deref_assignment(&self) -> bool {
+        if self.current() != Token::Star || !self.starts_line() {
+            return false;
+        }
+        let line_at = |pos: usize| self.spans.get(pos).map(|span| self.get_location(span.offset()).line);
+        let line = line_at(self.pos);
+        let mut depth = 0usize;
+        let mut pos = self.pos + 1;
+        while pos < self.tokens.len() && line_at(pos) == line {
+            match &self.tokens[pos] {
+                Token::LeftParen | Token::LeftBracket | Token::LeftBrace => depth += 1,
+                Token::RightParen | Token::RightBracket | Token::RightBrace => depth = depth.saturating_sub(1),
+                Token::Equals | Token::PlusEquals | Token::MinusEquals | Token::StarEquals | Token::SlashEquals
+                | Token::PercentEquals | Token::AmpersandEquals | Token::PipeEquals | Token::CaretEquals
+                | Token::LessLessEquals | Token::GreaterGreaterEquals if depth == 0 => return true,
+                _ => {}
+            }
+            pos += 1;
+        }
+        false
+    }
+
+    /// Consumes the `end` closing a block opened by `opener` at `opener_span`.
+    /// Running into EOF instead is reported at the opener rather than at EOF.
+    pub fn expect_block_end(&mut self, opener: &str, opener_span: SourceSpan) {
+        if self.current() != Token::EOF {
+            self.expect(Token::End, vec![]);
+            return;
+        }
+
+        self.diags.push(ParseDiagnostic {
+            message: format!("`{}` block is missing its `end`", opener),
+            span: opener_span,
+            severity: DiagnosticSeverity::Error,
+            help: Some("this block is never closed".to_string()),
+        });
+    }
+
+    /// Reports an error at the current token, then skips ahead to the next token in `sync`.
+    pub fn error_and_sync(&mut self, message: String, help: &str, sync: &[Token]) {
+        self.diags.push(ParseDiagnostic {
+            message,
+            span: self.current_span(),
+            severity: DiagnosticSeverity::Error,
+            help: Some(help.to_string()),
+        });
+
+        while !sync.contains(&self.current()) && self.current() != Token::EOF {
+            self.advance();
+        }
+    }
+
     pub fn expect(&mut self, expected: Token, sync: Vec<Token>) {
         let _start_pos = self.pos;
         let current = self.current();
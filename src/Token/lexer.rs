@@ -1,4 +1,5 @@
 use crate::import::*;
+use crate::Gen::API::error::Diagnostic;
 
 #[derive(Debug, Clone)]
 pub struct LexError {
@@ -61,7 +62,7 @@ impl Lexer {
         self.advance();  
         
          
-        let start_pos = self.pos;
+        let _start_pos = self.pos;
         let mut chars_count = 0;
         let mut temp_pos = self.pos;
         
@@ -190,7 +191,52 @@ impl Lexer {
     }
 
 
+    /// Reads `b'A'`, which must hold a single ASCII character.
+    fn read_byte_char(&mut self) -> Token {
+        let start = self.pos;
+        self.advance();
+        let message = match self.read_char() {
+            Token::Char(c) if (0..=0x7F).contains(&c) => return Token::ByteChar(c as u8),
+            Token::Char(c) => format!(
+                "Byte literal must be ASCII, found '{}'; use a character literal without `b` for codepoints",
+                char::from_u32(c as u32).unwrap_or('?')
+            ),
+            _ => "Byte literal must contain exactly one character".to_string(),
+        };
+        self.errors.push(LexError {
+            message,
+            span: SourceSpan::from(start..self.pos),
+        });
+        Token::ByteChar(0)
+    }
+
+    /// Reads a number, attaching a trailing integer suffix such as `u8` when present.
     fn read_number(&mut self) -> Token {
+        let token = self.read_untyped_number();
+        let value = match token {
+            Token::Number(n) => n,
+            Token::HexNumber(n) | Token::BinaryNumber(n) | Token::OctalNumber(n) => n as i64,
+            _ => return token,
+        };
+        match self.read_int_suffix() {
+            Some(suffix) => Token::TypedNumber(value, suffix),
+            None => token,
+        }
+    }
+
+    fn read_int_suffix(&mut self) -> Option<String> {
+        const SUFFIXES: [&str; 9] = ["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64", "usize"];
+        let len = (0..).take_while(|&i| self.peek(i).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')).count();
+        let word: String = self.chars[self.pos..self.pos + len].iter().collect();
+        if SUFFIXES.contains(&word.as_str()) {
+            self.pos += len;
+            Some(word)
+        } else {
+            None
+        }
+    }
+
+    fn read_untyped_number(&mut self) -> Token {
         let _start = self.pos;
 
  
@@ -198,71 +244,32 @@ impl Lexer {
             if self.peek(1) == Some('x') || self.peek(1) == Some('X') {
                 self.advance(); 
                 self.advance(); 
-                let mut num_str = String::new();
-                while let Some(ch) = self.current() {
-                    if ch.is_ascii_hexdigit() {
-                        num_str.push(ch);
-                        self.advance();
-                    } else {
-                        break;
-                    }
-                }
+                let num_str = self.read_digits(|ch| ch.is_ascii_hexdigit());
                 let num = u32::from_str_radix(&num_str, 16).unwrap_or(0);
                 return Token::HexNumber(num);
             } else if self.peek(1) == Some('b') || self.peek(1) == Some('B') {
                 self.advance(); 
                 self.advance(); 
-                let mut num_str = String::new();
-                while let Some(ch) = self.current() {
-                    if ch == '0' || ch == '1' {
-                        num_str.push(ch);
-                        self.advance();
-                    } else {
-                        break;
-                    }
-                }
+                let num_str = self.read_digits(|ch| ch == '0' || ch == '1');
                 let num = u32::from_str_radix(&num_str, 2).unwrap_or(0);
                 return Token::BinaryNumber(num);
             } else if self.peek(1) == Some('o') || self.peek(1) == Some('O') {
                 self.advance(); 
                 self.advance(); 
-                let mut num_str = String::new();
-                while let Some(ch) = self.current() {
-                    if ch.is_digit(8) {
-                        num_str.push(ch);
-                        self.advance();
-                    } else {
-                        break;
-                    }
-                }
+                let num_str = self.read_digits(|ch| ch.is_digit(8));
                 let num = u32::from_str_radix(&num_str, 8).unwrap_or(0);
                 return Token::OctalNumber(num);
             }
         }
 
  
-        let mut num_str = String::new();
-        while let Some(ch) = self.current() {
-            if ch.is_ascii_digit() {
-                num_str.push(ch);
-                self.advance();
-            } else {
-                break;
-            }
-        }
+        let mut num_str = self.read_digits(|ch| ch.is_ascii_digit());
 
  
         if self.current() == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
             num_str.push('.');
             self.advance();
-            while let Some(ch) = self.current() {
-                if ch.is_ascii_digit() {
-                    num_str.push(ch);
-                    self.advance();
-                } else {
-                    break;
-                }
-            }
+            num_str.push_str(&self.read_digits(|ch| ch.is_ascii_digit()));
             let float_val = num_str.parse::<f32>().unwrap_or(0.0);
             return Token::Float(OrderedFloat(float_val));
         }
@@ -271,6 +278,31 @@ impl Lexer {
         Token::Number(num)
     }
 
+    /// Reads a run of digits, dropping `_` separators. A separator has to sit between two digits.
+    fn read_digits(&mut self, is_digit: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        let mut digits = String::new();
+        let mut misplaced = false;
+        while let Some(ch) = self.current() {
+            if ch == '_' {
+                misplaced |= digits.is_empty() || self.peek(1).is_none_or(|next| !is_digit(next));
+            } else if is_digit(ch) {
+                digits.push(ch);
+            } else {
+                break;
+            }
+            self.advance();
+        }
+
+        if misplaced {
+            self.errors.push(LexError {
+                message: "Digit separators `_` must sit between two digits".to_string(),
+                span: SourceSpan::from(start..self.pos),
+            });
+        }
+        digits
+    }
+
        fn read_string(&mut self) -> Token {
         self.advance();
         let mut string = String::new();
@@ -281,56 +313,8 @@ impl Lexer {
                 break;
             } else if ch == '\\' {
                 self.advance();
-                if let Some(escaped) = self.current() {
-                    match escaped {
-                        'n' => string.push('\n'),
-                        't' => string.push('\t'),
-                        'r' => string.push('\r'),
-                        '\\' => string.push('\\'),
-                        '"' => string.push('"'),
-                        'x' => {
-                             
-                            self.advance();
-                            let mut hex = String::new();
-                            
-                             
-                            for _ in 0..2 {
-                                if let Some(h) = self.current() {
-                                    if h.is_ascii_hexdigit() {
-                                        hex.push(h);
-                                        self.advance();
-                                    } else {
-                                        break;
-                                    }
-                                } else {
-                                    break;
-                                }
-                            }
-                            
-                            if !hex.is_empty() {
-                                if let Ok(val) = u8::from_str_radix(&hex, 16) {
-                                    string.push(val as char);
-                                } else {
-                                     
-                                    string.push('\\');
-                                    string.push('x');
-                                    string.push_str(&hex);
-                                }
-                            } else {
-                                 
-                                string.push('\\');
-                                string.push('x');
-                            }
-                            continue;  
-                        }
-                        '0' => string.push('\0'),
-                        _ => {
-                             
-                            string.push('\\');
-                            string.push(escaped);
-                        }
-                    }
-                    self.advance();
+                if let Some(decoded) = self.read_escape() {
+                    string.push(decoded);
                 }
             } else {               
                 string.push(ch);
@@ -341,7 +325,122 @@ impl Lexer {
         Token::String(string)
     }
     
+    /// Reads `"""..."""`, keeping interior newlines and decoding escapes like `read_string`.
+    fn read_triple_quoted_string(&mut self) -> Token {
+        let start = self.pos;
+        self.pos += 3;
+        let mut string = String::new();
+
+        while let Some(ch) = self.current() {
+            if ch == '"' && self.peek(1) == Some('"') && self.peek(2) == Some('"') {
+                self.pos += 3;
+                return Token::String(string);
+            }
+            self.advance();
+            if ch == '\\' {
+                if let Some(decoded) = self.read_escape() {
+                    string.push(decoded);
+                }
+            } else {
+                string.push(ch);
+            }
+        }
+
+        self.errors.push(LexError {
+            message: "Unterminated triple-quoted string, expected `\"\"\"`".to_string(),
+            span: SourceSpan::from(start..self.pos),
+        });
+        Token::String(string)
+    }
+
+    /// The number of `#`s if the lexer is at the start of `r"..."` or `r#"..."#`.
+    fn raw_string_hashes(&self) -> Option<usize> {
+        let hashes = (1..).take_while(|&i| self.peek(i) == Some('#')).count();
+        (self.peek(hashes + 1) == Some('"')).then_some(hashes)
+    }
+
+    /// Reads a raw string verbatim, up to a `"` followed by the same number of `#`s.
+    fn read_raw_string(&mut self, hashes: usize) -> Token {
+        let start = self.pos;
+        self.pos += hashes + 2;
+        let mut string = String::new();
+
+        while let Some(ch) = self.current() {
+            if ch == '"' && (1..=hashes).all(|i| self.peek(i) == Some('#')) {
+                self.pos += hashes + 1;
+                return Token::String(string);
+            }
+            string.push(ch);
+            self.advance();
+        }
+
+        self.errors.push(LexError {
+            message: format!("Unterminated raw string, expected `\"{}`", "#".repeat(hashes)),
+            span: SourceSpan::from(start..self.pos),
+        });
+        Token::String(string)
+    }
+
+    /// Decodes the escape sequence after a `\`. Invalid escapes are reported and skipped.
+    fn read_escape(&mut self) -> Option<char> {
+        let start = self.pos - 1;
+        let Some(escaped) = self.advance() else {
+            return self.escape_error(start, "Unterminated escape sequence".to_string());
+        };
+        let decoded = match escaped {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            'x' => {
+                let digits = self.read_hex_digits(2);
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(byte) if digits.len() == 2 && byte <= 0x7F => byte as char,
+                    _ => return self.escape_error(start, "`\\x` escapes take exactly two hex digits from 00 to 7F".to_string()),
+                }
+            }
+            'u' => {
+                if self.current() != Some('{') {
+                    return self.escape_error(start, "Unicode escapes are written `\\u{...}`".to_string());
+                }
+                self.advance();
+                let digits = self.read_hex_digits(6);
+                if self.current() != Some('}') {
+                    return self.escape_error(start, "Unicode escapes take one to six hex digits and a closing `}`".to_string());
+                }
+                self.advance();
+                match u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32) {
+                    Some(ch) => ch,
+                    None => return self.escape_error(start, format!("`\\u{{{}}}` is not a valid Unicode character", digits)),
+                }
+            }
+            other => return self.escape_error(start, format!("Unknown escape sequence `\\{}`", other)),
+        };
+        Some(decoded)
+    }
+
+    fn read_hex_digits(&mut self, max: usize) -> String {
+        let mut digits = String::new();
+        while digits.len() < max && let Some(h) = self.current().filter(char::is_ascii_hexdigit) {
+            digits.push(h);
+            self.advance();
+        }
+        digits
+    }
+
+    fn escape_error(&mut self, start: usize, message: String) -> Option<char> {
+        self.errors.push(LexError {
+            message,
+            span: SourceSpan::from(start..self.pos),
+        });
+        None
+    }
+
     fn read_identifier(&mut self) -> Token {
+        let start = self.pos;
         let mut ident = String::new();
 
         while let Some(ch) = self.current() {
@@ -353,6 +452,13 @@ impl Lexer {
             }
         }
 
+        if ident.starts_with("__vix_") {
+            self.errors.push(LexError {
+                message: format!("Identifier '{}' uses the `__vix_` prefix reserved for the compiler", ident),
+                span: SourceSpan::from(start..self.pos),
+            });
+        }
+
         let is_type_ident = ident.chars().next().is_some_and(|c| c.is_lowercase()) &&
                         (ident.starts_with("int") || 
                             ident.starts_with("uint") || 
@@ -376,6 +482,8 @@ impl Lexer {
             "if" => Token::If,
             "else" => Token::Else,
             "while" => Token::While,
+            "loop" => Token::Loop,
+            "assert" => Token::Assert,
             "for" => Token::For,
             "break" => Token::Break,
             "continue" => Token::Continue,
@@ -393,6 +501,7 @@ impl Lexer {
             "case" => Token::Case,
             "default" => Token::Default,
             "unsafe" => Token::Unsafe,
+            "defer" => Token::Defer,
             "then" => Token::Then,
             "do" => Token::Do,
             "in" => Token::In,
@@ -452,17 +561,38 @@ impl Lexer {
         }
     }
 
+    /// Lexer errors as structured diagnostics with line and column resolved.
+    pub fn collect_diagnostics(&self) -> Vec<Diagnostic> {
+        self.errors.iter().map(|error| {
+            let offset = error.span.offset();
+            let line_start = self.chars[..offset].iter().rposition(|&c| c == '\n').map_or(0, |i| i + 1);
+            let location = SourceLocation {
+                file: "input".to_string(),
+                line: self.chars[..offset].iter().filter(|&&c| c == '\n').count() + 1,
+                column: offset - line_start + 1,
+                length: error.span.len().max(1),
+            };
+            Diagnostic::error("LexError", &error.message, ErrorContext {
+                primary_location: location,
+                secondary_locations: vec![],
+                help_message: None,
+                suggestions: vec![],
+            })
+        }).collect()
+    }
+
     pub fn tokenize(&mut self) -> Vec<Token> {
         let mut tokens = Vec::new();
 
         while self.pos < self.chars.len() {
-            let start = self.pos;
             self.skip_whitespace();
 
             if self.pos >= self.chars.len() {
                 break;
             }
 
+            let start = self.pos;
+
  
             if self.current() == Some('/') && self.peek(1) == Some('/') {
                 self.skip_comment();
@@ -502,6 +632,9 @@ impl Lexer {
                         self.advance();
                         self.advance();
                         Token::TripleDot
+                    } else if self.current() == Some('.') {
+                        self.advance();
+                        Token::DoubleDot
                     } else {
                         Token::Dot
                     }
@@ -634,8 +767,12 @@ impl Lexer {
                 Some('~') => { self.advance(); Token::Tilde }
                 Some('#') => { self.advance(); Token::Hash }
                 Some('@') => { self.advance(); Token::At }
+                Some('?') => { self.advance(); Token::Question }
+                Some('"') if self.peek(1) == Some('"') && self.peek(2) == Some('"') => self.read_triple_quoted_string(),
                 Some('"') => self.read_string(),
+                Some('r') if let Some(hashes) = self.raw_string_hashes() => self.read_raw_string(hashes),
                 Some('\'') => self.read_char(),
+                Some('b') if self.peek(1) == Some('\'') => self.read_byte_char(),
                 Some(ch) if ch.is_ascii_digit() => self.read_number(),
                 Some(ch) if ch.is_alphabetic() || ch == '_' => self.read_identifier(),
                 Some(ch) => {
@@ -659,4 +796,130 @@ impl Lexer {
         
         tokens
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex_string(source: &str) -> (Option<String>, Vec<String>) {
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let value = tokens.iter().find_map(|t| match t {
+            Token::String(s) => Some(s.clone()),
+            _ => None,
+        });
+        (value, lexer.errors.iter().map(|e| e.message.clone()).collect())
+    }
+
+    #[test]
+    fn test_string_escapes_are_decoded() {
+        let cases = [
+            (r#""a\nb""#, "a\nb"),
+            (r#""a\tb""#, "a\tb"),
+            (r#""a\\b""#, "a\\b"),
+            (r#""say \"hi\"""#, "say \"hi\""),
+            (r#""\x41\x7e""#, "A~"),
+            (r#""\u{48}\u{e9}\u{1F600}""#, "H\u{e9}\u{1F600}"),
+            (r#""nul\0""#, "nul\0"),
+        ];
+        for (source, expected) in cases {
+            let (value, errors) = lex_string(source);
+            assert_eq!(value.as_deref(), Some(expected), "{}", source);
+            assert!(errors.is_empty(), "{}: {:?}", source, errors);
+        }
+    }
+
+    #[test]
+    fn test_invalid_string_escapes_are_errors() {
+        let cases = [
+            (r#""\q""#, "Unknown escape sequence `\\q`"),
+            (r#""\x4""#, "`\\x` escapes take exactly two hex digits from 00 to 7F"),
+            (r#""\xff""#, "`\\x` escapes take exactly two hex digits from 00 to 7F"),
+            (r#""\u41""#, "Unicode escapes are written `\\u{...}`"),
+            (r#""\u{D800}""#, "`\\u{D800}` is not a valid Unicode character"),
+        ];
+        for (source, message) in cases {
+            let (_, errors) = lex_string(source);
+            assert_eq!(errors, vec![message.to_string()], "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_raw_strings_keep_contents_verbatim() {
+        let (value, errors) = lex_string(r#"r"C:\new\table""#);
+        assert_eq!(value.as_deref(), Some(r"C:\new\table"));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let (value, errors) = lex_string(r###"r#"say "hi"\n"#"###);
+        assert_eq!(value.as_deref(), Some(r#"say "hi"\n"#));
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        let mut lexer = Lexer::new("rate = r2");
+        assert!(!lexer.tokenize().iter().any(|t| matches!(t, Token::String(_))));
+    }
+
+    #[test]
+    fn test_unterminated_raw_string_is_an_error() {
+        let (value, errors) = lex_string(r##"r#"open "quote"##);
+        assert_eq!(value.as_deref(), Some(r#"open "quote"#));
+        assert_eq!(errors, vec!["Unterminated raw string, expected `\"#`".to_string()]);
+    }
+
+    #[test]
+    fn test_triple_quoted_string_spans_lines() {
+        let source = "create text = \"\"\"caf\u{e9}\n  \"quoted\"\nend\"\"\"\ncreate after = 1\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        assert!(lexer.errors.is_empty(), "{:?}", lexer.errors);
+        assert_eq!(tokens[3], Token::String("caf\u{e9}\n  \"quoted\"\nend".to_string()));
+
+        let after = tokens.iter().position(|t| *t == Token::Identifier("after".to_string())).unwrap();
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let location = parser.get_location(lexer.spans[after].offset());
+        assert_eq!((location.line, location.column), (4, 8));
+    }
+
+    #[test]
+    fn test_byte_and_codepoint_char_literals() {
+        let mut lexer = Lexer::new("b'A' 'A' '\u{e9}' b'\\n'");
+        let tokens = lexer.tokenize();
+        assert!(lexer.errors.is_empty(), "{:?}", lexer.errors);
+        assert_eq!(&tokens[..4], &[Token::ByteChar(b'A'), Token::Char('A' as i32), Token::Char(0xE9), Token::ByteChar(b'\n')]);
+
+        let mut lexer = Lexer::new("b'\u{e9}'");
+        lexer.tokenize();
+        let messages: Vec<_> = lexer.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["Byte literal must be ASCII, found '\u{e9}'; use a character literal without `b` for codepoints"]);
+    }
+
+    #[test]
+    fn test_numeric_literal_separators() {
+        let mut lexer = Lexer::new("1_000 0xFF_FF 0b1010_1010 0o7_7 3_141.5_9");
+        let tokens = lexer.tokenize();
+        assert!(lexer.errors.is_empty(), "{:?}", lexer.errors);
+        assert_eq!(&tokens[..5], &[
+            Token::Number(1000),
+            Token::HexNumber(0xFFFF),
+            Token::BinaryNumber(0b1010_1010),
+            Token::OctalNumber(0o77),
+            Token::Float(OrderedFloat(3141.59)),
+        ]);
+
+        for source in ["1__0", "1_", "0x_FF", "1_.5"] {
+            let mut lexer = Lexer::new(source);
+            lexer.tokenize();
+            let messages: Vec<_> = lexer.errors.iter().map(|e| e.message.as_str()).collect();
+            assert_eq!(messages, vec!["Digit separators `_` must sit between two digits"], "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_compiler_temporary_prefix_is_reserved() {
+        let mut lexer = Lexer::new("create __vix_t0 = 1\ncreate _t0 = 2\n");
+        lexer.tokenize();
+        assert_eq!(lexer.errors.len(), 1, "{:?}", lexer.errors);
+        assert!(lexer.errors[0].message.contains("'__vix_t0'"));
+        assert_eq!(lexer.errors[0].span, SourceSpan::from(7..15));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::import::*;
+use crate::Gen::API::error::Diagnostic;
 
 #[derive(Debug, Clone)]
 pub struct ImportContext {
@@ -58,7 +59,7 @@ impl ImportContext {
     }
 
     pub fn add_module_export(&mut self, module: String, function: String) {
-        self.module_exports.entry(module).or_insert_with(Vec::new).push(function);
+        self.module_exports.entry(module).or_default().push(function);
     }
 
     pub fn is_module_function(&self, name: &str) -> bool {
@@ -69,13 +70,35 @@ impl ImportContext {
 
 impl Parser {
     pub fn new(tokens: Vec<Token>, source: String, spans: Vec<SourceSpan>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.chars().enumerate().filter(|&(_, c)| c == '\n').map(|(i, _)| i + 1))
+            .collect();
+        let type_names = tokens
+            .windows(2)
+            .filter_map(|w| match (&w[0], &w[1]) {
+                (Token::Struct | Token::Enum, Token::Identifier(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
+        let module_names = tokens
+            .windows(4)
+            .filter_map(|w| match (&w[0], &w[1], &w[2], &w[3]) {
+                (Token::Mod, Token::Identifier(name), Token::LeftBrace, _) => Some(name.clone()),
+                (Token::Use, Token::Identifier(_), Token::As, Token::Identifier(alias)) => Some(alias.clone()),
+                _ => None,
+            })
+            .collect();
         Parser { 
             tokens, 
             spans, 
             pos: 0, 
             source: Arc::new(source), 
+            line_starts,
             diags: Vec::new(),
             type_aliases: HashMap::new(),
+            const_values: HashMap::new(),
+            type_names,
+            module_names,
         }
     }
 
@@ -126,44 +149,48 @@ impl Parser {
          
         let param = if self.current() == Token::LeftParen {
             self.advance();
-            let value = if let Token::String(s) = self.current() {
-                self.advance();
-                Some(s)
-            } else {
-                None
+            let value = match self.current() {
+                Token::String(s) | Token::Identifier(s) | Token::TypeIdentifier(s) => Some(s),
+                Token::Number(n) => Some(n.to_string()),
+                _ => None,
             };
+            if value.is_some() {
+                self.advance();
+            }
             self.expect(Token::RightParen, vec![Token::Extern]);
             value
-        } else if self.current() == Token::RightBracket {
-            self.expect(Token::RightBracket, vec![Token::Func]);
-            None
         } else {
             None
         };
+        if self.current() == Token::RightBracket {
+            self.expect(Token::RightBracket, vec![Token::Func]);
+        }
         
         (name, param)
     }
-    pub fn get_location(&self, pos: usize) -> SourceLocation {
-        let lines: Vec<&str> = self.source.lines().collect();
-        let mut current_pos = 0;
-        
-        for (line_num, line) in lines.iter().enumerate() {
-            let line_len = line.len() + 1; 
-            if current_pos + line_len > pos {
-                return SourceLocation {
-                    file: "input".to_string(),
-                    line: line_num + 1,
-                    column: pos - current_pos + 1,
-                    length: 1,
-                };
+    /// Parse diagnostics converted to the structured form codegen reports.
+    pub fn collect_diagnostics(&self) -> Vec<Diagnostic> {
+        self.diags.iter().map(|diag| {
+            let context = ErrorContext {
+                primary_location: self.get_location(diag.span.offset()),
+                secondary_locations: vec![],
+                help_message: diag.help.clone(),
+                suggestions: vec![],
+            };
+            match diag.severity {
+                DiagnosticSeverity::Error => Diagnostic::error("ParseError", &diag.message, context),
+                DiagnosticSeverity::Warning | DiagnosticSeverity::Info => Diagnostic::warning("ParseError", &diag.message, context),
             }
-            current_pos += line_len;
-        }
-        
+        }).collect()
+    }
+
+    /// `pos` is a character offset, as recorded in the lexer's spans.
+    pub fn get_location(&self, pos: usize) -> SourceLocation {
+        let line = self.line_starts.partition_point(|&start| start <= pos).max(1);
         SourceLocation {
             file: "input".to_string(),
-            line: lines.len(),
-            column: 1,
+            line,
+            column: pos - self.line_starts[line - 1] + 1,
             length: 1,
         }
     }
@@ -255,14 +282,12 @@ impl Parser {
                 if self.current() == Token::Semicolon {
                      
                     self.advance();
-                    if let Token::Number(size) = self.current() {
-                        self.advance();
-                        self.expect(Token::RightBracket, vec![Token::Semicolon, Token::End]);
-                        return Type::Array {
-                            element: Box::new(elem_type),
-                            size: Some(size as usize),
-                        };
-                    }
+                    let size = self.parse_array_size();
+                    self.expect(Token::RightBracket, vec![Token::Semicolon, Token::End]);
+                    return Type::Array {
+                        element: Box::new(elem_type),
+                        size: Some(size),
+                    };
                 }
                 
                 self.expect(Token::RightBracket, vec![Token::Semicolon, Token::End]);
@@ -311,17 +336,23 @@ impl Parser {
 
             Token::TypeIdentifier(type_name) => {
                 self.advance();
-                let base_type = Parser::parse_type_identifier(&type_name);
+                let base_type = if matches!(type_name.as_str(), "int" | "uint") && self.current() == Token::LeftParen {
+                    self.parse_int_width(type_name == "int")
+                } else {
+                    Parser::parse_type_identifier(&type_name)
+                };
                 let mut dimensions = Vec::new();
                 while self.current() == Token::LeftBracket {
                     self.advance();
-                    if let Token::Number(size) = self.current() {
-                        dimensions.push(size as usize);
+                    if self.current() == Token::RightBracket && dimensions.is_empty() {
                         self.advance();
-                        self.expect(Token::RightBracket, vec![Token::LeftBracket]);
-                    } else {
+                        return Type::Array { element: Box::new(base_type), size: None };
+                    }
+                    if matches!(self.current(), Token::RightBracket | Token::EOF) {
                         return Type::Void;
                     }
+                    dimensions.push(self.parse_array_size());
+                    self.expect(Token::RightBracket, vec![Token::LeftBracket]);
                 }
                 if !dimensions.is_empty() {
                     Type::MultiArray {
@@ -337,6 +368,22 @@ impl Parser {
                 let inner = self.parse_base_type();
                 Type::RawPtr(Box::new(inner))
             }
+            Token::Func => {
+                self.advance();
+                self.expect(Token::LeftParen, vec![Token::RightParen]);
+
+                let params = self.parse_comma_list(Token::RightParen, Self::parse_type);
+                self.expect(Token::RightParen, vec![Token::Colon, Token::Comma, Token::End]);
+
+                let ret = if matches!(self.current(), Token::Colon | Token::Arrow) {
+                    self.advance();
+                    self.parse_type()
+                } else {
+                    Type::Void
+                };
+
+                Type::FunctionPointer { params, ret: Box::new(ret) }
+            }
             Token::TripleDot => { self.advance(); Type::TripleDot }
             Token::Any => { self.advance(); Type::Any }
             Token::Tilde => {
@@ -345,13 +392,22 @@ impl Parser {
             }
             Token::Ampersand => {
                 self.advance();
-                if self.current() == Token::Mut {
+                if self.current() == Token::LeftBracket {
+                    self.advance();
+                    let element = self.parse_type();
+                    self.expect(Token::RightBracket, vec![Token::Comma, Token::End]);
+                    Type::Slice { element: Box::new(element) }
+                } else if self.current() == Token::Mut {
                     self.advance();
                     Type::MutRef(Box::new(self.parse_base_type()))
                 } else {
                     Type::Ref(Box::new(self.parse_base_type()))
                 }
             }
+            Token::Identifier(type_name) if type_name == "Self" => {
+                self.advance();
+                Type::SelfType
+            }
             Token::Identifier(type_name) => {
                 self.advance();
                 if self.current() == Token::LeftBracket {
@@ -503,6 +559,16 @@ impl Parser {
         let current = self.current();
 
         match current {
+            Token::If => {
+                self.advance();
+                let cond = self.parse_expr();
+                self.expect(Token::Then, vec![Token::Else]);
+                let then_expr = self.parse_expr();
+                self.expect(Token::Else, vec![]);
+                let else_expr = self.parse_expr();
+                Expr::IfExpr(Box::new(cond), Box::new(then_expr), Box::new(else_expr))
+            }
+
              Token::Plan => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
@@ -518,14 +584,7 @@ impl Parser {
                 let mut args = Vec::new();
                 if self.current() == Token::Comma {
                     self.advance();
-                    while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                        args.push(self.parse_expr());
-                        if self.current() == Token::Comma {
-                            self.advance();
-                        } else {
-                            break;
-                        }
-                    }
+                    args.extend(self.parse_comma_list(Token::RightParen, Self::parse_expr));
                 }
                 
                 self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
@@ -544,18 +603,20 @@ impl Parser {
                         let method = method_name.clone();
                         self.advance();
                         
+                        if matches!(method.as_str(), "len" | "length") {
+                            if self.current() == Token::LeftParen {
+                                self.advance();
+                                self.expect(Token::RightParen, vec![Token::Semicolon]);
+                            }
+                            return Expr::MethodCall(Box::new(Expr::String(string_val)), method, vec![]);
+                        }
+
                         if self.current() == Token::LeftParen {
                             self.advance();
                             
                              
                             let mut args = vec![Expr::String(string_val)];
-                            
-                            while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                                args.push(self.parse_expr());
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            }
+                            args.extend(self.parse_comma_list(Token::RightParen, Self::parse_expr));
                             self.expect(Token::RightParen, vec![Token::Semicolon]);
                             return Expr::Call(method, args);
                         } else {
@@ -568,6 +629,34 @@ impl Parser {
                 Expr::String(string_val)
             }
             
+            Token::TripleDot => {
+                self.advance();
+                Expr::Var("...".to_string())
+            }
+
+            Token::SizeOf | Token::Identifier(_) if self.is_sizeof_of_type() => {
+                self.advance();
+                self.advance();
+                let target_type = self.parse_type();
+                self.expect(Token::RightParen, vec![Token::Semicolon]);
+                Expr::SizeOf(target_type)
+            }
+
+            Token::SizeOf => {
+                self.advance();
+                self.expect(Token::LeftParen, vec![Token::RightParen]);
+                let expr = self.parse_expr();
+                self.expect(Token::RightParen, vec![Token::Semicolon]);
+                Expr::Call("sizeof".to_string(), vec![expr])
+            }
+
+            Token::Identifier(name) if name == "va_next" && self.peek(1) == Token::LeftParen => {
+                self.advance();
+                self.advance();
+                let ty = self.parse_type();
+                self.expect(Token::RightParen, vec![Token::Semicolon]);
+                Expr::VaNext(ty)
+            }
 
             Token::Identifier(name) => {
                 let var_name = name.clone();
@@ -578,7 +667,18 @@ impl Parser {
                      
                     return aliased_expr.clone();
                 }
+
+    if self.current() == Token::LeftBrace
+        && var_name.starts_with(char::is_uppercase)
+        && (self.peek(1) == Token::RightBrace
+            || (matches!(self.peek(1), Token::Identifier(_)) && self.peek(2) == Token::Equals)) {
+        return self.parse_struct_literal(var_name);
+    }
     
+    if self.current() == Token::Dot && self.module_names.contains(&var_name) {
+        return self.parse_module_path(var_name);
+    }
+
     if self.current() == Token::Dot {
         self.advance();
         
@@ -589,19 +689,10 @@ impl Parser {
             if self.current() == Token::LeftParen {
                 self.advance();
                 
-                let mut args = Vec::new();
-                while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                    args.push(self.parse_expr());
-                    if self.current() == Token::Comma {
-                        self.advance();
-                    } else {
-                        break;
-                    }
-                }
-                
+                let args = self.parse_comma_list(Token::RightParen, Self::parse_expr);
                 self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
                 
-                if var_name.chars().next().unwrap().is_uppercase() {
+                if self.type_names.contains(&var_name) {
                     return Expr::StaticMethodCall(var_name, method_or_field, args);
                 } else {
                     return Expr::MethodCall(
@@ -618,35 +709,18 @@ impl Parser {
         self.advance();
         
         if matches!(self.current(), Token::Identifier(_)) && self.peek(1) == Token::Equals {
-            let mut named_args = Vec::new();
-            while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                if let Token::Identifier(arg_name) = self.current() {
-                    self.advance();
-                    self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
-                    let arg_expr = self.parse_expr();
-                    named_args.push((arg_name, arg_expr));
-                    if self.current() == Token::Comma {
-                        self.advance();
-                    }
-                }
-            }
+            let named_args = self.parse_comma_list(Token::RightParen, Self::parse_named_arg);
             self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
             return Expr::CallNamed(var_name, named_args);
         }
         
-        let mut args = Vec::new();
-        while !matches!(self.current(), Token::RightParen | Token::EOF) {
-            args.push(self.parse_expr());
-            if self.current() == Token::Comma {
-                self.advance();
-            }
-        }
+        let args = self.parse_comma_list(Token::RightParen, Self::parse_expr);
         self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
         
         return Expr::Call(var_name, args);
     }
 
-    if var_name.chars().next().unwrap().is_uppercase() {
+    if self.type_names.contains(&var_name) {
         return Expr::Call(var_name, vec![]);
     }
     Expr::Var(var_name)
@@ -664,31 +738,23 @@ impl Parser {
                         
                         if self.current() == Token::LeftParen {
                             self.advance();
-                            let mut args = vec![Expr::Var("self".to_string())];
-                            
-                            while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                                args.push(self.parse_expr());
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            }
+                            let args = self.parse_comma_list(Token::RightParen, Self::parse_expr);
                             self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                            return Expr::MethodCall(Box::new(Expr::Var("self".to_string())), method, args[1..].to_vec());
+                            return Expr::MethodCall(Box::new(Expr::Var("self".to_string())), method, args);
                         } else {
                             return Expr::MemberAccess(Box::new(Expr::Var("self".to_string())), method);
                         }
                     }
                 } else if self.current() == Token::LeftParen {
+                    self.diags.push(ParseDiagnostic {
+                        message: "`self` is a value and cannot be called".to_string(),
+                        span: self.current_span(),
+                        severity: DiagnosticSeverity::Error,
+                        help: Some("Call a method on it instead, e.g. `self.method(...)`".to_string()),
+                    });
                     self.advance();
-                    let mut args = Vec::new();
-                    while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                        args.push(self.parse_expr());
-                        if self.current() == Token::Comma {
-                            self.advance();
-                        }
-                    }
+                    self.parse_comma_list(Token::RightParen, Self::parse_expr);
                     self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
-                    return Expr::Call("self".to_string(), args);
                 }
                 
                 Expr::Var("self".to_string())
@@ -697,13 +763,7 @@ impl Parser {
             Token::OneOf => {
                 self.advance();
                 self.expect(Token::LeftParen, vec![Token::RightParen]);
-                let mut exprs = Vec::new();
-                while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                    exprs.push(self.parse_expr());
-                    if self.current() == Token::Comma {
-                        self.advance();
-                    }
-                }
+                let exprs = self.parse_comma_list(Token::RightParen, Self::parse_expr);
                 self.expect(Token::RightParen, vec![Token::Semicolon]);
                 Expr::OneOf(exprs)
             }
@@ -747,7 +807,9 @@ impl Parser {
             }
             Token::None => {self.advance();Expr::None}
             Token::Number(n) => { self.advance(); Expr::Number(n) }
+            Token::TypedNumber(n, suffix) => { self.advance(); Expr::TypedNumber(n, suffix_type(&suffix)) }
             Token::Char(ch) => {  self.advance(); Expr::Char(ch) }
+            Token::ByteChar(b) => { self.advance(); Expr::ByteChar(b) }
             Token::Float(f) => { self.advance(); Expr::Float(f.into_inner()) }
             Token::HexNumber(n) => { self.advance(); Expr::HexNumber(n as i32) }
             Token::BinaryNumber(n) => { self.advance(); Expr::BinaryNumber(n as i32) }
@@ -786,12 +848,9 @@ impl Parser {
                 }
                 let first_expr = self.parse_expr();
                 if self.current() == Token::Comma {
+                    self.advance();
                     let mut elements = vec![first_expr];
-                    while self.current() == Token::Comma {
-                        self.advance();
-                        if self.current() == Token::RightParen { break; }
-                        elements.push(self.parse_expr());
-                    }
+                    elements.extend(self.parse_comma_list(Token::RightParen, Self::parse_expr));
                     self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
                     Expr::Tuple(elements)
                 } else {
@@ -801,13 +860,7 @@ impl Parser {
             }
             Token::LeftBracket => {
                 self.advance();
-                let mut elements = Vec::new();
-                while !matches!(self.current(), Token::RightBracket | Token::EOF) {
-                    elements.push(self.parse_expr());
-                    if self.current() == Token::Comma {
-                        self.advance();
-                    }
-                }
+                let elements = self.parse_comma_list(Token::RightBracket, Self::parse_expr);
                 self.expect(Token::RightBracket, vec![Token::Semicolon, Token::End]);
                 Expr::Array(elements)
             }
@@ -841,6 +894,37 @@ impl Parser {
         }
     }
 
+    /// Parses `.inner.func(args)` or `.inner.member` after the module name `first`.
+    fn parse_module_path(&mut self, first: String) -> Expr {
+        let mut path = vec![first];
+        loop {
+            self.advance();
+            let Token::Identifier(name) = self.current() else {
+                self.diags.push(ParseDiagnostic {
+                    message: format!("Expected a name after `{}.`, found {:?}", path.join("."), self.current()),
+                    span: self.current_span(),
+                    severity: DiagnosticSeverity::Error,
+                    help: Some("module members are written `module.name`".to_string()),
+                });
+                return Expr::Var(path.remove(0));
+            };
+            self.advance();
+
+            if self.current() == Token::Dot && self.module_names.contains(&name) {
+                path.push(name);
+                continue;
+            }
+
+            if self.current() == Token::LeftParen {
+                self.advance();
+                let args = self.parse_comma_list(Token::RightParen, Self::parse_expr);
+                self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
+                return Expr::ModuleCall(path, name, args);
+            }
+            return Expr::ModuleAccess(path, name);
+        }
+    }
+
     fn parse_post(&mut self, mut expr: Expr) -> Expr {
         loop {
             match self.current() {
@@ -855,14 +939,7 @@ impl Parser {
                         if self.current() == Token::LeftParen {
                             self.advance();
                             
-                            let mut args = Vec::new();
-                            while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                                args.push(self.parse_expr());
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            }
-                            
+                            let args = self.parse_comma_list(Token::RightParen, Self::parse_expr);
                             self.expect(Token::RightParen, vec![Token::Semicolon, Token::End]);
                             
                              
@@ -898,16 +975,23 @@ impl Parser {
                 Token::LeftBracket => {
                     self.advance();
                     let start = self.parse_expr();
-                    
-                    if self.current() == Token::DoubleDot {
-                        self.advance();
-                        let end = self.parse_expr();
-                        self.expect(Token::RightBracket, vec![Token::Dot, Token::LeftBracket]);
-                        expr = Expr::Slice(Box::new(expr), Box::new(start), Box::new(end));
-                    } else {
-                        self.expect(Token::RightBracket, vec![Token::Dot, Token::LeftBracket]);
-                        expr = Expr::Index(Box::new(expr), vec![start]);
-                    }
+                    self.expect(Token::RightBracket, vec![Token::Dot, Token::LeftBracket]);
+
+                    expr = match start {
+                        Expr::Range(lo, hi, inclusive) => {
+                            let end = if inclusive {
+                                Box::new(Expr::BinOp("+".to_string(), hi, Box::new(Expr::Number(1))))
+                            } else {
+                                hi
+                            };
+                            Expr::Slice(Box::new(expr), lo, end)
+                        }
+                        index => Expr::Index(Box::new(expr), vec![index]),
+                    };
+                }
+                Token::Question => {
+                    self.advance();
+                    expr = Expr::Try(Box::new(expr));
                 }
                 _ => break,
             }
@@ -924,18 +1008,17 @@ impl Parser {
             }
             Token::Ampersand => {
                 self.advance();
-                if let Token::Identifier(name) = self.current() {
-                    self.advance();
-                    if self.current() == Token::LeftParen {
+                if let Token::Identifier(name) = self.current()
+                    && !matches!(self.peek(1), Token::LeftParen | Token::Dot | Token::LeftBracket) {
                         self.advance();
-                        Expr::Number(0)
-                    } else {
                         Expr::FuncAddr(name)
+                    } else {
+                        Expr::AddressOf(Box::new(self.parse_unary()))
                     }
-                } else {
-                    let expr = self.parse_unary();
-                    Expr::UnOp("&".to_string(), Box::new(expr))
-                }
+            }
+            Token::Star => {
+                self.advance();
+                Expr::Deref(Box::new(self.parse_unary()))
             }
             Token::Minus => {
                 let op = match self.current() {
@@ -943,14 +1026,111 @@ impl Parser {
                     _ => unreachable!(),
                 };
                 self.advance();
-                let expr = self.parse_unary();
-                Expr::UnOp(op, Box::new(expr))
+                match self.parse_unary() {
+                    // folded so `-128i8` is checked as -128 rather than 128
+                    Expr::TypedNumber(n, ty) => Expr::TypedNumber(-n, ty),
+                    expr => Expr::UnOp(op, Box::new(expr)),
+                }
             }
             _ => {
                 let primary = self.parse_primary();
-                self.parse_post(primary)
+                let mut expr = self.parse_post(primary);
+                while self.current() == Token::As {
+                    self.advance();
+                    let ty = self.parse_type();
+                    expr = Expr::Cast(Box::new(expr), CastTarget::Type(ty));
+                }
+                expr
+            }
+        }
+    }
+
+    /// Parses the parameter list of a method after `(`, including an optional leading `self`.
+    fn parse_method_params(&mut self) -> (Option<SelfModifier>, Vec<(String, Type, ParamModifier)>) {
+        let mut params = Vec::new();
+        let mut self_modifier = None;
+        
+         
+        let is_self_token = |t: &Token| matches!(t, Token::Selfish) || matches!(t, Token::Identifier(s) if s == "self");
+        
+        if is_self_token(&self.current()) {
+            self.advance();
+            self_modifier = Some(SelfModifier::Immutable);
+            if self.current() == Token::Comma { self.advance(); }
+        } 
+        else if (matches!(self.current(), Token::Mutable | Token::Mut)) && is_self_token(&self.peek(1)) {
+            self.advance(); self.advance();
+            self_modifier = Some(SelfModifier::Mutable);
+            if self.current() == Token::Comma { self.advance(); }
+        }
+        else if (matches!(self.current(), Token::Reference | Token::Ampersand)) && is_self_token(&self.peek(1)) {
+            self.advance(); 
+            self.advance();
+
+            self_modifier = Some(SelfModifier::Reference);
+            if self.current() == Token::Comma { self.advance(); }
+        }
+        else if (matches!(self.current(), Token::Reference | Token::Ampersand)) && (matches!(self.peek(1), Token::Mutable | Token::Mut)) && is_self_token(&self.peek(2)) {
+            self.advance(); self.advance(); self.advance();
+             
+            self_modifier = Some(SelfModifier::Mutable); 
+            if self.current() == Token::Comma { self.advance(); }
+        }
+        else if let Token::Identifier(s) = self.current()
+            && s == "brw" && is_self_token(&self.peek(1)) {
+                self.advance(); self.advance();
+                self_modifier = Some(SelfModifier::Borrow);
+                if self.current() == Token::Comma { self.advance(); }
+            }
+                
+        while self.current() != Token::RightParen && self.current() != Token::EOF {
+            let modifier = match self.current() {
+                Token::Mutable => {
+                    self.advance();
+                    ParamModifier::Mutable
+                }
+                Token::Reference => {
+                    self.advance();
+                    ParamModifier::Reference
+                }
+                _ => ParamModifier::Immutable
+            };
+            
+            let pname = if let Token::Identifier(pname) = self.current() {
+                self.advance();
+                pname
+            } else {
+                self.advance();
+                "error".to_string()
+            };
+            
+            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
+            let ptype = self.parse_type();
+            params.push((pname, ptype, modifier));
+            
+            if self.current() == Token::Comma {
+                self.advance();
             }
         }
+
+        (self_modifier, params)
+    }
+
+    /// Whether the current `sizeof(` or `size_of(` takes a type rather than an expression.
+    fn is_sizeof_of_type(&self) -> bool {
+        let is_sizeof = match self.current() {
+            Token::SizeOf => true,
+            Token::Identifier(name) => name == "size_of",
+            _ => false,
+        };
+        if !is_sizeof || self.peek(1) != Token::LeftParen {
+            return false;
+        }
+        match self.peek(2) {
+            Token::TypeIdentifier(_) | Token::Bool | Token::Str | Token::StdStr | Token::Usize | Token::Void | Token::Option | Token::Result => true,
+            Token::Identifier(name) => self.type_names.contains(&name) && self.peek(3) == Token::RightParen,
+            _ => false,
+        }
     }
 
     pub fn is_type_token(&self, token: Token) -> bool {
@@ -959,12 +1139,26 @@ impl Parser {
             Token::Str | Token::Ampersand| Token::BitwiseAnd | Token::TripleDot |
             Token::Tilde | Token::Mut | Token::LeftParen | Token::LeftBracket |
             Token::Identifier(_) | Token::Option | Token::Result | Token::Selfish |
-            Token::Trait | Token::Caret | Token::StdStr
+            Token::Trait | Token::Caret | Token::StdStr | Token::Usize
         )
     }
 
     pub fn parse_expr(&mut self) -> Expr {
-        self.parse_logic_or()
+        let expr = self.parse_logic_or();
+
+        if self.current() == Token::DoubleDot {
+            self.advance();
+            let inclusive = if self.current() == Token::Equals {
+                self.advance();
+                true
+            } else {
+                false
+            };
+            let end = self.parse_logic_or();
+            return Expr::Range(Box::new(expr), Box::new(end), inclusive);
+        }
+
+        expr
     }
 
     fn parse_logic_or(&mut self) -> Expr {
@@ -1093,6 +1287,10 @@ impl Parser {
         let mut node = self.parse_unary();
 
         while matches!(self.current(), Token::Star | Token::Slash | Token::Percent) {
+            // `*p = v` on its own line is the next statement, not a multiplication
+            if self.starts_deref_assignment() {
+                break;
+            }
             let op = match self.current() {
                 Token::Star => "*".to_string(),
                 Token::Slash => "/".to_string(),
@@ -1107,28 +1305,197 @@ impl Parser {
         node
     }
 
-    fn parse_stmt(&mut self) -> Stmt {
-        match self.current() {
-            Token::Let => {
-                self.advance();
-                
-                let name = if let Token::Identifier(n) = self.current() {
-                    n
-                } else {
-                    panic!("Expected identifier after 'let'");
-                };
-                self.advance();
+    /// Parses the `(N)` of `int(N)` / `uint(N)`, an integer of any width from 1 to 64 bits.
+    fn parse_int_width(&mut self, signed: bool) -> Type {
+        self.advance();
+        let span = self.current_span();
+        let expr = self.parse_expr();
+        self.expect(Token::RightParen, vec![Token::Equals, Token::Comma]);
+
+        let error = match self.eval_const_expr(&expr) {
+            Ok(bits) if (1..=64).contains(&bits) => return Type::int(bits as usize, signed),
+            Ok(bits) => format!("Integer width must be between 1 and 64 bits, found {}", bits),
+            Err(message) => message,
+        };
 
-                let is_mutable = if self.current() == Token::Mut {
-                    self.advance();
-                    true
-                } else {
-                    false
-                };
+        self.diags.push(ParseDiagnostic {
+            message: error,
+            span,
+            severity: DiagnosticSeverity::Error,
+            help: Some("Use int128 or uint128 for wider integers.".to_string()),
+        });
+        Type::int(32, signed)
+    }
 
-                let ty = if self.current() == Token::Colon {
-                    self.advance();
-                    self.parse_type()
+    /// Parses an array size, folding it to a constant at parse time.
+    fn parse_array_size(&mut self) -> usize {
+        let span = self.current_span();
+        let expr = self.parse_expr();
+
+        let error = match self.eval_const_expr(&expr) {
+            Ok(size) if size >= 0 => return size as usize,
+            Ok(size) => format!("Array size must not be negative, found {}", size),
+            Err(message) => message,
+        };
+
+        self.diags.push(ParseDiagnostic {
+            message: error,
+            span,
+            severity: DiagnosticSeverity::Error,
+            help: Some("Array sizes must be integer literals, global constants or arithmetic on them.".to_string()),
+        });
+        0
+    }
+
+    fn parse_struct_literal(&mut self, name: String) -> Expr {
+        self.expect(Token::LeftBrace, vec![Token::RightBrace]);
+
+        let mut fields = Vec::new();
+        while !matches!(self.current(), Token::RightBrace | Token::EOF) {
+            let field_name = if let Token::Identifier(field) = self.current() {
+                self.advance();
+                field
+            } else {
+                self.advance();
+                "error".to_string()
+            };
+            self.expect(Token::Equals, vec![Token::Comma, Token::RightBrace]);
+            fields.push((field_name, self.parse_expr()));
+
+            if self.current() == Token::Comma {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        self.expect(Token::RightBrace, vec![Token::Semicolon, Token::End]);
+        Expr::StructLiteral(name, fields)
+    }
+
+    /// Parses `item`s separated by commas up to, but not including, `close`.
+    /// A trailing comma before `close` is accepted.
+    fn parse_comma_list<T>(&mut self, close: Token, mut item: impl FnMut(&mut Self) -> T) -> Vec<T> {
+        let mut items = Vec::new();
+        while self.current() != close && self.current() != Token::EOF {
+            items.push(item(self));
+            if self.current() != Token::Comma {
+                break;
+            }
+            self.advance();
+        }
+        items
+    }
+
+    /// Parses one `name = expr` argument of a named call.
+    fn parse_named_arg(&mut self) -> (String, Expr) {
+        let name = if let Token::Identifier(name) = self.current() {
+            self.advance();
+            name
+        } else {
+            self.advance();
+            "error".to_string()
+        };
+        self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
+        (name, self.parse_expr())
+    }
+
+    /// Turns variant-shaped case values such as `Some(x)`, `None` or `Circle(r)`
+    /// into patterns; anything else is compared by value.
+    fn case_pattern(value: Expr) -> Expr {
+        fn binding(expr: &Expr) -> Option<String> {
+            match expr {
+                Expr::Var(name) => Some(name.clone()),
+                _ => None,
+            }
+        }
+
+        match &value {
+            Expr::None => Expr::EnumPattern("None".to_string(), vec![]),
+            Expr::Some(inner) => match binding(inner) {
+                Some(name) => Expr::EnumPattern("Some".to_string(), vec![name]),
+                None => value,
+            },
+            Expr::ResultOk(inner) => match binding(inner) {
+                Some(name) => Expr::EnumPattern("Ok".to_string(), vec![name]),
+                None => value,
+            },
+            Expr::ResultErr(inner) => match binding(inner) {
+                Some(name) => Expr::EnumPattern("Err".to_string(), vec![name]),
+                None => value,
+            },
+            Expr::Call(name, args) if name.starts_with(char::is_uppercase) => {
+                match args.iter().map(binding).collect::<Option<Vec<_>>>() {
+                    Some(bindings) => Expr::EnumPattern(name.clone(), bindings),
+                    None => value,
+                }
+            }
+            _ => value,
+        }
+    }
+
+    /// A label after `break`/`continue` must sit on the same line, since
+    /// the next statement may also start with an identifier.
+    fn parse_loop_label_ref(&mut self, keyword_line: usize) -> Option<String> {
+        if let Token::Identifier(label) = self.current()
+            && self.get_location(self.current_span().offset()).line == keyword_line {
+                self.advance();
+                return Some(label);
+            }
+        None
+    }
+
+    fn parse_stmt(&mut self) -> Stmt {
+        let line = self.get_location(self.current_span().offset()).line;
+        Stmt::Located(line, Box::new(self.parse_stmt_kind()))
+    }
+
+    fn parse_stmt_kind(&mut self) -> Stmt {
+        match self.current() {
+            Token::Let => {
+                self.advance();
+                
+                let name = if let Token::Identifier(n) = self.current() {
+                    n
+                } else {
+                    self.error_and_sync(
+                        format!("Expected a variable name after `create`, found {:?}", self.current()),
+                        "write `create name = value`",
+                        &[Token::Equals, Token::Semicolon, Token::End],
+                    );
+                    if self.current() == Token::Equals {
+                        self.advance();
+                        self.parse_expr();
+                    }
+                    return Stmt::Expr(Expr::None);
+                };
+                self.advance();
+
+                if self.current() == Token::Comma {
+                    let mut names = vec![name];
+                    while self.current() == Token::Comma {
+                        self.advance();
+                        if let Token::Identifier(name) = self.current() {
+                            names.push(name);
+                            self.advance();
+                        }
+                    }
+
+                    self.expect(Token::Equals, vec![Token::Semicolon]);
+                    let value = self.parse_expr();
+                    return Stmt::TupleUnpack { names, value };
+                }
+
+                let is_mutable = if self.current() == Token::Mut {
+                    self.advance();
+                    true
+                } else {
+                    false
+                };
+
+                let ty = if self.current() == Token::Colon {
+                    self.advance();
+                    self.parse_type()
                 } else {
                     Type::Auto
                 };
@@ -1143,6 +1510,11 @@ impl Parser {
                     is_mutable,
                 }
             }
+        Token::Identifier(name) if self.peek(1) == Token::Colon && matches!(self.peek(2), Token::While | Token::For | Token::Loop) => {
+                self.advance();
+                self.advance();
+                Stmt::Labeled(name, Box::new(self.parse_stmt()))
+            }
         Token::Identifier(name) if self.peek(1) == Token::Colon => {
                 let var_name = name.clone();
                 self.advance();
@@ -1226,13 +1598,7 @@ impl Parser {
                         let name = first_name;
                         self.expect(Token::Equals, vec![Token::Semicolon]);
                         let value = self.parse_expr();
-                        
-                        let ty = if self.current() == Token::As {
-                            self.advance();
-                            self.parse_type()
-                        } else {
-                            Parser::infer_type(&value)
-                        };
+                        let ty = Parser::infer_type(&value);
                         
                         Stmt::TypedDeclaration {
                             name,
@@ -1243,7 +1609,7 @@ impl Parser {
                     }
                 } else {
                     self.advance();
-                    Stmt::Break
+                    Stmt::Break(None)
                 }
             }
             Token::Match => {
@@ -1272,7 +1638,7 @@ impl Parser {
                             self.advance();
                         }
                        
-                        let case_value = self.parse_expr();
+                        let case_value = Self::case_pattern(self.parse_expr());
                         self.expect(Token::Colon, vec![Token::End, Token::Case, Token::Default]);
                        
                         let mut case_stmts = Vec::new();
@@ -1305,15 +1671,33 @@ impl Parser {
                 };
                 Stmt::Return(expr)
             }
+            Token::Defer => {
+                self.advance();
+                Stmt::Defer(Box::new(self.parse_stmt()))
+            }
+            Token::Assert => {
+                self.advance();
+                let cond = self.parse_expr();
+                let message = if self.current() == Token::Comma {
+                    self.advance();
+                    Some(self.parse_expr())
+                } else {
+                    None
+                };
+                Stmt::Assert(cond, message)
+            }
             Token::Break => {
+                let line = self.get_location(self.current_span().offset()).line;
                 self.advance();
-                Stmt::Break
+                Stmt::Break(self.parse_loop_label_ref(line))
             }
             Token::Continue => {
+                let line = self.get_location(self.current_span().offset()).line;
                 self.advance();
-                Stmt::Continue
+                Stmt::Continue(self.parse_loop_label_ref(line))
             }
             Token::If => {
+                let if_span = self.current_span();
                 self.advance();
                 let expr = self.parse_expr();
                 
@@ -1335,7 +1719,9 @@ impl Parser {
                     
                     let else_body = if self.current() == Token::Else {
                         self.advance();
-                        self.expect(Token::Colon, vec![Token::End]);
+                        if self.current() == Token::Colon {
+                            self.advance();
+                        }
                         let mut stmts = Vec::new();
                         while !matches!(self.current(), Token::End | Token::EOF) {
                             stmts.push(self.parse_stmt());
@@ -1348,8 +1734,10 @@ impl Parser {
                         None
                     };
                     
-                    if matches!(self.current(), Token::End | Token::Comma) {
-                         self.advance();
+                    if self.current() == Token::Comma {
+                        self.advance();
+                    } else {
+                        self.expect_block_end("if", if_span);
                     }
                     
                     Stmt::IfLet {
@@ -1360,13 +1748,34 @@ impl Parser {
                     }
                 } else {
                     let mut then_body = Vec::new();
-                    let mut stmts = Vec::new();
-    
                     let cond = expr;
+
+                    if self.current() == Token::Then {
+                        self.advance()
+                    }
+
+                    while !matches!(self.current(), Token::Else | Token::End | Token::EOF) {
+                        then_body.push(self.parse_stmt());
+
+                        if self.current() == Token::Semicolon {
+                            self.advance();
+                        }
+                    }
+
                     let else_body = if self.current() == Token::Else {
                         self.advance();
-                        self.expect(Token::Colon, vec![Token::End]);
-                        
+
+                        if self.current() == Token::If {
+                            // `else if` shares the closing `end` of the whole chain,
+                            // so the nested if consumes it.
+                            return Stmt::If(cond, then_body, Some(vec![self.parse_stmt()]));
+                        }
+
+                        if self.current() == Token::Colon {
+                            self.advance();
+                        }
+
+                        let mut stmts = Vec::new();
                         while !matches!(self.current(), Token::End | Token::EOF) {
                             stmts.push(self.parse_stmt());
                             if self.current() == Token::Semicolon {
@@ -1377,28 +1786,19 @@ impl Parser {
                     } else {
                         None
                     };
-                   
-                    if self.current() == Token::Then {
-                        self.advance()
-                    }
-    
-                    while !matches!(self.current(), Token::Else | Token::End | Token::EOF) {
-                        then_body.push(self.parse_stmt());
-    
-                        if self.current() == Token::Semicolon {
-                            self.advance();
-                        }
-                    }
-    
-                    if matches!(self.current(), Token::End | Token::Comma) {
+
+                    if self.current() == Token::Comma {
                         self.advance();
+                    } else {
+                        self.expect_block_end("if", if_span);
                     }
-    
+
                     Stmt::If(cond, then_body, else_body)
                 }
             }
 
             Token::While => {
+                let while_span = self.current_span();
                 self.advance();
                 let cond = self.parse_expr();
                 
@@ -1413,14 +1813,32 @@ impl Parser {
                         self.advance();
                     }
                 }
-                
-                if self.current() == Token::End {
+
+                self.expect_block_end("while", while_span);
+                Stmt::While(cond, body)
+            }
+
+            Token::Loop => {
+                let loop_span = self.current_span();
+                self.advance();
+                if matches!(self.current(), Token::Colon | Token::Do) {
                     self.advance();
                 }
-                Stmt::While(cond, body)
+
+                let mut body = Vec::new();
+                while !matches!(self.current(), Token::End | Token::EOF) {
+                    body.push(self.parse_stmt());
+                    if self.current() == Token::Semicolon {
+                        self.advance();
+                    }
+                }
+
+                self.expect_block_end("loop", loop_span);
+                Stmt::Loop(body)
             }
 
             Token::For => {
+                let for_span = self.current_span();
                 self.advance();
 
                 let var_name = if let Token::Identifier(name) = self.current() {
@@ -1433,24 +1851,22 @@ impl Parser {
                 
                 self.expect(Token::In, vec![Token::Do, Token::End]);
                 let iter_expr = self.parse_expr();
-                println!("[DEBUG] parse_for: iter_expr={:?}", iter_expr);
+                debug_log!("[DEBUG] parse_for: iter_expr={:?}", iter_expr);
                 self.expect(Token::Do, vec![Token::End]);
                 
                 let mut body = Vec::new();
                 while !matches!(self.current(), Token::End | Token::EOF) {
-                    println!("[DEBUG] parse_for: about to parse stmt at {:?}", self.current());
+                    debug_log!("[DEBUG] parse_for: about to parse stmt at {:?}", self.current());
                     let s = self.parse_stmt();
-                    println!("[DEBUG] parse_for: parsed stmt: {:?}", s);
+                    debug_log!("[DEBUG] parse_for: parsed stmt: {:?}", s);
                     body.push(s);
                     if self.current() == Token::Semicolon {
                         self.advance();
                     }
                 }
 
-                println!("[DEBUG] parse_for: loop body length={}", body.len());
-                if self.current() == Token::End {
-                    self.advance();
-                }
+                debug_log!("[DEBUG] parse_for: loop body length={}", body.len());
+                self.expect_block_end("for", for_span);
                 Stmt::For(var_name, iter_expr, body)
             }
             Token::Hash => {
@@ -1474,12 +1890,14 @@ impl Parser {
                 
                 if self.current() == Token::Func {
                     Stmt::Function(self.parse_function_with_visibility(false, is_public, attributes))
+                } else if self.current() == Token::Struct {
+                    Stmt::StructDef(self.parse_struct(is_public, attributes))
                 } else {
                     self.diags.push(ParseDiagnostic {
-                        message: "Expected function after attributes".to_string(),
+                        message: "Expected function or struct after attributes".to_string(),
                         span: self.current_span(),
                         severity: DiagnosticSeverity::Error,
-                        help: Some("Attributes are currently only supported on functions".to_string()),
+                        help: Some("Attributes are currently only supported on functions and structs".to_string()),
                     });
 
                     self.advance();
@@ -1494,9 +1912,21 @@ impl Parser {
                 match self.current() {
                      Token::Mod => self.parse_module(true),
                      Token::Func => Stmt::Function(self.parse_function_with_visibility(false, true, vec![])),
-                     Token::Struct => Stmt::StructDef(self.parse_struct(true)),
+                     Token::Struct => Stmt::StructDef(self.parse_struct(true, vec![])),
                      Token::Enum => Stmt::EnumDef(self.parse_enum(true)),
-                     _ => panic!("Expected Item after Pub"), 
+                     Token::Impl => Stmt::ImplBlock(self.parse_impl_block(true)),
+                     Token::Trait => Stmt::TraitDef(self.parse_trait(true)),
+                     Token::Const => match self.parse_global_const(true) {
+                         Some(GlobalConst { name, ty, value, .. }) => {
+                             let ty = if matches!(ty, Type::Void) { Self::infer_type(&value) } else { ty };
+                             Stmt::TypedDeclaration { name, ty, value, is_mutable: false }
+                         }
+                         None => Stmt::Expr(Expr::None),
+                     },
+                     other => {
+                         self.report_invalid_pub_item(other);
+                         Stmt::Expr(Expr::None)
+                     }
                 }
             },
             Token::Mut | Token::Mutable => {
@@ -1530,6 +1960,7 @@ impl Parser {
                         Expr::Var(name) => Stmt::Assign(name, value),
                         Expr::Index(obj, indices) => Stmt::IndexAssign(obj, indices, value),
                         Expr::MemberAccess(obj, field) => Stmt::MemberAssign(obj, field, value),
+                        Expr::Deref(ptr) => Stmt::DerefAssign(ptr, value),
                         Expr::ModuleAccess(module, member) => Stmt::ModuleAssign(module, member, value),
                         _ => Stmt::Expr(expr)
                     }
@@ -1560,6 +1991,11 @@ impl Parser {
                    
                     match expr {
                         Expr::Var(name) => Stmt::CompoundAssign(name, op, self.parse_expr()),
+                        Expr::Index(obj, indices) => {
+                            let (obj, indices) = flatten_index(*obj, indices);
+                            Stmt::IndexCompoundAssign(obj, indices, op, self.parse_expr())
+                        }
+                        Expr::MemberAccess(obj, field) => Stmt::MemberCompoundAssign(obj, field, op, Box::new(self.parse_expr())),
                         Expr::ModuleAccess(module, member) => Stmt::ModuleCompoundAssign(module, member, op, self.parse_expr()),
                         _ => Stmt::Expr(expr)
                     }
@@ -1574,7 +2010,9 @@ impl Parser {
         }
     }
     
-    fn parse_struct(&mut self, is_public: bool) -> StructDef {
+    fn parse_struct(&mut self, is_public: bool, attributes: Vec<String>) -> StructDef {
+        let struct_span = self.current_span();
+        let line = self.get_location(struct_span.offset()).line;
         self.expect(Token::Struct, vec![Token::Colon, Token::End]);
         
         let mut fields = Vec::new();
@@ -1588,7 +2026,7 @@ impl Parser {
 
         if self.current() == Token::Semicolon {
             self.advance();
-            return StructDef { name, fields, is_public };
+            return StructDef { name, fields, is_public, attributes, line };
         }
 
         self.expect(Token::Colon, vec![Token::End]);
@@ -1629,11 +2067,9 @@ impl Parser {
             });
         }
         
-        if self.current() == Token::End {
-            self.advance();
-        }
+        self.expect_block_end("struct", struct_span);
         
-        StructDef { name, fields, is_public }
+        StructDef { name, fields, is_public, attributes, line }
     }
     fn parse_enum(&mut self, is_public: bool) -> EnumDef {
     self.expect(Token::Enum, vec![Token::Colon, Token::End]);
@@ -1687,13 +2123,7 @@ impl Parser {
                     variants.push(EnumVariant::Struct(vname, fields));
                 } else {
                      
-                    let mut types = Vec::new();
-                    while self.current() != Token::RightParen && self.current() != Token::EOF {
-                        types.push(self.parse_type());
-                        if self.current() == Token::Comma { 
-                            self.advance(); 
-                        }
-                    }
+                    let types = self.parse_comma_list(Token::RightParen, Self::parse_type);
                     self.expect(Token::RightParen, vec![Token::End]);
                     
                      
@@ -1725,6 +2155,7 @@ impl Parser {
    
     
     fn parse_function_with_visibility(&mut self, is_module: bool, is_public: bool, attributes: Vec<String>) -> Function {
+        let func_span = self.current_span();
         self.expect(Token::Func, vec![Token::Colon, Token::End]);
         
         let name = if let Token::Identifier(name) = self.current() {
@@ -1738,6 +2169,7 @@ impl Parser {
         self.expect(Token::LeftParen, vec![Token::RightParen, Token::Arrow, Token::Colon]);
         
         let mut params = Vec::new();
+        let mut param_defaults = Vec::new();
         
         let is_self_param = match self.current() {
             Token::Selfish => true,
@@ -1763,6 +2195,13 @@ impl Parser {
         }
 
         while self.current() != Token::RightParen {
+            if self.current() == Token::TripleDot {
+                self.advance();
+                params.push(("...".to_string(), Type::TripleDot, ParamModifier::Immutable));
+                param_defaults.push(None);
+                break;
+            }
+
             let modifier = match self.current() {
                 Token::Mutable => {
                     self.advance();
@@ -1780,6 +2219,7 @@ impl Parser {
                 _ => ParamModifier::Immutable
             };
             
+            let pname_span = self.current_span();
             let pname = if let Token::Identifier(pname) = self.current() {
                 self.advance();
                 pname
@@ -1790,7 +2230,33 @@ impl Parser {
             
             self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
             let ptype = self.parse_type();
+            let default = if self.current() == Token::Equals {
+                self.advance();
+                let default_span = self.current_span();
+                let default = self.parse_expr();
+                // Defaults are pasted into the caller, where the callee's other parameters don't exist.
+                if !self.is_const_default(&default) {
+                    self.diags.push(ParseDiagnostic {
+                        message: format!("Default value of `{}` must be a constant", pname),
+                        span: default_span,
+                        severity: DiagnosticSeverity::Error,
+                        help: Some("Defaults are evaluated at the call site; use a literal or a constant expression.".to_string()),
+                    });
+                }
+                Some(default)
+            } else {
+                None
+            };
+            if default.is_none() && param_defaults.iter().any(Option::is_some) {
+                self.diags.push(ParseDiagnostic {
+                    message: format!("Parameter `{}` without a default follows a parameter with one", pname),
+                    span: pname_span,
+                    severity: DiagnosticSeverity::Error,
+                    help: Some("Move defaulted parameters to the end of the list.".to_string()),
+                });
+            }
             params.push((pname, ptype, modifier));
+            param_defaults.push(default);
             
             if self.current() == Token::Comma {
                 self.advance();
@@ -1826,15 +2292,17 @@ impl Parser {
             }
         }
        
-        self.expect(Token::End, vec![]);
+        self.expect_block_end("func", func_span);
        
         Function {
             name,
             params,
+            param_defaults,
             return_type,
             body,
             is_public,
             attributes,
+            line: self.get_location(func_span.offset()).line,
         }
     }
 
@@ -1879,7 +2347,70 @@ impl Parser {
         
         ImportDecl::LibraryImport { name: "unknown".to_string() }
     }
+
+    /// Parses `name(params): return_type` of a foreign function declared in an `extern`.
+    fn parse_extern_signature(&mut self) -> ExternFunction {
+        let name = if let Token::Identifier(name) = self.current() {
+            self.advance();
+            name
+        } else {
+            self.advance();
+            "error".to_string()
+        };
+
+        self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
+
+        let mut params = Vec::new();
+        while self.current() != Token::RightParen && self.current() != Token::EOF {
+            if self.current() == Token::TripleDot {
+                self.advance();
+                params.push(("...".to_string(), Type::TripleDot));
+                if self.current() == Token::Comma {
+                    self.advance();
+                }
+                continue;
+            }
+
+            let pname = if let Token::Identifier(pname) = self.current() {
+                self.advance();
+                pname
+            } else {
+                self.advance();
+                "error".to_string()
+            };
+
+            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
+            let ptype = self.parse_type();
+            params.push((pname, ptype));
+
+            if self.current() == Token::Comma {
+                self.advance();
+            }
+        }
+
+        self.expect(Token::RightParen, vec![Token::Colon, Token::Arrow, Token::Semicolon]);
+
+        let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
+            self.advance();
+            if self.is_type_token(self.current()) {
+                self.parse_type()
+            } else {
+                Type::Void
+            }
+        } else {
+            Type::Void
+        };
+
+        ExternFunction {
+            name,
+            params,
+            return_type,
+            is_public: false,
+        }
+    }
+
     fn parse_module(&mut self, is_public: bool) -> Stmt {
+        let line = self.get_location(self.current_span().offset()).line;
         self.expect(Token::Mod, vec![Token::Identifier("".to_string())]);
         
         let name = if let Token::Identifier(name) = self.current() {
@@ -1894,30 +2425,296 @@ impl Parser {
         
         let mut body = Vec::new();
         while !matches!(self.current(), Token::RightBrace | Token::EOF) {
-             println!("DEBUG: Module body token: {:?}", self.current());
+             debug_log!("DEBUG: Module body token: {:?}", self.current());
              body.push(self.parse_stmt());
              if self.current() == Token::Semicolon {
                  self.advance();
              }
         }
         
-        println!("DEBUG: Expecting RightBrace");
+        debug_log!("DEBUG: Expecting RightBrace");
         self.expect(Token::RightBrace, vec![Token::Semicolon]);
-        println!("DEBUG: Finished parsing module");
+        debug_log!("DEBUG: Finished parsing module");
         
         Stmt::ModuleDef {
             name,
             body,
-            is_public
+            is_public,
+            line,
         }
     }
 
-    fn parse_type_alias(&mut self) -> Stmt {
-         
-        self.expect(Token::Type, vec![Token::Identifier("".to_string())]);
-        
-        let name = if let Token::Identifier(name) = self.current() {
-            self.advance();
+    fn report_invalid_pub_item(&mut self, found: Token) {
+        self.diags.push(ParseDiagnostic {
+            message: format!("Expected an item after `public`, found {:?}", found),
+            span: self.current_span(),
+            severity: DiagnosticSeverity::Error,
+            help: Some("`public` can only precede func, struct, enum, mod, const, impl or trait".to_string()),
+        });
+        self.advance();
+    }
+
+    fn parse_global_const(&mut self, is_public: bool) -> Option<GlobalConst> {
+        let line = self.get_location(self.current_span().offset()).line;
+        self.advance();
+        if let Token::Identifier(name) = self.current() {
+            self.advance();
+            let ty = if self.current() == Token::Colon {
+                self.advance();
+                self.parse_type()
+            } else {
+                Type::Void
+            };
+            
+            self.expect(Token::Equals, vec![Token::Semicolon]);
+            let value = self.parse_expr();
+            if self.current() == Token::Semicolon {
+                self.advance();
+            }
+            if let Ok(folded) = self.eval_const_expr(&value) {
+                self.const_values.insert(name.clone(), folded);
+            }
+            Some(GlobalConst { name, ty, value, is_public, line })
+        } else {
+            self.advance();
+            None
+        }
+    }
+
+    fn parse_impl_block(&mut self, is_public: bool) -> ImplBlock {
+        let line = self.get_location(self.current_span().offset()).line;
+        self.advance();
+        
+        let struct_name = if let Token::Identifier(name) = self.current() {
+            self.advance();
+            name
+        } else {
+            self.advance();
+            "error".to_string()
+        };
+        
+        let mut constructor_params = Vec::new();
+        if self.current() == Token::LeftParen {
+            self.advance();
+            
+            while self.current() != Token::RightParen && self.current() != Token::EOF {
+                let pname = if let Token::Identifier(pname) = self.current() {
+                    self.advance();
+                    pname
+                } else {
+                    self.advance();
+                    "error".to_string()
+                };
+                
+                self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
+                let ptype = self.parse_type();
+                constructor_params.push((pname, ptype));
+                
+                if self.current() == Token::Comma {
+                    self.advance();
+                }
+            }
+            
+            self.expect(Token::RightParen, vec![Token::For, Token::Colon]);
+        }
+        
+        let trait_name = if self.current() == Token::For {
+            self.advance();
+            if let Token::Identifier(trait_name) = self.current() {
+                self.advance();
+                Some(trait_name)
+            } else {
+                self.advance();
+                None
+            }
+        } else {
+            None
+        };
+        
+        self.expect(Token::Colon, vec![Token::End]);
+        
+        let mut methods = Vec::new();
+        let mut constructor_body = None;
+
+        while !matches!(self.current(), Token::End | Token::EOF) {
+            let mut init_fields = Vec::new();        
+       
+            if let Token::Identifier(name) = self.current()
+                && name == struct_name && self.peek(1) == Token::LeftParen {
+                     
+                    self.advance();
+                    self.advance();
+                    
+
+                    while !matches!(self.current(), Token::RightParen | Token::EOF) {
+                        if let Token::Identifier(field_name) = self.current() {
+                            self.advance();
+                            self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
+                            let field_expr = self.parse_expr();
+                            init_fields.push((field_name, field_expr));
+                            
+                            if self.current() == Token::Comma {
+                                self.advance();
+                            }
+                        } else {
+                            self.advance();
+                            break;
+                        }
+                    }
+                    
+                    self.expect(Token::RightParen, vec![Token::Func, Token::End]);
+                    constructor_body = Some(init_fields);
+                    continue;
+                }
+            
+             
+            if self.current() == Token::Unsafe {
+                self.advance();
+            }
+            
+            let is_public = if self.current() == Token::Pub {
+                self.advance();
+                true
+            } else {
+                false
+            };
+
+            if self.current() == Token::Func {
+                let method_line = self.get_location(self.current_span().offset()).line;
+                self.advance();
+                
+                let method_name = if let Token::Identifier(name) = self.current() {
+                    self.advance();
+                    name
+                } else {
+                    self.advance();
+                    "error".to_string()
+                };
+                
+                self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
+                
+                let (self_modifier, params) = self.parse_method_params();
+                
+                self.expect(Token::RightParen, vec![Token::Colon]);
+                
+                
+                let mut body = Vec::new();
+                let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
+                    self.advance();
+                    if self.is_type_token(self.current()) {
+                        self.parse_type()
+                    } else {
+                        Type::Void
+                    }
+                } else {
+                    Type::Void
+                };
+                
+                while !matches!(self.current(), Token::End | Token::Func | Token::EOF) {
+                
+                    if matches!(self.current(), Token::End | Token::Func) {
+                        break;
+                    }
+                    
+                    body.push(self.parse_stmt());
+                    if self.current() == Token::Semicolon {
+                        self.advance();
+                    }
+                }
+                
+                if self.current() == Token::End {
+                    self.advance();
+                }
+                
+                methods.push(ImplMethod {
+                    name: method_name,
+                    params,
+                    return_type,
+                    body,
+                    self_modifier,
+                    is_public,
+                    line: method_line,
+                });
+            } else if self.current() == Token::End {
+                break;
+            } else {
+                self.advance();
+            }
+        }
+        
+        if self.current() == Token::End {
+            self.advance();
+        }
+        
+        ImplBlock {
+            struct_name,
+            trait_name,
+            constructor_params,
+            constructor_body,
+            methods,
+            is_public,
+            line,
+        }
+    }
+
+    fn parse_trait(&mut self, is_public: bool) -> TraitDef {
+        self.advance();
+        let name = if let Token::Identifier(name) = self.current() { self.advance(); name } else { self.advance(); "error".to_string() };
+        self.expect(Token::Colon, vec![Token::End]);
+
+        let mut methods = Vec::new();
+        while !matches!(self.current(), Token::End | Token::EOF) {
+            if self.current() != Token::Func {
+                self.advance();
+                continue;
+            }
+            self.advance();
+
+            let method_name = if let Token::Identifier(name) = self.current() {
+                self.advance();
+                name
+            } else {
+                self.advance();
+                "error".to_string()
+            };
+
+            self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
+            let (self_modifier, params) = self.parse_method_params();
+            self.expect(Token::RightParen, vec![Token::Colon, Token::Func, Token::End]);
+
+            let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
+                self.advance();
+                if self.is_type_token(self.current()) {
+                    self.parse_type()
+                } else {
+                    Type::Void
+                }
+            } else {
+                Type::Void
+            };
+
+            if self.current() == Token::Semicolon {
+                self.advance();
+            }
+
+            methods.push(TraitMethod {
+                name: method_name,
+                params,
+                return_type,
+                self_modifier,
+            });
+        }
+        if self.current() == Token::End { self.advance(); }
+
+        TraitDef { name, methods, is_public }
+    }
+
+    fn parse_type_alias(&mut self) -> Stmt {
+         
+        self.expect(Token::Type, vec![Token::Identifier("".to_string())]);
+        
+        let name = if let Token::Identifier(name) = self.current() {
+            self.advance();
             name
         } else {
             self.advance();
@@ -1938,12 +2735,15 @@ impl Parser {
 
     fn parse_module_exports(&mut self) -> Stmt {
          
-        if let Token::Identifier(id) = self.current() {
-            if id != "export" {
-                panic!("Expected 'export' identifier");
-            }
-            self.advance();
+        if self.current() != Token::Identifier("export".to_string()) {
+            self.error_and_sync(
+                format!("Expected `export`, found {:?}", self.current()),
+                "module exports are written `export(name, other as alias)`",
+                &[Token::Semicolon, Token::End],
+            );
+            return Stmt::ModuleExports { exports: vec![] };
         }
+        self.advance();
         
          
         self.expect(Token::LeftParen, vec![Token::RightParen]);
@@ -1994,6 +2794,11 @@ impl Parser {
     
 
     pub fn parse(mut self) -> ParseResult {
+        self.parse_program()
+    }
+
+    /// Like `parse`, but keeps the parser around so `diags` can be inspected afterwards.
+    pub fn parse_program(&mut self) -> ParseResult {
         let mut functions = Vec::new();
         let mut structs = Vec::new();
         let mut enums = Vec::new();
@@ -2002,7 +2807,7 @@ impl Parser {
         let mut uses = Vec::new();
         let classes = Vec::new();
         let mut impls = Vec::new();
-        let traits = Vec::new();
+        let mut traits = Vec::new();
         let mut import_decls = Vec::new();
         let mut constants = Vec::new();
         let mut modules = Vec::new();
@@ -2021,27 +2826,37 @@ impl Parser {
         }
         
         let import_context = Self::build_import_context(&import_decls);
+        let mut definitions = HashMap::new();
         
         while self.current() != Token::EOF {
-            println!("DEBUG: Current token: {:?}", self.current());
+            debug_log!("DEBUG: Current token: {:?}", self.current());
+            let item_span = self.current_span();
+            let (known_functions, known_structs, known_enums) = (functions.len(), structs.len(), enums.len());
             match self.current() {
                 Token::Import => { self.advance(); }
                 
                 Token::Pub => {
                     self.advance();
-                    println!("DEBUG: Inside Pub");
+                    debug_log!("DEBUG: Inside Pub");
                     match self.current() {
                         Token::Mod => {
-                             println!("DEBUG: Parsing public module");
+                             debug_log!("DEBUG: Parsing public module");
                              modules.push(self.parse_module(true));
                         }
                         Token::Func => {
-                            println!("DEBUG: Parsing public function");
+                            debug_log!("DEBUG: Parsing public function");
                             functions.push(self.parse_function_with_visibility(false, true, vec![]));
                         }
-                        Token::Struct => structs.push(self.parse_struct(true)),
+                        Token::Struct => structs.push(self.parse_struct(true, vec![])),
                         Token::Enum => enums.push(self.parse_enum(true)),
-                        _ => { self.advance(); }
+                        Token::Impl => impls.push(self.parse_impl_block(true)),
+                        Token::Trait => traits.push(self.parse_trait(true)),
+                        Token::Const => {
+                            if let Some(constant) = self.parse_global_const(true) {
+                                constants.push(constant);
+                            }
+                        }
+                        other => self.report_invalid_pub_item(other),
                     }
                 }
 
@@ -2059,92 +2874,64 @@ impl Parser {
                         };
                         
                         let library = library_opt.unwrap_or_else(|| "".to_string());
+
+                        if self.current() == Token::Func {
+                            self.advance();
+                            let func = self.parse_extern_signature();
+                            externs.push(ExternDecl::Single { abi, library: Some(library), func });
+                        } else {
+                            self.expect(Token::Colon, vec![Token::End, Token::Func]);
                         
-                        self.expect(Token::Colon, vec![Token::End, Token::Func]);
-                        
-                        let mut functions_list = Vec::new();
+                            let mut functions_list = Vec::new();
                         
-                        while !matches!(self.current(), Token::End | Token::EOF) {
-                            if self.current() == Token::Func {
-                                self.advance();
-                                
-                                let name = if let Token::Identifier(name) = self.current() {
+                            while !matches!(self.current(), Token::End | Token::EOF) {
+                                if self.current() == Token::Func {
                                     self.advance();
-                                    name
+                                
+                                    functions_list.push(self.parse_extern_signature());
+                                } else if self.current() == Token::End {
+                                    break;
                                 } else {
                                     self.advance();
-                                    "error".to_string()
-                                };
-                                
-                                self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
-                                
-                                let mut params = Vec::new();
-                                while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                    if self.current() == Token::TripleDot {
-                                        self.advance();
-                                        params.push(("...".to_string(), Type::TripleDot));
-                                        if self.current() == Token::Comma {
-                                            self.advance();
-                                        }
-                                        continue;
-                                    }
-                                    
-                                    let pname = if let Token::Identifier(pname) = self.current() {
-                                        self.advance();
-                                        pname
-                                    } else {
-                                        self.advance();
-                                        "error".to_string()
-                                    };
-                                    
-                                    self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                                    let ptype = self.parse_type();
-                                    params.push((pname, ptype));
-                                    
-                                    if self.current() == Token::Comma {
-                                        self.advance();
-                                    }
                                 }
-                                
-                                self.expect(Token::RightParen, vec![Token::Colon]);
-                                
-                                let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
+                            
+                                if self.current() == Token::Semicolon {
                                     self.advance();
-                                    if self.is_type_token(self.current()) {
-                                        self.parse_type()
-                                    } else {
-                                        Type::Void
-                                    }
-                                } else {
-                                    Type::Void
-                                };
-                                
-                                functions_list.push(ExternFunction {
-                                    name,
-                                    params,
-                                    return_type,
-                                    is_public: false,
-                                });
-                            } else if self.current() == Token::End {
-                                break;
-                            } else {
-                                self.advance();
+                                }
                             }
-                            
-                            if self.current() == Token::Semicolon {
+                        
+                            if self.current() == Token::End {
                                 self.advance();
                             }
-                        }
                         
-                        if self.current() == Token::End {
+                            externs.push(ExternDecl::Block { 
+                                abi, 
+                                library, 
+                                functions: functions_list 
+                            });
+                        }
+                    } else if matches!(self.current(), Token::Func | Token::Struct | Token::Pub | Token::At) {
+                        let mut attributes = vec![match library_opt {
+                            Some(p) => format!("{}({})", attr_name, p),
+                            None => attr_name,
+                        }];
+                        while self.current() == Token::At {
+                            let (name, param) = self.parse_attribute();
+                            attributes.push(match param {
+                                Some(p) => format!("{}({})", name, p),
+                                None => name,
+                            });
+                        }
+
+                        let is_public = self.current() == Token::Pub;
+                        if is_public {
                             self.advance();
                         }
-                        
-                        externs.push(ExternDecl::Block { 
-                            abi, 
-                            library, 
-                            functions: functions_list 
-                        });
+                        if self.current() == Token::Struct {
+                            structs.push(self.parse_struct(is_public, attributes));
+                        } else {
+                            functions.push(self.parse_function_with_visibility(false, is_public, attributes));
+                        }
                     } else {
                          
                         self.diags.push(ParseDiagnostic {
@@ -2158,7 +2945,7 @@ impl Parser {
                 }
 
                 Token::Mod => {
-                     println!("DEBUG: Parsing Mod");
+                     debug_log!("DEBUG: Parsing Mod");
                      if let Token::String(_) = self.peek(1) {
                          self.advance();
                          if let Token::String(path) = self.current() {
@@ -2170,31 +2957,15 @@ impl Parser {
                      }
                 }
 
-                    Token::Const => {
-                        self.advance();
-                        if let Token::Identifier(name) = self.current() {
-                            self.advance();
-                            let ty = if self.current() == Token::Colon {
-                                self.advance();
-                                self.parse_type()
-                            } else {
-                                Type::Void
-                            };
-                            
-                            self.expect(Token::Equals, vec![Token::Semicolon]);
-                            let value = self.parse_expr();
-                            if self.current() == Token::Semicolon {
-                                self.advance();
-                            }
-                            constants.push(GlobalConst { name, ty, value });
-                        } else {
-                            self.advance();
-                        }
+                Token::Const => {
+                    if let Some(constant) = self.parse_global_const(false) {
+                        constants.push(constant);
                     }
+                }
 
                 Token::Type => {
-                    println!("DEBUG: Parsing type alias");
-                    let alias_stmt = self.parse_type_alias();
+                    debug_log!("DEBUG: Parsing type alias");
+                    let _alias_stmt = self.parse_type_alias();
                      
                      
                     if self.current() == Token::Semicolon {
@@ -2207,257 +2978,44 @@ impl Parser {
                 }
 
                 Token::Func => {
-                    println!("DEBUG: Parsing function");
-                    let func = self.parse_function_with_visibility(false, false, vec![]);
-                    functions.push(func);
-                    println!("DEBUG: Functions count: {}", functions.len());
-                }
-
-                Token::Struct => {
-                    println!("DEBUG: Parsing struct");
-                    structs.push(self.parse_struct(false));
-                }
-
-                Token::Impl => {
-                    self.advance();
-                    
-                    let struct_name = if let Token::Identifier(name) = self.current() {
-                        self.advance();
-                        name
-                    } else {
-                        self.advance();
-                        "error".to_string()
-                    };
-                    
-                    let mut constructor_params = Vec::new();
-                    if self.current() == Token::LeftParen {
-                        self.advance();
-                        
-                        while self.current() != Token::RightParen && self.current() != Token::EOF {
-                            let pname = if let Token::Identifier(pname) = self.current() {
-                                self.advance();
-                                pname
-                            } else {
-                                self.advance();
-                                "error".to_string()
-                            };
-                            
-                            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                            let ptype = self.parse_type();
-                            constructor_params.push((pname, ptype));
-                            
-                            if self.current() == Token::Comma {
-                                self.advance();
-                            }
-                        }
-                        
-                        self.expect(Token::RightParen, vec![Token::For, Token::Colon]);
-                    }
-                    
-                    let trait_name = if self.current() == Token::For {
-                        self.advance();
-                        if let Token::Identifier(trait_name) = self.current() {
-                            self.advance();
-                            Some(trait_name)
-                        } else {
-                            self.advance();
-                            None
-                        }
-                    } else {
-                        None
-                    };
-                    
-                    self.expect(Token::Colon, vec![Token::End]);
-                    
-                    let mut methods = Vec::new();
-                    let mut constructor_body = None;
-     
-                    while !matches!(self.current(), Token::End | Token::EOF) {
-                        let mut init_fields = Vec::new();        
-                   
-                        if let Token::Identifier(name) = self.current()
-                            && name == struct_name && self.peek(1) == Token::LeftParen {
-                                 
-                                self.advance();
-                                self.advance();
-                                
-
-                                while !matches!(self.current(), Token::RightParen | Token::EOF) {
-                                    if let Token::Identifier(field_name) = self.current() {
-                                        self.advance();
-                                        self.expect(Token::Equals, vec![Token::Comma, Token::RightParen]);
-                                        let field_expr = self.parse_expr();
-                                        init_fields.push((field_name, field_expr));
-                                        
-                                        if self.current() == Token::Comma {
-                                            self.advance();
-                                        }
-                                    } else {
-                                        self.advance();
-                                        break;
-                                    }
-                                }
-                                
-                                self.expect(Token::RightParen, vec![Token::Func, Token::End]);
-                                constructor_body = Some(init_fields);
-                                continue;
-                            }
-                        
-                         
-                        if self.current() == Token::Unsafe {
-                            self.advance();
-                        }
-                        
-                        let is_public = if self.current() == Token::Pub {
-                            self.advance();
-                            true
-                        } else {
-                            false
-                        };
-
-                        if self.current() == Token::Func {
-                            self.advance();
-                            
-                            let method_name = if let Token::Identifier(name) = self.current() {
-                                self.advance();
-                                name
-                            } else {
-                                self.advance();
-                                "error".to_string()
-                            };
-                            
-                            self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
-                            
-                            let mut params = Vec::new();
-                            let mut self_modifier = None;
-                            
-                             
-                            let is_self_token = |t: &Token| matches!(t, Token::Selfish) || matches!(t, Token::Identifier(s) if s == "self");
-                            
-                            if is_self_token(&self.current()) {
-                                self.advance();
-                                self_modifier = Some(SelfModifier::Immutable);
-                                if self.current() == Token::Comma { self.advance(); }
-                            } 
-                            else if (matches!(self.current(), Token::Mutable | Token::Mut)) && is_self_token(&self.peek(1)) {
-                                self.advance(); self.advance();
-                                self_modifier = Some(SelfModifier::Mutable);
-                                if self.current() == Token::Comma { self.advance(); }
-                            }
-                            else if (matches!(self.current(), Token::Reference | Token::Ampersand)) && is_self_token(&self.peek(1)) {
-                                self.advance(); 
-                                self.advance();
-
-                                self_modifier = Some(SelfModifier::Reference);
-                                if self.current() == Token::Comma { self.advance(); }
-                            }
-                            else if (matches!(self.current(), Token::Reference | Token::Ampersand)) && (matches!(self.peek(1), Token::Mutable | Token::Mut)) && is_self_token(&self.peek(2)) {
-                                self.advance(); self.advance(); self.advance();
-                                 
-                                self_modifier = Some(SelfModifier::Mutable); 
-                                if self.current() == Token::Comma { self.advance(); }
-                            }
-                            else if let Token::Identifier(s) = self.current()
-                                && s == "brw" && is_self_token(&self.peek(1)) {
-                                    self.advance(); self.advance();
-                                    self_modifier = Some(SelfModifier::Borrow);
-                                    if self.current() == Token::Comma { self.advance(); }
-                                }
-                                    
-                            while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                let modifier = match self.current() {
-                                    Token::Mutable => {
-                                        self.advance();
-                                        ParamModifier::Mutable
-                                    }
-                                    Token::Reference => {
-                                        self.advance();
-                                        ParamModifier::Reference
-                                    }
-                                    _ => ParamModifier::Immutable
-                                };
-                                
-                                let pname = if let Token::Identifier(pname) = self.current() {
-                                    self.advance();
-                                    pname
-                                } else {
-                                    self.advance();
-                                    "error".to_string()
-                                };
-                                
-                                self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                                let ptype = self.parse_type();
-                                params.push((pname, ptype, modifier));
-                                
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                            }
-                            
-                            self.expect(Token::RightParen, vec![Token::Colon]);
-                            
-                            
-                            let mut body = Vec::new();
-                            let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
-                                self.advance();
-                                if self.is_type_token(self.current()) {
-                                    self.parse_type()
-                                } else {
-                                    Type::Void
-                                }
-                            } else {
-                                Type::Void
-                            };
-                            
-                            while !matches!(self.current(), Token::End | Token::Func | Token::EOF) {
-                            
-                                if matches!(self.current(), Token::End | Token::Func) {
-                                    break;
-                                }
-                                
-                                body.push(self.parse_stmt());
-                                if self.current() == Token::Semicolon {
-                                    self.advance();
-                                }
-                            }
-                            
-                            if self.current() == Token::End {
-                                self.advance();
-                            }
-                            
-                            methods.push(ImplMethod {
-                                name: method_name,
-                                params,
-                                return_type,
-                                body,
-                                self_modifier,
-                                is_public,
-                            });
-                        } else if self.current() == Token::End {
-                            break;
-                        } else {
-                            self.advance();
-                        }
-                    }
-                    
-                    if self.current() == Token::End {
-                        self.advance();
-                    }
-                    
-                    impls.push(ImplBlock {
-                        struct_name,
-                        trait_name,
-                        constructor_params,
-                        constructor_body,
-                        methods,
-                    });
+                    debug_log!("DEBUG: Parsing function");
+                    let func = self.parse_function_with_visibility(false, false, vec![]);
+                    functions.push(func);
+                    debug_log!("DEBUG: Functions count: {}", functions.len());
+                }
+
+                Token::Struct => {
+                    debug_log!("DEBUG: Parsing struct");
+                    structs.push(self.parse_struct(false, vec![]));
                 }
 
+                Token::Impl => impls.push(self.parse_impl_block(false)),
+
                 Token::Use => {
                     self.advance();
                     if let Token::Identifier(module_name) = self.current() {
                         self.advance();
-                        uses.push(ModuleUse { module_name });
+                        let alias = if self.current() == Token::As {
+                            self.advance();
+                            match self.current() {
+                                Token::Identifier(alias) => {
+                                    self.advance();
+                                    Some(alias)
+                                }
+                                found => {
+                                    self.diags.push(ParseDiagnostic {
+                                        message: format!("Expected an alias after `as`, found {:?}", found),
+                                        span: self.current_span(),
+                                        severity: DiagnosticSeverity::Error,
+                                        help: Some("write `use module as name`".to_string()),
+                                    });
+                                    None
+                                }
+                            }
+                        } else {
+                            None
+                        };
+                        uses.push(ModuleUse { module_name, alias });
                     } else {
                         self.advance();
                     }
@@ -2472,7 +3030,26 @@ impl Parser {
                     } else {
                         "C".to_string()
                     };
-                    
+
+                    let library = if self.current() == Token::From {
+                        self.advance();
+                        match self.current() {
+                            Token::String(library) => {
+                                self.advance();
+                                library
+                            }
+                            found => {
+                                self.error_and_sync(
+                                    format!("Expected a library name after `from`, found {:?}", found),
+                                    "write `extern \"C\" from \"library\":`",
+                                    &[Token::Colon],
+                                );
+                                String::new()
+                            }
+                        }
+                    } else {
+                        String::new()
+                    };
                     
                     if self.current() == Token::Colon {
                         self.advance();
@@ -2483,63 +3060,7 @@ impl Parser {
                             if self.current() == Token::Func {
                                 self.advance();
                                 
-                                let name = if let Token::Identifier(name) = self.current() {
-                                    self.advance();
-                                    name
-                                } else {
-                                    self.advance();
-                                    "error".to_string()
-                                };
-                                
-                                self.expect(Token::LeftParen, vec![Token::RightParen, Token::Colon]);
-                                
-                                let mut params = Vec::new();
-                                while self.current() != Token::RightParen && self.current() != Token::EOF {
-                                    if self.current() == Token::TripleDot {
-                                        self.advance();
-                                        params.push(("...".to_string(), Type::TripleDot));
-                                        if self.current() == Token::Comma {
-                                            self.advance();
-                                        }
-                                        continue;
-                                    }
-                                    
-                                    let pname = if let Token::Identifier(pname) = self.current() {
-                                        self.advance();
-                                        pname
-                                    } else {
-                                        self.advance();
-                                        "error".to_string()
-                                    };
-                                    
-                                    self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                                    let ptype = self.parse_type();
-                                    params.push((pname, ptype));
-                                    
-                                    if self.current() == Token::Comma {
-                                        self.advance();
-                                    }
-                                }
-                                
-                                self.expect(Token::RightParen, vec![Token::Colon, Token::Arrow]);
-                                
-                                let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
-                                    self.advance();
-                                    if self.is_type_token(self.current()) {
-                                        self.parse_type()
-                                    } else {
-                                        Type::Void
-                                    }
-                                } else {
-                                    Type::Void
-                                };
-                                
-                                functions_list.push(ExternFunction {
-                                    name,
-                                    params,
-                                    return_type,
-                                    is_public: false,
-                                });
+                                functions_list.push(self.parse_extern_signature());
                             } else if self.current() == Token::End {
                                 break;
                             } else {
@@ -2557,71 +3078,26 @@ impl Parser {
                         
                         externs.push(ExternDecl::Block { 
                             abi, 
-                            library: String::new(), 
+                            library, 
                             functions: functions_list 
                         });
-                    } else {
-                        
-                        let name = if let Token::Identifier(name) = self.current() {
-                            self.advance();
-                            name
-                        } else {
-                            self.advance();
-                            "error".to_string()
-                        };
-                        
-                        self.expect(Token::LeftParen, vec![Token::RightParen]);
-                        
-                        let mut params = Vec::new();
-                        while self.current() != Token::RightParen && self.current() != Token::EOF {
-                            if self.current() == Token::TripleDot {
-                                self.advance();
-                                params.push(("...".to_string(), Type::TripleDot));
-                                if self.current() == Token::Comma {
-                                    self.advance();
-                                }
-                                continue;
-                            }
-                            
-                            let pname = if let Token::Identifier(pname) = self.current() {
-                                self.advance();
-                                pname
-                            } else {
-                                self.advance();
-                                "error".to_string()
-                            };
-                            
-                            self.expect(Token::Colon, vec![Token::Comma, Token::RightParen]);
-                            let ptype = self.parse_type();
-                            params.push((pname, ptype));
-                            
-                            if self.current() == Token::Comma {
-                                self.advance();
-                            }
-                        }
-                        
-                        self.expect(Token::RightParen, vec![Token::Colon, Token::Arrow, Token::Semicolon]);
-                        
-                        let return_type = if self.current() == Token::Arrow || self.current() == Token::Colon {
-                            self.advance();
-                            if self.is_type_token(self.current()) {
-                                self.parse_type()
-                            } else {
-                                Type::Void
-                            }
-                        } else {
-                            Type::Void
-                        };
-                        
-                        externs.push(ExternDecl::Single {
+                    } else if self.current() == Token::Func {
+                        // `extern "C" func name(...) ... end` defines a function callable from C.
+                        let func = self.parse_function(false);
+                        externs.push(ExternDecl::SingleWithBody {
                             abi,
-                            func: ExternFunction {
-                                name,
-                                params,
-                                return_type,
-                                is_public: false,
+                            func: ExternFunctionBody {
+                                name: func.name,
+                                params: func.params.into_iter().map(|(name, ty, _)| (name, ty)).collect(),
+                                return_type: func.return_type,
+                                is_public: true,
+                                body: func.body,
                             },
                         });
+                    } else {
+                        
+                        let func = self.parse_extern_signature();
+                        externs.push(ExternDecl::Single { abi, library: (!library.is_empty()).then_some(library), func });
                     
                         if self.current() == Token::Semicolon {
                             self.advance();
@@ -2650,12 +3126,14 @@ impl Parser {
                     if self.current() == Token::Func {
                         let func = self.parse_function_with_visibility(false, is_public, attributes);
                         functions.push(func);
+                    } else if self.current() == Token::Struct {
+                        structs.push(self.parse_struct(is_public, attributes));
                     } else {
                         self.diags.push(ParseDiagnostic {
-                            message: "Expected function after attributes".to_string(),
+                            message: "Expected function or struct after attributes".to_string(),
                             span: self.current_span(),
                             severity: DiagnosticSeverity::Error,
-                            help: Some("Attributes are currently only supported on functions".to_string()),
+                            help: Some("Attributes are currently only supported on functions and structs".to_string()),
                         });
                         self.advance();
                     }
@@ -2666,26 +3144,11 @@ impl Parser {
                     functions.push(self.parse_function(true));
                 }
                 
-                
-                Token::Type => {
-                    self.advance();
-                    let _name = if let Token::Identifier(name) = self.current() { self.advance(); name } else { self.advance(); "error".to_string() };
-                    self.expect(Token::Equals, vec![Token::End]);
-                    let _ty = self.parse_type();
-                }
 
-                Token::Trait => {
-                    self.advance();
-                    let _name = if let Token::Identifier(name) = self.current() { self.advance(); name } else { self.advance(); "error".to_string() };
-                    self.expect(Token::Colon, vec![Token::End]);
-                    while !matches!(self.current(), Token::End | Token::EOF) {
-                        self.advance(); 
-                    }
-                    if self.current() == Token::End { self.advance(); }
-                }
+                Token::Trait => traits.push(self.parse_trait(false)),
 
                 Token::Identifier(ref id) if id == "export" => {
-                    println!("DEBUG: Parsing module exports");
+                    debug_log!("DEBUG: Parsing module exports");
                     let export_stmt = self.parse_module_exports();
                      
                     modules.push(export_stmt);
@@ -2698,6 +3161,16 @@ impl Parser {
                     self.advance();
                 }
             }
+
+            for func in &functions[known_functions..] {
+                self.note_definition(&mut definitions, "function", &func.name, item_span);
+            }
+            for s in &structs[known_structs..] {
+                self.note_definition(&mut definitions, "struct", &s.name, item_span);
+            }
+            for e in &enums[known_enums..] {
+                self.note_definition(&mut definitions, "enum", &e.name, item_span);
+            }
         }
         
         let undefined = self.find_undefined_functions(&functions, &externs, &import_context);
@@ -2705,6 +3178,28 @@ impl Parser {
         (Program { functions, constants, modules }, structs, enums, externs, imports, uses, classes, impls, traits, undefined, import_decls)
     }
     
+    /// Functions share one namespace; structs and enums share the type namespace.
+    fn note_definition(
+        &mut self,
+        definitions: &mut HashMap<(bool, String), (&'static str, SourceSpan)>,
+        kind: &'static str,
+        name: &str,
+        span: SourceSpan,
+    ) {
+        let key = (kind == "function", name.to_string());
+        if let Some((first_kind, first_span)) = definitions.get(&key) {
+            let first_line = self.get_location(first_span.offset()).line;
+            self.diags.push(ParseDiagnostic {
+                message: format!("Duplicate definition of {} '{}'", kind, name),
+                span,
+                severity: DiagnosticSeverity::Error,
+                help: Some(format!("'{}' was already defined as a {} on line {}", name, first_kind, first_line)),
+            });
+        } else {
+            definitions.insert(key, (kind, span));
+        }
+    }
+
     fn find_undefined_functions(
         &self, 
         functions: &[Function], 
@@ -2752,7 +3247,7 @@ impl Parser {
         import_context: &ImportContext
     ) {
         for stmt in stmts {
-            match stmt {
+            match stmt.node() {
                 Stmt::Call(name, args) => {
                      
                     if import_context.is_library_function(name) {
@@ -2773,9 +3268,9 @@ impl Parser {
                         self.expr_calls(arg, defined, undefined, import_context);
                     }
                 }
-                Stmt::ModuleCall(module, _func, args) => {
+                Stmt::ModuleCall(path, _func, args) => {
                      
-                    if import_context.is_imported_symbol(module) {
+                    if import_context.is_imported_symbol(&path[0]) {
                          
                         continue;
                     }
@@ -2795,6 +3290,9 @@ impl Parser {
                     self.expr_calls(cond, defined, undefined, import_context);
                     self.stmt_calls(body, defined, undefined, import_context);
                 }
+                Stmt::Loop(body) => {
+                    self.stmt_calls(body, defined, undefined, import_context);
+                }
                 Stmt::Match(expr, cases, default) => {
                     self.expr_calls(expr, defined, undefined, import_context);
                     for case in cases {
@@ -2811,6 +3309,13 @@ impl Parser {
                 Stmt::Return(Some(expr)) | Stmt::Assign(_, expr) | Stmt::CompoundAssign(_, _, expr) => {
                     self.expr_calls(expr, defined, undefined, import_context);
                 }
+                Stmt::IndexCompoundAssign(obj, indices, _, value) => {
+                    self.expr_calls(obj, defined, undefined, import_context);
+                    for index in indices {
+                        self.expr_calls(index, defined, undefined, import_context);
+                    }
+                    self.expr_calls(value, defined, undefined, import_context);
+                }
                 _ => {}
             }
         }
@@ -2842,8 +3347,8 @@ impl Parser {
                     self.expr_calls(arg, defined, undefined, import_context);
                 }
             }
-            Expr::ModuleCall(module, _func, args) => {
-                if import_context.is_imported_symbol(module) {
+            Expr::ModuleCall(path, _func, args) => {
+                if import_context.is_imported_symbol(&path[0]) {
                      
                     return;
                 }
@@ -2856,7 +3361,7 @@ impl Parser {
                 self.expr_calls(left, defined, undefined, import_context);
                 self.expr_calls(right, defined, undefined, import_context);
             }
-            Expr::UnOp(_, expr) => {
+            Expr::UnOp(_, expr) | Expr::AddressOf(expr) | Expr::Deref(expr) => {
                 self.expr_calls(expr, defined, undefined, import_context);
             }
             Expr::Tuple(exprs) | Expr::Array(exprs) => {
@@ -2870,6 +3375,26 @@ impl Parser {
 
 }
 
+/// The type named by an integer literal suffix such as `u8` or `i64`.
+fn suffix_type(suffix: &str) -> Type {
+    match suffix {
+        "usize" => Type::Usize,
+        _ => Type::Int { bits: suffix[1..].parse().unwrap_or(32), signed: suffix.starts_with('i') },
+    }
+}
+
+/// Turns the parser's nested `a[i][j]` into a single index chain rooted at `a`.
+fn flatten_index(obj: Expr, indices: Vec<Expr>) -> (Box<Expr>, Vec<Expr>) {
+    match obj {
+        Expr::Index(inner, inner_indices) => {
+            let (root, mut chain) = flatten_index(*inner, inner_indices);
+            chain.extend(indices);
+            (root, chain)
+        }
+        other => (Box::new(other), indices),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2919,6 +3444,289 @@ mod tests {
         assert!(func.attributes.contains(&"Test".to_string()));
     }
 
+    #[test]
+    fn test_else_if_two_arms() {
+        let code = r#"
+func pick(x: int32): int32
+    if x == 1 then
+        return 10
+    else if x == 2 then
+        return 20
+    else:
+        return 30
+    end
+    return 0
+end
+"#;
+        let func = parse_func(code).expect("else if chain should parse");
+        assert_eq!(func.body.len(), 2);
+
+        let Stmt::If(_, then_body, Some(else_body)) = func.body[0].node() else {
+            panic!("expected if statement, got {:?}", func.body[0]);
+        };
+        assert_eq!(then_body.len(), 1);
+        assert_eq!(else_body.len(), 1);
+
+        let Stmt::If(_, inner_then, Some(inner_else)) = else_body[0].node() else {
+            panic!("expected nested if in else block, got {:?}", else_body[0]);
+        };
+        assert_eq!(inner_then.len(), 1);
+        assert!(matches!(inner_else.as_slice(), [stmt] if matches!(stmt.node(), Stmt::Return(Some(Expr::Number(30))))));
+    }
+
+    #[test]
+    fn test_else_if_three_arms() {
+        let code = r#"
+func pick(x: int32): int32
+    if x == 1 then
+        return 10
+    else if x == 2 then
+        return 20
+    else if x == 3 then
+        return 30
+    else:
+        return 40
+    end
+    return 0
+end
+"#;
+        let func = parse_func(code).expect("else if chain should parse");
+        assert_eq!(func.body.len(), 2);
+
+        let mut arms = 0;
+        let mut current = func.body[0].node();
+        loop {
+            let Stmt::If(_, then_body, Some(else_body)) = current else {
+                panic!("expected if statement, got {:?}", current);
+            };
+            arms += 1;
+            assert_eq!(then_body.len(), 1);
+
+            match else_body.iter().map(Stmt::node).collect::<Vec<_>>().as_slice() {
+                [nested @ Stmt::If(..)] => current = nested,
+                [Stmt::Return(Some(Expr::Number(40)))] => break,
+                other => panic!("unexpected else block {:?}", other),
+            }
+        }
+        assert_eq!(arms, 3);
+    }
+
+    #[test]
+    fn test_as_binds_between_member_access_and_binary_ops() {
+        let code = r#"
+func widen(p: Pair, b: int64): int64
+    return p.x as int64 + b
+end
+"#;
+        let func = parse_func(code).expect("as cast should parse");
+        let Stmt::Return(Some(Expr::BinOp(op, left, right))) = func.body[0].node() else {
+            panic!("expected binary return, got {:?}", func.body[0]);
+        };
+        assert_eq!(op, "+");
+        assert!(matches!(
+            left.as_ref(),
+            Expr::Cast(inner, CastTarget::Type(ty)) if matches!(inner.as_ref(), Expr::MemberAccess(_, f) if f == "x") && *ty == Type::i64()
+        ));
+        assert!(matches!(right.as_ref(), Expr::Var(b) if b == "b"));
+    }
+
+    #[test]
+    fn test_range_for_and_slice() {
+        let code = r#"
+func sum(n: int32, arr: [int32]): int32
+    for i in 0..n do
+        print(i)
+    end
+    for j in 1..=n do
+        print(j)
+    end
+    part: [int32] = arr[1..3]
+    return 0
+end
+"#;
+        let func = parse_func(code).expect("range loops should parse");
+
+        assert!(matches!(
+            func.body[0].node(),
+            Stmt::For(var, Expr::Range(start, end, false), _)
+                if var == "i" && matches!(**start, Expr::Number(0)) && matches!(**end, Expr::Var(ref n) if n == "n")
+        ));
+        assert!(matches!(func.body[1].node(), Stmt::For(_, Expr::Range(_, _, true), _)));
+
+        let Stmt::TypedDeclaration { value, .. } = func.body[2].node() else {
+            panic!("expected declaration, got {:?}", func.body[2]);
+        };
+        assert!(matches!(value, Expr::Slice(_, start, end)
+            if matches!(**start, Expr::Number(1)) && matches!(**end, Expr::Number(3))));
+    }
+
+    #[test]
+    fn test_nested_option_result_types() {
+        let code = r#"
+func nested(a: Option[Result[int32, str]], b: Result[Option[int32], str]): Result[Result[int32, str], str]
+    c: Option(Result(int32, str)) = a
+    d: [Option[int32]] = b
+    return 0
+end
+"#;
+        let func = parse_func(code).expect("nested generics should parse");
+        let str_ty = || Type::Str { len_type: Box::new(Type::i64()) };
+
+        assert_eq!(func.params[0].1, Type::option(Type::result(Type::i32(), str_ty())));
+        assert_eq!(func.params[1].1, Type::result(Type::option(Type::i32()), str_ty()));
+        assert_eq!(func.return_type, Type::result(Type::result(Type::i32(), str_ty()), str_ty()));
+
+        assert!(matches!(func.body[0].node(), Stmt::TypedDeclaration { ty, .. }
+            if *ty == Type::option(Type::result(Type::i32(), str_ty()))));
+        assert!(matches!(func.body[1].node(), Stmt::TypedDeclaration { ty: Type::Array { element, size: None }, .. }
+            if **element == Type::option(Type::i32())));
+        assert!(matches!(func.body[2].node(), Stmt::Return(_)));
+    }
+
+    #[test]
+    fn test_const_folded_array_sizes() {
+        let func = parse_func("func f(a: int32[2*4], b: [int32; 3 + 1], c: int32[0x2][1 << 2])\nend\n")
+            .expect("constant sizes should fold");
+        assert_eq!(func.params[0].1, Type::MultiArray { element: Box::new(Type::i32()), dimensions: vec![8] });
+        assert_eq!(func.params[1].1, Type::Array { element: Box::new(Type::i32()), size: Some(4) });
+        assert_eq!(func.params[2].1, Type::MultiArray { element: Box::new(Type::i32()), dimensions: vec![2, 4] });
+
+        let code = "const SIZE: int32 = 4\nconst DOUBLE: int32 = SIZE * 2\nfunc g(buf: int32[SIZE], big: int32[DOUBLE])\nend\n";
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let (program, ..) = Parser::new(tokens, code.to_string(), lexer.spans.clone()).parse();
+        let params = &program.functions[0].params;
+        assert_eq!(params[0].1, Type::MultiArray { element: Box::new(Type::i32()), dimensions: vec![4] });
+        assert_eq!(params[1].1, Type::MultiArray { element: Box::new(Type::i32()), dimensions: vec![8] });
+    }
+
+    #[test]
+    fn test_runtime_array_size_rejected() {
+        let err = parse_func("func f(x: int32, a: int32[x])\nend\n").expect_err("runtime size must be rejected");
+        assert!(err.contains("'x' is not a compile-time constant"), "{}", err);
+    }
+
+    fn parse_diagnostics(code: &str) -> Vec<ParseDiagnostic> {
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        parser.parse_program();
+        parser.diags
+    }
+
+    #[test]
+    fn test_duplicate_function_definition() {
+        let diags = parse_diagnostics("func area(): int32\n    return 1\nend\n\nfunc area(): int32\n    return 2\nend\n");
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].message, "Duplicate definition of function 'area'");
+        assert_eq!(diags[0].span.offset(), 37);
+        assert!(diags[0].help.as_deref().unwrap().contains("line 1"));
+    }
+
+    #[test]
+    fn test_duplicate_struct_definition() {
+        let diags = parse_diagnostics("struct Point:\n    x = int32\nend\n\npub struct Point:\n    y = int32\nend\n");
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].message, "Duplicate definition of struct 'Point'");
+    }
+
+    #[test]
+    fn test_duplicate_enum_definition() {
+        let diags = parse_diagnostics("enum Color:\n    Red\nend\n\nenum Color:\n    Blue\nend\n\nstruct Shade:\n    v = int32\nend\n\nenum Shade:\n    Dark\nend\n");
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Duplicate definition of enum 'Color'", "Duplicate definition of enum 'Shade'"]);
+        assert!(diags[1].help.as_deref().unwrap().contains("as a struct"));
+    }
+
+    #[test]
+    fn test_unclosed_function_points_at_opener() {
+        let diags = parse_diagnostics("func main()\n    print(1)\n");
+        assert_eq!(diags.len(), 1, "{:?}", diags.iter().map(|d| &d.message).collect::<Vec<_>>());
+        assert_eq!(diags[0].message, "`func` block is missing its `end`");
+        assert_eq!(diags[0].span.offset(), 0);
+        assert_eq!(diags[0].help.as_deref(), Some("this block is never closed"));
+    }
+
+    #[test]
+    fn test_unclosed_if_points_at_opener() {
+        let code = "func main()\n    if true then\n        print(1)\n";
+        let diags = parse_diagnostics(code);
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["`if` block is missing its `end`", "`func` block is missing its `end`"]);
+        assert_eq!(diags[0].span.offset(), code.find("if").unwrap());
+        assert!(diags.iter().all(|d| !d.message.contains("EOF")));
+    }
+
+    #[test]
+    fn test_pub_items_record_visibility() {
+        let code = "public const LIMIT = 8\n\npublic trait Shape:\n    func area(self): int32\nend\n\nstruct Square:\n    side = int32\nend\n\npublic impl Square:\n    func area(self): int32\n        return self.side * self.side\n    end\nend\n\npublic 42\n";
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let (program, _, _, _, _, _, _, impls, traits, _, _) = parser.parse_program();
+
+        assert!(program.constants[0].is_public);
+        assert!(traits[0].is_public);
+        assert!(impls[0].is_public);
+        let messages: Vec<&str> = parser.diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Expected an item after `public`, found Number(42)"]);
+    }
+
+    #[test]
+    fn test_create_without_name_is_a_diagnostic() {
+        let code = "func main()\n    create 5 = 3\n    create ok = 1\nend\n";
+        let diags = parse_diagnostics(code);
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Expected a variable name after `create`, found Number(5)"]);
+        assert_eq!(diags[0].span.offset(), code.find('5').unwrap());
+    }
+
+    #[test]
+    fn test_invalid_public_item_in_module_is_a_diagnostic() {
+        let code = "mod tools {\n    public 42\n}\n";
+        let messages: Vec<String> = parse_diagnostics(code).into_iter().map(|d| d.message).collect();
+        assert_eq!(messages, vec!["Expected an item after `public`, found Number(42)"]);
+    }
+
+    #[test]
+    fn test_malformed_exports_is_a_diagnostic() {
+        let code = "exports(a, b)\n";
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let stmt = parser.parse_module_exports();
+        assert!(matches!(stmt, Stmt::ModuleExports { exports } if exports.is_empty()));
+        let messages: Vec<&str> = parser.diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Expected `export`, found Identifier(\"exports\")"]);
+    }
+
+    #[test]
+    fn test_labeled_loops() {
+        let code = r#"
+func scan()
+    rows: while true do
+        for x in 0..4 do
+            continue rows
+        end
+        break
+        print(1)
+    end
+end
+"#;
+        let func = parse_func(code).expect("labeled loops should parse");
+
+        let Stmt::Labeled(label, inner) = func.body[0].node() else {
+            panic!("expected labeled loop, got {:?}", func.body[0]);
+        };
+        assert_eq!(label, "rows");
+        let Stmt::While(_, body) = inner.node() else {
+            panic!("expected while, got {:?}", inner);
+        };
+        assert!(matches!(body[0].node(), Stmt::For(_, _, inner) if matches!(inner[0].node(), Stmt::Continue(Some(l)) if l == "rows")));
+        assert!(matches!(body[1].node(), Stmt::Break(None)));
+        assert!(matches!(body[2].node(), Stmt::Call(name, _) if name == "print"));
+    }
+
     #[test]
     fn test_full_game_parser() {
         let game_render_code = r#"
@@ -2979,7 +3787,8 @@ end
 
         println!("Parsed: {} functions, {} structs, {} externs, {} constants", program1.functions.len(), structs1.len(), externs1.len(), program1.constants.len());
 
-        assert!(true, "Parser completed successfully");
+        assert_eq!(structs1.len(), 1);
+        assert_eq!(externs1.len(), 1);
 
         let mut lexer2 = Lexer::new(main_code);
         let tokens2 = lexer2.tokenize();
@@ -2988,10 +3797,207 @@ end
         let (program2, structs2, _enums2, _externs2, _imports2, _uses2, _classes2, impls2, _traits2, _undef2, _import_decls2) = parser2.parse();
         
         println!("Main parsed: {} functions, {} structs, {} impls", program2.functions.len(), structs2.len(), impls2.len());
-        assert!(true, "Parser completed successfully on main code");
+        assert_eq!(structs2.len(), 1);
+        assert_eq!(impls2.len(), 1);
+    }
+
+    #[test]
+    fn test_location_columns_count_characters() {
+        let source = "func main()\n    create s = \"h\u{e9}llo\" $\nend\n";
+        let mut lexer = Lexer::new(source);
+        let tokens = lexer.tokenize();
+        let error = lexer.errors.first().expect("`$` should be a lexer error");
+
+        let parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+        let location = parser.get_location(error.span.offset());
+        assert_eq!((location.line, location.column), (2, 24));
+    }
+
+    #[test]
+    fn test_calling_self_is_a_diagnostic() {
+        let code = r#"
+struct Counter:
+    value = int32
+end
+
+impl Counter:
+    func next(&self): int32
+        create a = self(1)
+        create b = self.value
+        return self.peek()
+    end
+end
+"#;
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let (_, _, _, _, _, _, _, impls, ..) = parser.parse_program();
+        let messages: Vec<&str> = parser.diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["`self` is a value and cannot be called"]);
+
+        let method = &impls[0].methods[0];
+        assert!(matches!(method.body[1].node(), Stmt::TypedDeclaration { value: Expr::MemberAccess(obj, field), .. }
+            if *obj.as_ref() == Expr::Var("self".to_string()) && field == "value"));
+        assert!(matches!(method.body[2].node(), Stmt::Return(Some(Expr::MethodCall(_, name, args))) if name == "peek" && args.is_empty()));
+    }
+
+    #[test]
+    fn test_required_parameter_after_default_is_reported() {
+        let diags = parse_diagnostics("func connect(port: int32 = 8080, host: str)\nend\n");
+        assert_eq!(diags.len(), 1, "{:?}", diags);
+        assert_eq!(diags[0].message, "Parameter `host` without a default follows a parameter with one");
+    }
+
+    #[test]
+    fn test_non_constant_default_is_reported() {
+        let diags = parse_diagnostics("func f(a: int32, b: int32 = a)
+end
+");
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["Default value of `b` must be a constant"]);
+
+        let diags = parse_diagnostics("const BASE = 8000\nfunc f(port: int32 = BASE + 80, ratio: float32 = -0.5, name: str = \"h\", on: bool = true)\nend\n");
+        assert!(diags.is_empty(), "{:?}", diags);
+    }
+
+    #[test]
+    fn test_trailing_commas_are_accepted() {
+        let code = r#"
+enum Shape:
+    Rect(int32, int32,)
+end
+
+struct P:
+    x = int32
+end
+
+impl P:
+    func m(&self, a: int32,): int32
+        return a
+    end
+end
+
+func f(a: int32, b: int32,): int32
+    create xs = [1, 2,]
+    create t = (a, b,)
+    o.m(a, b,)
+    create n = f(a = 1, b = 2,)
+    return f(a, b,)
+end
+"#;
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let (program, _, enums, _, _, _, _, impls, ..) = parser.parse_program();
+        assert!(parser.diags.is_empty(), "{:?}", parser.diags);
+
+        assert!(matches!(&enums[0].variants[0], EnumVariant::Tuple(_, types) if types.len() == 2));
+        assert_eq!(impls[0].methods[0].params.len(), 1);
+        let func = &program.functions[0];
+        assert_eq!(func.params.len(), 2);
+        let two = vec![Expr::Var("a".to_string()), Expr::Var("b".to_string())];
+        assert!(matches!(func.body[0].node(), Stmt::TypedDeclaration { value: Expr::Array(items), .. } if items.len() == 2));
+        assert!(matches!(func.body[1].node(), Stmt::TypedDeclaration { value: Expr::Tuple(items), .. } if *items == two));
+        assert!(matches!(func.body[2].node(), Stmt::Expr(Expr::MethodCall(_, _, args)) if *args == two));
+        assert!(matches!(func.body[3].node(), Stmt::TypedDeclaration { value: Expr::CallNamed(_, args), .. } if args.len() == 2));
+        assert!(matches!(func.body[4].node(), Stmt::Return(Some(Expr::Call(_, args))) if *args == two));
+    }
+
+    #[test]
+    fn test_nested_module_path_call() {
+        let code = r#"mod outer {
+    mod inner {
+        public func triple(x: int32): int32
+            return x * 3
+        end
     }
 }
 
+func main(): int32
+    outer.inner.triple(1)
+    return outer.inner.triple(2)
+end
+"#;
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let (program, ..) = parser.parse_program();
+        assert!(parser.diags.is_empty(), "{:?}", parser.diags);
+
+        let path = vec!["outer".to_string(), "inner".to_string()];
+        let func = &program.functions[0];
+        assert!(matches!(func.body[0].node(), Stmt::ModuleCall(p, name, args) if *p == path && name == "triple" && args.len() == 1));
+        assert!(matches!(func.body[1].node(), Stmt::Return(Some(Expr::ModuleCall(p, name, _))) if *p == path && name == "triple"));
+    }
+
+    #[test]
+    fn test_ffi_attribute_on_single_extern() {
+        let code = "@ffi(\"user32\") extern \"stdcall\" func MessageBoxA(hwnd: int64, text: int64, caption: int64, kind: uint32): int32\n\nfunc main(): int32\n    return MessageBoxA(0, 0, 0, 0)\nend\n";
+        let mut lexer = Lexer::new(code);
+        let tokens = lexer.tokenize();
+        let mut parser = Parser::new(tokens, code.to_string(), lexer.spans.clone());
+        let (program, _, _, externs, ..) = parser.parse_program();
+        assert!(parser.diags.is_empty(), "{:?}", parser.diags);
+
+        let [ExternDecl::Single { abi, library, func }] = externs.as_slice() else {
+            panic!("expected one single extern, got {:?}", externs);
+        };
+        assert_eq!(abi, "stdcall");
+        assert_eq!(library.as_deref(), Some("user32"));
+        assert_eq!(func.name, "MessageBoxA");
+        assert_eq!(func.params.len(), 4);
+        assert_eq!(program.functions.len(), 1);
+    }
+
+    #[test]
+    fn test_int_width_syntax() {
+        let func = parse_func("func f(a: int(24), b: uint(7)): int(64)\n    return 0\nend\n").expect("int(N) should parse");
+        assert_eq!(func.params[0].1, Type::int(24, true));
+        assert_eq!(func.params[1].1, Type::int(7, false));
+        assert_eq!(func.return_type, Type::i64());
+
+        let diags = parse_diagnostics("func g(a: int(65), b: uint(0))\nend\n");
+        let messages: Vec<&str> = diags.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec![
+            "Integer width must be between 1 and 64 bits, found 65",
+            "Integer width must be between 1 and 64 bits, found 0",
+        ]);
+        assert_eq!(diags[0].span.offset(), 14);
+    }
+
+    #[test]
+    fn test_address_of_expressions() {
+        let func = parse_func("func f()\n    create a = &x\n    create b = &p.x\n    create c = &one()\nend\n").expect("address-of should parse");
+        let values: Vec<&Expr> = func.body.iter().map(|stmt| match stmt.node() {
+            Stmt::TypedDeclaration { value, .. } => value,
+            other => panic!("expected declaration, got {:?}", other),
+        }).collect();
+        assert!(matches!(values[0], Expr::FuncAddr(name) if name == "x"));
+        assert!(matches!(values[1], Expr::AddressOf(inner) if matches!(**inner, Expr::MemberAccess(_, ref field) if field == "x")));
+        assert!(matches!(values[2], Expr::AddressOf(inner) if matches!(**inner, Expr::Call(ref name, _) if name == "one")));
+    }
 
+    #[test]
+    fn test_deref_expressions() {
+        let func = parse_func("func f(p: &mut int32)\n    *p = *p * 2\nend\n").expect("deref should parse");
+        let Stmt::DerefAssign(target, value) = func.body[0].node() else {
+            panic!("expected deref assignment, got {:?}", func.body[0]);
+        };
+        assert!(matches!(&**target, Expr::Var(name) if name == "p"));
+        assert!(matches!(value, Expr::BinOp(op, left, _) if op == "*" && matches!(**left, Expr::Deref(_))));
+    }
 
+    #[test]
+    fn test_line_leading_deref_starts_a_statement() {
+        let func = parse_func("func f(q: &mut int32)\n    create m = 2\n    *q = 5\nend\n").expect("deref should parse");
+        assert_eq!(func.body.len(), 2, "{:?}", func.body);
+        assert!(matches!(func.body[0].node(), Stmt::TypedDeclaration { name, value: Expr::Number(2), .. } if name == "m"), "{:?}", func.body[0]);
+        assert!(matches!(func.body[1].node(), Stmt::DerefAssign(target, Expr::Number(5)) if matches!(&**target, Expr::Var(q) if q == "q")));
+
+        let func = parse_func("func f(a: int32, b: int32): int32\n    mut x: int32 = a\n        * b\n    return x\nend\n").expect("continued product should parse");
+        assert_eq!(func.body.len(), 2, "{:?}", func.body);
+        assert!(matches!(func.body[0].node(), Stmt::TypedDeclaration { value: Expr::BinOp(op, left, right), .. }
+            if op == "*" && matches!(&**left, Expr::Var(a) if a == "a") && matches!(&**right, Expr::Var(b) if b == "b")), "{:?}", func.body[0]);
+    }
+}
 
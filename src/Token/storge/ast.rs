@@ -19,6 +19,8 @@ pub struct GlobalConst {
     pub name: String,
     pub ty: Type,
     pub value: Expr,
+    pub is_public: bool,
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -28,16 +30,19 @@ pub enum Type {
     Bool,
     Char { bits: usize, signed: bool },
     Void,
+    Never,
     Ptr(Box<Type>),
     RawPtr(Box<Type>),
     Str { len_type: Box<Type> },
     StrSlice { char_type: Box<Type>, length_type: Box<Type> },
     Struct { name: String },
     Array { element: Box<Type>, size: Option<usize> },
+    Slice { element: Box<Type> },
     MultiArray { element: Box<Type>, dimensions: Vec<usize> },
     Tuple { fields: Vec<Type> },
     Union { variants: Vec<Type> },
     FnPtr { params: Vec<Type>, return_type: Box<Type> },
+    FunctionPointer { params: Vec<Type>, ret: Box<Type> },
     Option { inner: Box<Type> },
     Result { ok: Box<Type>, err: Box<Type> },
     Intersection { types: Vec<Type> },
@@ -141,10 +146,14 @@ pub enum SelfModifier {
 pub struct Function {
     pub name: String,
     pub params: Vec<(String, Type, ParamModifier)>,
+    /// Default value of each parameter in `params`, if it has one.
+    pub param_defaults: Vec<Option<Expr>>,
     pub return_type: Type,
     pub body: Vec<Stmt>,
     pub is_public: bool,
     pub attributes: Vec<String>,
+    /// Line of the `func` keyword.
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -168,6 +177,8 @@ pub struct ExternFunctionBody {
 pub enum ExternDecl {
     Single {
         abi: String,
+        /// Library named by a preceding `@ffi("lib")`.
+        library: Option<String>,
         func: ExternFunction,
     },
     Block {
@@ -194,6 +205,9 @@ pub struct StructDef {
     pub name: String,
     pub fields: Vec<StructField>,
     pub is_public: bool,
+    pub attributes: Vec<String>,
+    /// Line of the `struct` keyword.
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -208,6 +222,7 @@ pub struct TraitMethod {
 pub struct TraitDef {
     pub name: String,
     pub methods: Vec<TraitMethod>,
+    pub is_public: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -218,6 +233,8 @@ pub struct ImplMethod {
     pub body: Vec<Stmt>,
     pub self_modifier: Option<SelfModifier>,
     pub is_public: bool,
+    /// Line of the method's `func` keyword.
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -227,6 +244,9 @@ pub struct ImplBlock {
     pub constructor_params: Vec<(String, Type)>,
     pub constructor_body: Option<Vec<(String, Expr)>>,
     pub methods: Vec<ImplMethod>,
+    pub is_public: bool,
+    /// Line of the `impl` keyword.
+    pub line: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -260,9 +280,11 @@ pub enum Stmt {
     Assign(String, Expr),
     CompoundAssign(String, String, Expr),
     IndexAssign(Box<Expr>, Vec<Expr>, Expr),
+    IndexCompoundAssign(Box<Expr>, Vec<Expr>, String, Expr),
     MemberAssign(Box<Expr>, String, Expr),
-    ModuleAssign(String, String, Expr),
-    ModuleCompoundAssign(String, String, String, Expr),
+    DerefAssign(Box<Expr>, Expr),
+    ModuleAssign(Vec<String>, String, Expr),
+    ModuleCompoundAssign(Vec<String>, String, String, Expr),
     If(Expr, Vec<Stmt>, Option<Vec<Stmt>>),
     While(Expr, Vec<Stmt>),
     For(String, Expr, Vec<Stmt>),
@@ -270,14 +292,17 @@ pub enum Stmt {
     Loop(Vec<Stmt>),
     Match(Expr, Vec<MatchCase>, Option<Vec<Stmt>>),
     Call(String, Vec<Expr>),
-    ModuleCall(String, String, Vec<Expr>),
+    ModuleCall(Vec<String>, String, Vec<Expr>),
     MethodCall(Box<Expr>, String, Vec<Expr>),
     MethodCallNamed(Box<Expr>, String, Vec<(String, Expr)>),
     StaticMethodCall(String, String, Vec<Expr>),
     StaticMethodCallNamed(String, String, Vec<(String, Expr)>),
     Return(Option<Expr>),
-    Break,
-    Continue,
+    Break(Option<String>),
+    Continue(Option<String>),
+    Labeled(String, Box<Stmt>),
+    Defer(Box<Stmt>),
+    Assert(Expr, Option<Expr>),
     Unsafe(Vec<Stmt>),
     Scope(Vec<Stmt>),
     StructDef(StructDef),
@@ -291,6 +316,8 @@ pub enum Stmt {
         name: String,
         body: Vec<Stmt>,
         is_public: bool,
+        /// Line of the `mod` keyword.
+        line: usize,
     },
     Function(Function),
     IfLet {
@@ -307,6 +334,8 @@ pub enum Stmt {
         exports: Vec<(String, Option<String>)>,
     },
     Expr(Expr),
+    /// A parsed statement and the line it starts on.
+    Located(usize, Box<Stmt>),
 }
 
 
@@ -318,6 +347,8 @@ pub struct ModuleImport {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ModuleUse {
     pub module_name: String,
+    /// Name given by `use module as alias`.
+    pub alias: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -423,8 +454,12 @@ pub struct IR {
     pub functions: String,
     pub added_typedefs: HashSet<String>,
     pub added_function_decls: HashSet<String>,
+    pub added_includes: HashSet<String>,
+    /// Set when `vix_init_modules` was generated and `main` has to call it.
+    pub init_modules: bool,
 }
 
+#[allow(clippy::type_complexity)]
 pub struct Codegen {
     pub config: CodegenConfig,
     pub type_registry: TypeRegistry,
@@ -435,17 +470,24 @@ pub struct Codegen {
     pub label_count: usize,
     pub vars: HashMap<String, (String, Type)>,
     pub owned_vars: HashSet<String>,
+    /// Owned variables whose value has been moved out; reading them again is an error.
+    pub moved_vars: HashSet<String>,
+    /// Locals declared without `mut`; taking their address gives a shared reference.
+    pub immutable_vars: HashSet<String>,
     pub extern_functions: HashMap<String, ExternFunctionMap>,
     pub extern_block: HashMap<String, ExternFunctionMap>,
     pub structs: HashMap<String, StructInfo>,
     pub module_vars: HashMap<(String, String), (String, Type, bool)>,
     pub module_functions: HashMap<(String, String), (Vec<(String, Type)>, Type, bool)>,
+    pub module_aliases: HashMap<String, String>,
     pub compilation_mode: CompilationMode,
     pub exported_functions: Vec<String>,
     pub externs_bodies: HashSet<String>,
     pub unsafe_depth: usize,
     pub scope_depth: usize,
     pub user_functions: HashMap<String, (Vec<(String, Type)>, Type)>,
+    pub param_modifiers: HashMap<String, Vec<ParamModifier>>,
+    pub param_defaults: HashMap<String, Vec<Option<Expr>>>,
     pub ir: IR,
     pub arch: ArchConfig,
     pub diagnostics: DiagnosticHandler,
@@ -456,12 +498,39 @@ pub struct Codegen {
     pub module_init_functions: Vec<String>,
     pub current_return_type: Option<Type>,
     pub import_function_map: HashMap<String, String>,
+    pub emit_line_directives: bool,
+    pub loop_stack: Vec<LoopLabels>,
+    pub pending_loop_label: Option<String>,
+    pub defer_stack: Vec<Vec<Stmt>>,
+    pub global_consts: HashMap<String, (String, Type)>,
+    /// Progress lines go here instead of stdout when set.
+    pub progress_log: Option<Vec<String>>,
+    /// Line of the statement being generated; 0 outside function bodies.
+    pub current_line: usize,
+    /// Path the generated C is written to, named by the `#line` that follows each function body.
+    pub c_file: String,
+}
+
+pub struct LoopLabels {
+    pub name: Option<String>,
+    pub break_label: String,
+    pub continue_label: String,
+    pub break_used: bool,
+    pub continue_used: bool,
+    /// Owned variables declared before the loop; moving one inside it would repeat the move.
+    pub outer_owned: HashSet<String>,
+    /// Defer frames open when the loop was entered; `break`/`continue` run the ones above it.
+    pub defer_depth: usize,
 }
 
 pub struct CodegenConfig {
     pub arch: ArchConfig,
     pub optimization_level: OptimizationLevel,
     pub debug_info: bool,
+    /// Emit a runtime length check before indexing slices and strings.
+    pub bounds_checks: bool,
+    /// Write the generated C source but stop before invoking clang.
+    pub dry_run: bool,
 }
 
 
@@ -482,6 +551,11 @@ pub enum OptimizationLevel {
     O1,
     O2,
     O3,
+    Size,
+    /// No optimization, with debug info for stepping through the generated C.
+    Debug,
+    /// Like `O2`, but `assert` statements are compiled out.
+    Release,
 }
 
 pub struct Parser {
@@ -489,8 +563,15 @@ pub struct Parser {
     pub spans: Vec<SourceSpan>,
     pub pos: usize,
     pub source: Arc<String>,
+    /// Character offset at which each source line starts.
+    pub line_starts: Vec<usize>,
     pub diags: Vec<ParseDiagnostic>,
     pub type_aliases: HashMap<String, Expr>,
+    pub const_values: HashMap<String, i64>,
+    /// Structs and enums declared anywhere in the token stream.
+    pub type_names: HashSet<String>,
+    /// Modules declared with `mod`, plus the aliases given to them by `use ... as`.
+    pub module_names: HashSet<String>,
 }
 
 
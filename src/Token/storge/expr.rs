@@ -11,6 +11,24 @@ impl Expr {
     }
 }
 
+impl Stmt {
+    /// The statement without its `Located` wrapper.
+    pub fn node(&self) -> &Stmt {
+        match self {
+            Stmt::Located(_, inner) => inner.node(),
+            stmt => stmt,
+        }
+    }
+
+    /// Line the parser recorded for the statement, if it came from source.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Stmt::Located(line, _) => Some(*line),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionWithLocation {
     pub name: String,
@@ -62,10 +80,12 @@ impl Default for Function {
         Self {
             name: String::new(),
             params: Vec::new(),
+            param_defaults: Vec::new(),
             return_type: Type::Void,
             body: Vec::new(),
             is_public: false,
             attributes: Vec::new(),
+            line: 0,
         }
     }
 }
@@ -80,6 +100,7 @@ pub enum Expr {
     Not(Box<Expr>),
     Wait(Box<Expr>),
     Unwrap(Box<Expr>),
+    Try(Box<Expr>),
     UnwrapOr(Box<Expr>, Box<Expr>),
     Chars(Box<Expr>),
     Have { obj: Box<Expr>, item: Box<Expr> },
@@ -90,6 +111,7 @@ pub enum Expr {
     IndexOf { obj: Box<Expr>, item: Box<Expr> },
     IsEmpty(Box<Expr>),
     Number(i64),
+    TypedNumber(i64, Type),
     Float(f32),
     String(String),
     Bool(bool),
@@ -97,14 +119,18 @@ pub enum Expr {
     BinaryNumber(i32),
     OctalNumber(i32),
     Char(i32),
+    ByteChar(u8),
     None,
     Some(Box<Expr>),
     Var(String),
     Call(String, Vec<Expr>),
     CallNamed(String, Vec<(String, Expr)>),
     FuncAddr(String),
+    EnumPattern(String, Vec<String>),
     BinOp(String, Box<Expr>, Box<Expr>),
     UnOp(String, Box<Expr>),
+    AddressOf(Box<Expr>),
+    Deref(Box<Expr>),
     Tuple(Vec<Expr>),
     Array(Vec<Expr>),
     Index(Box<Expr>, Vec<Expr>),
@@ -114,15 +140,17 @@ pub enum Expr {
     MethodCallNamed(Box<Expr>, String, Vec<(String, Expr)>),
     StaticMethodCall(String, String, Vec<Expr>),
     StaticMethodCallNamed(String, String, Vec<(String, Expr)>),
-    ModuleAccess(String, String),
-    ModuleCall(String, String, Vec<Expr>),
+    ModuleAccess(Vec<String>, String),
+    ModuleCall(Vec<String>, String, Vec<Expr>),
     ModuleCallNamed(String, String, Vec<(String, Expr)>),
     StructInit(String, Vec<(String, Expr)>),
+    StructLiteral(String, Vec<(String, Expr)>),
     Cast(Box<Expr>, CastTarget),
     ReferenceTo(Type),
     Pipe(Box<Expr>, Box<Expr>),
     SizeOf(Type),
     AlignOf(Type),
+    VaNext(Type),
     TypeOf(Box<Expr>),
     OffsetOf { struct_type: String, field: String },
     OneOf(Vec<Expr>),
@@ -137,6 +165,8 @@ pub enum Expr {
     Slots(Box<Expr>),
     Map { obj: Box<Expr>, func: Box<Expr>, params: Vec<String> },
     Slice(Box<Expr>, Box<Expr>, Box<Expr>),
+    Range(Box<Expr>, Box<Expr>, bool),
+    IfExpr(Box<Expr>, Box<Expr>, Box<Expr>),
     HashMap(Vec<(Expr, Expr)>),   
     NullPtr,
 }
\ No newline at end of file
@@ -3,14 +3,14 @@ use crate::import::*;
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
     Create, Pub, Func, End, As, Return,
-    If, Else, While, For, Break, Continue, Module,
+    If, Else, While, For, Loop, Break, Continue, Module,
     True, False, Extern, From, Mut, Struct, Enum, Any,
     TripleDot,  
     Mod, To, BitwiseOr,
     BitwiseAnd, 
     DoubleDot,
     Use,  
-    Match, Case, Default, Unsafe,
+    Match, Case, Default, Unsafe, Defer, Assert,
     Then, Do, In, Refrence, Scope, Const,
     Mutable,
     Reference, None, Null,
@@ -50,12 +50,14 @@ pub enum Token {
     
     Identifier(String),
     Number(i64),
+    TypedNumber(i64, String),
     Float(OrderedFloat<f32>),
     String(String),
     HexNumber(u32),
     BinaryNumber(u32),
     OctalNumber(u32),
     Char(i32),
+    ByteChar(u8),
 
     LeftParen, RightParen, LeftBrace, RightBrace, LeftBracket, RightBracket,
     Equals, EqualsEquals, NotEquals,
@@ -64,7 +66,7 @@ pub enum Token {
     And, Or,
     Ampersand, Pipe,
     
-    Semicolon, Comma, Colon, Arrow, Dot,
+    Semicolon, Comma, Colon, Arrow, Dot, Question,
     DoubleColon,
 
     LeftShift, RightShift,
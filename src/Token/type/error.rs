@@ -1,4 +1,3 @@
-use crate::import::*;
 
 struct ErrorType {
     wrong_type: Vec<(bool, String)>,
@@ -0,0 +1,152 @@
+use crate::import::*;
+use crate::Gen::API::error::Diagnostic;
+
+/// Compiles Vix source to C entirely in memory: no files are read or written,
+/// clang is not invoked and codegen's progress output is discarded. Any error-level diagnostic
+/// fails the compile, and every diagnostic collected up to that stage is returned.
+pub fn compile_source(source: &str, arch: ArchConfig) -> Result<String, Vec<Diagnostic>> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize();
+
+    if !lexer.errors.is_empty() {
+        return Err(lexer.collect_diagnostics());
+    }
+
+    let mut parser = Parser::new(tokens, source.to_string(), lexer.spans.clone());
+    let (program, structs, enums, externs, _, uses, _, impls, traits, _, _) = parser.parse_program();
+
+    let mut diagnostics = parser.collect_diagnostics();
+    if diagnostics.iter().any(|d| d.level == DiagnosticLevel::Error) {
+        return Err(diagnostics);
+    }
+
+    let mut codegen = Codegen::new(arch, source.to_string(), "input".to_string());
+    codegen.diagnostics.echo = false;
+    codegen.progress_log = Some(Vec::new());
+    codegen.set_module_uses(&uses);
+    codegen.check_trait_impls(&traits, &impls).ok();
+    let c_code = codegen.codegen_program_full(&program, &structs, &enums, &impls, &externs, &[], &[]);
+
+    match c_code {
+        Ok(c_code) if !codegen.diagnostics.has_errors() => Ok(c_code),
+        _ => {
+            diagnostics.extend(codegen.collect_diagnostics());
+            Err(diagnostics)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compile_source_to_c() {
+        let source = "func square(x: int32): int32\n    return x * x\nend\n\nfunc main(): int32\n    return square(7)\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|_| panic!("should compile"));
+        assert!(c_code.contains("static int32_t square(int32_t var_x) {"), "{}", c_code);
+        assert!(c_code.contains("int32_t vix_main()"), "{}", c_code);
+        assert!(c_code.contains("int main() {\n    vix_main();"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_compile_source_collects_diagnostics() {
+        let Err(diagnostics) = compile_source("func main(): int32\n    return missing\nend\n", ArchConfig::x86_64()) else {
+            panic!("undefined variable should fail");
+        };
+        assert!(diagnostics.iter().any(|d| d.code == "UndefinedVariable"));
+
+        let Err(diagnostics) = compile_source("func main()\n    create s = \"\\q\"\nend\n", ArchConfig::x86_64()) else {
+            panic!("unknown escape should fail");
+        };
+        assert_eq!(diagnostics[0].code, "LexError");
+
+        let Err(diagnostics) = compile_source("func main(a: int32 = 1, b: int32)\nend\n", ArchConfig::x86_64()) else {
+            panic!("parse error should fail");
+        };
+        assert_eq!(diagnostics[0].code, "ParseError");
+        assert_eq!(diagnostics[0].context.primary_location.line, 1);
+
+        let source = "func main(): int32\n    while undefined_thing do\n    end\n    while true do\n        create x = undefined_too\n    end\n    return 0\nend\n";
+        let Err(diagnostics) = compile_source(source, ArchConfig::x86_64()) else {
+            panic!("bad while condition should fail");
+        };
+        let undefined: Vec<_> = diagnostics.iter()
+            .filter(|d| d.code == "UndefinedVariable")
+            .map(|d| d.context.primary_location.line)
+            .collect();
+        assert_eq!(undefined, vec![2, 5]);
+    }
+
+    #[test]
+    fn test_diagnostics_round_trip_through_json() {
+        let Err(diagnostics) = compile_source("func main(a: int32 = 1, b: int32)\nend\n", ArchConfig::x86_64()) else {
+            panic!("parse error should fail");
+        };
+        let json = diagnostics_to_json(&diagnostics);
+        let decoded: Vec<JsonDiagnostic> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.len(), diagnostics.len());
+        assert_eq!(decoded[0], JsonDiagnostic::from(&diagnostics[0]));
+        assert_eq!(decoded[0].severity, "error");
+        assert_eq!(decoded[0].code, "ParseError");
+        assert_eq!(decoded[0].line, 1);
+        assert!(decoded[0].message.contains("`b`"), "{}", decoded[0].message);
+    }
+
+    #[test]
+    fn test_module_called_through_use_alias() {
+        let source = "mod geometry {\n    public func area(w: int32, h: int32): int32\n        return w * h\n    end\n}\n\nuse geometry as geo\n\nfunc main(): int32\n    return geo.area(2, 3) + geometry.area(1, 1)\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|d| panic!("should compile: {:?}", d));
+        assert_eq!(c_code.matches(" = area(").count(), 2, "{}", c_code);
+    }
+
+    #[test]
+    fn test_doubly_nested_module_call() {
+        let source = "mod outer {\n    mod inner {\n        public func triple(x: int32): int32\n            return x * 3\n        end\n    }\n}\n\nfunc main(): int32\n    outer.inner.triple(1)\n    return outer.inner.triple(2)\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|d| panic!("should compile: {:?}", d));
+        assert_eq!(c_code.matches(" = triple(").count(), 2, "{}", c_code);
+        assert!(c_code.contains("void outer_inner_init()"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_module_inits_run_before_main_in_dependency_order() {
+        let source = "mod report {\n    create doubled = settings.level * 2\n}\n\nmod settings {\n    create level = 3\n}\n\nfunc main(): int32\n    return report.doubled\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|d| panic!("should compile: {:?}", d));
+        assert!(c_code.contains("settings_level = var_level;"), "{}", c_code);
+        assert!(c_code.contains("void vix_init_modules() {\n    settings_init();\n    report_init();\n}"), "{}", c_code);
+        assert!(c_code.contains("int main() {\n    vix_init_modules();\n    vix_main();"), "{}", c_code);
+
+        let source = "mod a {\n    create x = b.y\n}\n\nmod b {\n    create y = a.x\n}\n\nfunc main(): int32\n    return 0\nend\n";
+        let Err(diagnostics) = compile_source(source, ArchConfig::x86_64()) else {
+            panic!("init cycle should fail");
+        };
+        assert!(diagnostics.iter().any(|d| d.code == "ModuleInitCycle" && d.message.contains("a -> b -> a")), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_module_init_cycle_is_reported_at_the_module_and_keeps_generating() {
+        let source = "func main(): int32\n    return 0\nend\n\nmod a {\n    create x = b.y\n}\n\nmod b {\n    create y = a.x\n    create z = missing\n}\n";
+        let Err(diagnostics) = compile_source(source, ArchConfig::x86_64()) else {
+            panic!("init cycle should fail");
+        };
+        let cycle = diagnostics.iter().find(|d| d.code == "ModuleInitCycle").expect("cycle should be reported");
+        assert_eq!(cycle.context.primary_location.line, 5);
+        assert!(diagnostics.iter().any(|d| d.message.contains("missing")), "{:?}", diagnostics);
+    }
+
+    #[test]
+    fn test_calls_between_module_functions_are_not_an_init_cycle() {
+        let source = "mod a {\n    public func f(n: int32): int32\n        return b.g(n)\n    end\n}\n\nmod b {\n    public func g(n: int32): int32\n        return a.f(n - 1)\n    end\n}\n\nfunc main(): int32\n    return 0\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|d| panic!("should compile: {:?}", d));
+        assert!(c_code.contains("void vix_init_modules() {\n    a_init();\n    b_init();\n}"), "{}", c_code);
+    }
+
+    #[test]
+    fn test_nested_module_initializes_before_its_parent() {
+        let source = "mod outer {\n    create x = 1\n    mod inner {\n        create y = 2\n    }\n}\n\nfunc main(): int32\n    return outer.x\nend\n";
+        let c_code = compile_source(source, ArchConfig::x86_64()).unwrap_or_else(|d| panic!("should compile: {:?}", d));
+        assert!(c_code.contains("void vix_init_modules() {\n    outer_inner_init();\n    outer_init();\n}"), "{}", c_code);
+    }
+}
+
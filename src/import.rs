@@ -9,7 +9,7 @@ pub use rayon::prelude::*;
 pub use serde::{Deserialize,  Serialize};
 pub use regex::Regex;
 pub use libloading::{Library, Symbol};
-pub use std::sync::Arc;
+pub use std::sync::{Arc, OnceLock};
 pub use levenshtein::levenshtein;
 pub use miette::{Diagnostic, NamedSource, Report, SourceSpan};
 pub use ordered_float::OrderedFloat;
@@ -30,5 +30,6 @@ pub use crate::Gen::r#type::{EnumDefinition, StructDefinition, TypeRegistry};
 pub use crate::Gen::API::error::*;
 pub use crate::Token::storge::expr::Expr;
 pub use crate::Token::storge::ast::StructInfo;
+pub use crate::Token::storge::ast::LoopLabels;
 pub use crate::Library::manager::{DependencyInfo, PackageInfo, PackageInformation, PackageJson, FootprintPack, LibraryError, LibraryMetadata};
 pub use crate::Library::manager::LibraryManager;
@@ -1,7 +1,18 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
+macro_rules! debug_log {
+    ($($arg:tt)*) => {
+        if std::env::var_os("VIX_DEBUG").is_some() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
 pub mod import;
 pub mod Token;
 pub mod Gen;
 pub mod Library;
+pub mod compile;
+
+pub use compile::compile_source;
@@ -5,6 +5,14 @@ use std::path::Path;
 
 const VERSION: &str = "Alpha 3.0v";
 
+/// Prints `diagnostics` to stdout as a JSON array and exits, failing if any is an error.
+/// In this mode nothing else is written to stdout, so it parses as a whole.
+fn emit_json_diagnostics(diagnostics: &[Vix::Gen::API::error::Diagnostic]) -> ! {
+    println!("{}", diagnostics_to_json(diagnostics));
+    let failed = diagnostics.iter().any(|d| d.level == DiagnosticLevel::Error);
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
 fn print_help() {
     println!("{}", "Vix Compiler".bright_cyan().bold());
     println!("Usage: vix [command] [options]");
@@ -18,8 +26,11 @@ fn print_help() {
     println!();
     println!("Options:");
     println!("  --debug               Enable debug output");
+    println!("  --release             Optimize and compile out assert statements");
+    println!("  --dry-run             Write the generated C to release/bin/output.c without compiling");
     println!("  --target <OS>         Target operating system (windows, linux, macos, freebsd)");
     println!("  --output <name>       Output executable name (default: program)");
+    println!("  --diagnostics=json    Check the program and print its diagnostics as JSON instead of building");
     println!();
     println!("Examples:");
     println!("  vix run                      # Compile and run for current OS");
@@ -76,6 +87,9 @@ fn main() {
     }
 
     let debug_mode = args.contains(&"--debug".to_string());
+    let release_mode = args.contains(&"--release".to_string());
+    let dry_run = args.contains(&"--dry-run".to_string());
+    let json_diagnostics = args.contains(&"--diagnostics=json".to_string());
     let should_run = command == "run";
 
     let target_os = if let Some(pos) = args.iter().position(|arg| arg == "--target") {
@@ -139,6 +153,13 @@ fn main() {
         let tokens = lexer.tokenize();
 
         if !lexer.errors.is_empty() {
+            if json_diagnostics {
+                let mut diagnostics = lexer.collect_diagnostics();
+                for diagnostic in &mut diagnostics {
+                    diagnostic.context.primary_location.file = source_file.display().to_string();
+                }
+                emit_json_diagnostics(&diagnostics);
+            }
             for err in &lexer.errors {
                 eprintln!("   {} in {}: {}", "Error:".red(), source_file.display(), err.message);
             }
@@ -151,9 +172,11 @@ fn main() {
     }
 
     let footprint_packs = if !all_import_decls.is_empty() {
-        match LibraryManager::process_imports_from_decls(&all_import_decls, Some(target)) {
+        match LibraryManager::process_imports_from_decls(&all_import_decls, Some(target), dry_run || json_diagnostics, json_diagnostics) {
             Ok(packs) => {
-                println!("   {} Processed {} libraries", "success:".green(), packs.len());
+                if !json_diagnostics {
+                    println!("   {} Processed {} libraries", "success:".green(), packs.len());
+                }
                 if let Err(e) = LibraryManager::validate_imports(&all_import_decls, &packs) {
                     eprintln!("{} Import validation failed: {:?}", "Error:".red(), e);
                     std::process::exit(1);
@@ -177,7 +200,7 @@ fn main() {
         all_library_functions.extend(pack.function_signatures.clone());
     }
 
-    if debug_mode && !all_library_functions.is_empty() {
+    if debug_mode && !json_diagnostics && !all_library_functions.is_empty() {
         println!("   {} Extracted {} function signatures", "success:".bright_black(), all_library_functions.len());
         for sig in &all_library_functions {
             println!("      {} {}({}) -> {}", "success:".bright_black(), sig.name, 
@@ -195,27 +218,51 @@ fn main() {
 
     let mut lexer = Lexer::new(&combined_source_code);
     let tokens = lexer.tokenize();
-    let parser = Parser::new(tokens, combined_source_code.clone(), lexer.spans.clone());
-    let (program, all_structs, all_enums, all_externs, _, _, _, all_impls, _, _, _) = parser.parse();
+    let mut parser = Parser::new(tokens, combined_source_code.clone(), lexer.spans.clone());
+    let (program, all_structs, all_enums, all_externs, _, all_uses, _, all_impls, all_traits, _, _) = parser.parse_program();
+    let main_filename = source_files.first().map(|p| p.display().to_string()).unwrap_or_else(|| "main.vix".to_string());
+
+    let mut parse_diagnostics = parser.collect_diagnostics();
+    for diagnostic in &mut parse_diagnostics {
+        diagnostic.context.primary_location.file = main_filename.clone();
+    }
+    if json_diagnostics && parse_diagnostics.iter().any(|d| d.level == DiagnosticLevel::Error) {
+        emit_json_diagnostics(&parse_diagnostics);
+    }
 
     if program.functions.is_empty() {
         eprintln!("{} No functions found to compile", "Error:".red());
         std::process::exit(1);
     }
 
-    println!("   {} Found {} functions, {} structs, {} enums", 
-        "success:".green(), 
-        program.functions.len(),
-        all_structs.len(),
-        all_enums.len()
-    );
+    if !json_diagnostics {
+        println!("   {} Found {} functions, {} structs, {} enums", 
+            "success:".green(), 
+            program.functions.len(),
+            all_structs.len(),
+            all_enums.len()
+        );
+    }
 
 
-    let arch = ArchConfig::x86_64();
-    let main_filename = source_files.first().map(|p| p.display().to_string()).unwrap_or_else(|| "main.vix".to_string());
+    let arch = target.arch();
     let mut codegen = Codegen::new(arch, combined_source_code.clone(), main_filename);
+    codegen.diagnostics.echo = !json_diagnostics;
+    if json_diagnostics {
+        codegen.progress_log = Some(Vec::new());
+    }
+    let optimization = if debug_mode {
+        OptimizationLevel::Debug
+    } else if release_mode {
+        OptimizationLevel::Release
+    } else {
+        OptimizationLevel::O2
+    };
+    codegen.config.optimization_level = optimization;
+    codegen.config.dry_run = dry_run;
 
     codegen.set_import_context(&all_import_decls, &all_library_functions);
+    codegen.set_module_uses(&all_uses);
 
      
     for func_sig in &all_library_functions {
@@ -231,12 +278,14 @@ fn main() {
         let decl = format!("{} {}({});", func_sig.return_type, func_sig.name, params_str);
         codegen.ir.add_forward_decl(decl.clone());
         
-        if debug_mode {
+        if debug_mode && !json_diagnostics {
             println!("   {} Added forward declaration: {}", "success:".bright_black(), decl);
         }
     }
 
-    let main_c_code = match codegen.codegen_program_full(
+    codegen.check_trait_impls(&all_traits, &all_impls).ok();
+
+    let main_c_code = codegen.codegen_program_full(
         &program, 
         &all_structs, 
         &all_enums, 
@@ -244,7 +293,14 @@ fn main() {
         &all_externs, 
         &all_library_includes,
         &all_library_functions
-    ) {
+    );
+
+    if json_diagnostics {
+        parse_diagnostics.extend(codegen.collect_diagnostics());
+        emit_json_diagnostics(&parse_diagnostics);
+    }
+
+    let main_c_code = match main_c_code {
         Ok(code) => {
             println!("   {} Main program C code generated", "success:".green());
             
@@ -281,8 +337,19 @@ fn main() {
     }
 
 
-    let main_obj = Path::new("release/bin/main.o");
-    match Clang::compile_to_object(&main_c_code, main_obj, Some(target)) {
+    let main_obj_path = PathBuf::from("release/bin").join(format!("main{}", target.object_extension()));
+    let main_obj = main_obj_path.as_path();
+    if codegen.config.dry_run {
+        match Clang::write_c_source(&main_c_code, Path::new(Clang::MAIN_C_SOURCE)) {
+            Ok(()) => println!("   {} Dry run, generated C written to: {}", "success:".green(), Clang::MAIN_C_SOURCE),
+            Err(e) => {
+                eprintln!("{} {}", "Error:".red(), e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    match Clang::compile_to_object(&main_c_code, Path::new(Clang::MAIN_C_SOURCE), main_obj, Some(target), optimization) {
         Ok(_) => println!("   {} Main object: {}", "success:".green(), main_obj.display()),
         Err(e) => {
             eprintln!("\n{} Main compilation failed!", "Error:".red().bold());
@@ -307,10 +374,10 @@ fn main() {
 
     println!("   {} Linking {} object files", "success:".green(), object_files.len());
 
-    match Clang::link_executable(&object_files, output_name, &linked_libs, Some(target)) {
+    match Clang::link_executable(&object_files, output_name, linked_libs, Some(target)) {
         Ok(_) => {
              
-            let _ = fs::remove_file("release/bin/main.o");
+            let _ = fs::remove_file(main_obj);
             let _ = fs::remove_file("release/bin/output.c");
             
             println!("   {} Compilation Successful!", "success:".green().bold());
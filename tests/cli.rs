@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// A Vix home with a one-function core library and a `greeter` library, plus a
+/// project in `proj/` that imports from it.
+fn fixture(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("vix-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    let files = [
+        ("home/Library/core/core.vix", "public func core_id(x: int32): int32\n    return x\nend\n"),
+        (
+            "home/library/greeter/package.json",
+            r#"{"Information": {"name": "greeter", "version": "1.0.0", "publisher": "vix"},
+                "src": {"scripts": ["main.vix"]}, "syntax": {"syntax": [], "error": []}}"#,
+        ),
+        ("home/library/greeter/src/main.vix", "public func greet(): int32\n    return 7\nend\n"),
+        ("proj/src/main.vix", "import greet from greeter\n\nfunc main(): int32\n    return greet()\nend\n"),
+    ];
+    for (path, contents) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    fs::create_dir_all(root.join("empty-path")).unwrap();
+    root
+}
+
+/// Runs `vix` in the fixture's project with an empty PATH, so any attempt to spawn clang fails.
+fn vix(root: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_Vix"))
+        .args(args)
+        .current_dir(root.join("proj"))
+        .env("VIX_HOME", root.join("home"))
+        .env("PATH", root.join("empty-path"))
+        .env_remove("VIX_DEBUG")
+        .output()
+        .unwrap()
+}
+
+fn files_under(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(files_under(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[test]
+fn test_dry_run_writes_all_c_without_running_clang() {
+    let root = fixture("dry-run");
+
+    let output = vix(&root, &["build", "--dry-run"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let release = root.join("proj").join("release");
+    let main_c = fs::read_to_string(release.join("bin/output.c")).unwrap();
+    assert!(main_c.contains("greet()"), "{}", main_c);
+    let library_c = fs::read_to_string(release.join("library/code/greeter-1.0.0.c")).unwrap();
+    assert!(library_c.contains("greet("), "{}", library_c);
+    assert!(release.join("library/code/core.c").exists());
+
+    let objects: Vec<PathBuf> = files_under(&release)
+        .into_iter()
+        .filter(|path| matches!(path.extension().and_then(|e| e.to_str()), Some("o" | "obj")))
+        .collect();
+    assert!(objects.is_empty(), "{:?}", objects);
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn test_json_diagnostics_are_the_whole_of_stdout() {
+    let root = fixture("json");
+    fs::write(
+        root.join("proj/src/main.vix"),
+        "import greet from greeter\n\nfunc main(): int32\n    create unused = 1\n    return greet() + missing\nend\n",
+    )
+    .unwrap();
+
+    let output = vix(&root, &["build", "--diagnostics=json"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let diagnostics: Vec<serde_json::Value> = serde_json::from_str(&stdout).unwrap_or_else(|e| panic!("{}: {}", e, stdout));
+    let codes: Vec<&str> = diagnostics.iter().map(|d| d["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, ["UnusedVariable", "UndefinedVariable"]);
+
+    fs::remove_dir_all(&root).unwrap();
+}